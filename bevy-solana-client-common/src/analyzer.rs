@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use solana_sdk::{packet::PACKET_DATA_SIZE, pubkey::Pubkey, transaction::Transaction};
+
+/// Size and account locks of a single transaction of a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionFootprint {
+    /// Serialized size in bytes, must not exceed [`PACKET_DATA_SIZE`].
+    pub size: usize,
+    pub writable: BTreeSet<Pubkey>,
+    pub readonly: BTreeSet<Pubkey>,
+}
+
+impl TransactionFootprint {
+    pub fn of(tx: &Transaction) -> Self {
+        let size = bincode::serialized_size(tx).map_or(usize::MAX, |size| size as usize);
+
+        let (writable, readonly) = tx
+            .message
+            .account_keys
+            .iter()
+            .enumerate()
+            .partition::<Vec<_>, _>(|(i, _)| tx.message.is_maybe_writable(*i, None));
+
+        Self {
+            size,
+            writable: writable.into_iter().map(|(_, key)| *key).collect(),
+            readonly: readonly.into_iter().map(|(_, key)| *key).collect(),
+        }
+    }
+
+    pub fn fits_in_packet(&self) -> bool {
+        self.size <= PACKET_DATA_SIZE
+    }
+
+    /// Bytes left before the transaction no longer fits into a packet.
+    pub fn headroom(&self) -> isize {
+        PACKET_DATA_SIZE as isize - self.size as isize
+    }
+}
+
+/// How two or more transactions compete for the lock of an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LockConflictKind {
+    /// Several transactions write the account, they run strictly one after another.
+    WriteWrite,
+    /// One transaction writes the account while others read it.
+    ReadWrite,
+}
+
+/// An account locked by more than one transaction of a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockConflict {
+    pub account: Pubkey,
+    pub kind: LockConflictKind,
+    /// Indices of the transactions writing the account.
+    pub writers: Vec<usize>,
+    /// Indices of the transactions only reading the account.
+    pub readers: Vec<usize>,
+}
+
+/// Report over a batch of pending transactions, see [`analyze_batch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchAnalysis {
+    /// One footprint per transaction, in batch order.
+    pub transactions: Vec<TransactionFootprint>,
+    /// Conflicting accounts, write-write conflicts first.
+    pub conflicts: Vec<LockConflict>,
+}
+
+impl BatchAnalysis {
+    /// Indices of the transactions too large to be sent.
+    pub fn oversized(&self) -> impl Iterator<Item = usize> + '_ {
+        self.transactions
+            .iter()
+            .enumerate()
+            .filter(|(_, tx)| !tx.fits_in_packet())
+            .map(|(i, _)| i)
+    }
+
+    /// Accounts written by at least `min_writers` transactions, the most contended first.
+    ///
+    /// Leaders execute writes to the same account sequentially, bursts hitting such an
+    /// account are likely to spill into later blocks.
+    pub fn hot_accounts(&self, min_writers: usize) -> Vec<(Pubkey, usize)> {
+        let mut hot: Vec<_> = self
+            .conflicts
+            .iter()
+            .filter(|c| c.writers.len() >= min_writers)
+            .map(|c| (c.account, c.writers.len()))
+            .collect();
+        hot.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hot
+    }
+
+    /// Partitions the batch into groups of transactions linked by lock conflicts.
+    ///
+    /// Groups can be submitted in parallel without contending with each other, the
+    /// transactions within a group are candidates to be sequenced or merged. Fee payers
+    /// are write locked too, so a shared fee payer links every transaction it pays for.
+    pub fn conflict_groups(&self) -> Vec<Vec<usize>> {
+        let mut parent: Vec<usize> = (0..self.transactions.len()).collect();
+
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        for conflict in &self.conflicts {
+            let mut members = conflict.writers.iter().chain(&conflict.readers);
+            let Some(first) = members.next() else {
+                continue;
+            };
+            let first = root(&mut parent, *first);
+            for member in members {
+                let member = root(&mut parent, *member);
+                parent[member] = first;
+            }
+        }
+
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for i in 0..self.transactions.len() {
+            let group = root(&mut parent, i);
+            groups.entry(group).or_default().push(i);
+        }
+
+        let mut groups: Vec<_> = groups.into_values().collect();
+        groups.sort_by_key(|group| group[0]);
+        groups
+    }
+}
+
+/// Reports serialized sizes and account lock conflicts of `transactions`, so bursts
+/// can be reordered or merged before submission.
+///
+/// Writability is approximated without the cluster's reserved account keys, the same
+/// way wallets and explorers do.
+pub fn analyze_batch(transactions: &[Transaction]) -> BatchAnalysis {
+    let footprints: Vec<_> = transactions.iter().map(TransactionFootprint::of).collect();
+
+    let mut locks: BTreeMap<Pubkey, (Vec<usize>, Vec<usize>)> = BTreeMap::new();
+    for (i, footprint) in footprints.iter().enumerate() {
+        for key in &footprint.writable {
+            locks.entry(*key).or_default().0.push(i);
+        }
+        for key in &footprint.readonly {
+            locks.entry(*key).or_default().1.push(i);
+        }
+    }
+
+    let mut conflicts: Vec<_> = locks
+        .into_iter()
+        .filter_map(|(account, (writers, readers))| {
+            let kind = match (writers.len(), readers.len()) {
+                (0, _) => return None,
+                (1, 0) => return None,
+                (1, _) => LockConflictKind::ReadWrite,
+                _ => LockConflictKind::WriteWrite,
+            };
+            Some(LockConflict {
+                account,
+                kind,
+                writers,
+                readers,
+            })
+        })
+        .collect();
+    conflicts.sort_by_key(|c| c.kind);
+
+    BatchAnalysis {
+        transactions: footprints,
+        conflicts,
+    }
+}
@@ -0,0 +1,162 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    system_program,
+    transaction::Transaction,
+};
+
+use crate::{
+    builder::TransactionBuilder,
+    das::{get_asset, get_asset_proof, DasAsset, DasAssetProof},
+    idl::instruction_discriminator,
+    rpc_client::RpcClient,
+};
+
+/// The Metaplex Bubblegum program minting compressed NFTs.
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey =
+    pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+/// Bytes in front of the tree of a concurrent merkle tree account.
+const TREE_HEADER_LEN: usize = 56;
+
+/// The tree authority of `merkle_tree`.
+pub fn tree_authority(merkle_tree: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[merkle_tree.as_ref()], &BUBBLEGUM_PROGRAM_ID).0
+}
+
+/// The leaf of a compressed NFT as the Bubblegum program hashes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressedLeaf {
+    pub merkle_tree: Pubkey,
+    pub owner: Pubkey,
+    /// The owner unless a delegate was set.
+    pub delegate: Pubkey,
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub leaf_index: u32,
+}
+
+impl CompressedLeaf {
+    /// The leaf of `asset` at the root of `proof`.
+    pub fn from_das(asset: &DasAsset, proof: &DasAssetProof) -> Result<Self> {
+        let compression = asset
+            .compression
+            .as_ref()
+            .filter(|compression| compression.compressed)
+            .with_context(|| format!("{} is not a compressed asset", asset.id))?;
+
+        Ok(Self {
+            merkle_tree: proof.tree_id,
+            owner: asset.ownership.owner,
+            delegate: asset.ownership.delegate.unwrap_or(asset.ownership.owner),
+            root: proof.root.to_bytes(),
+            data_hash: hash_from_str(&compression.data_hash)?,
+            creator_hash: hash_from_str(&compression.creator_hash)?,
+            leaf_index: compression.leaf_id.try_into()?,
+        })
+    }
+}
+
+/// Moves the compressed NFT at `leaf` to `new_owner`, the owner has to sign.
+///
+/// `proof` is the merkle proof without the nodes cached in the tree's canopy, see
+/// [`trim_proof`].
+pub fn transfer(leaf: &CompressedLeaf, new_owner: &Pubkey, proof: &[Pubkey]) -> Instruction {
+    let mut data = instruction_discriminator("transfer").to_vec();
+    data.extend_from_slice(&leaf.root);
+    data.extend_from_slice(&leaf.data_hash);
+    data.extend_from_slice(&leaf.creator_hash);
+    data.extend_from_slice(&(leaf.leaf_index as u64).to_le_bytes());
+    data.extend_from_slice(&leaf.leaf_index.to_le_bytes());
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(tree_authority(&leaf.merkle_tree), false),
+        AccountMeta::new_readonly(leaf.owner, true),
+        AccountMeta::new_readonly(leaf.delegate, false),
+        AccountMeta::new_readonly(*new_owner, false),
+        AccountMeta::new(leaf.merkle_tree, false),
+        AccountMeta::new_readonly(NOOP_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(
+        proof
+            .iter()
+            .map(|node| AccountMeta::new_readonly(*node, false)),
+    );
+
+    Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
+
+/// Depth of the canopy of the concurrent merkle tree account `data`, the number of
+/// upper proof levels the tree stores on chain.
+pub fn canopy_depth(data: &[u8]) -> Result<u32> {
+    if data.len() < TREE_HEADER_LEN || data[0] != 1 {
+        bail!("not a concurrent merkle tree account");
+    }
+    let max_buffer_size = u32::from_le_bytes(data[2..6].try_into()?) as usize;
+    let max_depth = u32::from_le_bytes(data[6..10].try_into()?) as usize;
+
+    // sequence number, active index and buffer size, then the change logs and the
+    // rightmost proof
+    let change_log_len = 32 + 32 * max_depth + 8;
+    let rightmost_proof_len = 32 * max_depth + 32 + 8;
+    let tree_len = 24 + max_buffer_size * change_log_len + rightmost_proof_len;
+
+    let canopy_len = data
+        .len()
+        .checked_sub(TREE_HEADER_LEN + tree_len)
+        .context("concurrent merkle tree account is truncated")?;
+    // a canopy of depth d stores 2^(d+1) - 2 nodes
+    let nodes = canopy_len / 32;
+    Ok((nodes + 2).ilog2().saturating_sub(1))
+}
+
+/// Drops the top `canopy_depth` nodes of `proof`, the program reads them from the tree.
+pub fn trim_proof(proof: &[Pubkey], canopy_depth: u32) -> &[Pubkey] {
+    &proof[..proof.len().saturating_sub(canopy_depth as usize)]
+}
+
+/// An unsigned transaction moving the compressed NFT `asset_id` to `new_owner`, paid and
+/// signed by its current owner.
+///
+/// The proof comes from the DAS endpoint and is valid until the tree changes again, send
+/// the transaction right away.
+pub async fn transfer_compressed_nft(
+    rpc: &impl RpcClient,
+    asset_id: &Pubkey,
+    new_owner: &Pubkey,
+) -> Result<Transaction> {
+    let asset = get_asset(rpc, asset_id).await?;
+    let proof = get_asset_proof(rpc, asset_id).await?;
+    let leaf = CompressedLeaf::from_das(&asset, &proof)?;
+
+    let tree = rpc.get_account(&leaf.merkle_tree).await?;
+    if tree.owner != ACCOUNT_COMPRESSION_PROGRAM_ID {
+        bail!("{} is not a concurrent merkle tree", leaf.merkle_tree);
+    }
+    let proof = trim_proof(&proof.proof, canopy_depth(&tree.data)?);
+
+    TransactionBuilder::new(leaf.owner)
+        .instruction(transfer(&leaf, new_owner, proof))
+        .build(rpc)
+        .await
+}
+
+/// Hashes are base58 encoded like pubkeys.
+fn hash_from_str(s: &str) -> Result<[u8; 32]> {
+    Ok(Pubkey::from_str(s)
+        .with_context(|| format!("invalid hash {:?}", s))?
+        .to_bytes())
+}
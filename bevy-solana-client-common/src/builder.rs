@@ -0,0 +1,199 @@
+use anyhow::Result;
+use solana_sdk::{
+    instruction::Instruction,
+    message::Message,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    transaction::{Transaction, VersionedTransaction, MAX_TX_ACCOUNT_LOCKS},
+};
+
+use crate::{
+    compute_budget::{
+        is_compute_budget_instruction, set_compute_unit_limit, set_compute_unit_price,
+        AutoComputeBudget, MAX_COMPUTE_UNIT_LIMIT,
+    },
+    error::{SimulationError, TransactionLimitError},
+    memo::memo,
+    rpc_client::RpcClient,
+};
+
+/// Assembles the instructions of a transaction and turns them into an unsigned
+/// [`Transaction`] against the latest blockhash.
+///
+/// Building fails with a [`TransactionLimitError`] if the transaction could not be sent.
+pub struct TransactionBuilder {
+    payer: Pubkey,
+    instructions: Vec<Instruction>,
+    compute_budget: Option<AutoComputeBudget>,
+    memo: Option<String>,
+}
+
+impl TransactionBuilder {
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            instructions: vec![],
+            compute_budget: None,
+            memo: None,
+        }
+    }
+
+    pub fn instruction(mut self, ix: Instruction) -> Self {
+        self.instructions.push(ix);
+        self
+    }
+
+    pub fn instructions(mut self, ixs: impl IntoIterator<Item = Instruction>) -> Self {
+        self.instructions.extend(ixs);
+        self
+    }
+
+    /// Prepends compute budget instructions sized by simulating the transaction and
+    /// priced from the fees recently paid for its writable accounts.
+    ///
+    /// Skipped if the instructions already set a compute budget. A failing simulation
+    /// fails the build with a [`SimulationError`], before anything is signed.
+    pub fn with_auto_compute_budget(mut self, config: AutoComputeBudget) -> Self {
+        self.compute_budget = Some(config);
+        self
+    }
+
+    /// Appends a [`memo`](crate::memo::memo) instruction, e.g. the game session id, to
+    /// find the game's transactions in explorers and indexers.
+    pub fn with_memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer
+    }
+
+    pub async fn build(self, rpc: &impl RpcClient) -> Result<Transaction> {
+        let mut instructions = self.instructions;
+        if let Some(text) = &self.memo {
+            instructions.push(memo(text, &[]));
+        }
+        let manual_budget = instructions.iter().any(is_compute_budget_instruction);
+        if let Some(config) = self.compute_budget.filter(|_| !manual_budget) {
+            instructions = with_compute_budget(rpc, &self.payer, config, instructions).await?;
+        }
+
+        let mut tx = Transaction::new_unsigned(Message::new(&instructions, Some(&self.payer)));
+        validate_transaction(&tx)?;
+        tx.message.recent_blockhash = rpc.recent_blockhash().await?;
+        Ok(tx)
+    }
+}
+
+/// Checks `tx` against the packet size, account lock and signature limits, so oversized
+/// transactions fail before a wallet asks the player to sign them.
+pub fn validate_transaction(tx: &Transaction) -> Result<(), TransactionLimitError> {
+    validate(
+        bincode::serialized_size(tx).map_or(usize::MAX, |size| size as usize),
+        tx.message.account_keys.len(),
+        tx.message.header.num_required_signatures as usize,
+        tx.signatures.len(),
+    )
+}
+
+/// [`validate_transaction`] for v0 transactions, counting the accounts loaded through
+/// lookup tables too.
+pub fn validate_versioned_transaction(
+    tx: &VersionedTransaction,
+) -> Result<(), TransactionLimitError> {
+    let looked_up: usize = tx
+        .message
+        .address_table_lookups()
+        .unwrap_or_default()
+        .iter()
+        .map(|lookup| lookup.writable_indexes.len() + lookup.readonly_indexes.len())
+        .sum();
+
+    validate(
+        bincode::serialized_size(tx).map_or(usize::MAX, |size| size as usize),
+        tx.message.static_account_keys().len() + looked_up,
+        tx.message.header().num_required_signatures as usize,
+        tx.signatures.len(),
+    )
+}
+
+fn validate(
+    size: usize,
+    accounts: usize,
+    required_signatures: usize,
+    signatures: usize,
+) -> Result<(), TransactionLimitError> {
+    if required_signatures != signatures {
+        return Err(TransactionLimitError::SignatureCount {
+            required: required_signatures,
+            present: signatures,
+        });
+    }
+    if accounts > MAX_TX_ACCOUNT_LOCKS {
+        return Err(TransactionLimitError::TooManyAccounts {
+            count: accounts,
+            max: MAX_TX_ACCOUNT_LOCKS,
+        });
+    }
+    if size > PACKET_DATA_SIZE {
+        return Err(TransactionLimitError::TooLarge {
+            size,
+            max: PACKET_DATA_SIZE,
+        });
+    }
+    Ok(())
+}
+
+/// Prepends the compute budget of `config` to `instructions`.
+async fn with_compute_budget(
+    rpc: &impl RpcClient,
+    payer: &Pubkey,
+    config: AutoComputeBudget,
+    instructions: Vec<Instruction>,
+) -> Result<Vec<Instruction>> {
+    // simulate with the instructions that will be prepended, they cost units too
+    let mut budgeted = vec![
+        set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+        set_compute_unit_price(0),
+    ];
+    budgeted.extend(instructions);
+
+    let tx = Transaction::new_unsigned(Message::new(&budgeted, Some(payer)));
+    validate_transaction(&tx)?;
+    let result = rpc.simulate_transaction(&tx).await?;
+    if let Some(err) = result.err {
+        return Err(SimulationError {
+            err,
+            logs: result.logs.unwrap_or_default(),
+        }
+        .into());
+    }
+
+    let mut writable: Vec<Pubkey> = budgeted[2..]
+        .iter()
+        .flat_map(|ix| &ix.accounts)
+        .filter(|meta| meta.is_writable)
+        .map(|meta| meta.pubkey)
+        .collect();
+    writable.sort_unstable();
+    writable.dedup();
+    let price = config
+        .fee
+        .estimate(&rpc.get_recent_prioritization_fees(&writable).await?);
+
+    if price > 0 {
+        budgeted[1] = set_compute_unit_price(price);
+    } else {
+        budgeted.remove(1);
+    }
+    match result.units_consumed {
+        Some(units) if units > 0 => budgeted[0] = set_compute_unit_limit(config.unit_limit(units)),
+        // old nodes do not report units and a report of 0 says nothing, leave the
+        // default limit
+        _ => {
+            budgeted.remove(0);
+        }
+    }
+    Ok(budgeted)
+}
@@ -0,0 +1,178 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use serde_json::Value;
+use web_time::Instant;
+
+/// Settings of the opt-in [`ResponseCache`].
+#[derive(Debug, Clone)]
+pub struct ResponseCacheConfig {
+    /// Maximum number of cached responses, the least recently used is evicted first.
+    pub capacity: usize,
+    /// Time to live per rpc method, methods without an entry are never cached.
+    pub ttls: HashMap<String, Duration>,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            ttls: HashMap::from([
+                ("getLatestBlockhash".to_string(), Duration::from_millis(400)),
+                ("getAccountInfo".to_string(), Duration::from_secs(2)),
+                ("getBalance".to_string(), Duration::from_secs(2)),
+                ("getMultipleAccounts".to_string(), Duration::from_secs(2)),
+                ("getProgramAccounts".to_string(), Duration::from_secs(2)),
+                ("getTokenAccountBalance".to_string(), Duration::from_secs(2)),
+                ("getGenesisHash".to_string(), Duration::from_secs(3600)),
+                ("getVersion".to_string(), Duration::from_secs(60)),
+            ]),
+        }
+    }
+}
+
+impl ResponseCacheConfig {
+    pub fn with_ttl(mut self, method: impl Into<String>, ttl: Duration) -> Self {
+        self.ttls.insert(method.into(), ttl);
+        self
+    }
+
+    pub fn without_method(mut self, method: &str) -> Self {
+        self.ttls.remove(method);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    method: String,
+    params: String,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    result: Value,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    entries: HashMap<CacheKey, CacheEntry>,
+    clock: u64,
+}
+
+/// LRU cache of rpc results keyed by `(method, params)`.
+#[derive(Debug)]
+pub struct ResponseCache {
+    config: ResponseCacheConfig,
+    entries: Mutex<CacheEntries>,
+}
+
+impl ResponseCache {
+    pub fn new(config: ResponseCacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::default(),
+        }
+    }
+
+    pub fn is_cacheable(&self, method: &str) -> bool {
+        self.config.ttls.contains_key(method)
+    }
+
+    pub fn get(&self, method: &str, params: &Value) -> Option<Value> {
+        if !self.is_cacheable(method) {
+            return None;
+        }
+
+        let key = CacheKey {
+            method: method.to_string(),
+            params: params.to_string(),
+        };
+
+        let mut entries = self.entries.lock().ok()?;
+        entries.clock += 1;
+        let clock = entries.clock;
+
+        match entries.entries.get_mut(&key) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                entry.last_used = clock;
+                Some(entry.result.clone())
+            }
+            Some(_) => {
+                entries.entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, method: &str, params: &Value, result: Value) {
+        let Some(ttl) = self.config.ttls.get(method) else {
+            return;
+        };
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+
+        entries.clock += 1;
+        let entry = CacheEntry {
+            result,
+            expires_at: Instant::now() + *ttl,
+            last_used: entries.clock,
+        };
+        entries.entries.insert(
+            CacheKey {
+                method: method.to_string(),
+                params: params.to_string(),
+            },
+            entry,
+        );
+
+        while entries.entries.len() > self.config.capacity {
+            let lru = entries
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(k, _)| k.clone());
+
+            match lru {
+                Some(key) => entries.entries.remove(&key),
+                None => break,
+            };
+        }
+    }
+
+    /// Drops every cached response of `method`.
+    pub fn invalidate_method(&self, method: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.entries.retain(|key, _| key.method != method);
+        }
+    }
+
+    /// Drops every cached response whose params mention `needle`, e.g. an account address.
+    pub fn invalidate_matching(&self, needle: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries
+                .entries
+                .retain(|key, _| !key.params.contains(needle));
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.entries.clear();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .map(|e| e.entries.len())
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
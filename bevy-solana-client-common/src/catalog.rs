@@ -0,0 +1,336 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use serde_json::Value;
+
+use crate::{
+    error::{
+        transaction_error_of, RpcError, RpcErrorKind, SendTransactionError, SimulationError,
+        TransportError,
+    },
+    program_error::ProgramErrorTable,
+};
+
+/// Broad causes of failed requests and transactions, as far as players are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    RateLimited,
+    BlockhashExpired,
+    InsufficientFunds,
+    NodeBehind,
+    Timeout,
+    Network,
+    AccountNotFound,
+    SimulationFailed,
+    InvalidRequest,
+    Unknown,
+}
+
+impl ErrorCategory {
+    pub const ALL: [ErrorCategory; 10] = [
+        ErrorCategory::RateLimited,
+        ErrorCategory::BlockhashExpired,
+        ErrorCategory::InsufficientFunds,
+        ErrorCategory::NodeBehind,
+        ErrorCategory::Timeout,
+        ErrorCategory::Network,
+        ErrorCategory::AccountNotFound,
+        ErrorCategory::SimulationFailed,
+        ErrorCategory::InvalidRequest,
+        ErrorCategory::Unknown,
+    ];
+
+    /// Classifies an error returned by an [`RpcClient`](crate::rpc_client::RpcClient).
+    pub fn of(err: &anyhow::Error) -> ErrorCategory {
+        if let Some(err) = err.downcast_ref::<TransportError>() {
+            return match err {
+                TransportError::Http { status: 429, .. } => ErrorCategory::RateLimited,
+                TransportError::Timeout(_) => ErrorCategory::Timeout,
+                TransportError::Http { .. } | TransportError::Network(_) => ErrorCategory::Network,
+            };
+        }
+
+        if let Some(err) = err.downcast_ref::<RpcError>() {
+            return Self::of_rpc_error(err);
+        }
+
+        if err.downcast_ref::<SimulationError>().is_some() {
+            return ErrorCategory::SimulationFailed;
+        }
+
+        if err.to_string().contains("could not find account") {
+            return ErrorCategory::AccountNotFound;
+        }
+
+        ErrorCategory::Unknown
+    }
+
+    fn of_rpc_error(err: &RpcError) -> ErrorCategory {
+        match err.kind() {
+            RpcErrorKind::Other(429) => ErrorCategory::RateLimited,
+            RpcErrorKind::NodeUnhealthy | RpcErrorKind::MinContextSlotNotReached => {
+                ErrorCategory::NodeBehind
+            }
+            RpcErrorKind::InvalidParams
+            | RpcErrorKind::InvalidRequest
+            | RpcErrorKind::ParseError => ErrorCategory::InvalidRequest,
+            _ if err.is_blockhash_not_found() => ErrorCategory::BlockhashExpired,
+            _ if is_insufficient_funds(err) => ErrorCategory::InsufficientFunds,
+            RpcErrorKind::SendTransactionPreflightFailure => ErrorCategory::SimulationFailed,
+            _ => ErrorCategory::Unknown,
+        }
+    }
+}
+
+fn is_insufficient_funds(err: &RpcError) -> bool {
+    let tx_error = err.transaction_error().and_then(Value::as_str);
+    if matches!(
+        tx_error,
+        Some("InsufficientFundsForFee" | "InsufficientFundsForRent")
+    ) {
+        return true;
+    }
+
+    err.logs().is_some_and(|logs| {
+        logs.iter().any(|log| {
+            let log = log.to_ascii_lowercase();
+            log.contains("insufficient lamports") || log.contains("insufficient funds")
+        })
+    })
+}
+
+/// A short explanation of an error and what the player can do about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorExplanation {
+    pub summary: Cow<'static, str>,
+    pub hint: Cow<'static, str>,
+}
+
+impl ErrorExplanation {
+    pub fn new(summary: impl Into<Cow<'static, str>>, hint: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            summary: summary.into(),
+            hint: hint.into(),
+        }
+    }
+}
+
+/// Player facing explanations per [`ErrorCategory`] and language.
+///
+/// Ships English, German and Spanish texts, games can override any entry or add
+/// languages with [`ErrorCatalog::with_entry`]. Missing translations fall back to English.
+///
+/// Custom errors of the game's programs found in its [`ProgramErrorTable`] are explained
+/// with their own message instead of their category's.
+#[derive(Debug, Clone)]
+pub struct ErrorCatalog {
+    language: String,
+    entries: HashMap<(String, ErrorCategory), ErrorExplanation>,
+    program_errors: ProgramErrorTable,
+}
+
+pub const FALLBACK_LANGUAGE: &str = "en";
+
+impl Default for ErrorCatalog {
+    fn default() -> Self {
+        let mut catalog = Self {
+            language: FALLBACK_LANGUAGE.to_string(),
+            entries: HashMap::new(),
+            program_errors: ProgramErrorTable::default(),
+        };
+
+        for (language, texts) in [("en", EN), ("de", DE), ("es", ES)] {
+            for (category, (summary, hint)) in ErrorCategory::ALL.into_iter().zip(texts) {
+                catalog =
+                    catalog.with_entry(language, category, ErrorExplanation::new(summary, hint));
+            }
+        }
+
+        catalog
+    }
+}
+
+impl ErrorCatalog {
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn set_language(&mut self, language: impl Into<String>) {
+        self.language = language.into();
+    }
+
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.set_language(language);
+        self
+    }
+
+    /// Adds or overrides the explanation of `category` in `language`.
+    pub fn with_entry(
+        mut self,
+        language: impl Into<String>,
+        category: ErrorCategory,
+        explanation: ErrorExplanation,
+    ) -> Self {
+        self.entries
+            .insert((language.into(), category), explanation);
+        self
+    }
+
+    pub fn with_program_errors(mut self, program_errors: ProgramErrorTable) -> Self {
+        self.program_errors = program_errors;
+        self
+    }
+
+    pub fn program_errors(&self) -> &ProgramErrorTable {
+        &self.program_errors
+    }
+
+    pub fn program_errors_mut(&mut self) -> &mut ProgramErrorTable {
+        &mut self.program_errors
+    }
+
+    pub fn get(&self, category: ErrorCategory) -> Option<&ErrorExplanation> {
+        self.entries
+            .get(&(self.language.clone(), category))
+            .or_else(|| self.entries.get(&(FALLBACK_LANGUAGE.to_string(), category)))
+    }
+
+    /// Classifies `err` and returns its explanation in the current language.
+    pub fn explain(&self, err: &anyhow::Error) -> (ErrorCategory, ErrorExplanation) {
+        let category = ErrorCategory::of(err);
+        let mut explanation = self
+            .get(category)
+            .cloned()
+            .unwrap_or_else(|| ErrorExplanation::new(err.to_string(), ""));
+
+        let program_ids = err
+            .downcast_ref::<SendTransactionError>()
+            .map(|err| err.program_ids.as_slice())
+            .unwrap_or_default();
+        let program_error = transaction_error_of(err)
+            .and_then(|tx_err| self.program_errors.describe(&tx_err, program_ids).cloned());
+        if let Some(entry) = program_error {
+            explanation.summary = entry.message.into();
+        }
+        (category, explanation)
+    }
+}
+
+/// Texts in the order of [`ErrorCategory::ALL`].
+type Texts = [(&'static str, &'static str); 10];
+
+const EN: Texts = [
+    ("The network is busy.", "Wait a moment and try again."),
+    (
+        "The transaction expired before it was processed.",
+        "Try again, it will be sent with a fresh blockhash.",
+    ),
+    (
+        "Not enough funds for this action.",
+        "Top up your wallet with SOL or the required token.",
+    ),
+    (
+        "The game server's node is catching up with the network.",
+        "Try again in a few seconds.",
+    ),
+    (
+        "The network did not answer in time.",
+        "Check your connection and try again.",
+    ),
+    (
+        "Could not reach the network.",
+        "Check your connection and try again.",
+    ),
+    (
+        "The account does not exist yet.",
+        "It may still be created, try again shortly.",
+    ),
+    (
+        "The transaction would fail.",
+        "Check the requirements of this action and try again.",
+    ),
+    (
+        "The request was rejected.",
+        "Please report this to the game developers.",
+    ),
+    ("Something went wrong.", "Try again later."),
+];
+
+const DE: Texts = [
+    (
+        "Das Netzwerk ist ausgelastet.",
+        "Warte einen Moment und versuche es erneut.",
+    ),
+    (
+        "Die Transaktion ist abgelaufen, bevor sie verarbeitet wurde.",
+        "Versuche es erneut, sie wird mit einem neuen Blockhash gesendet.",
+    ),
+    (
+        "Nicht genug Guthaben für diese Aktion.",
+        "Lade deine Wallet mit SOL oder dem benötigten Token auf.",
+    ),
+    (
+        "Der Knoten des Spiels holt das Netzwerk gerade ein.",
+        "Versuche es in ein paar Sekunden erneut.",
+    ),
+    (
+        "Das Netzwerk hat nicht rechtzeitig geantwortet.",
+        "Prüfe deine Verbindung und versuche es erneut.",
+    ),
+    (
+        "Das Netzwerk ist nicht erreichbar.",
+        "Prüfe deine Verbindung und versuche es erneut.",
+    ),
+    (
+        "Das Konto existiert noch nicht.",
+        "Es wird eventuell noch erstellt, versuche es gleich erneut.",
+    ),
+    (
+        "Die Transaktion würde fehlschlagen.",
+        "Prüfe die Voraussetzungen dieser Aktion und versuche es erneut.",
+    ),
+    (
+        "Die Anfrage wurde abgelehnt.",
+        "Bitte melde das den Entwicklern des Spiels.",
+    ),
+    ("Etwas ist schiefgelaufen.", "Versuche es später erneut."),
+];
+
+const ES: Texts = [
+    (
+        "La red está saturada.",
+        "Espera un momento e inténtalo de nuevo.",
+    ),
+    (
+        "La transacción expiró antes de procesarse.",
+        "Inténtalo de nuevo, se enviará con un blockhash nuevo.",
+    ),
+    (
+        "No hay fondos suficientes para esta acción.",
+        "Recarga tu billetera con SOL o el token necesario.",
+    ),
+    (
+        "El nodo del juego se está poniendo al día con la red.",
+        "Inténtalo de nuevo en unos segundos.",
+    ),
+    (
+        "La red no respondió a tiempo.",
+        "Revisa tu conexión e inténtalo de nuevo.",
+    ),
+    (
+        "No se pudo conectar con la red.",
+        "Revisa tu conexión e inténtalo de nuevo.",
+    ),
+    (
+        "La cuenta todavía no existe.",
+        "Puede que aún se esté creando, inténtalo en breve.",
+    ),
+    (
+        "La transacción fallaría.",
+        "Revisa los requisitos de esta acción e inténtalo de nuevo.",
+    ),
+    (
+        "La solicitud fue rechazada.",
+        "Por favor, informa de esto a los desarrolladores del juego.",
+    ),
+    ("Algo salió mal.", "Inténtalo más tarde."),
+];
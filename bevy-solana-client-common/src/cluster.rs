@@ -0,0 +1,129 @@
+use std::{fmt, net::IpAddr};
+
+use solana_sdk::{hash::Hash, pubkey};
+
+use crate::{
+    config::SolanaClientConfig,
+    endpoint::RpcEndpoints,
+    rpc_client::{SOLANA_DEVNET_URL, SOLANA_MAINNET_URL, SOLANA_TESTNET_URL},
+};
+
+const MAINNET_GENESIS_HASH: Hash =
+    Hash::new_from_array(pubkey!("5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d").to_bytes());
+const DEVNET_GENESIS_HASH: Hash =
+    Hash::new_from_array(pubkey!("EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG").to_bytes());
+const TESTNET_GENESIS_HASH: Hash =
+    Hash::new_from_array(pubkey!("4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY").to_bytes());
+
+/// A Solana cluster, identified by the hash of its genesis block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+    Testnet,
+    /// Any other cluster, e.g. a local test validator.
+    Other(Hash),
+}
+
+impl Cluster {
+    /// The cluster whose genesis block hashes to `genesis_hash`, as returned by
+    /// [`RpcClient::get_genesis_hash`](crate::rpc_client::RpcClient::get_genesis_hash).
+    pub fn from_genesis_hash(genesis_hash: &Hash) -> Self {
+        match *genesis_hash {
+            MAINNET_GENESIS_HASH => Cluster::Mainnet,
+            DEVNET_GENESIS_HASH => Cluster::Devnet,
+            TESTNET_GENESIS_HASH => Cluster::Testnet,
+            other => Cluster::Other(other),
+        }
+    }
+
+    pub fn genesis_hash(&self) -> Hash {
+        match self {
+            Cluster::Mainnet => MAINNET_GENESIS_HASH,
+            Cluster::Devnet => DEVNET_GENESIS_HASH,
+            Cluster::Testnet => TESTNET_GENESIS_HASH,
+            Cluster::Other(hash) => *hash,
+        }
+    }
+}
+
+/// Whether `url` points at this machine, e.g. a local test validator on any port.
+pub fn is_loopback_url(url: &str) -> bool {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host_port.strip_prefix('[') {
+        // ipv6 literal, e.g. `[::1]:8899`
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => host_port.split(':').next().unwrap_or_default(),
+    };
+
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+impl fmt::Display for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cluster::Mainnet => write!(f, "mainnet-beta"),
+            Cluster::Devnet => write!(f, "devnet"),
+            Cluster::Testnet => write!(f, "testnet"),
+            Cluster::Other(hash) => write!(f, "cluster {}", hash),
+        }
+    }
+}
+
+/// Where to connect to for a cluster, e.g. one entry of a devnet/mainnet toggle.
+#[derive(Debug, Clone)]
+pub struct ClusterConfig {
+    /// Endpoints in order of preference, see [`RpcEndpoints`].
+    pub urls: Vec<String>,
+    pub client: SolanaClientConfig,
+    /// The cluster the endpoints are expected to be on, checked after connecting.
+    pub cluster: Option<Cluster>,
+}
+
+impl ClusterConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            urls: vec![url.into()],
+            client: SolanaClientConfig::default(),
+            cluster: None,
+        }
+    }
+
+    /// The public mainnet-beta endpoint, rate limited and not meant for production traffic.
+    pub fn mainnet() -> Self {
+        Self::new(SOLANA_MAINNET_URL).with_cluster(Cluster::Mainnet)
+    }
+
+    pub fn devnet() -> Self {
+        Self::new(SOLANA_DEVNET_URL).with_cluster(Cluster::Devnet)
+    }
+
+    pub fn testnet() -> Self {
+        Self::new(SOLANA_TESTNET_URL).with_cluster(Cluster::Testnet)
+    }
+
+    /// Adds an endpoint to fail over to.
+    pub fn with_fallback(mut self, url: impl Into<String>) -> Self {
+        self.urls.push(url.into());
+        self
+    }
+
+    pub fn with_client_config(mut self, client: SolanaClientConfig) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn with_cluster(mut self, cluster: Cluster) -> Self {
+        self.cluster = Some(cluster);
+        self
+    }
+
+    pub fn endpoints(&self) -> RpcEndpoints {
+        RpcEndpoints::new(self.urls.iter().cloned())
+    }
+}
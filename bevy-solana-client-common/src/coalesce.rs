@@ -0,0 +1,117 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use anyhow::{anyhow, Result};
+use futures_channel::oneshot;
+use serde_json::Value;
+
+use crate::error::{RpcError, TransportError};
+
+/// Methods with side effects, identical requests must not be merged or retried.
+const NON_IDEMPOTENT_METHODS: &[&str] = &["requestAirdrop"];
+
+/// Whether sending `method` twice has the same effect as sending it once.
+pub(crate) fn is_idempotent(method: &str) -> bool {
+    !NON_IDEMPOTENT_METHODS.contains(&method)
+}
+
+type RequestKey = (String, String);
+type Waiter = oneshot::Sender<Result<Value>>;
+
+/// Identical requests currently on the wire, so concurrent callers can share one response.
+#[derive(Debug, Default)]
+pub struct InFlightRequests {
+    pending: Mutex<HashMap<RequestKey, Vec<Waiter>>>,
+}
+
+pub enum InFlight<'a> {
+    /// No identical request is pending, the caller has to send it and complete the guard.
+    Leader(InFlightGuard<'a>),
+    /// An identical request is pending, its result will arrive on the receiver.
+    Follower(oneshot::Receiver<Result<Value>>),
+}
+
+impl InFlightRequests {
+    pub fn join(&self, method: &str, params: &Value) -> Option<InFlight<'_>> {
+        if !is_idempotent(method) {
+            return None;
+        }
+
+        let key = (method.to_string(), params.to_string());
+        let mut pending = self.pending.lock().ok()?;
+
+        match pending.get_mut(&key) {
+            Some(waiters) => {
+                let (sender, receiver) = oneshot::channel();
+                waiters.push(sender);
+                Some(InFlight::Follower(receiver))
+            }
+            None => {
+                pending.insert(key.clone(), vec![]);
+                Some(InFlight::Leader(InFlightGuard {
+                    requests: self,
+                    key: Some(key),
+                }))
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.lock().map(|p| p.len()).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Held by the caller that actually sends a request.
+///
+/// Dropping it without completing (e.g. when the future is cancelled) fails the waiters.
+pub struct InFlightGuard<'a> {
+    requests: &'a InFlightRequests,
+    key: Option<RequestKey>,
+}
+
+impl InFlightGuard<'_> {
+    pub fn complete(mut self, result: &Result<Value>) {
+        for waiter in self.take_waiters() {
+            let _ = waiter.send(share_result(result));
+        }
+    }
+
+    fn take_waiters(&mut self) -> Vec<Waiter> {
+        let Some(key) = self.key.take() else {
+            return vec![];
+        };
+
+        self.requests
+            .pending
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.remove(&key))
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        // dropping the senders wakes the waiters with `Canceled`
+        self.take_waiters();
+    }
+}
+
+/// Copies a result for another caller, keeping typed errors downcastable.
+fn share_result(result: &Result<Value>) -> Result<Value> {
+    match result {
+        Ok(value) => Ok(value.clone()),
+        Err(err) => {
+            if let Some(e) = err.downcast_ref::<RpcError>() {
+                Err(e.clone().into())
+            } else if let Some(e) = err.downcast_ref::<TransportError>() {
+                Err(e.clone().into())
+            } else {
+                Err(anyhow!("{:#}", err))
+            }
+        }
+    }
+}
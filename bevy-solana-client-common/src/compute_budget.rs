@@ -0,0 +1,168 @@
+use solana_sdk::{
+    compute_budget::{self, ComputeBudgetInstruction},
+    instruction::Instruction,
+};
+
+use crate::rpc_client::RpcPrioritizationFee;
+
+/// Most compute units a transaction can request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Caps the compute units the transaction may use, the fee is charged per requested unit.
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    ComputeBudgetInstruction::set_compute_unit_limit(units)
+}
+
+/// Sets the priority fee in micro-lamports per requested compute unit.
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    ComputeBudgetInstruction::set_compute_unit_price(micro_lamports)
+}
+
+pub fn is_compute_budget_instruction(ix: &Instruction) -> bool {
+    ix.program_id == compute_budget::id()
+}
+
+/// Picks a priority fee from the fees recently paid to write the same accounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityFeeEstimator {
+    /// Percentile of the recent fees to pay, 50 pays the median.
+    pub percentile: u8,
+    /// Micro-lamports per compute unit paid at least.
+    pub min_price: u64,
+    /// Micro-lamports per compute unit paid at most, however congested the cluster is.
+    pub max_price: u64,
+}
+
+impl Default for PriorityFeeEstimator {
+    fn default() -> Self {
+        Self {
+            percentile: 75,
+            min_price: 0,
+            max_price: 1_000_000,
+        }
+    }
+}
+
+impl PriorityFeeEstimator {
+    /// The price to pay given the `getRecentPrioritizationFees` of the last slots.
+    pub fn estimate(&self, recent: &[RpcPrioritizationFee]) -> u64 {
+        let mut fees: Vec<u64> = recent.iter().map(|fee| fee.prioritization_fee).collect();
+        fees.sort_unstable();
+
+        let fee = match fees.len() {
+            0 => 0,
+            len => {
+                let percentile = self.percentile.min(100) as usize;
+                fees[(len - 1) * percentile / 100]
+            }
+        };
+        fee.clamp(self.min_price, self.max_price.max(self.min_price))
+    }
+}
+
+/// Sizes the compute budget of built transactions, see
+/// [`TransactionBuilder::with_auto_compute_budget`](crate::builder::TransactionBuilder::with_auto_compute_budget).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoComputeBudget {
+    /// Extra units requested on top of what the simulation consumed, in percent.
+    pub margin_percent: u32,
+    pub fee: PriorityFeeEstimator,
+}
+
+impl Default for AutoComputeBudget {
+    fn default() -> Self {
+        Self {
+            margin_percent: 10,
+            fee: PriorityFeeEstimator::default(),
+        }
+    }
+}
+
+impl AutoComputeBudget {
+    pub fn with_margin_percent(mut self, margin_percent: u32) -> Self {
+        self.margin_percent = margin_percent;
+        self
+    }
+
+    pub fn with_fee(mut self, fee: PriorityFeeEstimator) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    /// The unit limit to request for a transaction that consumed `units` in simulation.
+    ///
+    /// A simulation reporting no units says nothing about the transaction, a limit of 0
+    /// would fail every instruction so the maximum is requested instead.
+    pub fn unit_limit(&self, units: u64) -> u32 {
+        if units == 0 {
+            return MAX_COMPUTE_UNIT_LIMIT;
+        }
+        let limit = units.saturating_mul(100 + self.margin_percent as u64) / 100;
+        limit.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fees(fees: &[u64]) -> Vec<RpcPrioritizationFee> {
+        fees.iter()
+            .enumerate()
+            .map(|(slot, &prioritization_fee)| RpcPrioritizationFee {
+                slot: slot as u64,
+                prioritization_fee,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn estimate_picks_percentile_of_sorted_fees() {
+        let recent = fees(&[400, 0, 100, 300, 200]);
+        let estimator = |percentile| PriorityFeeEstimator {
+            percentile,
+            ..Default::default()
+        };
+
+        assert_eq!(estimator(0).estimate(&recent), 0);
+        assert_eq!(estimator(50).estimate(&recent), 200);
+        assert_eq!(estimator(75).estimate(&recent), 300);
+        assert_eq!(estimator(100).estimate(&recent), 400);
+        // out of range percentiles pay the highest fee
+        assert_eq!(estimator(200).estimate(&recent), 400);
+    }
+
+    #[test]
+    fn estimate_clamps_to_bounds() {
+        let estimator = PriorityFeeEstimator {
+            percentile: 50,
+            min_price: 1_000,
+            max_price: 5_000,
+        };
+
+        assert_eq!(estimator.estimate(&[]), 1_000);
+        assert_eq!(estimator.estimate(&fees(&[10, 20, 30])), 1_000);
+        assert_eq!(estimator.estimate(&fees(&[10_000, 20_000])), 5_000);
+        assert_eq!(estimator.estimate(&fees(&[2_000, 3_000, 4_000])), 3_000);
+
+        // a max below the min never undercuts the min
+        let inverted = PriorityFeeEstimator {
+            max_price: 10,
+            ..estimator
+        };
+        assert_eq!(inverted.estimate(&fees(&[10_000])), 1_000);
+    }
+
+    #[test]
+    fn unit_limit_adds_margin_and_caps() {
+        let budget = AutoComputeBudget::default();
+        assert_eq!(budget.unit_limit(0), MAX_COMPUTE_UNIT_LIMIT);
+        assert_eq!(budget.unit_limit(100_000), 110_000);
+        assert_eq!(budget.with_margin_percent(0).unit_limit(123_456), 123_456);
+        assert_eq!(
+            budget.with_margin_percent(50).unit_limit(1_000_000),
+            MAX_COMPUTE_UNIT_LIMIT
+        );
+        assert_eq!(budget.unit_limit(u64::MAX), MAX_COMPUTE_UNIT_LIMIT);
+    }
+}
@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use solana_sdk::commitment_config::CommitmentConfig;
+use url::Url;
+
+use crate::{
+    cache::ResponseCacheConfig, encoding::RpcEncoding, retry::RetryPolicy,
+    sticky::StickySessionConfig,
+};
+
+/// Client-wide settings shared by every [`RpcClient`](crate::rpc_client::RpcClient) implementation.
+#[derive(Debug, Clone)]
+pub struct SolanaClientConfig {
+    /// Commitment used by every method that is not called through its
+    /// `*_with_commitment` variant. `getRecentPrioritizationFees`, `getSlotLeaders` and
+    /// `getClusterNodes` take none.
+    pub commitment: CommitmentConfig,
+    /// Commitment the `sendTransaction` preflight simulation runs at, `processed` by default.
+    ///
+    /// Preflighting at a higher commitment than the transaction's inputs have reached,
+    /// e.g. an account funded a moment ago, fails transactions that would land fine.
+    pub preflight_commitment: CommitmentConfig,
+    /// Backoff applied to rate limited, failing or lagging endpoints.
+    pub retry: RetryPolicy,
+    /// Opt-in cache of recent responses, disabled by default.
+    pub cache: Option<ResponseCacheConfig>,
+    /// How long a single attempt may take before it is cancelled, `None` waits forever.
+    pub timeout: Option<Duration>,
+    /// Encoding of transactions sent and account data requested, base64 by default.
+    pub encoding: RpcEncoding,
+    /// Extra headers sent with every request, e.g. provider API keys.
+    pub headers: Vec<(String, String)>,
+    /// Extra query parameters appended to every endpoint url, e.g. `api-key` tokens.
+    pub query_params: Vec<(String, String)>,
+    /// Opt-in replay of a provider's session token, disabled by default.
+    pub sticky_session: Option<StickySessionConfig>,
+}
+
+impl Default for SolanaClientConfig {
+    fn default() -> Self {
+        Self {
+            commitment: CommitmentConfig::finalized(),
+            preflight_commitment: CommitmentConfig::processed(),
+            retry: RetryPolicy::default(),
+            cache: None,
+            timeout: Some(Duration::from_secs(30)),
+            encoding: RpcEncoding::default(),
+            headers: Vec::new(),
+            query_params: Vec::new(),
+            sticky_session: None,
+        }
+    }
+}
+
+impl SolanaClientConfig {
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn with_preflight_commitment(mut self, preflight_commitment: CommitmentConfig) -> Self {
+        self.preflight_commitment = preflight_commitment;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_cache(mut self, cache: ResponseCacheConfig) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_encoding(mut self, encoding: RpcEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn with_query_param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query_params.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn with_sticky_session(mut self, sticky_session: StickySessionConfig) -> Self {
+        self.sticky_session = Some(sticky_session);
+        self
+    }
+
+    /// Returns `url` with the configured [`query_params`](Self::query_params) appended.
+    pub fn request_url(&self, url: &str) -> Result<String> {
+        if self.query_params.is_empty() {
+            return Ok(url.to_string());
+        }
+
+        let mut url = Url::parse(url).with_context(|| format!("invalid rpc url {:?}", url))?;
+        url.query_pairs_mut().extend_pairs(&self.query_params);
+        Ok(url.into())
+    }
+}
+
+/// Options of `sendTransaction`, the defaults match the node's except for the client's
+/// [`preflight_commitment`](SolanaClientConfig::preflight_commitment) and
+/// [`encoding`](SolanaClientConfig::encoding).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SendConfig {
+    /// Sends without simulating first, saves a round trip during congestion but failing
+    /// transactions still pay their fee.
+    pub skip_preflight: bool,
+    /// Overrides the client's preflight commitment.
+    pub preflight_commitment: Option<CommitmentConfig>,
+    /// How often the node forwards the transaction to leaders, `None` retries until the
+    /// blockhash expires. `Some(0)` leaves rebroadcasting to the caller.
+    pub max_retries: Option<usize>,
+    /// Rejects the send if the node has not reached this slot yet. Fails up front on
+    /// nodes known to be older than 1.14, which lack the field.
+    pub min_context_slot: Option<u64>,
+    /// Overrides the client's encoding of the wire transaction.
+    pub encoding: Option<RpcEncoding>,
+}
+
+impl SendConfig {
+    pub fn with_skip_preflight(mut self, skip_preflight: bool) -> Self {
+        self.skip_preflight = skip_preflight;
+        self
+    }
+
+    pub fn with_preflight_commitment(mut self, preflight_commitment: CommitmentConfig) -> Self {
+        self.preflight_commitment = Some(preflight_commitment);
+        self
+    }
+
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn with_min_context_slot(mut self, min_context_slot: u64) -> Self {
+        self.min_context_slot = Some(min_context_slot);
+        self
+    }
+
+    pub fn with_encoding(mut self, encoding: RpcEncoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+}
+
+/// How [`send_with_rebroadcast`](crate::rpc_client::RpcClient::send_with_rebroadcast)
+/// resubmits a transaction until it lands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebroadcastConfig {
+    /// Slots between resubmissions.
+    pub interval_slots: u64,
+    /// Commitment the transaction has to reach.
+    pub commitment: CommitmentConfig,
+    /// Options of the first submission, resubmissions skip the preflight and the node's
+    /// own retries.
+    pub send: SendConfig,
+}
+
+impl Default for RebroadcastConfig {
+    fn default() -> Self {
+        Self {
+            interval_slots: 4,
+            commitment: CommitmentConfig::confirmed(),
+            send: SendConfig::default().with_max_retries(0),
+        }
+    }
+}
+
+impl RebroadcastConfig {
+    pub fn with_interval_slots(mut self, interval_slots: u64) -> Self {
+        self.interval_slots = interval_slots;
+        self
+    }
+
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn with_send(mut self, send: SendConfig) -> Self {
+        self.send = send;
+        self
+    }
+}
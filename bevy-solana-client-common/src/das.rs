@@ -0,0 +1,214 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use serde::{Deserialize, Deserializer};
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::rpc_client::RpcClient;
+
+/// Most assets a single `getAssetsByOwner` page holds.
+pub const MAX_PAGE_LIMIT: u32 = 1000;
+
+/// An asset as returned by the Digital Asset Standard API, compressed or not.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DasAsset {
+    #[serde(deserialize_with = "pubkey_from_str")]
+    pub id: Pubkey,
+    /// E.g. `V1_NFT`, `ProgrammableNFT` or `FungibleToken`.
+    pub interface: String,
+    #[serde(default)]
+    pub content: DasContent,
+    pub compression: Option<DasCompression>,
+    #[serde(default)]
+    pub grouping: Vec<DasGroup>,
+    #[serde(default)]
+    pub creators: Vec<DasCreator>,
+    pub ownership: DasOwnership,
+    #[serde(default)]
+    pub mutable: bool,
+    #[serde(default)]
+    pub burnt: bool,
+}
+
+impl DasAsset {
+    pub fn is_compressed(&self) -> bool {
+        self.compression
+            .as_ref()
+            .is_some_and(|compression| compression.compressed)
+    }
+
+    /// The collection the asset is grouped under, DAS only groups verified collections.
+    pub fn collection(&self) -> Option<Pubkey> {
+        self.grouping
+            .iter()
+            .find(|group| group.group_key == "collection")
+            .and_then(|group| Pubkey::from_str(&group.group_value).ok())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.content.metadata.name
+    }
+
+    /// Url of the asset's image, as served by the provider's cdn if it has one.
+    pub fn image_url(&self) -> Option<&str> {
+        self.content
+            .links
+            .as_ref()?
+            .get("image")?
+            .as_str()
+            .filter(|url| !url.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DasContent {
+    pub json_uri: String,
+    pub metadata: DasMetadata,
+    /// Links of the off-chain JSON, e.g. `image` and `external_url`.
+    pub links: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DasMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub description: String,
+    pub attributes: Vec<crate::metadata::NftAttribute>,
+}
+
+/// Where a compressed asset lives in its merkle tree.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DasCompression {
+    pub compressed: bool,
+    #[serde(default)]
+    pub data_hash: String,
+    #[serde(default)]
+    pub creator_hash: String,
+    #[serde(default)]
+    pub tree: String,
+    #[serde(default)]
+    pub leaf_id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DasGroup {
+    pub group_key: String,
+    pub group_value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DasCreator {
+    #[serde(deserialize_with = "pubkey_from_str")]
+    pub address: Pubkey,
+    pub share: u8,
+    pub verified: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DasOwnership {
+    #[serde(deserialize_with = "pubkey_from_str")]
+    pub owner: Pubkey,
+    #[serde(default, deserialize_with = "optional_pubkey_from_str")]
+    pub delegate: Option<Pubkey>,
+    #[serde(default)]
+    pub delegated: bool,
+    #[serde(default)]
+    pub frozen: bool,
+}
+
+/// A page of `getAssetsByOwner`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DasAssetPage {
+    pub total: u32,
+    pub limit: u32,
+    pub page: u32,
+    pub items: Vec<DasAsset>,
+}
+
+/// The merkle proof of a compressed asset, needed to transfer or burn it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DasAssetProof {
+    #[serde(deserialize_with = "pubkey_from_str")]
+    pub root: Pubkey,
+    #[serde(deserialize_with = "pubkeys_from_str")]
+    pub proof: Vec<Pubkey>,
+    pub node_index: u64,
+    #[serde(deserialize_with = "pubkey_from_str")]
+    pub leaf: Pubkey,
+    #[serde(deserialize_with = "pubkey_from_str")]
+    pub tree_id: Pubkey,
+}
+
+/// Fetches the asset `id`, see [`DasAsset`]. Needs an rpc provider implementing DAS.
+pub async fn get_asset(rpc: &impl RpcClient, id: &Pubkey) -> Result<DasAsset> {
+    rpc.rpc_post("getAsset", json!({ "id": id.to_string() }))
+        .await
+}
+
+/// A page of the assets held by `owner`, compressed ones included, `page` starts at 1.
+pub async fn get_assets_by_owner(
+    rpc: &impl RpcClient,
+    owner: &Pubkey,
+    page: u32,
+    limit: u32,
+) -> Result<DasAssetPage> {
+    rpc.rpc_post(
+        "getAssetsByOwner",
+        json!({
+            "ownerAddress": owner.to_string(),
+            "page": page,
+            "limit": limit.min(MAX_PAGE_LIMIT),
+        }),
+    )
+    .await
+}
+
+/// Every asset held by `owner`, fetched page by page.
+pub async fn get_all_assets_by_owner(
+    rpc: &impl RpcClient,
+    owner: &Pubkey,
+) -> Result<Vec<DasAsset>> {
+    let mut assets = vec![];
+    for page in 1.. {
+        let mut page = get_assets_by_owner(rpc, owner, page, MAX_PAGE_LIMIT).await?;
+        // providers may cap the limit below the requested one, a page is only the last
+        // one if it is short of the limit the provider applied
+        let last = page.items.is_empty() || page.items.len() < page.limit as usize;
+        assets.append(&mut page.items);
+        if last {
+            break;
+        }
+    }
+    Ok(assets)
+}
+
+pub async fn get_asset_proof(rpc: &impl RpcClient, id: &Pubkey) -> Result<DasAssetProof> {
+    rpc.rpc_post("getAssetProof", json!({ "id": id.to_string() }))
+        .await
+}
+
+fn pubkey_from_str<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    Pubkey::from_str(&s).map_err(serde::de::Error::custom)
+}
+
+fn optional_pubkey_from_str<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Pubkey>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) if !s.is_empty() => Pubkey::from_str(&s)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+fn pubkeys_from_str<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Pubkey>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|s| Pubkey::from_str(s).map_err(serde::de::Error::custom))
+        .collect()
+}
@@ -0,0 +1,62 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use solana_sdk::bs58;
+
+/// Binary encoding used for transactions and account data on the wire.
+///
+/// Base58 is limited to small payloads by most nodes, and some providers reject it
+/// entirely for large transactions or accounts, so base64 is the default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RpcEncoding {
+    Base58,
+    #[default]
+    Base64,
+}
+
+impl RpcEncoding {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            RpcEncoding::Base58 => "base58",
+            RpcEncoding::Base64 => "base64",
+        }
+    }
+
+    pub fn encode(self, bytes: impl AsRef<[u8]>) -> String {
+        match self {
+            RpcEncoding::Base58 => bs58::encode(bytes).into_string(),
+            RpcEncoding::Base64 => BASE64_STANDARD.encode(bytes),
+        }
+    }
+
+    pub fn decode(self, data: &str) -> Result<Vec<u8>> {
+        match self {
+            RpcEncoding::Base58 => bs58::decode(data)
+                .into_vec()
+                .map_err(|e| anyhow!("invalid base58 data: {}", e)),
+            RpcEncoding::Base64 => BASE64_STANDARD
+                .decode(data)
+                .map_err(|e| anyhow!("invalid base64 data: {}", e)),
+        }
+    }
+}
+
+impl FromStr for RpcEncoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "base58" => Ok(RpcEncoding::Base58),
+            "base64" => Ok(RpcEncoding::Base64),
+            _ => bail!("unsupported encoding {:?}", s),
+        }
+    }
+}
+
+impl fmt::Display for RpcEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,162 @@
+use std::{sync::Mutex, time::Duration};
+
+use web_time::Instant;
+
+use crate::error::{RpcError, RpcErrorKind, TransportError};
+
+/// Request statistics and cooldown state of a single endpoint.
+#[derive(Debug, Clone, Default)]
+pub struct EndpointHealth {
+    pub consecutive_failures: u32,
+    pub total_requests: u64,
+    pub total_failures: u64,
+    pub cooldown_until: Option<Instant>,
+}
+
+impl EndpointHealth {
+    pub fn is_cooling_down(&self, now: Instant) -> bool {
+        self.cooldown_until.is_some_and(|until| until > now)
+    }
+}
+
+#[derive(Debug)]
+struct RpcEndpoint {
+    url: String,
+    health: Mutex<EndpointHealth>,
+}
+
+/// Ordered list of rpc endpoints, e.g. a primary provider followed by public fallbacks.
+///
+/// Requests go to the first endpoint that is not cooling down. An endpoint
+/// that fails `failure_threshold` times in a row is skipped for `cooldown`,
+/// after which it is preferred again according to its position.
+#[derive(Debug)]
+pub struct RpcEndpoints {
+    endpoints: Vec<RpcEndpoint>,
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+impl RpcEndpoints {
+    /// # Panics
+    ///
+    /// Panics if `urls` is empty.
+    pub fn new<S: Into<String>>(urls: impl IntoIterator<Item = S>) -> Self {
+        let endpoints: Vec<RpcEndpoint> = urls
+            .into_iter()
+            .map(|url| RpcEndpoint {
+                url: url.into(),
+                health: Mutex::default(),
+            })
+            .collect();
+        assert!(
+            !endpoints.is_empty(),
+            "at least one rpc endpoint is required"
+        );
+
+        Self {
+            endpoints,
+            failure_threshold: 1,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+
+    pub fn single(url: impl Into<String>) -> Self {
+        Self::new([url.into()])
+    }
+
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    pub fn with_failure_threshold(mut self, failure_threshold: u32) -> Self {
+        self.failure_threshold = failure_threshold.max(1);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    pub fn url(&self, index: usize) -> &str {
+        &self.endpoints[index].url
+    }
+
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        self.endpoints.iter().map(|e| e.url.as_str())
+    }
+
+    pub fn health(&self, index: usize) -> EndpointHealth {
+        self.endpoints[index]
+            .health
+            .lock()
+            .map(|h| h.clone())
+            .unwrap_or_default()
+    }
+
+    /// Picks the first healthy endpoint, or the one that recovers soonest if all are cooling down.
+    pub fn select(&self) -> usize {
+        let now = Instant::now();
+        let healths: Vec<EndpointHealth> = (0..self.len()).map(|i| self.health(i)).collect();
+
+        healths
+            .iter()
+            .position(|h| !h.is_cooling_down(now))
+            .or_else(|| {
+                healths
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, h)| h.cooldown_until)
+                    .map(|(i, _)| i)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether any endpoint is currently available without waiting for a cooldown.
+    pub fn has_available(&self) -> bool {
+        let now = Instant::now();
+        (0..self.len()).any(|i| !self.health(i).is_cooling_down(now))
+    }
+
+    pub fn report_success(&self, index: usize) {
+        if let Ok(mut health) = self.endpoints[index].health.lock() {
+            health.total_requests += 1;
+            health.consecutive_failures = 0;
+            health.cooldown_until = None;
+        }
+    }
+
+    pub fn report_failure(&self, index: usize) {
+        let Ok(mut health) = self.endpoints[index].health.lock() else {
+            return;
+        };
+
+        health.total_requests += 1;
+        health.total_failures += 1;
+        health.consecutive_failures += 1;
+
+        if health.consecutive_failures >= self.failure_threshold {
+            log::debug!(
+                "rpc endpoint {} is cooling down for {:?}",
+                self.endpoints[index].url,
+                self.cooldown
+            );
+            health.cooldown_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+/// Whether an error says something about the endpoint rather than the request.
+pub fn is_endpoint_failure(err: &anyhow::Error) -> bool {
+    if err.downcast_ref::<TransportError>().is_some() {
+        return true;
+    }
+
+    err.downcast_ref::<RpcError>()
+        .is_some_and(|e| e.kind() == RpcErrorKind::NodeUnhealthy)
+}
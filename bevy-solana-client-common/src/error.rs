@@ -0,0 +1,312 @@
+use std::{fmt, time::Duration};
+
+use serde_json::Value;
+use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::TransactionError};
+
+/// Well known JSON-RPC and Solana RPC error codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcErrorKind {
+    ParseError,
+    InvalidRequest,
+    MethodNotFound,
+    InvalidParams,
+    InternalError,
+    BlockCleanedUp,
+    /// `sendTransaction` preflight simulation failed, see [`RpcError::logs`].
+    SendTransactionPreflightFailure,
+    TransactionSignatureVerificationFailure,
+    BlockNotAvailable,
+    /// The node is lagging behind the cluster.
+    NodeUnhealthy,
+    TransactionPrecompileVerificationFailure,
+    SlotSkipped,
+    NoSnapshot,
+    LongTermStorageSlotSkipped,
+    KeyExcludedFromSecondaryIndex,
+    TransactionHistoryNotAvailable,
+    ScanError,
+    TransactionSignatureLenMismatch,
+    BlockStatusNotAvailableYet,
+    UnsupportedTransactionVersion,
+    MinContextSlotNotReached,
+    Other(i64),
+}
+
+impl From<i64> for RpcErrorKind {
+    fn from(code: i64) -> Self {
+        match code {
+            -32700 => RpcErrorKind::ParseError,
+            -32600 => RpcErrorKind::InvalidRequest,
+            -32601 => RpcErrorKind::MethodNotFound,
+            -32602 => RpcErrorKind::InvalidParams,
+            -32603 => RpcErrorKind::InternalError,
+            -32001 => RpcErrorKind::BlockCleanedUp,
+            -32002 => RpcErrorKind::SendTransactionPreflightFailure,
+            -32003 => RpcErrorKind::TransactionSignatureVerificationFailure,
+            -32004 => RpcErrorKind::BlockNotAvailable,
+            -32005 => RpcErrorKind::NodeUnhealthy,
+            -32006 => RpcErrorKind::TransactionPrecompileVerificationFailure,
+            -32007 => RpcErrorKind::SlotSkipped,
+            -32008 => RpcErrorKind::NoSnapshot,
+            -32009 => RpcErrorKind::LongTermStorageSlotSkipped,
+            -32010 => RpcErrorKind::KeyExcludedFromSecondaryIndex,
+            -32011 => RpcErrorKind::TransactionHistoryNotAvailable,
+            -32012 => RpcErrorKind::ScanError,
+            -32013 => RpcErrorKind::TransactionSignatureLenMismatch,
+            -32014 => RpcErrorKind::BlockStatusNotAvailableYet,
+            -32015 => RpcErrorKind::UnsupportedTransactionVersion,
+            -32016 => RpcErrorKind::MinContextSlotNotReached,
+            code => RpcErrorKind::Other(code),
+        }
+    }
+}
+
+/// The `error` object of a JSON-RPC response.
+///
+/// Returned inside [`anyhow::Error`], so callers can match on it with
+/// `err.downcast_ref::<RpcError>()` instead of comparing strings.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    pub fn kind(&self) -> RpcErrorKind {
+        self.code.into()
+    }
+
+    /// Program logs of a failed preflight simulation.
+    pub fn logs(&self) -> Option<Vec<&str>> {
+        let logs = self.data.as_ref()?.get("logs")?.as_array()?;
+        Some(logs.iter().filter_map(Value::as_str).collect())
+    }
+
+    /// The `TransactionError` reported by a failed preflight simulation.
+    pub fn transaction_error(&self) -> Option<&Value> {
+        self.data.as_ref()?.get("err").filter(|err| !err.is_null())
+    }
+
+    /// [`transaction_error`](Self::transaction_error) decoded.
+    pub fn decoded_transaction_error(&self) -> Option<TransactionError> {
+        decode_transaction_error(self.transaction_error()?)
+    }
+
+    /// How many slots an unhealthy node is behind, when it reports it.
+    pub fn num_slots_behind(&self) -> Option<u64> {
+        self.data.as_ref()?.get("numSlotsBehind")?.as_u64()
+    }
+
+    pub fn is_blockhash_not_found(&self) -> bool {
+        self.transaction_error()
+            .and_then(Value::as_str)
+            .is_some_and(|err| err == "BlockhashNotFound")
+    }
+
+    pub fn is_node_behind(&self) -> bool {
+        self.kind() == RpcErrorKind::NodeUnhealthy
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rpc error {}: {}", self.code, self.message)?;
+
+        if let Some(logs) = self.logs() {
+            for log in logs {
+                write!(f, "\n  {}", log)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Failure to get a JSON-RPC response out of the endpoint at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportError {
+    /// The endpoint answered with a non-success HTTP status.
+    Http { status: u16, body: String },
+    /// The request could not be sent or the connection dropped.
+    Network(String),
+    /// No response arrived within the configured timeout, the request was cancelled.
+    Timeout(Duration),
+}
+
+impl TransportError {
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            TransportError::Http { status, .. } => Some(*status),
+            TransportError::Network(_) | TransportError::Timeout(_) => None,
+        }
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, TransportError::Timeout(_))
+    }
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportError::Http { status, body } => write!(f, "http error {}: {}", status, body),
+            TransportError::Network(message) => write!(f, "network error: {}", message),
+            TransportError::Timeout(timeout) => write!(f, "request timed out after {:?}", timeout),
+        }
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+/// A simulated transaction failed, e.g. a view call that hit a program error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationError {
+    /// The `TransactionError` reported by the node.
+    pub err: Value,
+    pub logs: Vec<String>,
+}
+
+impl SimulationError {
+    pub fn transaction_error(&self) -> Option<TransactionError> {
+        decode_transaction_error(&self.err)
+    }
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "simulation failed: {}", self.err)?;
+
+        for log in &self.logs {
+            write!(f, "\n  {}", log)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for SimulationError {}
+
+/// A sent transaction did not reach the requested commitment.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfirmationError {
+    /// The transaction landed but failed, its fee was still charged.
+    Failed { signature: Signature, err: Value },
+    /// No status within `timeout`, the transaction may still land until its blockhash
+    /// expires.
+    TimedOut {
+        signature: Signature,
+        timeout: Duration,
+    },
+    /// The block height passed the transaction's last valid block height, it can no
+    /// longer land and has to be signed again with a new blockhash.
+    Expired {
+        signature: Signature,
+        last_valid_block_height: u64,
+    },
+}
+
+impl ConfirmationError {
+    /// The decoded error of a [`ConfirmationError::Failed`] transaction.
+    pub fn transaction_error(&self) -> Option<TransactionError> {
+        match self {
+            ConfirmationError::Failed { err, .. } => decode_transaction_error(err),
+            ConfirmationError::TimedOut { .. } | ConfirmationError::Expired { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for ConfirmationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfirmationError::Failed { signature, err } => {
+                write!(f, "transaction {} failed: {}", signature, err)
+            }
+            ConfirmationError::TimedOut { signature, timeout } => write!(
+                f,
+                "transaction {} was not confirmed within {:?}",
+                signature, timeout
+            ),
+            ConfirmationError::Expired {
+                signature,
+                last_valid_block_height,
+            } => write!(
+                f,
+                "transaction {} expired after block height {}",
+                signature, last_valid_block_height
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfirmationError {}
+
+/// A transaction breaks a limit of the cluster and would be rejected once sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionLimitError {
+    /// The serialized transaction does not fit into a packet.
+    TooLarge { size: usize, max: usize },
+    /// The transaction locks more accounts than the runtime allows.
+    TooManyAccounts { count: usize, max: usize },
+    /// The signature slots do not match the signers the message requires.
+    SignatureCount { required: usize, present: usize },
+}
+
+impl fmt::Display for TransactionLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionLimitError::TooLarge { size, max } => write!(
+                f,
+                "transaction is {} bytes but at most {} fit into a packet, split its instructions or use an address lookup table",
+                size, max
+            ),
+            TransactionLimitError::TooManyAccounts { count, max } => write!(
+                f,
+                "transaction locks {} accounts but at most {} are allowed",
+                count, max
+            ),
+            TransactionLimitError::SignatureCount { required, present } => write!(
+                f,
+                "transaction requires {} signatures but has {} signature slots",
+                required, present
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TransactionLimitError {}
+
+/// Context of a failed `sendTransaction`, keeps the programs of the transaction's
+/// instructions so a custom error can be traced back to the program that raised it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendTransactionError {
+    pub program_ids: Vec<Pubkey>,
+}
+
+impl fmt::Display for SendTransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "could not send transaction")
+    }
+}
+
+impl std::error::Error for SendTransactionError {}
+
+/// Decodes a `TransactionError` as reported in signature statuses and simulations,
+/// `None` for errors this sdk version does not know.
+pub fn decode_transaction_error(err: &Value) -> Option<TransactionError> {
+    serde_json::from_value(err.clone()).ok()
+}
+
+/// The `TransactionError` behind an error of an [`RpcClient`](crate::rpc_client::RpcClient),
+/// from a failed preflight, simulation or confirmation.
+pub fn transaction_error_of(err: &anyhow::Error) -> Option<TransactionError> {
+    if let Some(err) = err.downcast_ref::<RpcError>() {
+        return err.decoded_transaction_error();
+    }
+    if let Some(err) = err.downcast_ref::<SimulationError>() {
+        return err.transaction_error();
+    }
+    err.downcast_ref::<ConfirmationError>()?.transaction_error()
+}
@@ -0,0 +1,409 @@
+//! Deterministic accounts and instruction assertions for locking in transaction contents.
+//!
+//! Instruction builders of this crate pin their encoding with golden-byte tests below,
+//! games can use the same helpers to pin the instructions they build.
+
+use std::fmt::Write;
+
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+/// Fee payer and default signer of fixture instructions.
+pub const PAYER: Pubkey = Pubkey::new_from_array([1; 32]);
+/// Receiver of fixture transfers.
+pub const RECIPIENT: Pubkey = Pubkey::new_from_array([2; 32]);
+/// Mint of fixture token instructions.
+pub const MINT: Pubkey = Pubkey::new_from_array([3; 32]);
+/// Authority of fixture token, stake and program accounts.
+pub const AUTHORITY: Pubkey = Pubkey::new_from_array([4; 32]);
+/// Amount of fixture transfers, chosen so every byte of its encoding differs.
+pub const AMOUNT: u64 = 0x0807_0605_0403_0201;
+
+/// Asserts that two instructions are identical, describing every difference on failure.
+///
+/// Unlike `assert_eq!` the message names the mismatching account or data offset
+/// instead of dumping both instructions.
+#[track_caller]
+pub fn assert_ix_eq(actual: &Instruction, expected: &Instruction) {
+    if let Some(diff) = instruction_diff(actual, expected) {
+        panic!("instructions differ:\n{}", diff);
+    }
+}
+
+/// Describes how `actual` differs from `expected`, `None` if they are identical.
+pub fn instruction_diff(actual: &Instruction, expected: &Instruction) -> Option<String> {
+    let mut diff = String::new();
+
+    if actual.program_id != expected.program_id {
+        let _ = writeln!(
+            diff,
+            "  program id: {} != {}",
+            actual.program_id, expected.program_id
+        );
+    }
+
+    if actual.accounts.len() != expected.accounts.len() {
+        let _ = writeln!(
+            diff,
+            "  account count: {} != {}",
+            actual.accounts.len(),
+            expected.accounts.len()
+        );
+    }
+    for (i, (a, e)) in actual.accounts.iter().zip(&expected.accounts).enumerate() {
+        if a != e {
+            let _ = writeln!(
+                diff,
+                "  account #{}: {} (signer: {}, writable: {}) != {} (signer: {}, writable: {})",
+                i, a.pubkey, a.is_signer, a.is_writable, e.pubkey, e.is_signer, e.is_writable
+            );
+        }
+    }
+
+    if actual.data != expected.data {
+        let offset = actual
+            .data
+            .iter()
+            .zip(&expected.data)
+            .position(|(a, e)| a != e)
+            .unwrap_or(actual.data.len().min(expected.data.len()));
+        let _ = writeln!(
+            diff,
+            "  data differs at byte {}:\n    actual:   {}\n    expected: {}",
+            offset,
+            hex(&actual.data),
+            hex(&expected.data)
+        );
+    }
+
+    (!diff.is_empty()).then_some(diff)
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{instruction::AccountMeta, system_instruction, system_program};
+
+    use super::*;
+    use crate::{compute_budget, lookup_table, memo, metadata, system, token};
+
+    fn transfer_fixture() -> Instruction {
+        Instruction {
+            program_id: system_program::id(),
+            accounts: vec![
+                AccountMeta::new(PAYER, true),
+                AccountMeta::new(RECIPIENT, false),
+            ],
+            data: vec![2, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8],
+        }
+    }
+
+    #[test]
+    fn system_transfer_golden_bytes() {
+        let ix = system_instruction::transfer(&PAYER, &RECIPIENT, AMOUNT);
+        assert_ix_eq(&ix, &transfer_fixture());
+    }
+
+    #[test]
+    fn compute_budget_golden_bytes() {
+        assert_ix_eq(
+            &compute_budget::set_compute_unit_limit(0x0403_0201),
+            &Instruction {
+                program_id: solana_sdk::compute_budget::id(),
+                accounts: vec![],
+                data: vec![2, 1, 2, 3, 4],
+            },
+        );
+        assert_ix_eq(
+            &compute_budget::set_compute_unit_price(AMOUNT),
+            &Instruction {
+                program_id: solana_sdk::compute_budget::id(),
+                accounts: vec![],
+                data: vec![3, 1, 2, 3, 4, 5, 6, 7, 8],
+            },
+        );
+    }
+
+    #[test]
+    fn memo_golden_bytes() {
+        assert_ix_eq(
+            &memo::memo("gg", &[PAYER]),
+            &Instruction {
+                program_id: memo::MEMO_PROGRAM_ID,
+                accounts: vec![AccountMeta::new_readonly(PAYER, true)],
+                data: vec![b'g', b'g'],
+            },
+        );
+    }
+
+    #[test]
+    fn assign_golden_bytes() {
+        assert_ix_eq(
+            &system::assign(&RECIPIENT, &AUTHORITY),
+            &Instruction {
+                program_id: system_program::id(),
+                accounts: vec![AccountMeta::new(RECIPIENT, true)],
+                data: [vec![1, 0, 0, 0], AUTHORITY.to_bytes().to_vec()].concat(),
+            },
+        );
+    }
+
+    #[test]
+    fn token_transfer_golden_bytes() {
+        assert_ix_eq(
+            &token::transfer_checked(
+                &token::TOKEN_PROGRAM_ID,
+                &PAYER,
+                &MINT,
+                &RECIPIENT,
+                &AUTHORITY,
+                AMOUNT,
+                9,
+            ),
+            &Instruction {
+                program_id: token::TOKEN_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(PAYER, false),
+                    AccountMeta::new_readonly(MINT, false),
+                    AccountMeta::new(RECIPIENT, false),
+                    AccountMeta::new_readonly(AUTHORITY, true),
+                ],
+                data: vec![12, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+            },
+        );
+        assert_ix_eq(
+            &token::transfer(
+                &token::TOKEN_PROGRAM_ID,
+                &PAYER,
+                &RECIPIENT,
+                &AUTHORITY,
+                AMOUNT,
+            ),
+            &Instruction {
+                program_id: token::TOKEN_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(PAYER, false),
+                    AccountMeta::new(RECIPIENT, false),
+                    AccountMeta::new_readonly(AUTHORITY, true),
+                ],
+                data: vec![3, 1, 2, 3, 4, 5, 6, 7, 8],
+            },
+        );
+    }
+
+    #[test]
+    fn create_associated_token_account_golden_bytes() {
+        let ata = token::associated_token_address(&RECIPIENT, &MINT, &token::TOKEN_PROGRAM_ID);
+        assert_ix_eq(
+            &token::create_associated_token_account_idempotent(
+                &PAYER,
+                &RECIPIENT,
+                &MINT,
+                &token::TOKEN_PROGRAM_ID,
+            ),
+            &Instruction {
+                program_id: token::ASSOCIATED_TOKEN_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(PAYER, true),
+                    AccountMeta::new(ata, false),
+                    AccountMeta::new_readonly(RECIPIENT, false),
+                    AccountMeta::new_readonly(MINT, false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                    AccountMeta::new_readonly(token::TOKEN_PROGRAM_ID, false),
+                ],
+                data: vec![1],
+            },
+        );
+    }
+
+    #[test]
+    fn initialize_mint_golden_bytes() {
+        assert_ix_eq(
+            &token::initialize_mint2(&token::TOKEN_PROGRAM_ID, &MINT, &AUTHORITY, None, 6),
+            &Instruction {
+                program_id: token::TOKEN_PROGRAM_ID,
+                accounts: vec![AccountMeta::new(MINT, false)],
+                data: [vec![20, 6], AUTHORITY.to_bytes().to_vec(), vec![0]].concat(),
+            },
+        );
+        assert_ix_eq(
+            &token::initialize_mint2(
+                &token::TOKEN_2022_PROGRAM_ID,
+                &MINT,
+                &AUTHORITY,
+                Some(&PAYER),
+                0,
+            ),
+            &Instruction {
+                program_id: token::TOKEN_2022_PROGRAM_ID,
+                accounts: vec![AccountMeta::new(MINT, false)],
+                data: [
+                    vec![20, 0],
+                    AUTHORITY.to_bytes().to_vec(),
+                    vec![1],
+                    PAYER.to_bytes().to_vec(),
+                ]
+                .concat(),
+            },
+        );
+    }
+
+    #[test]
+    fn create_metadata_golden_bytes() {
+        assert_ix_eq(
+            &metadata::create_metadata_account_v3(
+                &MINT, &AUTHORITY, &PAYER, "Gem", "GEM", "u", true,
+            ),
+            &Instruction {
+                program_id: metadata::TOKEN_METADATA_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(metadata::metadata_address(&MINT), false),
+                    AccountMeta::new_readonly(MINT, false),
+                    AccountMeta::new_readonly(AUTHORITY, true),
+                    AccountMeta::new(PAYER, true),
+                    AccountMeta::new_readonly(AUTHORITY, true),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                ],
+                data: vec![
+                    33, 3, 0, 0, 0, b'G', b'e', b'm', 3, 0, 0, 0, b'G', b'E', b'M', 1, 0, 0, 0,
+                    b'u', 0, 0, 0, 0, 0, 1, 0,
+                ],
+            },
+        );
+    }
+
+    #[test]
+    fn lookup_table_golden_bytes() {
+        let program_id = solana_sdk::address_lookup_table::program::id();
+        let (table, bump) =
+            Pubkey::find_program_address(&[AUTHORITY.as_ref(), &AMOUNT.to_le_bytes()], &program_id);
+
+        let (create, address) = lookup_table::create_lookup_table(AUTHORITY, PAYER, AMOUNT);
+        assert_eq!(address, table);
+        assert_ix_eq(
+            &create,
+            &Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new(table, false),
+                    AccountMeta::new_readonly(AUTHORITY, false),
+                    AccountMeta::new(PAYER, true),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                ],
+                data: vec![0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8, bump],
+            },
+        );
+
+        let extend = lookup_table::extend_lookup_table(table, AUTHORITY, PAYER, &[RECIPIENT]);
+        assert_eq!(extend.len(), 1);
+        assert_ix_eq(
+            &extend[0],
+            &Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new(table, false),
+                    AccountMeta::new_readonly(AUTHORITY, true),
+                    AccountMeta::new(PAYER, true),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                ],
+                data: [
+                    vec![2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0],
+                    RECIPIENT.to_bytes().to_vec(),
+                ]
+                .concat(),
+            },
+        );
+
+        assert_ix_eq(
+            &lookup_table::deactivate_lookup_table(table, AUTHORITY),
+            &Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new(table, false),
+                    AccountMeta::new_readonly(AUTHORITY, true),
+                ],
+                data: vec![3, 0, 0, 0],
+            },
+        );
+
+        assert_ix_eq(
+            &lookup_table::close_lookup_table(table, AUTHORITY, RECIPIENT),
+            &Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new(table, false),
+                    AccountMeta::new_readonly(AUTHORITY, true),
+                    AccountMeta::new(RECIPIENT, false),
+                ],
+                data: vec![4, 0, 0, 0],
+            },
+        );
+    }
+
+    #[cfg(feature = "das")]
+    #[test]
+    fn bubblegum_transfer_golden_bytes() {
+        use crate::bubblegum::{self, CompressedLeaf};
+
+        let tree = Pubkey::new_from_array([5; 32]);
+        let node = Pubkey::new_from_array([6; 32]);
+        let leaf = CompressedLeaf {
+            merkle_tree: tree,
+            owner: PAYER,
+            delegate: AUTHORITY,
+            root: [7; 32],
+            data_hash: [8; 32],
+            creator_hash: [9; 32],
+            leaf_index: 0x0403_0201,
+        };
+
+        assert_ix_eq(
+            &bubblegum::transfer(&leaf, &RECIPIENT, &[node]),
+            &Instruction {
+                program_id: bubblegum::BUBBLEGUM_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new_readonly(bubblegum::tree_authority(&tree), false),
+                    AccountMeta::new_readonly(PAYER, true),
+                    AccountMeta::new_readonly(AUTHORITY, false),
+                    AccountMeta::new_readonly(RECIPIENT, false),
+                    AccountMeta::new(tree, false),
+                    AccountMeta::new_readonly(bubblegum::NOOP_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(bubblegum::ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                    AccountMeta::new_readonly(node, false),
+                ],
+                data: [
+                    vec![163, 52, 200, 231, 140, 3, 69, 186],
+                    vec![7; 32],
+                    vec![8; 32],
+                    vec![9; 32],
+                    vec![1, 2, 3, 4, 0, 0, 0, 0],
+                    vec![1, 2, 3, 4],
+                ]
+                .concat(),
+            },
+        );
+    }
+
+    #[test]
+    fn diff_names_mismatches() {
+        let mut ix = transfer_fixture();
+        ix.accounts[1].is_writable = false;
+        ix.data[4] = 0xff;
+
+        let diff = instruction_diff(&ix, &transfer_fixture()).unwrap();
+        assert!(diff.contains("account #1"), "{}", diff);
+        assert!(diff.contains("data differs at byte 4"), "{}", diff);
+        assert!(!diff.contains("program id"), "{}", diff);
+    }
+
+    #[test]
+    #[should_panic(expected = "instructions differ")]
+    fn assert_ix_eq_panics_on_mismatch() {
+        let mut ix = transfer_fixture();
+        ix.program_id = AUTHORITY;
+        assert_ix_eq(&ix, &transfer_fixture());
+    }
+}
@@ -0,0 +1,283 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use solana_sdk::{
+    hash::hashv,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+/// An Anchor IDL, parsed from the JSON written to `target/idl/` by `anchor build`.
+///
+/// Both the legacy format of Anchor < 0.30 and the current one are understood, missing
+/// discriminators are derived the way Anchor derives them. Types are kept as the IDL's
+/// JSON since their layout is only needed by generic decoders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Idl {
+    pub name: String,
+    pub version: String,
+    pub address: Option<Pubkey>,
+    pub instructions: Vec<IdlInstruction>,
+    pub accounts: Vec<IdlAccount>,
+    pub types: Vec<IdlTypeDef>,
+    pub errors: Vec<IdlError>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdlInstruction {
+    pub name: String,
+    pub discriminator: [u8; 8],
+    pub accounts: Vec<IdlInstructionAccount>,
+    pub args: Vec<IdlField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct IdlInstructionAccount {
+    pub name: String,
+    #[serde(default, alias = "isMut")]
+    pub writable: bool,
+    #[serde(default, alias = "isSigner")]
+    pub signer: bool,
+    #[serde(default, alias = "isOptional")]
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IdlField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdlAccount {
+    pub name: String,
+    pub discriminator: [u8; 8],
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IdlTypeDef {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct IdlError {
+    pub code: u32,
+    pub name: String,
+    #[serde(default)]
+    pub msg: Option<String>,
+}
+
+impl Idl {
+    pub fn from_slice(json: &[u8]) -> Result<Self> {
+        let raw: RawIdl = serde_json::from_slice(json).context("invalid Anchor IDL")?;
+
+        let metadata = raw.metadata.unwrap_or_default();
+        let name = raw.name.or(metadata.name).unwrap_or_default();
+        let version = raw.version.or(metadata.version).unwrap_or_default();
+        let address = match raw.address.or(metadata.address) {
+            Some(address) => Some(
+                Pubkey::from_str(&address)
+                    .with_context(|| format!("invalid program address {}", address))?,
+            ),
+            None => None,
+        };
+
+        let mut types = raw.types;
+        let mut accounts = vec![];
+        for account in raw.accounts {
+            let discriminator = match account.discriminator {
+                Some(discriminator) => discriminator,
+                None => account_discriminator(&account.name),
+            };
+            // the legacy format defines the layout next to the account
+            if let Some(ty) = account.ty {
+                if !types.iter().any(|def| def.name == account.name) {
+                    types.push(IdlTypeDef {
+                        name: account.name.clone(),
+                        ty,
+                    });
+                }
+            }
+            accounts.push(IdlAccount {
+                name: account.name,
+                discriminator,
+            });
+        }
+
+        let instructions = raw
+            .instructions
+            .into_iter()
+            .map(|ix| IdlInstruction {
+                discriminator: ix
+                    .discriminator
+                    .unwrap_or_else(|| instruction_discriminator(&ix.name)),
+                name: ix.name,
+                accounts: ix.accounts,
+                args: ix.args,
+            })
+            .collect();
+
+        Ok(Self {
+            name,
+            version,
+            address,
+            instructions,
+            accounts,
+            types,
+            errors: raw.errors,
+        })
+    }
+
+    pub fn instruction(&self, name: &str) -> Option<&IdlInstruction> {
+        self.instructions.iter().find(|ix| ix.name == name)
+    }
+
+    pub fn account(&self, name: &str) -> Option<&IdlAccount> {
+        self.accounts.iter().find(|account| account.name == name)
+    }
+
+    /// The account type of account `data`, looked up by its discriminator.
+    pub fn account_of(&self, data: &[u8]) -> Option<&IdlAccount> {
+        let discriminator = data.get(..8)?;
+        self.accounts
+            .iter()
+            .find(|account| account.discriminator == discriminator)
+    }
+
+    pub fn type_def(&self, name: &str) -> Option<&IdlTypeDef> {
+        self.types.iter().find(|def| def.name == name)
+    }
+
+    /// The error of a failed transaction's `Custom(code)` instruction error.
+    pub fn error(&self, code: u32) -> Option<&IdlError> {
+        self.errors.iter().find(|error| error.code == code)
+    }
+}
+
+impl IdlInstruction {
+    /// Builds the instruction from its accounts in IDL order and its borsh encoded `args`.
+    ///
+    /// Optional accounts that are left out are passed as `program_id`, like Anchor does.
+    pub fn build(
+        &self,
+        program_id: Pubkey,
+        accounts: &[Option<Pubkey>],
+        args: &[u8],
+    ) -> Result<Instruction> {
+        if accounts.len() != self.accounts.len() {
+            bail!(
+                "instruction {} takes {} accounts, got {}",
+                self.name,
+                self.accounts.len(),
+                accounts.len()
+            );
+        }
+
+        let accounts = self
+            .accounts
+            .iter()
+            .zip(accounts)
+            .map(|(account, pubkey)| match pubkey {
+                Some(pubkey) if account.writable => Ok(AccountMeta::new(*pubkey, account.signer)),
+                Some(pubkey) => Ok(AccountMeta::new_readonly(*pubkey, account.signer)),
+                None if account.optional => Ok(AccountMeta::new_readonly(program_id, false)),
+                None => bail!("account {} of {} is missing", account.name, self.name),
+            })
+            .collect::<Result<_>>()?;
+
+        let mut data = self.discriminator.to_vec();
+        data.extend_from_slice(args);
+        Ok(Instruction {
+            program_id,
+            accounts,
+            data,
+        })
+    }
+}
+
+/// Anchor's discriminator of instruction `name`, in either camel or snake case.
+pub fn instruction_discriminator(name: &str) -> [u8; 8] {
+    discriminator("global", &to_snake_case(name))
+}
+
+/// Anchor's discriminator of account type `name`, e.g. `GameState`.
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    discriminator("account", name)
+}
+
+fn discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    let hash = hashv(&[namespace.as_bytes(), b":", name.as_bytes()]);
+    let mut discriminator = [0; 8];
+    discriminator.copy_from_slice(&hash.as_ref()[..8]);
+    discriminator
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.char_indices() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+#[derive(Deserialize)]
+struct RawIdl {
+    #[serde(default)]
+    address: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    metadata: Option<RawMetadata>,
+    #[serde(default)]
+    instructions: Vec<RawInstruction>,
+    #[serde(default)]
+    accounts: Vec<RawAccount>,
+    #[serde(default)]
+    types: Vec<IdlTypeDef>,
+    #[serde(default)]
+    errors: Vec<IdlError>,
+}
+
+#[derive(Default, Deserialize)]
+struct RawMetadata {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    address: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawInstruction {
+    name: String,
+    #[serde(default)]
+    discriminator: Option<[u8; 8]>,
+    #[serde(default)]
+    accounts: Vec<IdlInstructionAccount>,
+    #[serde(default)]
+    args: Vec<IdlField>,
+}
+
+#[derive(Deserialize)]
+struct RawAccount {
+    name: String,
+    #[serde(default)]
+    discriminator: Option<[u8; 8]>,
+    #[serde(default, rename = "type")]
+    ty: Option<Value>,
+}
@@ -1 +1,34 @@
+pub mod analyzer;
+#[cfg(feature = "das")]
+pub mod bubblegum;
+pub mod builder;
+pub mod cache;
+pub mod catalog;
+pub mod cluster;
+pub mod coalesce;
+pub mod compute_budget;
+pub mod config;
+#[cfg(feature = "das")]
+pub mod das;
+pub mod encoding;
+pub mod endpoint;
+pub mod error;
+pub mod fixtures;
+pub mod idl;
+pub mod lookup_table;
+pub mod memo;
+pub mod metadata;
+pub mod metrics;
+pub mod partial_sign;
+pub mod program_error;
+pub mod retry;
 pub mod rpc_client;
+pub mod scan;
+pub mod session;
+pub mod signer;
+pub mod state;
+pub mod sticky;
+pub mod system;
+pub mod token;
+pub mod version;
+pub mod versioned;
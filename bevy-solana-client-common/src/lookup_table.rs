@@ -0,0 +1,97 @@
+use anyhow::{anyhow, Result};
+use solana_sdk::{
+    address_lookup_table::{instruction, state::AddressLookupTable, AddressLookupTableAccount},
+    clock::Slot,
+    instruction::Instruction,
+    pubkey::Pubkey,
+};
+
+pub use solana_sdk::address_lookup_table::state::LOOKUP_TABLE_MAX_ADDRESSES;
+
+/// Addresses per extend instruction that still fit into a single transaction.
+pub const MAX_EXTEND_ADDRESSES: usize = 30;
+
+/// A decoded address lookup table account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupTable {
+    pub key: Pubkey,
+    /// Who may extend, deactivate and close the table, `None` once frozen.
+    pub authority: Option<Pubkey>,
+    /// Slot the table was deactivated in, `u64::MAX` while it is active.
+    pub deactivation_slot: Slot,
+    pub last_extended_slot: Slot,
+    pub addresses: Vec<Pubkey>,
+}
+
+impl LookupTable {
+    pub fn decode(key: Pubkey, data: &[u8]) -> Result<Self> {
+        let table = AddressLookupTable::deserialize(data)
+            .map_err(|err| anyhow!("{} is not an address lookup table: {}", key, err))?;
+
+        Ok(Self {
+            key,
+            authority: table.meta.authority,
+            deactivation_slot: table.meta.deactivation_slot,
+            last_extended_slot: table.meta.last_extended_slot,
+            addresses: table.addresses.to_vec(),
+        })
+    }
+
+    pub fn is_deactivated(&self) -> bool {
+        self.deactivation_slot != Slot::MAX
+    }
+
+    /// Room left for [`extend_lookup_table`].
+    pub fn remaining_capacity(&self) -> usize {
+        LOOKUP_TABLE_MAX_ADDRESSES.saturating_sub(self.addresses.len())
+    }
+
+    /// The table as needed to compile v0 messages against it.
+    pub fn to_account(&self) -> AddressLookupTableAccount {
+        AddressLookupTableAccount {
+            key: self.key,
+            addresses: self.addresses.clone(),
+        }
+    }
+}
+
+/// Creates a lookup table of `authority`, returning the instruction and the table address.
+///
+/// `recent_slot` must be a recently finalized slot, e.g. from
+/// [`get_slot_with_commitment`](crate::rpc_client::RpcClient::get_slot_with_commitment).
+pub fn create_lookup_table(
+    authority: Pubkey,
+    payer: Pubkey,
+    recent_slot: Slot,
+) -> (Instruction, Pubkey) {
+    instruction::create_lookup_table(authority, payer, recent_slot)
+}
+
+/// Adds `addresses` to `table`, split into instructions of at most
+/// [`MAX_EXTEND_ADDRESSES`] each. Send every instruction in its own transaction.
+///
+/// Added addresses can be used from the slot after the extension on.
+pub fn extend_lookup_table(
+    table: Pubkey,
+    authority: Pubkey,
+    payer: Pubkey,
+    addresses: &[Pubkey],
+) -> Vec<Instruction> {
+    addresses
+        .chunks(MAX_EXTEND_ADDRESSES)
+        .map(|chunk| {
+            instruction::extend_lookup_table(table, authority, Some(payer), chunk.to_vec())
+        })
+        .collect()
+}
+
+/// Deactivates `table`, it can be closed once the deactivation slot is no longer recent,
+/// about 513 slots later.
+pub fn deactivate_lookup_table(table: Pubkey, authority: Pubkey) -> Instruction {
+    instruction::deactivate_lookup_table(table, authority)
+}
+
+/// Closes the deactivated `table`, returning its rent to `recipient`.
+pub fn close_lookup_table(table: Pubkey, authority: Pubkey, recipient: Pubkey) -> Instruction {
+    instruction::close_lookup_table(table, authority, recipient)
+}
@@ -0,0 +1,23 @@
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+};
+
+/// The SPL Memo program, v2.
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Records `memo` in the transaction's logs and in the `memo` of its
+/// `getSignaturesForAddress` entries, e.g. a game session id for indexers.
+///
+/// Every pubkey of `signers` has to sign, an empty list records the memo unsigned.
+pub fn memo(memo: &str, signers: &[Pubkey]) -> Instruction {
+    Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: signers
+            .iter()
+            .map(|signer| AccountMeta::new_readonly(*signer, true))
+            .collect(),
+        data: memo.as_bytes().to_vec(),
+    }
+}
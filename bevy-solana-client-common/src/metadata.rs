@@ -0,0 +1,290 @@
+use anyhow::{anyhow, bail, Context, Result};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::rpc_client::RpcClient;
+
+/// The Metaplex Token Metadata program.
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Account key of a `MetadataV1` account.
+const METADATA_V1_KEY: u8 = 4;
+
+/// The metadata account of `mint`.
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            mint.as_ref(),
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Creator {
+    pub address: Pubkey,
+    /// Whether the creator signed the metadata, unverified creators can be anyone.
+    pub verified: bool,
+    /// Share of royalties in percent.
+    pub share: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Collection {
+    /// Mint of the collection NFT.
+    pub key: Pubkey,
+    /// Whether the collection authority confirmed membership, check this before trusting
+    /// an item to belong to the game's collection.
+    pub verified: bool,
+}
+
+/// A decoded Metaplex `Metadata` account.
+///
+/// Fields added in later program versions are `None` on accounts created before them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    pub update_authority: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    /// Off-chain JSON with the image and attributes.
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<Creator>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    /// `TokenStandard` discriminant, e.g. 0 for a non-fungible, 2 for a fungible token.
+    pub token_standard: Option<u8>,
+    pub collection: Option<Collection>,
+}
+
+impl Metadata {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut reader = Reader(data);
+        if reader.u8()? != METADATA_V1_KEY {
+            bail!("not a metadata account");
+        }
+
+        let update_authority = reader.pubkey()?;
+        let mint = reader.pubkey()?;
+        let name = reader.string()?;
+        let symbol = reader.string()?;
+        let uri = reader.string()?;
+        let seller_fee_basis_points = reader.u16()?;
+        let creators = match reader.bool()? {
+            true => (0..reader.u32()?)
+                .map(|_| {
+                    Ok(Creator {
+                        address: reader.pubkey()?,
+                        verified: reader.bool()?,
+                        share: reader.u8()?,
+                    })
+                })
+                .collect::<Result<_>>()?,
+            false => vec![],
+        };
+        let primary_sale_happened = reader.bool()?;
+        let is_mutable = reader.bool()?;
+
+        // older accounts end here, newer ones are zero padded
+        let edition_nonce = reader.option(Reader::u8).unwrap_or_default();
+        let token_standard = reader.option(Reader::u8).unwrap_or_default();
+        let collection = reader
+            .option(|reader| {
+                Ok(Collection {
+                    verified: reader.bool()?,
+                    key: reader.pubkey()?,
+                })
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            update_authority,
+            mint,
+            name,
+            symbol,
+            uri,
+            seller_fee_basis_points,
+            creators,
+            primary_sale_happened,
+            is_mutable,
+            edition_nonce,
+            token_standard,
+            collection,
+        })
+    }
+
+    /// Whether `creator` is a verified creator, e.g. the game's authority.
+    pub fn is_verified_creator(&self, creator: &Pubkey) -> bool {
+        self.creators
+            .iter()
+            .any(|c| c.verified && c.address == *creator)
+    }
+
+    /// The verified collection the item belongs to.
+    pub fn verified_collection(&self) -> Option<Pubkey> {
+        self.collection
+            .filter(|collection| collection.verified)
+            .map(|collection| collection.key)
+    }
+}
+
+/// Fetches and decodes the metadata of `mint`.
+pub async fn get_token_metadata(rpc: &impl RpcClient, mint: &Pubkey) -> Result<Metadata> {
+    let address = metadata_address(mint);
+    let account = rpc.get_account(&address).await?;
+    if account.owner != TOKEN_METADATA_PROGRAM_ID {
+        bail!("{} is not owned by the token metadata program", address);
+    }
+    Metadata::decode(&account.data).map_err(|err| anyhow!("metadata of {}: {}", mint, err))
+}
+
+/// Creates the metadata of `mint` without creators, collection or royalties.
+///
+/// `mint_authority` signs and becomes the update authority, `payer` funds the account.
+pub fn create_metadata_account_v3(
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    payer: &Pubkey,
+    name: &str,
+    symbol: &str,
+    uri: &str,
+    is_mutable: bool,
+) -> Instruction {
+    let mut data = vec![33];
+    for field in [name, symbol, uri] {
+        data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+        data.extend_from_slice(field.as_bytes());
+    }
+    // no royalties, creators, collection, uses, then is_mutable and no collection details
+    data.extend_from_slice(&[0, 0, 0, 0, 0]);
+    data.push(is_mutable as u8);
+    data.push(0);
+
+    Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(metadata_address(mint), false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// The off-chain JSON a [`Metadata::uri`] points to, following the Metaplex token standard.
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+#[serde(default)]
+pub struct OffChainMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub description: String,
+    /// Image url, resolve it with [`resolve_uri`] before fetching.
+    pub image: Option<String>,
+    pub animation_url: Option<String>,
+    pub external_url: Option<String>,
+    pub attributes: Vec<NftAttribute>,
+}
+
+/// A trait of an NFT, e.g. `{"trait_type": "Rarity", "value": "Epic"}`.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct NftAttribute {
+    pub trait_type: String,
+    /// A string or a number depending on the collection.
+    pub value: serde_json::Value,
+}
+
+impl OffChainMetadata {
+    /// The value of the attribute named `trait_type`.
+    pub fn attribute(&self, trait_type: &str) -> Option<&serde_json::Value> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.trait_type == trait_type)
+            .map(|attribute| &attribute.value)
+    }
+}
+
+/// Turns `ipfs://` and `ar://` uris into gateway urls that can be fetched over https,
+/// other uris are returned unchanged.
+pub fn resolve_uri(uri: &str) -> String {
+    let uri = uri.trim();
+    if let Some(path) = uri.strip_prefix("ipfs://") {
+        format!("https://ipfs.io/ipfs/{}", path.trim_start_matches("ipfs/"))
+    } else if let Some(path) = uri.strip_prefix("ar://") {
+        format!("https://arweave.net/{}", path)
+    } else {
+        uri.to_string()
+    }
+}
+
+/// Fetches the off-chain JSON at `uri`, within the client's request timeout.
+pub async fn get_off_chain_metadata(rpc: &impl RpcClient, uri: &str) -> Result<OffChainMetadata> {
+    let url = resolve_uri(uri);
+    let body = match rpc.config().timeout {
+        Some(timeout) => rpc.get_with_timeout(&url, timeout).await?,
+        None => rpc.get(&url).await?,
+    };
+    serde_json::from_slice(&body).with_context(|| format!("invalid metadata json at {}", url))
+}
+
+/// Reads borsh encoded fields front to back.
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn bytes(&mut self, len: usize) -> Result<&[u8]> {
+        if self.0.len() < len {
+            bail!("metadata account is truncated");
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into()?))
+    }
+
+    fn pubkey(&mut self) -> Result<Pubkey> {
+        Ok(Pubkey::try_from(self.bytes(32)?)?)
+    }
+
+    /// A string without the zero padding Metaplex adds to fixed size fields.
+    fn string(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        let bytes = self.bytes(len)?;
+        Ok(String::from_utf8_lossy(bytes)
+            .trim_end_matches('\0')
+            .to_string())
+    }
+
+    fn option<T>(&mut self, read: impl FnOnce(&mut Self) -> Result<T>) -> Result<Option<T>> {
+        match self.bool()? {
+            true => read(self).map(Some),
+            false => Ok(None),
+        }
+    }
+}
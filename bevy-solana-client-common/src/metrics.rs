@@ -0,0 +1,302 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+/// Upper bounds of the latency histogram buckets, the last bucket is unbounded.
+pub const LATENCY_BUCKETS: [Duration; 12] = [
+    Duration::from_millis(10),
+    Duration::from_millis(25),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(2500),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+];
+
+/// Fixed bucket histogram of request latencies.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    count: u64,
+    sum: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency: Duration) {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| latency <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum += latency;
+        self.max = self.max.max(latency);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.count).ok().filter(|c| *c > 0)?;
+        Some(self.sum / count)
+    }
+
+    /// Counts per bucket, matching [`LATENCY_BUCKETS`] plus a trailing overflow bucket.
+    pub fn buckets(&self) -> &[u64] {
+        &self.buckets
+    }
+
+    /// Upper bound of the bucket containing the `q` quantile, e.g. `0.99` for p99.
+    ///
+    /// Falls back to the maximum seen latency for the overflow bucket.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let rank = ((self.count as f64) * q.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(LATENCY_BUCKETS.get(bucket).copied().unwrap_or(self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// The latencies recorded after `earlier`, a previous snapshot of this histogram.
+    ///
+    /// The maximum can not be recovered for a window and is carried over as is.
+    pub fn since(&self, earlier: &LatencyHistogram) -> LatencyHistogram {
+        let mut buckets = self.buckets;
+        for (bucket, count) in buckets.iter_mut().zip(earlier.buckets) {
+            *bucket = bucket.saturating_sub(count);
+        }
+
+        LatencyHistogram {
+            buckets,
+            count: self.count.saturating_sub(earlier.count),
+            sum: self.sum.saturating_sub(earlier.sum),
+            max: self.max,
+        }
+    }
+
+    /// Adds the latencies recorded by `other` to this histogram.
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        for (bucket, count) in self.buckets.iter_mut().zip(other.buckets) {
+            *bucket += count;
+        }
+        self.count += other.count;
+        self.sum += other.sum;
+        self.max = self.max.max(other.max);
+    }
+}
+
+/// Counters of a single RPC method.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MethodMetrics {
+    /// Requests put on the wire, cache hits and coalesced requests are not counted.
+    pub requests: u64,
+    /// Requests that failed with a transport or JSON-RPC error.
+    pub errors: u64,
+    pub latency: LatencyHistogram,
+}
+
+impl MethodMetrics {
+    /// The activity recorded after `earlier`, a previous snapshot of these metrics.
+    pub fn since(&self, earlier: &MethodMetrics) -> MethodMetrics {
+        MethodMetrics {
+            requests: self.requests.saturating_sub(earlier.requests),
+            errors: self.errors.saturating_sub(earlier.errors),
+            latency: self.latency.since(&earlier.latency),
+        }
+    }
+
+    /// Adds the counters of `other`, e.g. to combine several methods.
+    pub fn merge(&mut self, other: &MethodMetrics) {
+        self.requests += other.requests;
+        self.errors += other.errors;
+        self.latency.merge(&other.latency);
+    }
+}
+
+/// Per method request metrics of a client, and the confirmation times of the
+/// transactions sent through it.
+#[derive(Debug, Default)]
+pub struct RpcMetrics {
+    methods: Mutex<BTreeMap<String, MethodMetrics>>,
+    confirmations: Mutex<LatencyHistogram>,
+}
+
+impl RpcMetrics {
+    pub fn record(&self, method: &str, latency: Duration, success: bool) {
+        let mut methods = self.methods.lock().unwrap_or_else(|e| e.into_inner());
+        let metrics = match methods.get_mut(method) {
+            Some(metrics) => metrics,
+            None => methods.entry(method.to_string()).or_default(),
+        };
+
+        metrics.requests += 1;
+        if !success {
+            metrics.errors += 1;
+        }
+        metrics.latency.record(latency);
+    }
+
+    /// A copy of the current counters, keyed by method name.
+    pub fn snapshot(&self) -> BTreeMap<String, MethodMetrics> {
+        self.methods
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// The counters of every method combined.
+    pub fn total(&self) -> MethodMetrics {
+        let methods = self.methods.lock().unwrap_or_else(|e| e.into_inner());
+        methods
+            .values()
+            .fold(MethodMetrics::default(), |mut total, m| {
+                total.merge(m);
+                total
+            })
+    }
+
+    /// Records how long a transaction took from being sent to being confirmed.
+    pub fn record_confirmation(&self, elapsed: Duration) {
+        self.confirmations
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .record(elapsed);
+    }
+
+    /// Send to confirmation times of every transaction recorded so far.
+    pub fn confirmation_times(&self) -> LatencyHistogram {
+        self.confirmations
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn reset(&self) {
+        self.methods
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        *self.confirmations.lock().unwrap_or_else(|e| e.into_inner()) = LatencyHistogram::default();
+    }
+
+    /// Renders every counter in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        let methods = self.snapshot();
+        out.push_str("# HELP solana_rpc_requests_total RPC requests sent, by method.\n");
+        out.push_str("# TYPE solana_rpc_requests_total counter\n");
+        for (method, metrics) in &methods {
+            let _ = writeln!(
+                out,
+                "solana_rpc_requests_total{{method=\"{}\"}} {}",
+                method, metrics.requests
+            );
+        }
+
+        out.push_str("# HELP solana_rpc_errors_total RPC requests that failed, by method.\n");
+        out.push_str("# TYPE solana_rpc_errors_total counter\n");
+        for (method, metrics) in &methods {
+            let _ = writeln!(
+                out,
+                "solana_rpc_errors_total{{method=\"{}\"}} {}",
+                method, metrics.errors
+            );
+        }
+
+        out.push_str(
+            "# HELP solana_rpc_request_duration_seconds RPC request latency, by method.\n",
+        );
+        out.push_str("# TYPE solana_rpc_request_duration_seconds histogram\n");
+        for (method, metrics) in &methods {
+            let labels = format!("method=\"{}\"", method);
+            write_histogram(
+                &mut out,
+                "solana_rpc_request_duration_seconds",
+                &labels,
+                &metrics.latency,
+            );
+        }
+
+        out.push_str("# HELP solana_transaction_confirmation_seconds Time from sending a transaction to its confirmation.\n");
+        out.push_str("# TYPE solana_transaction_confirmation_seconds histogram\n");
+        write_histogram(
+            &mut out,
+            "solana_transaction_confirmation_seconds",
+            "",
+            &self.confirmation_times(),
+        );
+
+        out
+    }
+}
+
+fn write_histogram(out: &mut String, name: &str, labels: &str, histogram: &LatencyHistogram) {
+    let sep = if labels.is_empty() { "" } else { "," };
+
+    let mut cumulative = 0;
+    for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets()) {
+        cumulative += count;
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{}{}le=\"{}\"}} {}",
+            name,
+            labels,
+            sep,
+            bound.as_secs_f64(),
+            cumulative
+        );
+    }
+
+    let braces = |labels: &str| {
+        if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{}}}", labels)
+        }
+    };
+    let _ = writeln!(
+        out,
+        "{}_bucket{{{}{}le=\"+Inf\"}} {}",
+        name,
+        labels,
+        sep,
+        histogram.count()
+    );
+    let _ = writeln!(
+        out,
+        "{}_sum{} {}",
+        name,
+        braces(labels),
+        histogram.sum().as_secs_f64()
+    );
+    let _ = writeln!(
+        out,
+        "{}_count{} {}",
+        name,
+        braces(labels),
+        histogram.count()
+    );
+}
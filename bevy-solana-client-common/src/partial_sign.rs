@@ -0,0 +1,102 @@
+use anyhow::{bail, Context, Result};
+use solana_sdk::{
+    pubkey::Pubkey,
+    sanitize::Sanitize,
+    signature::Signature,
+    transaction::{Transaction, VersionedTransaction},
+};
+
+use crate::{encoding::RpcEncoding, signer::GameSigner};
+
+/// Signers of `tx` whose signature is still missing.
+pub fn missing_signers(tx: &Transaction) -> Vec<Pubkey> {
+    let required = tx.message.header.num_required_signatures;
+    missing(&tx.message.account_keys, required, &tx.signatures)
+}
+
+pub fn missing_versioned_signers(tx: &VersionedTransaction) -> Vec<Pubkey> {
+    let required = tx.message.header().num_required_signatures;
+    missing(tx.message.static_account_keys(), required, &tx.signatures)
+}
+
+fn missing(keys: &[Pubkey], required: u8, signatures: &[Signature]) -> Vec<Pubkey> {
+    keys.iter()
+        .enumerate()
+        .take(required as usize)
+        .filter(|(i, _)| {
+            signatures
+                .get(*i)
+                .is_none_or(|sig| *sig == Signature::default())
+        })
+        .map(|(_, signer)| *signer)
+        .collect()
+}
+
+/// Has every signer of `signers` sign `tx` in turn, keeping the signatures already on it.
+///
+/// E.g. a local session key signs first and the browser wallet adds the fee payer
+/// signature, or the other way round.
+pub async fn sign_with(tx: &mut Transaction, signers: &[&dyn GameSigner]) -> Result<()> {
+    for signer in signers {
+        signer.sign_transaction(tx).await?;
+    }
+    Ok(())
+}
+
+pub async fn sign_versioned_with(
+    tx: &mut VersionedTransaction,
+    signers: &[&dyn GameSigner],
+) -> Result<()> {
+    for signer in signers {
+        signer.sign_versioned_transaction(tx).await?;
+    }
+    Ok(())
+}
+
+/// Checks the signatures present on `tx`, e.g. before adding the player's signature to a
+/// transaction a server signed, so a message altered after signing is caught.
+pub fn verify_present_signatures(tx: &Transaction) -> Result<()> {
+    let message = tx.message_data();
+    for (signature, signer) in tx.signatures.iter().zip(&tx.message.account_keys) {
+        if *signature != Signature::default() && !signature.verify(signer.as_ref(), &message) {
+            bail!("signature of {} does not match the transaction", signer);
+        }
+    }
+    Ok(())
+}
+
+pub fn verify_present_versioned_signatures(tx: &VersionedTransaction) -> Result<()> {
+    let message = tx.message.serialize();
+    let signers = tx.message.static_account_keys();
+    for (signature, signer) in tx.signatures.iter().zip(signers) {
+        if *signature != Signature::default() && !signature.verify(signer.as_ref(), &message) {
+            bail!("signature of {} does not match the transaction", signer);
+        }
+    }
+    Ok(())
+}
+
+/// Serializes `tx` to base64 with the signatures it has so far, e.g. to pass it to a
+/// co-signing server.
+pub fn encode_transaction(tx: &Transaction) -> Result<String> {
+    Ok(RpcEncoding::Base64.encode(bincode::serialize(tx)?))
+}
+
+/// Reads back a transaction of [`encode_transaction`], missing signatures are all zero.
+pub fn decode_transaction(encoded: &str) -> Result<Transaction> {
+    let bytes = RpcEncoding::Base64.decode(encoded)?;
+    let tx: Transaction = bincode::deserialize(&bytes).context("invalid transaction")?;
+    tx.sanitize().context("malformed transaction")?;
+    Ok(tx)
+}
+
+pub fn encode_versioned_transaction(tx: &VersionedTransaction) -> Result<String> {
+    Ok(RpcEncoding::Base64.encode(bincode::serialize(tx)?))
+}
+
+pub fn decode_versioned_transaction(encoded: &str) -> Result<VersionedTransaction> {
+    let bytes = RpcEncoding::Base64.decode(encoded)?;
+    let tx: VersionedTransaction = bincode::deserialize(&bytes).context("invalid transaction")?;
+    tx.sanitize().context("malformed transaction")?;
+    Ok(tx)
+}
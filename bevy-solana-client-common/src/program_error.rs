@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    transaction::{Transaction, TransactionError, VersionedTransaction},
+};
+
+use crate::idl::Idl;
+
+/// A custom error code of a program, e.g. Anchor's `#[error_code]` variants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramErrorEntry {
+    pub code: u32,
+    pub name: String,
+    /// Player facing text, e.g. "Not enough energy".
+    pub message: String,
+}
+
+/// Custom error codes of the game's programs, turning `Custom(6001)` into something
+/// players understand.
+#[derive(Debug, Default, Clone)]
+pub struct ProgramErrorTable {
+    by_program: HashMap<Pubkey, HashMap<u32, ProgramErrorEntry>>,
+}
+
+impl ProgramErrorTable {
+    pub fn with_error(
+        mut self,
+        program_id: Pubkey,
+        code: u32,
+        name: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        self.insert(
+            program_id,
+            ProgramErrorEntry {
+                code,
+                name: name.into(),
+                message: message.into(),
+            },
+        );
+        self
+    }
+
+    /// Adds the errors of an Anchor IDL, using their `msg` or name as message.
+    pub fn with_idl(mut self, program_id: Pubkey, idl: &Idl) -> Self {
+        for error in &idl.errors {
+            self.insert(
+                program_id,
+                ProgramErrorEntry {
+                    code: error.code,
+                    name: error.name.clone(),
+                    message: error.msg.clone().unwrap_or_else(|| error.name.clone()),
+                },
+            );
+        }
+        self
+    }
+
+    pub fn insert(&mut self, program_id: Pubkey, entry: ProgramErrorEntry) {
+        self.by_program
+            .entry(program_id)
+            .or_default()
+            .insert(entry.code, entry);
+    }
+
+    pub fn get(&self, program_id: &Pubkey, code: u32) -> Option<&ProgramErrorEntry> {
+        self.by_program.get(program_id)?.get(&code)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_program.is_empty()
+    }
+
+    /// The entry of the custom error in `err`.
+    ///
+    /// `program_ids` are the programs of the transaction's instructions in order, used to
+    /// find the program that failed. Without them the code is looked up in every program
+    /// and only resolved if a single one defines it.
+    pub fn describe(
+        &self,
+        err: &TransactionError,
+        program_ids: &[Pubkey],
+    ) -> Option<&ProgramErrorEntry> {
+        let (index, code) = custom_error_code(err)?;
+        if let Some(program_id) = program_ids.get(index as usize) {
+            return self.get(program_id, code);
+        }
+
+        let mut matches = self
+            .by_program
+            .values()
+            .filter_map(|errors| errors.get(&code));
+        match (matches.next(), matches.next()) {
+            (Some(entry), None) => Some(entry),
+            _ => None,
+        }
+    }
+}
+
+/// The failed instruction's index and its custom error code, if `err` is one.
+pub fn custom_error_code(err: &TransactionError) -> Option<(u8, u32)> {
+    match err {
+        TransactionError::InstructionError(index, InstructionError::Custom(code)) => {
+            Some((*index, *code))
+        }
+        _ => None,
+    }
+}
+
+/// The programs of `tx`'s instructions in order, as [`ProgramErrorTable::describe`] takes them.
+pub fn transaction_program_ids(tx: &Transaction) -> Vec<Pubkey> {
+    tx.message
+        .instructions
+        .iter()
+        .map(|ix| *ix.program_id(&tx.message.account_keys))
+        .collect()
+}
+
+/// The programs of a versioned `tx`'s instructions in order, see [`transaction_program_ids`].
+pub fn versioned_transaction_program_ids(tx: &VersionedTransaction) -> Vec<Pubkey> {
+    let keys = tx.message.static_account_keys();
+    tx.message
+        .instructions()
+        .iter()
+        .map(|ix| *ix.program_id(keys))
+        .collect()
+}
@@ -0,0 +1,104 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use crate::error::{RpcError, RpcErrorKind, TransportError};
+
+/// Which failures are worth another attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryOn {
+    /// HTTP 429 and JSON-RPC errors carrying code 429.
+    pub rate_limited: bool,
+    /// HTTP 5xx.
+    pub server_error: bool,
+    /// Connection failures and timeouts.
+    pub network: bool,
+    /// The node reported itself as behind the cluster.
+    pub node_behind: bool,
+}
+
+impl Default for RetryOn {
+    fn default() -> Self {
+        Self {
+            rate_limited: true,
+            server_error: true,
+            network: true,
+            node_behind: true,
+        }
+    }
+}
+
+/// Exponential backoff with jitter applied by [`RpcClient::rpc_post`](crate::rpc_client::RpcClient::rpc_post),
+/// methods with side effects like `requestAirdrop` are never retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one, `1` disables retries.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub retry_on: RetryOn,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(5),
+            retry_on: RetryOn::default(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn should_retry(&self, err: &anyhow::Error) -> bool {
+        if let Some(err) = err.downcast_ref::<TransportError>() {
+            return match err {
+                TransportError::Http { status: 429, .. } => self.retry_on.rate_limited,
+                TransportError::Http { status, .. } => *status >= 500 && self.retry_on.server_error,
+                TransportError::Network(_) | TransportError::Timeout(_) => self.retry_on.network,
+            };
+        }
+
+        if let Some(err) = err.downcast_ref::<RpcError>() {
+            return match err.kind() {
+                RpcErrorKind::NodeUnhealthy => self.retry_on.node_behind,
+                RpcErrorKind::Other(429) => self.retry_on.rate_limited,
+                _ => false,
+            };
+        }
+
+        false
+    }
+
+    /// Backoff before retry number `attempt` (starting at 1), half fixed and half random.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+
+        let half = exp / 2;
+        let jitter_ms = half.as_millis() as u64;
+        if jitter_ms == 0 {
+            return exp;
+        }
+
+        half + Duration::from_millis(random_u64() % (jitter_ms + 1))
+    }
+}
+
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(crate::rpc_client::next_request_id());
+    hasher.finish()
+}
@@ -1,18 +1,61 @@
-use std::str::FromStr;
+use std::{
+    net::SocketAddr,
+    pin::pin,
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
 
-use anyhow::{anyhow, Context, Result};
-use base64::{prelude::BASE64_STANDARD, Engine};
+use anyhow::{anyhow, bail, Context, Result};
+use futures_util::future::{select, Either};
 use serde_json::json;
 use solana_sdk::{
-    account::Account, bs58, message::Message, pubkey::Pubkey, signature::Keypair,
-    system_instruction, transaction::Transaction,
+    account::Account,
+    address_lookup_table::AddressLookupTableAccount,
+    bs58,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    system_instruction,
+    transaction::{Transaction, TransactionError, VersionedTransaction},
+};
+use web_time::Instant;
+
+use crate::{
+    coalesce::{is_idempotent, InFlight},
+    config::{RebroadcastConfig, SendConfig, SolanaClientConfig},
+    encoding::RpcEncoding,
+    endpoint::{is_endpoint_failure, RpcEndpoints},
+    error::{
+        decode_transaction_error, ConfirmationError, RpcError, SendTransactionError,
+        SimulationError, TransportError,
+    },
+    lookup_table::LookupTable,
+    program_error::versioned_transaction_program_ids,
+    scan::{ProgramScan, RpcFilter, ScanProgress},
+    state::RpcClientState,
+    token::{
+        RpcKeyedTokenAccount, RpcTransactionTokenBalance, RpcUiTokenAmount, TokenAccountBalance,
+        TokenAmount,
+    },
+    version::{NodeVersion, RpcApiVersion, RpcFeature},
+    versioned::new_v0_transaction,
 };
 
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Returns a process-wide unique JSON-RPC request id.
+pub fn next_request_id() -> u64 {
+    NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed)
+}
+
 #[derive(serde::Serialize)]
 pub struct RpcRequest<T> {
     jsonrpc: String,
     method: String,
-    id: u32,
+    id: u64,
     params: T,
 }
 
@@ -21,25 +64,77 @@ impl<T> RpcRequest<T> {
         Self {
             jsonrpc: "2.0".to_string(),
             method: method.to_string(),
-            id: 1,
+            id: next_request_id(),
             params,
         }
     }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn params(&self) -> &T {
+        &self.params
+    }
 }
 
 #[derive(serde::Deserialize)]
 pub struct RpcResponse<T> {
     pub jsonrpc: String,
     pub result: Option<T>,
-    pub error: Option<serde_json::Value>,
-    pub id: u64,
+    pub error: Option<RpcError>,
+    /// `null` when the node could not parse the request at all.
+    pub id: Option<u64>,
+}
+
+impl<T> RpcResponse<T> {
+    /// Checks that the response answers the request with `request_id` and extracts its result.
+    pub fn into_result(self, request_id: u64) -> Result<T> {
+        match self.id {
+            Some(id) if id != request_id => {
+                bail!(
+                    "rpc response id mismatch: expected {}, got {}",
+                    request_id,
+                    id
+                )
+            }
+            None if self.error.is_none() => bail!("rpc response is missing its id"),
+            _ => {}
+        }
+
+        if let Some(e) = self.error {
+            return Err(e.into());
+        }
+
+        self.result.context("no result")
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcResponseContext {
+    pub slot: u64,
+    pub api_version: Option<String>,
 }
 
 #[derive(serde::Deserialize)]
 pub struct RpcResult<T> {
+    context: Option<RpcResponseContext>,
     value: T,
 }
 
+#[derive(serde::Deserialize)]
+pub struct GetVersion {
+    #[serde(rename = "solana-core")]
+    pub solana_core: String,
+    #[serde(rename = "feature-set")]
+    pub feature_set: Option<u32>,
+}
+
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetLatestBlockhash {
@@ -47,41 +142,429 @@ pub struct GetLatestBlockhash {
     pub last_valid_block_height: u64,
 }
 
+/// Upper bound of signatures per `getSignatureStatuses` request.
+pub const MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS: usize = 256;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfirmationStatus {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSignatureStatus {
+    pub slot: u64,
+    /// `None` once the transaction is rooted.
+    pub confirmations: Option<usize>,
+    pub err: Option<serde_json::Value>,
+    pub confirmation_status: Option<ConfirmationStatus>,
+}
+
+impl RpcSignatureStatus {
+    pub fn transaction_error(&self) -> Option<TransactionError> {
+        decode_transaction_error(self.err.as_ref()?)
+    }
+
+    pub fn satisfies_commitment(&self, commitment: CommitmentConfig) -> bool {
+        let status = match self.confirmation_status {
+            Some(status) => status,
+            None if self.confirmations.is_none() => ConfirmationStatus::Finalized,
+            None => ConfirmationStatus::Confirmed,
+        };
+
+        if commitment.is_finalized() {
+            status == ConfirmationStatus::Finalized
+        } else if commitment.is_confirmed() {
+            status >= ConfirmationStatus::Confirmed
+        } else {
+            true
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcConfirmedSignature {
+    pub signature: String,
+    pub slot: u64,
+    pub err: Option<serde_json::Value>,
+    pub memo: Option<String>,
+    pub block_time: Option<i64>,
+    pub confirmation_status: Option<ConfirmationStatus>,
+}
+
+/// Status meta of a confirmed transaction.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionMeta {
+    /// Fee in lamports, charged to the fee payer even if the transaction failed.
+    pub fee: u64,
+    pub err: Option<serde_json::Value>,
+    pub pre_balances: Vec<u64>,
+    pub post_balances: Vec<u64>,
+    #[serde(default)]
+    pub pre_token_balances: Vec<RpcTransactionTokenBalance>,
+    #[serde(default)]
+    pub post_token_balances: Vec<RpcTransactionTokenBalance>,
+    /// Accounts a v0 transaction loaded from address lookup tables.
+    #[serde(default)]
+    pub loaded_addresses: Option<RpcLoadedAddresses>,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct RpcLoadedAddresses {
+    pub writable: Vec<String>,
+    pub readonly: Vec<String>,
+}
+
+impl RpcTransactionMeta {
+    pub fn transaction_error(&self) -> Option<TransactionError> {
+        decode_transaction_error(self.err.as_ref()?)
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcUiMessage {
+    /// Static account keys, the fee payer comes first.
+    pub account_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct RpcUiTransaction {
+    pub signatures: Vec<String>,
+    pub message: RpcUiMessage,
+}
+
+/// A transaction as returned by `getTransaction` with `json` encoding.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcConfirmedTransaction {
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub meta: Option<RpcTransactionMeta>,
+    pub transaction: RpcUiTransaction,
+}
+
+impl RpcConfirmedTransaction {
+    pub fn fee_payer(&self) -> Option<Pubkey> {
+        self.transaction.message.account_keys.first()?.parse().ok()
+    }
+
+    /// Every account of the transaction in the order of the meta's balances: the static
+    /// keys, then the writable and readonly ones loaded from lookup tables.
+    pub fn account_keys(&self) -> impl Iterator<Item = &str> {
+        let loaded = self
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.loaded_addresses.as_ref());
+        self.transaction
+            .message
+            .account_keys
+            .iter()
+            .chain(loaded.into_iter().flat_map(|loaded| &loaded.writable))
+            .chain(loaded.into_iter().flat_map(|loaded| &loaded.readonly))
+            .map(String::as_str)
+    }
+}
+
+/// Result of `simulateTransaction`.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateTransactionResult {
+    pub err: Option<serde_json::Value>,
+    pub logs: Option<Vec<String>>,
+    pub units_consumed: Option<u64>,
+    pub return_data: Option<RpcTransactionReturnData>,
+}
+
+impl RpcSimulateTransactionResult {
+    pub fn transaction_error(&self) -> Option<TransactionError> {
+        decode_transaction_error(self.err.as_ref()?)
+    }
+}
+
+/// Data a program set with `sol_set_return_data`.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionReturnData {
+    pub program_id: String,
+    /// `[data, encoding]`, nodes always answer with base64.
+    pub data: (String, String),
+}
+
+impl RpcTransactionReturnData {
+    pub fn decode(&self) -> Result<Vec<u8>> {
+        let encoding: RpcEncoding = self.data.1.parse()?;
+        encoding.decode(&self.data.0)
+    }
+}
+
+/// Priority fee paid in a recent slot, as returned by `getRecentPrioritizationFees`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPrioritizationFee {
+    pub slot: u64,
+    /// Micro-lamports per compute unit.
+    pub prioritization_fee: u64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GetRecentBlockhash {
+    pub blockhash: String,
+}
+
+/// Gossip contact info of a cluster node as returned by `getClusterNodes`.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcContactInfo {
+    pub pubkey: String,
+    pub gossip: Option<SocketAddr>,
+    pub tpu: Option<SocketAddr>,
+    pub tpu_quic: Option<SocketAddr>,
+    pub tpu_forwards: Option<SocketAddr>,
+    pub tpu_forwards_quic: Option<SocketAddr>,
+    pub tpu_vote: Option<SocketAddr>,
+    pub rpc: Option<SocketAddr>,
+    pub pubsub: Option<SocketAddr>,
+    pub version: Option<String>,
+    pub feature_set: Option<u32>,
+    pub shred_version: Option<u16>,
+}
+
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcAccountInfo {
-    data: [String; 2],
+    /// `[data, encoding]`
+    data: (String, String),
     executable: bool,
     lamports: u64,
     owner: String,
     rent_epoch: u64,
 }
 
-impl From<RpcAccountInfo> for Account {
-    fn from(rpc_acc: RpcAccountInfo) -> Self {
-        let data = BASE64_STANDARD.decode(rpc_acc.data[0].as_bytes()).unwrap();
-        let owner = Pubkey::from_str(&rpc_acc.owner).unwrap();
-        let lamports = rpc_acc.lamports;
-        let rent_epoch = rpc_acc.rent_epoch;
-        let executable = rpc_acc.executable;
+impl TryFrom<RpcAccountInfo> for Account {
+    type Error = anyhow::Error;
 
-        Account {
+    fn try_from(rpc_acc: RpcAccountInfo) -> Result<Self> {
+        let (data, encoding) = rpc_acc.data;
+        let data = encoding.parse::<RpcEncoding>()?.decode(&data)?;
+        let owner = Pubkey::from_str(&rpc_acc.owner)
+            .map_err(|e| anyhow!("invalid account owner {:?}: {}", rpc_acc.owner, e))?;
+
+        Ok(Account {
             data,
             owner,
-            lamports,
-            rent_epoch,
-            executable,
-        }
+            lamports: rpc_acc.lamports,
+            rent_epoch: rpc_acc.rent_epoch,
+            executable: rpc_acc.executable,
+        })
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct RpcKeyedAccount {
+    pubkey: String,
+    account: RpcAccountInfo,
+}
+
+impl TryFrom<RpcKeyedAccount> for (Account, Pubkey) {
+    type Error = anyhow::Error;
+
+    fn try_from(keyed: RpcKeyedAccount) -> Result<Self> {
+        let pubkey = Pubkey::from_str(&keyed.pubkey)
+            .map_err(|e| anyhow!("invalid account pubkey {:?}: {}", keyed.pubkey, e))?;
+        Ok((keyed.account.try_into()?, pubkey))
     }
 }
 
 #[async_trait::async_trait(?Send)]
 pub trait RpcClient {
+    fn config(&self) -> &SolanaClientConfig;
+
+    /// Endpoints to send requests to, in order of preference.
+    fn endpoints(&self) -> &RpcEndpoints;
+
+    fn state(&self) -> &RpcClientState;
+
+    /// Version of the node this client talks to, learned from response contexts.
+    fn node_version(&self) -> &NodeVersion {
+        &self.state().node_version
+    }
+
+    fn commitment(&self) -> CommitmentConfig {
+        self.config().commitment
+    }
+
+    /// Posts a JSON-RPC request to `url` and returns the raw response body.
+    ///
+    /// Failures to reach the endpoint and non-success statuses should be
+    /// reported as [`TransportError`] so the retry policy can classify them.
+    /// Dropping the future should cancel the request.
+    async fn post(&self, url: &str, request: &RpcRequest<serde_json::Value>) -> Result<String>;
+
+    /// Fetches `url` with a plain GET and returns the body, e.g. off-chain NFT metadata
+    /// or images.
+    ///
+    /// No rpc headers are sent, failures are reported as [`TransportError`] like for
+    /// [`RpcClient::post`]. Backends without plain GET support keep the default, which
+    /// fails.
+    async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        bail!("GET {} is not supported by this rpc client", url)
+    }
+
+    /// Waits for `duration` on the backend's runtime, used to back off between retries.
+    async fn sleep(&self, duration: Duration);
+
+    /// Races [`RpcClient::post`] against `timeout`, cancelling the request when it expires.
+    async fn post_with_timeout(
+        &self,
+        url: &str,
+        request: &RpcRequest<serde_json::Value>,
+        timeout: Duration,
+    ) -> Result<String> {
+        let post = pin!(self.post(url, request));
+        let sleep = pin!(self.sleep(timeout));
+
+        match select(post, sleep).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(TransportError::Timeout(timeout).into()),
+        }
+    }
+
+    /// Races [`RpcClient::get`] against `timeout`, cancelling the request when it expires.
+    async fn get_with_timeout(&self, url: &str, timeout: Duration) -> Result<Vec<u8>> {
+        let get = pin!(self.get(url));
+        let sleep = pin!(self.sleep(timeout));
+
+        match select(get, sleep).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(TransportError::Timeout(timeout).into()),
+        }
+    }
+
     async fn rpc_post<De: serde::de::DeserializeOwned>(
         &self,
         method: &str,
         params: serde_json::Value,
-    ) -> Result<De>;
+    ) -> Result<De> {
+        self.rpc_post_with_timeout(method, params, self.config().timeout)
+            .await
+    }
+
+    /// Like [`RpcClient::rpc_post`], but overrides the client's default timeout for every attempt.
+    async fn rpc_post_with_timeout<De: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Option<Duration>,
+    ) -> Result<De> {
+        let policy = self.config().retry;
+        let mut attempt = 1;
+
+        loop {
+            match self.rpc_post_once(method, params.clone(), timeout).await {
+                // a request with side effects may have gone through before it failed
+                Err(err)
+                    if attempt < policy.max_attempts
+                        && is_idempotent(method)
+                        && policy.should_retry(&err) =>
+                {
+                    // fail over right away if another endpoint can take the request
+                    let delay = if self.endpoints().len() > 1 && self.endpoints().has_available() {
+                        Duration::ZERO
+                    } else {
+                        policy.delay(attempt)
+                    };
+                    log::debug!(
+                        "{} failed (attempt {}), retrying in {:?}: {}",
+                        method,
+                        attempt,
+                        delay,
+                        err
+                    );
+                    if !delay.is_zero() {
+                        self.sleep(delay).await;
+                    }
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends a single request without applying the retry policy.
+    ///
+    /// Served from the response cache instead if it is enabled and holds a fresh result,
+    /// and merged with an identical request that is already in flight.
+    async fn rpc_post_once<De: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Option<Duration>,
+    ) -> Result<De> {
+        let cache = self.state().response_cache.as_ref();
+        if let Some(cached) = cache.and_then(|c| c.get(method, &params)) {
+            log::debug!("{} served from cache", method);
+            return Ok(serde_json::from_value(cached)?);
+        }
+
+        let guard = match self.state().in_flight.join(method, &params) {
+            Some(InFlight::Follower(receiver)) => {
+                log::debug!("{} coalesced with an in-flight request", method);
+                let result = receiver
+                    .await
+                    .map_err(|_| anyhow!("coalesced {} request was cancelled", method))??;
+                return Ok(serde_json::from_value(result)?);
+            }
+            Some(InFlight::Leader(guard)) => Some(guard),
+            None => None,
+        };
+
+        let request = RpcRequest::new(method, params);
+        let endpoints = self.endpoints();
+        let index = endpoints.select();
+
+        let started = Instant::now();
+        let result = async {
+            let url = endpoints.url(index);
+            let resp_str = match timeout {
+                Some(timeout) => self.post_with_timeout(url, &request, timeout).await?,
+                None => self.post(url, &request).await?,
+            };
+
+            log::debug!("resp_str: {:?}", resp_str);
+            let resp: RpcResponse<serde_json::Value> = serde_json::from_str(&resp_str)?;
+
+            resp.into_result(request.id())
+        }
+        .await;
+
+        self.state()
+            .metrics
+            .record(method, started.elapsed(), result.is_ok());
+
+        match &result {
+            Err(err) if is_endpoint_failure(err) => endpoints.report_failure(index),
+            _ => endpoints.report_success(index),
+        }
+
+        if let Some(guard) = guard {
+            guard.complete(&result);
+        }
+
+        let result = result?;
+        if let Some(cache) = cache {
+            cache.insert(method, request.params(), result.clone());
+        }
+
+        Ok(serde_json::from_value(result)?)
+    }
 
     async fn rpc_post_expect_str(&self, method: &str, params: serde_json::Value) -> Result<String> {
         self.rpc_post::<String>(method, params).await
@@ -92,49 +575,627 @@ pub trait RpcClient {
         method: &str,
         params: serde_json::Value,
     ) -> Result<De> {
-        self.rpc_post::<RpcResult<De>>(method, params)
-            .await
-            .map(|r| r.value)
+        let resp = self.rpc_post::<RpcResult<De>>(method, params).await?;
+
+        if let Some(api_version) = resp.context.and_then(|c| c.api_version) {
+            self.node_version().observe(&api_version);
+        }
+
+        Ok(resp.value)
+    }
+
+    /// Queries the node's `solana-core` version and remembers it for request adaptation.
+    async fn get_version(&self) -> Result<RpcApiVersion> {
+        let resp: GetVersion = self.rpc_post("getVersion", json!([])).await?;
+        let version: RpcApiVersion = resp.solana_core.parse()?;
+        self.node_version().set(version);
+        Ok(version)
+    }
+
+    /// Hash of the cluster's genesis block, see
+    /// [`Cluster::from_genesis_hash`](crate::cluster::Cluster::from_genesis_hash).
+    async fn get_genesis_hash(&self) -> Result<solana_sdk::hash::Hash> {
+        let hash = self
+            .rpc_post_expect_str("getGenesisHash", json!([]))
+            .await?;
+        hash.parse().context("invalid genesis hash")
     }
 
     async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
-        self.rpc_post_expect_result("getBalance", json![pubkey.to_string()])
+        self.get_balance_with_commitment(pubkey, self.commitment())
             .await
     }
 
+    async fn get_balance_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<u64> {
+        self.rpc_post_expect_result("getBalance", json!([pubkey.to_string(), commitment]))
+            .await
+    }
+
+    /// Lamports an account of `space` bytes needs to be rent exempt.
+    async fn get_minimum_balance_for_rent_exemption(&self, space: usize) -> Result<u64> {
+        self.rpc_post(
+            "getMinimumBalanceForRentExemption",
+            json!([space, self.commitment()]),
+        )
+        .await
+    }
+
     async fn get_account(&self, pubkey: &Pubkey) -> Result<Account> {
+        self.get_account_with_commitment(pubkey, self.commitment())
+            .await
+    }
+
+    async fn get_account_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<Account> {
         let opt_acc_val: Option<RpcAccountInfo> = self
             .rpc_post_expect_result(
                 "getAccountInfo",
-                json!([pubkey.to_string(), {"encoding": "base64"}]),
+                json!([
+                    pubkey.to_string(),
+                    {"encoding": self.config().encoding, "commitment": commitment.commitment}
+                ]),
             )
             .await?;
 
-        Ok(opt_acc_val.context("could not find account")?.into())
+        opt_acc_val.context("could not find account")?.try_into()
     }
 
-    async fn send_transaction(&self, tx: &Transaction) -> Result<String> {
-        let tx_bytes = bincode::serialize(tx)?;
-        let tx: String = bs58::encode(tx_bytes).into_string();
-        let resp = self
-            .rpc_post_expect_str("sendTransaction", json!([tx]))
+    /// Sends a transaction, preflighting it at
+    /// [`preflight_commitment`](SolanaClientConfig::preflight_commitment).
+    async fn send_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        self.send_transaction_with_config(tx, &SendConfig::default())
             .await
-            .context("could not send transaction")?;
-        log::debug!("tx hash: {}", resp);
-        Ok(tx)
+    }
+
+    /// Sends a transaction, running the preflight simulation at `commitment`.
+    async fn send_transaction_with_commitment(
+        &self,
+        tx: &Transaction,
+        commitment: CommitmentConfig,
+    ) -> Result<Signature> {
+        let config = SendConfig::default().with_preflight_commitment(commitment);
+        self.send_transaction_with_config(tx, &config).await
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        tx: &Transaction,
+        config: &SendConfig,
+    ) -> Result<Signature> {
+        self.send_wire_transaction(bincode::serialize(tx)?, config)
+            .await
+    }
+
+    /// Sends a legacy or v0 transaction, preflighting it at
+    /// [`preflight_commitment`](SolanaClientConfig::preflight_commitment).
+    async fn send_versioned_transaction(&self, tx: &VersionedTransaction) -> Result<Signature> {
+        self.send_versioned_transaction_with_config(tx, &SendConfig::default())
+            .await
+    }
+
+    async fn send_versioned_transaction_with_commitment(
+        &self,
+        tx: &VersionedTransaction,
+        commitment: CommitmentConfig,
+    ) -> Result<Signature> {
+        let config = SendConfig::default().with_preflight_commitment(commitment);
+        self.send_versioned_transaction_with_config(tx, &config)
+            .await
+    }
+
+    async fn send_versioned_transaction_with_config(
+        &self,
+        tx: &VersionedTransaction,
+        config: &SendConfig,
+    ) -> Result<Signature> {
+        self.send_wire_transaction(bincode::serialize(tx)?, config)
+            .await
+    }
+
+    /// Sends a transaction already serialized in its wire format.
+    async fn send_wire_transaction(
+        &self,
+        wire_tx: Vec<u8>,
+        config: &SendConfig,
+    ) -> Result<Signature> {
+        let encoding = config.encoding.unwrap_or(self.config().encoding);
+        let preflight_commitment = config
+            .preflight_commitment
+            .unwrap_or(self.config().preflight_commitment);
+
+        let mut options = json!({
+            "encoding": encoding,
+            "skipPreflight": config.skip_preflight,
+            "preflightCommitment": preflight_commitment.commitment,
+        });
+        if let Some(max_retries) = config.max_retries {
+            options["maxRetries"] = json!(max_retries);
+        }
+        if let Some(min_context_slot) = config.min_context_slot {
+            self.node_version()
+                .ensure_supported(RpcFeature::MinContextSlot)?;
+            options["minContextSlot"] = json!(min_context_slot);
+        }
+
+        let program_ids = bincode::deserialize::<VersionedTransaction>(&wire_tx)
+            .map(|tx| versioned_transaction_program_ids(&tx))
+            .unwrap_or_default();
+        let tx = encoding.encode(wire_tx);
+        let signature = self
+            .rpc_post_expect_str("sendTransaction", json!([tx, options]))
+            .await
+            .inspect_err(|err| {
+                let expired = err
+                    .downcast_ref::<RpcError>()
+                    .is_some_and(RpcError::is_blockhash_not_found);
+                if expired {
+                    self.state().recent_blockhash.clear();
+                }
+            })
+            .context(SendTransactionError { program_ids })?;
+        log::debug!("tx hash: {}", signature);
+        Signature::from_str(&signature).context("invalid transaction signature")
+    }
+
+    /// Sends `tx` and waits until it reached `commitment`, failing with a
+    /// [`ConfirmationError`] if it failed on chain or `timeout` passed first.
+    async fn send_and_confirm_transaction(
+        &self,
+        tx: &Transaction,
+        commitment: CommitmentConfig,
+        timeout: Duration,
+    ) -> Result<Signature> {
+        let signature = self.send_transaction(tx).await?;
+        self.confirm_transaction(&signature, commitment, timeout)
+            .await?;
+        Ok(signature)
+    }
+
+    async fn send_and_confirm_versioned_transaction(
+        &self,
+        tx: &VersionedTransaction,
+        commitment: CommitmentConfig,
+        timeout: Duration,
+    ) -> Result<Signature> {
+        let signature = self.send_versioned_transaction(tx).await?;
+        self.confirm_transaction(&signature, commitment, timeout)
+            .await?;
+        Ok(signature)
+    }
+
+    /// Sends the signed `tx` and resubmits it every
+    /// [`interval_slots`](RebroadcastConfig::interval_slots) until it reached the
+    /// commitment, instead of relying on the node's retries which drop transactions
+    /// under congestion.
+    ///
+    /// Fails with [`ConfirmationError::Expired`] once the block height passed
+    /// `last_valid_block_height` of the transaction's blockhash, see
+    /// [`get_latest_blockhash_with_expiry`](Self::get_latest_blockhash_with_expiry).
+    async fn send_with_rebroadcast(
+        &self,
+        tx: &Transaction,
+        last_valid_block_height: u64,
+        config: &RebroadcastConfig,
+    ) -> Result<Signature> {
+        let signature = *tx.signatures.first().context("transaction is not signed")?;
+        self.send_wire_with_rebroadcast(
+            bincode::serialize(tx)?,
+            signature,
+            last_valid_block_height,
+            config,
+        )
+        .await
+    }
+
+    async fn send_versioned_with_rebroadcast(
+        &self,
+        tx: &VersionedTransaction,
+        last_valid_block_height: u64,
+        config: &RebroadcastConfig,
+    ) -> Result<Signature> {
+        let signature = *tx.signatures.first().context("transaction is not signed")?;
+        self.send_wire_with_rebroadcast(
+            bincode::serialize(tx)?,
+            signature,
+            last_valid_block_height,
+            config,
+        )
+        .await
+    }
+
+    async fn send_wire_with_rebroadcast(
+        &self,
+        wire_tx: Vec<u8>,
+        signature: Signature,
+        last_valid_block_height: u64,
+        config: &RebroadcastConfig,
+    ) -> Result<Signature> {
+        const SLOT_DURATION: Duration = Duration::from_millis(400);
+
+        self.send_wire_transaction(wire_tx.clone(), &config.send)
+            .await?;
+        let resend = config
+            .send
+            .clone()
+            .with_skip_preflight(true)
+            .with_max_retries(0);
+
+        let mut slots = 0;
+        loop {
+            self.sleep(SLOT_DURATION).await;
+            slots += 1;
+
+            let status = self
+                .get_signature_statuses(&[signature])
+                .await?
+                .pop()
+                .flatten();
+            if let Some(status) = status {
+                if let Some(err) = status.err {
+                    return Err(ConfirmationError::Failed { signature, err }.into());
+                }
+                if status.satisfies_commitment(config.commitment) {
+                    return Ok(signature);
+                }
+                // landed, only waiting for the commitment
+                continue;
+            }
+
+            if slots % config.interval_slots.max(1) != 0 {
+                continue;
+            }
+            if self.get_block_height().await? > last_valid_block_height {
+                return Err(ConfirmationError::Expired {
+                    signature,
+                    last_valid_block_height,
+                }
+                .into());
+            }
+            log::debug!("rebroadcasting {}", signature);
+            if let Err(err) = self.send_wire_transaction(wire_tx.clone(), &resend).await {
+                // e.g. "already processed", the status shows up with the next poll
+                log::debug!("rebroadcast of {} failed: {:?}", signature, err);
+            }
+        }
+    }
+
+    /// Polls the status of `signature` until it reached `commitment`, see
+    /// [`send_and_confirm_transaction`](Self::send_and_confirm_transaction).
+    async fn confirm_transaction(
+        &self,
+        signature: &Signature,
+        commitment: CommitmentConfig,
+        timeout: Duration,
+    ) -> Result<()> {
+        const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+        let started = Instant::now();
+        loop {
+            let status = self
+                .get_signature_statuses(&[*signature])
+                .await?
+                .pop()
+                .flatten();
+            if let Some(status) = status {
+                if let Some(err) = status.err {
+                    return Err(ConfirmationError::Failed {
+                        signature: *signature,
+                        err,
+                    }
+                    .into());
+                }
+                if status.satisfies_commitment(commitment) {
+                    return Ok(());
+                }
+            }
+
+            if started.elapsed() >= timeout {
+                return Err(ConfirmationError::TimedOut {
+                    signature: *signature,
+                    timeout,
+                }
+                .into());
+            }
+            self.sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Asks the cluster's faucet for `lamports`, only available on devnet, testnet and
+    /// local validators.
+    async fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> Result<Signature> {
+        let signature = self
+            .rpc_post_expect_str(
+                "requestAirdrop",
+                json!([
+                    pubkey.to_string(),
+                    lamports,
+                    {"commitment": self.commitment().commitment}
+                ]),
+            )
+            .await
+            .context("airdrop failed")?;
+        Signature::from_str(&signature).context("invalid airdrop signature")
+    }
+
+    /// Simulates `tx` against the latest blockhash, without verifying its signatures.
+    async fn simulate_transaction(&self, tx: &Transaction) -> Result<RpcSimulateTransactionResult> {
+        self.simulate_wire_transaction(bincode::serialize(tx)?)
+            .await
+    }
+
+    /// Simulates a legacy or v0 transaction like
+    /// [`simulate_transaction`](Self::simulate_transaction).
+    async fn simulate_versioned_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> Result<RpcSimulateTransactionResult> {
+        self.simulate_wire_transaction(bincode::serialize(tx)?)
+            .await
+    }
+
+    async fn simulate_wire_transaction(
+        &self,
+        wire_tx: Vec<u8>,
+    ) -> Result<RpcSimulateTransactionResult> {
+        let encoding = self.config().encoding;
+        let tx = encoding.encode(wire_tx);
+        self.rpc_post_expect_result(
+            "simulateTransaction",
+            json!([
+                tx,
+                {
+                    "encoding": encoding,
+                    "commitment": self.commitment().commitment,
+                    "sigVerify": false,
+                    "replaceRecentBlockhash": true
+                }
+            ]),
+        )
+        .await
+    }
+
+    /// Calls a read-only "view" instruction through simulation and returns the data
+    /// `program_id` set with `sol_set_return_data`, empty if it set none.
+    ///
+    /// Nothing is signed or sent. The first signer of `ix` pays the (simulated) fee and
+    /// must exist on chain, use [`view_call_with_payer`](Self::view_call_with_payer) for
+    /// instructions without signers. A failed simulation returns a [`SimulationError`].
+    async fn view_call(&self, program_id: &Pubkey, ix: Instruction) -> Result<Vec<u8>> {
+        let payer = ix
+            .accounts
+            .iter()
+            .find(|meta| meta.is_signer)
+            .map(|meta| meta.pubkey)
+            .context("view call instruction has no signer to pay the fee, pass a payer")?;
+        self.view_call_with_payer(program_id, ix, &payer).await
+    }
+
+    async fn view_call_with_payer(
+        &self,
+        program_id: &Pubkey,
+        ix: Instruction,
+        payer: &Pubkey,
+    ) -> Result<Vec<u8>> {
+        let tx = Transaction::new_unsigned(Message::new(&[ix], Some(payer)));
+        let result = self.simulate_transaction(&tx).await?;
+
+        if let Some(err) = result.err {
+            return Err(SimulationError {
+                err,
+                logs: result.logs.unwrap_or_default(),
+            }
+            .into());
+        }
+
+        match result.return_data {
+            Some(data) if data.program_id == program_id.to_string() => data.decode(),
+            Some(data) => bail!(
+                "view call returned data of {} instead of {}",
+                data.program_id,
+                program_id
+            ),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Lowest priority fees of the recent slots that landed transactions writing all of
+    /// `writable` accounts, of any transaction if `writable` is empty.
+    ///
+    /// Takes no commitment, nodes answer from the slots they have processed.
+    async fn get_recent_prioritization_fees(
+        &self,
+        writable: &[Pubkey],
+    ) -> Result<Vec<RpcPrioritizationFee>> {
+        let writable: Vec<String> = writable.iter().map(Pubkey::to_string).collect();
+        self.rpc_post("getRecentPrioritizationFees", json!([writable]))
+            .await
+    }
+
+    /// Looks up the statuses of `signatures`, batching them into requests of at most
+    /// [`MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS`] signatures each.
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<RpcSignatureStatus>>> {
+        let mut statuses = Vec::with_capacity(signatures.len());
+
+        for chunk in signatures.chunks(MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS) {
+            let chunk: Vec<String> = chunk.iter().map(|s| s.to_string()).collect();
+            let resp: Vec<Option<RpcSignatureStatus>> = self
+                .rpc_post_expect_result("getSignatureStatuses", json!([chunk]))
+                .await?;
+            statuses.extend(resp);
+        }
+
+        Ok(statuses)
+    }
+
+    /// Returns the raw balance and decimals of the token account at `address`.
+    async fn get_token_account_balance(&self, address: &Pubkey) -> Result<TokenAmount> {
+        self.get_token_account_balance_with_commitment(address, self.commitment())
+            .await
+    }
+
+    async fn get_token_account_balance_with_commitment(
+        &self,
+        address: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<TokenAmount> {
+        let amount: RpcUiTokenAmount = self
+            .rpc_post_expect_result(
+                "getTokenAccountBalance",
+                json!([address.to_string(), commitment]),
+            )
+            .await?;
+
+        amount.try_into()
+    }
+
+    /// Lists the token accounts of `owner` held under the token program `program_id`.
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<Vec<TokenAccountBalance>> {
+        let accounts: Vec<RpcKeyedTokenAccount> = self
+            .rpc_post_expect_result(
+                "getTokenAccountsByOwner",
+                json!([
+                    owner.to_string(),
+                    {"programId": program_id.to_string()},
+                    {"encoding": "jsonParsed", "commitment": self.commitment().commitment}
+                ]),
+            )
+            .await?;
+
+        accounts.into_iter().map(TryInto::try_into).collect()
+    }
+
+    /// Returns up to `limit` of the most recent signatures involving `address`, newest first.
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedSignature>> {
+        let commitment = if self.commitment().is_finalized() {
+            CommitmentConfig::finalized()
+        } else {
+            CommitmentConfig::confirmed()
+        };
+
+        self.rpc_post(
+            "getSignaturesForAddress",
+            json!([
+                address.to_string(),
+                {"limit": limit, "commitment": commitment.commitment}
+            ]),
+        )
+        .await
+    }
+
+    /// Fetches a confirmed transaction with its status meta, `None` if it is unknown.
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<RpcConfirmedTransaction>> {
+        // getTransaction does not support processed
+        let commitment = if self.commitment().is_finalized() {
+            CommitmentConfig::finalized()
+        } else {
+            CommitmentConfig::confirmed()
+        };
+
+        let mut config = json!({"encoding": "json", "commitment": commitment.commitment});
+        if self
+            .node_version()
+            .supports(RpcFeature::MaxSupportedTransactionVersion)
+        {
+            config["maxSupportedTransactionVersion"] = json!(0);
+        }
+
+        self.rpc_post("getTransaction", json!([signature.to_string(), config]))
+            .await
+    }
+
+    /// Fetches the address lookup tables `keys` to compile v0 messages against.
+    async fn get_address_lookup_table_accounts(
+        &self,
+        keys: &[Pubkey],
+    ) -> Result<Vec<AddressLookupTableAccount>> {
+        let mut tables = Vec::with_capacity(keys.len());
+        for key in keys {
+            tables.push(self.get_address_lookup_table(key).await?.to_account());
+        }
+        Ok(tables)
+    }
+
+    /// Fetches and decodes the address lookup table `key`.
+    async fn get_address_lookup_table(&self, key: &Pubkey) -> Result<LookupTable> {
+        let account = self
+            .get_account(key)
+            .await
+            .with_context(|| format!("could not fetch address lookup table {}", key))?;
+        LookupTable::decode(*key, &account.data)
+    }
+
+    /// An unsigned v0 transaction of `instructions` against the latest blockhash, loading
+    /// accounts through the lookup tables `lookup_table_keys`.
+    async fn new_v0_transaction(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        lookup_table_keys: &[Pubkey],
+    ) -> Result<VersionedTransaction> {
+        let lookup_tables = self
+            .get_address_lookup_table_accounts(lookup_table_keys)
+            .await?;
+        let blockhash = self.recent_blockhash().await?;
+        new_v0_transaction(payer, instructions, &lookup_tables, blockhash)
     }
 
     async fn sign_tx(&self, mut tx: Transaction, kp: &Keypair) -> Result<Transaction> {
-        tx.sign(&[kp], self.get_latest_blockhash().await?);
+        tx.sign(&[kp], self.recent_blockhash().await?);
         Ok(tx)
     }
 
+    /// The blockhash to build a transaction against: the one kept in
+    /// [`RecentBlockhash`](crate::state::RecentBlockhash) if set, a fresh one otherwise.
+    async fn recent_blockhash(&self) -> Result<solana_sdk::hash::Hash> {
+        match self.state().recent_blockhash.get() {
+            Some(blockhash) => Ok(blockhash),
+            None => self.get_latest_blockhash().await,
+        }
+    }
+
     async fn get_latest_blockhash(&self) -> Result<solana_sdk::hash::Hash> {
-        let resp: GetLatestBlockhash = self
-            .rpc_post_expect_result("getLatestBlockhash", json!([{"commitment": "finalized"}]))
-            .await?;
+        self.get_latest_blockhash_with_commitment(self.commitment())
+            .await
+    }
+
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> Result<solana_sdk::hash::Hash> {
+        let blockhash = if self.node_version().supports(RpcFeature::GetLatestBlockhash) {
+            let resp: GetLatestBlockhash = self
+                .rpc_post_expect_result("getLatestBlockhash", json!([commitment]))
+                .await?;
+            resp.blockhash
+        } else {
+            let resp: GetRecentBlockhash = self
+                .rpc_post_expect_result("getRecentBlockhash", json!([commitment]))
+                .await?;
+            resp.blockhash
+        };
 
-        let hash_bytes: [u8; 32] = bs58::decode(resp.blockhash)
+        let hash_bytes: [u8; 32] = bs58::decode(blockhash)
             .into_vec()
             .context("could not decode blockhash")?
             .try_into()
@@ -145,19 +1206,143 @@ pub trait RpcClient {
         Ok(hash)
     }
 
+    /// The latest blockhash and the last block height a transaction using it can land at.
+    async fn get_latest_blockhash_with_expiry(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> Result<(solana_sdk::hash::Hash, u64)> {
+        self.node_version()
+            .ensure_supported(RpcFeature::GetLatestBlockhash)?;
+        let resp: GetLatestBlockhash = self
+            .rpc_post_expect_result("getLatestBlockhash", json!([commitment]))
+            .await?;
+
+        let hash = resp
+            .blockhash
+            .parse()
+            .context("could not decode blockhash")?;
+        Ok((hash, resp.last_valid_block_height))
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        self.rpc_post("getBlockHeight", json!([self.commitment()]))
+            .await
+    }
+
+    async fn get_slot(&self) -> Result<u64> {
+        self.get_slot_with_commitment(self.commitment()).await
+    }
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> Result<u64> {
+        self.rpc_post("getSlot", json!([commitment])).await
+    }
+
+    /// Returns the leaders of `limit` consecutive slots starting at `start_slot`.
+    ///
+    /// Takes no commitment, the leader schedule is fixed for the whole epoch.
+    async fn get_slot_leaders(&self, start_slot: u64, limit: u64) -> Result<Vec<Pubkey>> {
+        let leaders: Vec<String> = self
+            .rpc_post("getSlotLeaders", json!([start_slot, limit]))
+            .await?;
+
+        leaders
+            .iter()
+            .map(|leader| Pubkey::from_str(leader).map_err(|e| anyhow!("{:?}", e)))
+            .collect()
+    }
+
+    /// Lists every node in the cluster with its gossip, TPU and RPC endpoints.
+    ///
+    /// Takes no commitment, the nodes come from gossip rather than from a bank.
+    async fn get_cluster_nodes(&self) -> Result<Vec<RpcContactInfo>> {
+        self.rpc_post("getClusterNodes", json!([])).await
+    }
+
     async fn get_program_accounts(&self, program_id: &Pubkey) -> Result<Vec<(Account, Pubkey)>> {
-        let resp: Vec<(Account, Pubkey)> = self
+        self.get_program_accounts_with_commitment(program_id, self.commitment())
+            .await
+    }
+
+    async fn get_program_accounts_with_commitment(
+        &self,
+        program_id: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<Vec<(Account, Pubkey)>> {
+        let resp: Vec<RpcKeyedAccount> = self
             .rpc_post(
                 "getProgramAccounts",
-                json!([program_id.to_string(), {"encoding": "base64"}]),
+                json!([
+                    program_id.to_string(),
+                    {"encoding": self.config().encoding, "commitment": commitment.commitment}
+                ]),
             )
             .await?;
 
-        Ok(resp.into_iter().collect())
+        resp.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn get_program_accounts_with_filters(
+        &self,
+        program_id: &Pubkey,
+        filters: &[RpcFilter],
+        commitment: CommitmentConfig,
+    ) -> Result<Vec<(Account, Pubkey)>> {
+        let filters: Vec<_> = filters.iter().map(RpcFilter::to_json).collect();
+        let resp: Vec<RpcKeyedAccount> = self
+            .rpc_post(
+                "getProgramAccounts",
+                json!([
+                    program_id.to_string(),
+                    {
+                        "encoding": self.config().encoding,
+                        "commitment": commitment.commitment,
+                        "filters": filters,
+                    }
+                ]),
+            )
+            .await?;
+
+        resp.into_iter().map(TryInto::try_into).collect()
+    }
+
+    /// Fetches the accounts of a large program chunk by chunk, so no single request
+    /// has to scan and return the whole program.
+    ///
+    /// `on_progress` is called after every chunk. A failing chunk fails the scan, the
+    /// retry policy has already been applied to it.
+    async fn scan_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        scan: &ProgramScan,
+        on_progress: &mut dyn FnMut(ScanProgress),
+    ) -> Result<Vec<(Account, Pubkey)>> {
+        let mut accounts = Vec::new();
+
+        for chunk in 0..ProgramScan::CHUNKS {
+            let found = self
+                .get_program_accounts_with_filters(
+                    program_id,
+                    &scan.chunk_filters(chunk),
+                    self.commitment(),
+                )
+                .await
+                .with_context(|| format!("scan chunk {} of {}", chunk, program_id))?;
+            accounts.extend(found);
+
+            on_progress(ScanProgress {
+                chunks_done: chunk + 1,
+                chunks_total: ProgramScan::CHUNKS,
+                accounts: accounts.len(),
+            });
+        }
+
+        Ok(accounts)
     }
 }
 
+pub const SOLANA_MAINNET_URL: &str = "https://api.mainnet-beta.solana.com";
 pub const SOLANA_DEVNET_URL: &str = "https://api.devnet.solana.com";
+pub const SOLANA_TESTNET_URL: &str = "https://api.testnet.solana.com";
 pub const SOLANA_LOCAL_URL: &str = "http://127.0.0.1:8899";
 
 pub fn test_transfer_tx(pubkey: Pubkey) -> Result<Transaction> {
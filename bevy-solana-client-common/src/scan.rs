@@ -0,0 +1,81 @@
+use serde_json::{json, Value};
+use solana_sdk::bs58;
+
+/// Server side filter of `getProgramAccounts`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcFilter {
+    /// Only accounts with exactly this many data bytes.
+    DataSize(u64),
+    /// Only accounts whose data contains `bytes` at `offset`.
+    Memcmp { offset: usize, bytes: Vec<u8> },
+}
+
+impl RpcFilter {
+    pub fn memcmp(offset: usize, bytes: impl Into<Vec<u8>>) -> Self {
+        RpcFilter::Memcmp {
+            offset,
+            bytes: bytes.into(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        match self {
+            RpcFilter::DataSize(size) => json!({ "dataSize": size }),
+            RpcFilter::Memcmp { offset, bytes } => json!({
+                "memcmp": { "offset": offset, "bytes": bs58::encode(bytes).into_string() }
+            }),
+        }
+    }
+}
+
+/// Splits one huge `getProgramAccounts` call into 256 smaller ones, one per value of
+/// the data byte at `offset`, see
+/// [`RpcClient::scan_program_accounts`](crate::rpc_client::RpcClient::scan_program_accounts).
+///
+/// Pick an offset with evenly distributed values that lies within every account of
+/// interest, e.g. the first byte of a pubkey field. Accounts with less than
+/// `offset + 1` data bytes match no chunk and are skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramScan {
+    pub offset: usize,
+    /// Filters applied to every chunk, e.g. an account discriminator.
+    pub filters: Vec<RpcFilter>,
+}
+
+impl ProgramScan {
+    pub fn new(offset: usize) -> Self {
+        Self {
+            offset,
+            filters: Vec::new(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: RpcFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub const CHUNKS: usize = 256;
+
+    /// The filters of chunk `chunk`, `0..CHUNKS`.
+    pub fn chunk_filters(&self, chunk: usize) -> Vec<RpcFilter> {
+        let mut filters = self.filters.clone();
+        filters.push(RpcFilter::memcmp(self.offset, [chunk as u8]));
+        filters
+    }
+}
+
+/// Progress of a running [`ProgramScan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanProgress {
+    pub chunks_done: usize,
+    pub chunks_total: usize,
+    /// Accounts found so far.
+    pub accounts: usize,
+}
+
+impl ScanProgress {
+    pub fn fraction(&self) -> f32 {
+        self.chunks_done as f32 / self.chunks_total.max(1) as f32
+    }
+}
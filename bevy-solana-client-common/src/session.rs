@@ -0,0 +1,211 @@
+use std::{
+    fmt::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use anyhow::{bail, Result};
+use solana_sdk::{
+    ed25519_program,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::{Transaction, VersionedTransaction},
+};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::signer::GameSigner;
+
+/// What a session key may do on behalf of the main wallet, signed once by that wallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionGrant {
+    pub authority: Pubkey,
+    pub session_key: Pubkey,
+    /// Programs the session key may call, transactions touching any other are refused.
+    pub programs: Vec<Pubkey>,
+    /// Unix timestamp the grant ends at.
+    pub expires_at: i64,
+}
+
+impl SessionGrant {
+    /// The text the authority signs, readable in the wallet's signing prompt.
+    pub fn message(&self) -> Vec<u8> {
+        let mut message = String::from("Approve game session key\n");
+        let _ = writeln!(message, "authority: {}", self.authority);
+        let _ = writeln!(message, "session key: {}", self.session_key);
+        for program in &self.programs {
+            let _ = writeln!(message, "program: {}", program);
+        }
+        let _ = write!(message, "expires: {}", self.expires_at);
+        message.into_bytes()
+    }
+
+    pub fn is_expired(&self, now_unix: i64) -> bool {
+        now_unix >= self.expires_at
+    }
+
+    /// Whether every instruction of `tx` calls one of the granted programs.
+    pub fn allows(&self, tx: &Transaction) -> bool {
+        let keys = &tx.message.account_keys;
+        tx.message.instructions.iter().all(|ix| {
+            keys.get(ix.program_id_index as usize)
+                .is_some_and(|program| self.programs.contains(program))
+        })
+    }
+
+    /// [`allows`](Self::allows) for legacy and v0 transactions, whose program ids are
+    /// never loaded from lookup tables.
+    pub fn allows_versioned(&self, tx: &VersionedTransaction) -> bool {
+        let keys = tx.message.static_account_keys();
+        tx.message.instructions().iter().all(|ix| {
+            keys.get(ix.program_id_index as usize)
+                .is_some_and(|program| self.programs.contains(program))
+        })
+    }
+}
+
+/// An ephemeral keypair that signs gameplay transactions without wallet prompts,
+/// within the limits of its [`SessionGrant`].
+pub struct SessionKey {
+    keypair: Keypair,
+    pub grant: SessionGrant,
+    /// The authority's signature over [`SessionGrant::message`].
+    pub approval: Signature,
+    revoked: AtomicBool,
+}
+
+impl SessionKey {
+    /// Has `authority` approve `keypair` for `programs` until `expires_at`, prompting
+    /// the wallet once.
+    pub async fn approve(
+        authority: &(impl GameSigner + ?Sized),
+        keypair: Keypair,
+        programs: Vec<Pubkey>,
+        expires_at: i64,
+    ) -> Result<Self> {
+        let grant = SessionGrant {
+            authority: authority.pubkey(),
+            session_key: Signer::pubkey(&keypair),
+            programs,
+            expires_at,
+        };
+        let approval = authority.sign_message(&grant.message()).await?;
+
+        if !approval.verify(grant.authority.as_ref(), &grant.message()) {
+            bail!("wallet returned an invalid session approval");
+        }
+
+        Ok(Self {
+            keypair,
+            grant,
+            approval,
+            revoked: AtomicBool::new(false),
+        })
+    }
+
+    pub fn keypair(&self) -> &Keypair {
+        &self.keypair
+    }
+
+    /// Stops the key from signing anything else.
+    pub fn revoke(&self) {
+        self.revoked.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked.load(Ordering::Relaxed)
+    }
+
+    /// Whether the key can still sign, by the local clock.
+    pub fn is_active(&self) -> bool {
+        !self.is_revoked() && !self.grant.is_expired(now_unix())
+    }
+
+    /// Fails if the key can no longer sign or the transaction is not `allowed` by the grant.
+    fn check_can_sign(&self, allowed: bool) -> Result<()> {
+        if self.is_revoked() {
+            bail!("session key {} was revoked", self.grant.session_key);
+        }
+        if self.grant.is_expired(now_unix()) {
+            bail!("session key {} expired", self.grant.session_key);
+        }
+        if !allowed {
+            bail!("transaction calls a program outside the session grant");
+        }
+        Ok(())
+    }
+
+    /// An `Ed25519Program` instruction proving the approval, for programs that check
+    /// session keys on chain by inspecting the instructions sysvar.
+    pub fn approval_instruction(&self) -> Instruction {
+        const OFFSETS_START: u16 = 2;
+        const OFFSETS_SIZE: u16 = 14;
+        const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+        let message = self.grant.message();
+        let public_key_offset = OFFSETS_START + OFFSETS_SIZE;
+        let signature_offset = public_key_offset + 32;
+        let message_offset = signature_offset + 64;
+
+        let mut data = vec![1, 0];
+        for value in [
+            signature_offset,
+            CURRENT_INSTRUCTION,
+            public_key_offset,
+            CURRENT_INSTRUCTION,
+            message_offset,
+            message.len() as u16,
+            CURRENT_INSTRUCTION,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(self.grant.authority.as_ref());
+        data.extend_from_slice(self.approval.as_ref());
+        data.extend_from_slice(&message);
+
+        Instruction::new_with_bytes(ed25519_program::id(), &data, vec![])
+    }
+}
+
+impl std::fmt::Debug for SessionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionKey")
+            .field("grant", &self.grant)
+            .field("revoked", &self.is_revoked())
+            .finish()
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl GameSigner for SessionKey {
+    fn pubkey(&self) -> Pubkey {
+        Signer::pubkey(&self.keypair)
+    }
+
+    /// Refuses transactions once the session ended or that call programs outside the grant.
+    async fn sign_transaction(&self, tx: &mut Transaction) -> Result<()> {
+        self.check_can_sign(self.grant.allows(tx))?;
+
+        GameSigner::sign_transaction(&self.keypair, tx).await
+    }
+
+    async fn sign_versioned_transaction(&self, tx: &mut VersionedTransaction) -> Result<()> {
+        self.check_can_sign(self.grant.allows_versioned(tx))?;
+
+        GameSigner::sign_versioned_transaction(&self.keypair, tx).await
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        if !self.is_active() {
+            bail!("session key {} is no longer active", self.grant.session_key);
+        }
+        Ok(Signer::sign_message(&self.keypair, message))
+    }
+}
+
+/// Seconds since the unix epoch by the local clock.
+pub fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() as i64)
+}
@@ -0,0 +1,99 @@
+use anyhow::{bail, Result};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::{Transaction, VersionedTransaction},
+};
+
+use crate::{rpc_client::RpcClient, versioned::partial_sign_versioned};
+
+/// Something that can sign for a player, a browser wallet on wasm or a local keypair.
+///
+/// Gameplay code written against this trait compiles the same on every target, and
+/// tests can swap in a [`Keypair`].
+#[async_trait::async_trait(?Send)]
+pub trait GameSigner {
+    fn pubkey(&self) -> Pubkey;
+
+    /// Adds this signer's signature to `tx`, leaving the other signatures untouched.
+    async fn sign_transaction(&self, tx: &mut Transaction) -> Result<()>;
+
+    /// Like [`sign_transaction`](Self::sign_transaction) for legacy and v0 messages.
+    ///
+    /// Signers that only handle legacy transactions keep the default, which fails.
+    async fn sign_versioned_transaction(&self, _tx: &mut VersionedTransaction) -> Result<()> {
+        bail!("{} cannot sign versioned transactions", self.pubkey())
+    }
+
+    /// Signs arbitrary bytes off-chain, e.g. a server nonce.
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature>;
+
+    /// Signs `tx` and submits it if the signer can send transactions itself, like a
+    /// browser wallet. Returns the signature if `tx` was sent, `None` if it was only
+    /// signed and still has to be sent.
+    ///
+    /// Use [`sign_and_send_transaction`] instead of calling this directly.
+    async fn sign_and_submit_transaction(&self, tx: &mut Transaction) -> Result<Option<Signature>> {
+        self.sign_transaction(tx).await?;
+        Ok(None)
+    }
+
+    /// Like [`sign_and_submit_transaction`](Self::sign_and_submit_transaction) for legacy
+    /// and v0 messages.
+    async fn sign_and_submit_versioned_transaction(
+        &self,
+        tx: &mut VersionedTransaction,
+    ) -> Result<Option<Signature>> {
+        self.sign_versioned_transaction(tx).await?;
+        Ok(None)
+    }
+}
+
+/// Has `signer` sign `tx` and send it, through the wallet if it submits transactions
+/// itself and through `rpc` otherwise.
+///
+/// This is the default way to submit a player's transaction, wallets like Phantom flag
+/// transactions they only signed for another app to send.
+pub async fn sign_and_send_transaction(
+    signer: &(impl GameSigner + ?Sized),
+    tx: &mut Transaction,
+    rpc: &impl RpcClient,
+) -> Result<Signature> {
+    match signer.sign_and_submit_transaction(tx).await? {
+        Some(signature) => Ok(signature),
+        None => rpc.send_transaction(tx).await,
+    }
+}
+
+pub async fn sign_and_send_versioned_transaction(
+    signer: &(impl GameSigner + ?Sized),
+    tx: &mut VersionedTransaction,
+    rpc: &impl RpcClient,
+) -> Result<Signature> {
+    match signer.sign_and_submit_versioned_transaction(tx).await? {
+        Some(signature) => Ok(signature),
+        None => rpc.send_versioned_transaction(tx).await,
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl GameSigner for Keypair {
+    fn pubkey(&self) -> Pubkey {
+        Signer::pubkey(self)
+    }
+
+    async fn sign_transaction(&self, tx: &mut Transaction) -> Result<()> {
+        let blockhash = tx.message.recent_blockhash;
+        tx.try_partial_sign(&[self], blockhash)?;
+        Ok(())
+    }
+
+    async fn sign_versioned_transaction(&self, tx: &mut VersionedTransaction) -> Result<()> {
+        partial_sign_versioned(tx, self)
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(Signer::sign_message(self, message))
+    }
+}
@@ -0,0 +1,60 @@
+use std::sync::RwLock;
+
+use solana_sdk::hash::Hash;
+
+use crate::{
+    cache::ResponseCache, coalesce::InFlightRequests, config::SolanaClientConfig,
+    metrics::RpcMetrics, sticky::StickySession, version::NodeVersion,
+};
+
+/// Runtime state shared by every request of a client, as opposed to its static config.
+#[derive(Debug, Default)]
+pub struct RpcClientState {
+    pub node_version: NodeVersion,
+    /// Only present if enabled through [`SolanaClientConfig::cache`].
+    pub response_cache: Option<ResponseCache>,
+    /// Requests currently on the wire, shared with identical concurrent requests.
+    pub in_flight: InFlightRequests,
+    /// Request counts, errors and latencies per method.
+    pub metrics: RpcMetrics,
+    /// Only present if enabled through [`SolanaClientConfig::sticky_session`].
+    pub sticky_session: Option<StickySession>,
+    /// See [`RecentBlockhash`].
+    pub recent_blockhash: RecentBlockhash,
+}
+
+impl RpcClientState {
+    pub fn new(config: &SolanaClientConfig) -> Self {
+        Self {
+            node_version: NodeVersion::default(),
+            response_cache: config.cache.clone().map(ResponseCache::new),
+            in_flight: InFlightRequests::default(),
+            metrics: RpcMetrics::default(),
+            sticky_session: config.sticky_session.clone().map(StickySession::new),
+            recent_blockhash: RecentBlockhash::default(),
+        }
+    }
+}
+
+/// A blockhash kept fresh in the background, e.g. by the wasm crate's `BlockhashCache`,
+/// that [`RpcClient::recent_blockhash`](crate::rpc_client::RpcClient::recent_blockhash)
+/// builds transactions against instead of fetching one.
+///
+/// Cleared when a transaction fails with `BlockhashNotFound`, the owner is expected to
+/// notice and fetch a new one.
+#[derive(Debug, Default)]
+pub struct RecentBlockhash(RwLock<Option<Hash>>);
+
+impl RecentBlockhash {
+    pub fn get(&self) -> Option<Hash> {
+        *self.0.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set(&self, blockhash: Option<Hash>) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = blockhash;
+    }
+
+    pub fn clear(&self) {
+        self.set(None);
+    }
+}
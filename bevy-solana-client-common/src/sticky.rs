@@ -0,0 +1,95 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use web_time::Instant;
+
+/// Keeps reads of a load-balanced provider on the same node, by replaying the session
+/// token the provider hands out.
+///
+/// Browsers only expose response headers listed in `Access-Control-Expose-Headers`,
+/// providers supporting sticky sessions from web pages list theirs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StickySessionConfig {
+    /// Header of responses carrying the session token.
+    pub response_header: String,
+    /// Header the token is sent back in, usually the same as `response_header`.
+    pub request_header: String,
+    /// How long a token is replayed after it was last seen.
+    pub window: Duration,
+}
+
+impl StickySessionConfig {
+    pub fn new(header: impl Into<String>) -> Self {
+        let header = header.into();
+        Self {
+            response_header: header.clone(),
+            request_header: header,
+            window: Duration::from_secs(30),
+        }
+    }
+
+    pub fn with_request_header(mut self, header: impl Into<String>) -> Self {
+        self.request_header = header.into();
+        self
+    }
+
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+}
+
+/// The session tokens captured per endpoint url.
+#[derive(Debug)]
+pub struct StickySession {
+    config: StickySessionConfig,
+    tokens: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl StickySession {
+    pub fn new(config: StickySessionConfig) -> Self {
+        Self {
+            config,
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &StickySessionConfig {
+        &self.config
+    }
+
+    /// The header to add to the next request to `url`, if a token is still fresh.
+    pub fn request_header(&self, url: &str) -> Option<(&str, String)> {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        match tokens.get(url) {
+            Some((token, seen)) if seen.elapsed() < self.config.window => {
+                Some((self.config.request_header.as_str(), token.clone()))
+            }
+            Some(_) => {
+                log::debug!("sticky session of {} expired", url);
+                tokens.remove(url);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Records the value of [`StickySessionConfig::response_header`] of a response from `url`.
+    pub fn capture(&self, url: &str, token: Option<&str>) {
+        let Some(token) = token.filter(|token| !token.is_empty()) else {
+            return;
+        };
+
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        if tokens.get(url).is_none_or(|(known, _)| known != token) {
+            log::debug!("sticky session of {}: {}", url, token);
+        }
+        tokens.insert(url.to_string(), (token.to_string(), Instant::now()));
+    }
+
+    pub fn clear(&self) {
+        self.tokens
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
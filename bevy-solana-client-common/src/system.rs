@@ -0,0 +1,85 @@
+use anyhow::Result;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_instruction};
+
+use crate::rpc_client::RpcClient;
+
+/// Creates `new_account` with `space` bytes owned by `owner`, funded rent exempt by
+/// `payer`. Both have to sign.
+pub async fn create_account(
+    rpc: &impl RpcClient,
+    payer: &Pubkey,
+    new_account: &Pubkey,
+    space: u64,
+    owner: &Pubkey,
+) -> Result<Instruction> {
+    let lamports = rpc
+        .get_minimum_balance_for_rent_exemption(space as usize)
+        .await?;
+    Ok(system_instruction::create_account(
+        payer,
+        new_account,
+        lamports,
+        space,
+        owner,
+    ))
+}
+
+/// Creates the account derived from `base` and `seed`, so no new keypair has to sign,
+/// returning the instruction and the account address.
+pub async fn create_account_with_seed(
+    rpc: &impl RpcClient,
+    payer: &Pubkey,
+    base: &Pubkey,
+    seed: &str,
+    space: u64,
+    owner: &Pubkey,
+) -> Result<(Instruction, Pubkey)> {
+    let address = Pubkey::create_with_seed(base, seed, owner)?;
+    let lamports = rpc
+        .get_minimum_balance_for_rent_exemption(space as usize)
+        .await?;
+    let ix = system_instruction::create_account_with_seed(
+        payer, &address, base, seed, lamports, space, owner,
+    );
+    Ok((ix, address))
+}
+
+/// Grows the system owned `account` to `space` bytes, topping it up to rent exemption
+/// first. `account` has to sign.
+pub async fn allocate(
+    rpc: &impl RpcClient,
+    payer: &Pubkey,
+    account: &Pubkey,
+    space: u64,
+) -> Result<Vec<Instruction>> {
+    let mut instructions: Vec<Instruction> = fund_rent_exempt(rpc, payer, account, space)
+        .await?
+        .into_iter()
+        .collect();
+    instructions.push(system_instruction::allocate(account, space));
+    Ok(instructions)
+}
+
+/// Hands the system owned `account` over to `owner`. `account` has to sign.
+pub fn assign(account: &Pubkey, owner: &Pubkey) -> Instruction {
+    system_instruction::assign(account, owner)
+}
+
+/// Transfers what `account` lacks to be rent exempt with `space` bytes, `None` if it
+/// already is.
+///
+/// Programs usually create their PDAs themselves but expect them to hold enough lamports.
+pub async fn fund_rent_exempt(
+    rpc: &impl RpcClient,
+    payer: &Pubkey,
+    account: &Pubkey,
+    space: u64,
+) -> Result<Option<Instruction>> {
+    let required = rpc
+        .get_minimum_balance_for_rent_exemption(space as usize)
+        .await?;
+    let balance = rpc.get_balance(account).await?;
+
+    Ok((balance < required)
+        .then(|| system_instruction::transfer(payer, account, required - balance)))
+}
@@ -0,0 +1,457 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    system_program,
+    transaction::Transaction,
+};
+
+use crate::{
+    builder::TransactionBuilder, metadata::create_metadata_account_v3, rpc_client::RpcClient,
+    system,
+};
+
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Size of a mint account of the token program, token-2022 mints with extensions are larger.
+pub const MINT_LEN: usize = 82;
+
+/// A raw token amount together with its mint's decimals.
+///
+/// Amounts are never converted to floats, use [`TokenAmount::to_ui_string`] and
+/// [`TokenAmount::from_ui_str`] to convert from and to exact decimal strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TokenAmount {
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    pub fn new(amount: u64, decimals: u8) -> Self {
+        Self { amount, decimals }
+    }
+
+    /// Formats the amount as an exact decimal string without trailing zeros, e.g. `1.5`.
+    pub fn to_ui_string(&self) -> String {
+        let (int, frac) = self.split();
+        let frac = frac.trim_end_matches('0');
+        if frac.is_empty() {
+            int
+        } else {
+            format!("{}.{}", int, frac)
+        }
+    }
+
+    /// Formats the amount with all `decimals` fractional digits, e.g. `1.500000`.
+    pub fn to_ui_string_padded(&self) -> String {
+        let (int, frac) = self.split();
+        if frac.is_empty() {
+            int
+        } else {
+            format!("{}.{}", int, frac)
+        }
+    }
+
+    /// Parses an exact decimal string such as `1.5` into a raw amount with `decimals`.
+    ///
+    /// Fails instead of rounding if `s` has more fractional digits than the mint.
+    pub fn from_ui_str(s: &str, decimals: u8) -> Result<Self> {
+        let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+        if int.is_empty() && frac.is_empty()
+            || !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit())
+        {
+            bail!("invalid token amount {:?}", s);
+        }
+
+        let frac = frac.trim_end_matches('0');
+        if frac.len() > decimals as usize {
+            bail!(
+                "token amount {:?} has more than {} decimal places",
+                s,
+                decimals
+            );
+        }
+
+        let digits = format!("{}{:0<width$}", int, frac, width = decimals as usize);
+        let amount = digits
+            .parse::<u64>()
+            .map_err(|e| anyhow!("invalid token amount {:?}: {}", s, e))?;
+
+        Ok(Self::new(amount, decimals))
+    }
+
+    /// Splits the amount into its integer and zero padded fractional digits.
+    fn split(&self) -> (String, String) {
+        let decimals = self.decimals as usize;
+        let digits = format!("{:0>width$}", self.amount, width = decimals + 1);
+        let (int, frac) = digits.split_at(digits.len() - decimals);
+        (int.to_string(), frac.to_string())
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_ui_string())
+    }
+}
+
+/// A token account and its balance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenAccountBalance {
+    pub address: Pubkey,
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub program_id: Pubkey,
+    pub amount: TokenAmount,
+}
+
+impl TokenAccountBalance {
+    /// Whether this looks like an NFT holding, a single indivisible token.
+    pub fn is_nft(&self) -> bool {
+        self.amount.amount == 1 && self.amount.decimals == 0
+    }
+}
+
+/// A token amount as returned by the RPC, the raw amount is a string.
+///
+/// The float `uiAmount` fields are ignored on purpose, convert into a [`TokenAmount`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcUiTokenAmount {
+    pub amount: String,
+    pub decimals: u8,
+}
+
+/// A token balance before or after a transaction, from `preTokenBalances` / `postTokenBalances`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionTokenBalance {
+    /// Index of the token account in the transaction's account keys.
+    pub account_index: usize,
+    pub mint: String,
+    pub owner: Option<String>,
+    pub ui_token_amount: RpcUiTokenAmount,
+}
+
+impl TryFrom<RpcUiTokenAmount> for TokenAmount {
+    type Error = anyhow::Error;
+
+    fn try_from(ui: RpcUiTokenAmount) -> Result<Self> {
+        let amount = ui
+            .amount
+            .parse()
+            .map_err(|e| anyhow!("invalid token amount {:?}: {}", ui.amount, e))?;
+        Ok(TokenAmount::new(amount, ui.decimals))
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub(crate) struct RpcKeyedTokenAccount {
+    pub pubkey: String,
+    pub account: RpcParsedTokenAccount,
+}
+
+#[derive(serde::Deserialize)]
+pub(crate) struct RpcParsedTokenAccount {
+    pub owner: String,
+    pub data: RpcParsedTokenAccountData,
+}
+
+#[derive(serde::Deserialize)]
+pub(crate) struct RpcParsedTokenAccountData {
+    pub parsed: RpcParsedTokenAccountInfo,
+}
+
+#[derive(serde::Deserialize)]
+pub(crate) struct RpcParsedTokenAccountInfo {
+    pub info: RpcTokenAccountInfo,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RpcTokenAccountInfo {
+    pub mint: String,
+    pub owner: String,
+    pub token_amount: RpcUiTokenAmount,
+}
+
+impl TryFrom<RpcKeyedTokenAccount> for TokenAccountBalance {
+    type Error = anyhow::Error;
+
+    fn try_from(keyed: RpcKeyedTokenAccount) -> Result<Self> {
+        let info = keyed.account.data.parsed.info;
+        Ok(TokenAccountBalance {
+            address: parse_pubkey(&keyed.pubkey)?,
+            mint: parse_pubkey(&info.mint)?,
+            owner: parse_pubkey(&info.owner)?,
+            program_id: parse_pubkey(&keyed.account.owner)?,
+            amount: info.token_amount.try_into()?,
+        })
+    }
+}
+
+/// The parts of a mint needed to move its tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mint {
+    pub address: Pubkey,
+    /// The token program owning the mint, [`TOKEN_PROGRAM_ID`] or [`TOKEN_2022_PROGRAM_ID`].
+    pub program_id: Pubkey,
+    pub supply: u64,
+    pub decimals: u8,
+}
+
+impl Mint {
+    /// Reads the base mint layout, which token-2022 shares.
+    pub fn decode(address: Pubkey, program_id: Pubkey, data: &[u8]) -> Result<Self> {
+        if program_id != TOKEN_PROGRAM_ID && program_id != TOKEN_2022_PROGRAM_ID {
+            bail!("{} is not owned by a token program", address);
+        }
+        if data.len() < MINT_LEN || data[45] != 1 {
+            bail!("{} is not an initialized mint", address);
+        }
+        Ok(Self {
+            address,
+            program_id,
+            supply: u64::from_le_bytes(data[36..44].try_into()?),
+            decimals: data[44],
+        })
+    }
+}
+
+pub async fn get_mint(rpc: &impl RpcClient, mint: &Pubkey) -> Result<Mint> {
+    let account = rpc.get_account(mint).await?;
+    Mint::decode(*mint, account.owner, &account.data)
+}
+
+/// The associated token account of `owner` for `mint` under the token program `program_id`.
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), program_id.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Creates the associated token account of `owner` for `mint`, paid by `payer`.
+///
+/// Succeeds without changes if the account already exists.
+pub fn create_associated_token_account_idempotent(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    program_id: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(associated_token_address(owner, mint, program_id), false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*program_id, false),
+        ],
+        data: vec![1],
+    }
+}
+
+/// Moves `amount` raw tokens between two token accounts, `authority` has to sign.
+///
+/// Token-2022 rejects this for mints with transfer fees or hooks, prefer [`transfer_checked`].
+pub fn transfer(
+    program_id: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut data = vec![3];
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+/// [`transfer`] that fails unless `decimals` match the mint's, so an amount scaled for
+/// the wrong mint is never sent.
+pub fn transfer_checked(
+    program_id: &Pubkey,
+    source: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+    decimals: u8,
+) -> Instruction {
+    let mut data = vec![12];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(decimals);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+/// An unsigned transaction moving `amount` raw tokens of `mint` from the associated token
+/// account of `from_owner` to the one of `to_owner`, paid and signed by `from_owner`.
+///
+/// The destination account is created if it does not exist yet.
+pub async fn transfer_token(
+    rpc: &impl RpcClient,
+    mint: &Pubkey,
+    from_owner: &Pubkey,
+    to_owner: &Pubkey,
+    amount: u64,
+) -> Result<Transaction> {
+    let mint = get_mint(rpc, mint).await?;
+    let source = associated_token_address(from_owner, &mint.address, &mint.program_id);
+    let destination = associated_token_address(to_owner, &mint.address, &mint.program_id);
+
+    let mut builder = TransactionBuilder::new(*from_owner);
+    // token accounts always hold rent, a zero balance means there is none yet
+    if rpc.get_balance(&destination).await? == 0 {
+        builder = builder.instruction(create_associated_token_account_idempotent(
+            from_owner,
+            to_owner,
+            &mint.address,
+            &mint.program_id,
+        ));
+    }
+    builder
+        .instruction(transfer_checked(
+            &mint.program_id,
+            &source,
+            &mint.address,
+            &destination,
+            from_owner,
+            amount,
+            mint.decimals,
+        ))
+        .build(rpc)
+        .await
+}
+
+/// Initializes the allocated `mint`, no signature needed besides the transaction's.
+pub fn initialize_mint2(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
+    decimals: u8,
+) -> Instruction {
+    let mut data = vec![20, decimals];
+    data.extend_from_slice(authority.as_ref());
+    match freeze_authority {
+        Some(freeze_authority) => {
+            data.push(1);
+            data.extend_from_slice(freeze_authority.as_ref());
+        }
+        None => data.push(0),
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new(*mint, false)],
+        data,
+    }
+}
+
+/// A new mint for [`create_mint`], e.g. a seasonal currency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MintConfig {
+    pub decimals: u8,
+    /// Who may mint tokens, also the metadata's update authority.
+    pub authority: Pubkey,
+    pub freeze_authority: Option<Pubkey>,
+    /// [`TOKEN_PROGRAM_ID`] or [`TOKEN_2022_PROGRAM_ID`].
+    pub program_id: Pubkey,
+    /// Name, symbol and uri of Metaplex metadata to create along with the mint.
+    pub metadata: Option<(String, String, String)>,
+}
+
+impl MintConfig {
+    pub fn new(decimals: u8, authority: Pubkey) -> Self {
+        Self {
+            decimals,
+            authority,
+            freeze_authority: None,
+            program_id: TOKEN_PROGRAM_ID,
+            metadata: None,
+        }
+    }
+
+    pub fn with_freeze_authority(mut self, freeze_authority: Pubkey) -> Self {
+        self.freeze_authority = Some(freeze_authority);
+        self
+    }
+
+    pub fn with_program_id(mut self, program_id: Pubkey) -> Self {
+        self.program_id = program_id;
+        self
+    }
+
+    /// Creates Metaplex metadata, so wallets and explorers show the token's name and icon.
+    /// The authority has to sign then.
+    pub fn with_metadata(
+        mut self,
+        name: impl Into<String>,
+        symbol: impl Into<String>,
+        uri: impl Into<String>,
+    ) -> Self {
+        self.metadata = Some((name.into(), symbol.into(), uri.into()));
+        self
+    }
+}
+
+/// Creates and initializes `mint` rent exempt, paid by `payer`. `payer` and the new
+/// `mint` keypair have to sign.
+pub async fn create_mint(
+    rpc: &impl RpcClient,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    config: &MintConfig,
+) -> Result<Vec<Instruction>> {
+    let mut instructions = vec![
+        system::create_account(rpc, payer, mint, MINT_LEN as u64, &config.program_id).await?,
+        initialize_mint2(
+            &config.program_id,
+            mint,
+            &config.authority,
+            config.freeze_authority.as_ref(),
+            config.decimals,
+        ),
+    ];
+    if let Some((name, symbol, uri)) = &config.metadata {
+        instructions.push(create_metadata_account_v3(
+            mint,
+            &config.authority,
+            payer,
+            name,
+            symbol,
+            uri,
+            true,
+        ));
+    }
+    Ok(instructions)
+}
+
+fn parse_pubkey(s: &str) -> Result<Pubkey> {
+    Pubkey::from_str(s).map_err(|e| anyhow!("invalid pubkey {:?}: {}", s, e))
+}
@@ -0,0 +1,121 @@
+use std::{fmt, str::FromStr, sync::RwLock};
+
+use anyhow::{anyhow, bail, Result};
+
+/// A `solana-core` version as reported by `getVersion` or a response's `apiVersion` context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RpcApiVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+}
+
+impl RpcApiVersion {
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl FromStr for RpcApiVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // strip pre-release / build suffixes such as `1.18.0-beta` or `2.0.3+abc`
+        let core = s.split(['-', '+']).next().unwrap_or_default();
+        let mut parts = core.split('.').map(|p| {
+            p.parse::<u16>()
+                .map_err(|e| anyhow!("invalid api version {:?}: {}", s, e))
+        });
+
+        let major = parts.next().ok_or_else(|| anyhow!("empty api version"))??;
+        let minor = parts.next().transpose()?.unwrap_or_default();
+        let patch = parts.next().transpose()?.unwrap_or_default();
+
+        Ok(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for RpcApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// RPC methods and config fields that older validators (e.g. stale localnets) lack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcFeature {
+    /// `getLatestBlockhash`, older nodes only know `getRecentBlockhash`.
+    GetLatestBlockhash,
+    /// The `maxSupportedTransactionVersion` config field.
+    MaxSupportedTransactionVersion,
+    /// The `minContextSlot` config field.
+    MinContextSlot,
+}
+
+impl RpcFeature {
+    pub const fn min_version(self) -> RpcApiVersion {
+        match self {
+            RpcFeature::GetLatestBlockhash => RpcApiVersion::new(1, 9, 0),
+            RpcFeature::MaxSupportedTransactionVersion => RpcApiVersion::new(1, 11, 0),
+            RpcFeature::MinContextSlot => RpcApiVersion::new(1, 14, 0),
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            RpcFeature::GetLatestBlockhash => "getLatestBlockhash",
+            RpcFeature::MaxSupportedTransactionVersion => "maxSupportedTransactionVersion",
+            RpcFeature::MinContextSlot => "minContextSlot",
+        }
+    }
+}
+
+/// The last known version of the node a client talks to.
+///
+/// Unknown versions are treated optimistically, every feature is assumed to be supported.
+#[derive(Debug, Default)]
+pub struct NodeVersion(RwLock<Option<RpcApiVersion>>);
+
+impl NodeVersion {
+    pub fn get(&self) -> Option<RpcApiVersion> {
+        *self.0.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set(&self, version: RpcApiVersion) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = Some(version);
+    }
+
+    /// Records the `apiVersion` field of a response context, ignoring malformed values.
+    pub fn observe(&self, api_version: &str) {
+        match api_version.parse() {
+            Ok(version) if self.get() != Some(version) => {
+                log::debug!("rpc node api version: {}", version);
+                self.set(version);
+            }
+            Ok(_) => {}
+            Err(err) => log::debug!("{:?}", err),
+        }
+    }
+
+    pub fn supports(&self, feature: RpcFeature) -> bool {
+        self.get()
+            .is_none_or(|version| version >= feature.min_version())
+    }
+
+    /// Fails with a descriptive error if the node is known to be too old for `feature`.
+    pub fn ensure_supported(&self, feature: RpcFeature) -> Result<()> {
+        match self.get() {
+            Some(version) if version < feature.min_version() => bail!(
+                "{} requires solana-core >= {}, but the rpc node runs {}",
+                feature.name(),
+                feature.min_version(),
+                version
+            ),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use anyhow::{Context, Result};
+use solana_sdk::{
+    address_lookup_table::AddressLookupTableAccount,
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::VersionedTransaction,
+};
+
+use crate::builder::validate_versioned_transaction;
+
+/// Compiles `instructions` into an unsigned v0 transaction paid by `payer`.
+///
+/// Accounts found in `lookup_tables` are loaded through them instead of taking 32 bytes
+/// each in the message, signers and program ids always stay in the message. Fails with a
+/// [`TransactionLimitError`](crate::error::TransactionLimitError) if the transaction could
+/// not be sent.
+pub fn new_v0_transaction(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+) -> Result<VersionedTransaction> {
+    let message = v0::Message::try_compile(payer, instructions, lookup_tables, recent_blockhash)
+        .context("could not compile v0 message")?;
+    let signatures = vec![Signature::default(); message.header.num_required_signatures as usize];
+
+    let tx = VersionedTransaction {
+        signatures,
+        message: VersionedMessage::V0(message),
+    };
+    validate_versioned_transaction(&tx)?;
+    Ok(tx)
+}
+
+/// Position of `pubkey` among the signers of `message`, which is also the index of its
+/// signature in the transaction.
+pub fn signer_index(message: &VersionedMessage, pubkey: &Pubkey) -> Option<usize> {
+    let signers = message.header().num_required_signatures as usize;
+    message
+        .static_account_keys()
+        .iter()
+        .take(signers)
+        .position(|key| key == pubkey)
+}
+
+/// Adds the signature of `keypair` to `tx`, leaving the other signatures untouched.
+pub fn partial_sign_versioned(tx: &mut VersionedTransaction, keypair: &Keypair) -> Result<()> {
+    let pubkey = keypair.pubkey();
+    let index = signer_index(&tx.message, &pubkey)
+        .with_context(|| format!("{} is not a signer of the transaction", pubkey))?;
+
+    tx.signatures[index] = keypair.sign_message(&tx.message.serialize());
+    Ok(())
+}
@@ -0,0 +1,326 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use bevy_solana_client_common::{signer::GameSigner, versioned::signer_index};
+use hidapi::{HidApi, HidDevice};
+use solana_sdk::{
+    offchain_message::OffchainMessage,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{Transaction, VersionedTransaction},
+};
+
+const LEDGER_VENDOR_ID: u16 = 0x2c97;
+const LEDGER_USAGE_PAGE: u16 = 0xffa0;
+
+const HID_PACKET_SIZE: usize = 64;
+const HID_TIMEOUT_MS: i32 = 5 * 60 * 1000;
+const CHANNEL: [u8; 2] = [0x01, 0x01];
+const APDU_TAG: u8 = 0x05;
+const MAX_CHUNK_SIZE: usize = 255;
+
+const CLA: u8 = 0xe0;
+const INS_GET_PUBKEY: u8 = 0x05;
+const INS_SIGN_MESSAGE: u8 = 0x06;
+const INS_SIGN_OFFCHAIN_MESSAGE: u8 = 0x07;
+const P1_NON_CONFIRM: u8 = 0x00;
+const P1_CONFIRM: u8 = 0x01;
+const P2_EXTEND: u8 = 0x01;
+const P2_MORE: u8 = 0x02;
+
+const SW_OK: u16 = 0x9000;
+const SW_USER_REJECTED: u16 = 0x6985;
+
+const HARDENED: u32 = 0x8000_0000;
+
+/// BIP44 path of a Solana key, `m/44'/501'/account'/change'`.
+///
+/// Every component is hardened, as required by ed25519 derivation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DerivationPath {
+    pub account: Option<u32>,
+    pub change: Option<u32>,
+}
+
+impl DerivationPath {
+    /// `m/44'/501'/account'`, the path used by the Solana CLI for `usb://ledger?key=account`.
+    pub fn account(account: u32) -> Self {
+        Self {
+            account: Some(account),
+            change: None,
+        }
+    }
+
+    pub fn with_change(mut self, change: u32) -> Self {
+        self.change = Some(change);
+        self
+    }
+
+    fn components(&self) -> Vec<u32> {
+        let mut components = vec![44, 501];
+        if let Some(account) = self.account {
+            components.push(account);
+            components.extend(self.change);
+        }
+        components
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let components = self.components();
+        let mut bytes = vec![components.len() as u8];
+        for component in components {
+            bytes.extend_from_slice(&(component | HARDENED).to_be_bytes());
+        }
+        bytes
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for component in self.components() {
+            write!(f, "/{}'", component)?;
+        }
+        Ok(())
+    }
+}
+
+/// A Ledger status word other than success.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedgerError(pub u16);
+
+impl LedgerError {
+    pub fn is_user_rejected(&self) -> bool {
+        self.0 == SW_USER_REJECTED
+    }
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            SW_USER_REJECTED => write!(f, "the request was rejected on the ledger"),
+            0x6d00 | 0x6e00 | 0x6e01 => write!(f, "the solana app is not open on the ledger"),
+            0x5515 => write!(f, "the ledger is locked"),
+            status => write!(f, "ledger error status {:#06x}", status),
+        }
+    }
+}
+
+impl std::error::Error for LedgerError {}
+
+/// [`GameSigner`] approving transactions on a Ledger running the Solana app, over USB HID.
+///
+/// Requests block until they are approved on the device, so they run on tokio's
+/// blocking pool instead of the rpc thread.
+pub struct LedgerSigner {
+    device: Arc<Mutex<HidDevice>>,
+    path: DerivationPath,
+    pubkey: Pubkey,
+}
+
+impl LedgerSigner {
+    /// Opens the first connected Ledger and reads the pubkey at `path`.
+    pub fn connect(path: DerivationPath) -> Result<Self> {
+        let api = HidApi::new().context("could not initialize hid")?;
+        let info = api
+            .device_list()
+            .find(|info| {
+                info.vendor_id() == LEDGER_VENDOR_ID
+                    && (info.usage_page() == LEDGER_USAGE_PAGE || info.interface_number() == 0)
+            })
+            .context("no ledger connected")?;
+        let device = info.open_device(&api).context("could not open ledger")?;
+
+        let pubkey = exchange(
+            &device,
+            INS_GET_PUBKEY,
+            P1_NON_CONFIRM,
+            0,
+            &path.serialize(),
+        )?;
+        let pubkey = Pubkey::try_from(pubkey.as_slice())
+            .map_err(|_| anyhow!("ledger returned an invalid pubkey"))?;
+        log::info!("connected to ledger {} at {}", pubkey, path);
+
+        Ok(Self {
+            device: Arc::new(Mutex::new(device)),
+            path,
+            pubkey,
+        })
+    }
+
+    pub fn derivation_path(&self) -> DerivationPath {
+        self.path
+    }
+
+    async fn sign(&self, ins: u8, data: Vec<u8>) -> Result<Signature> {
+        let device = self.device.clone();
+        let path = self.path;
+
+        let signature = tokio::task::spawn_blocking(move || {
+            let device = device.lock().unwrap_or_else(|e| e.into_inner());
+            sign_chunked(&device, ins, &path, &data)
+        })
+        .await??;
+
+        Signature::try_from(signature).map_err(|_| anyhow!("ledger returned an invalid signature"))
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl GameSigner for LedgerSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_transaction(&self, tx: &mut Transaction) -> Result<()> {
+        let index = tx
+            .message
+            .signer_keys()
+            .iter()
+            .position(|key| **key == self.pubkey)
+            .with_context(|| format!("{} is not a signer of the transaction", self.pubkey))?;
+
+        tx.signatures[index] = self.sign(INS_SIGN_MESSAGE, tx.message_data()).await?;
+        Ok(())
+    }
+
+    async fn sign_versioned_transaction(&self, tx: &mut VersionedTransaction) -> Result<()> {
+        let index = signer_index(&tx.message, &self.pubkey)
+            .with_context(|| format!("{} is not a signer of the transaction", self.pubkey))?;
+
+        tx.signatures[index] = self.sign(INS_SIGN_MESSAGE, tx.message.serialize()).await?;
+        Ok(())
+    }
+
+    /// The Solana app only signs messages wrapped in the off-chain message format, so
+    /// the signature has to be checked with [`OffchainMessage::verify`].
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        let message = OffchainMessage::new(0, message)
+            .and_then(|message| message.serialize())
+            .map_err(|err| anyhow!("message can not be signed on a ledger: {}", err))?;
+
+        self.sign(INS_SIGN_OFFCHAIN_MESSAGE, message).await
+    }
+}
+
+/// Sends `data` in as many APDUs as needed, the first carrying the derivation path.
+fn sign_chunked(
+    device: &HidDevice,
+    ins: u8,
+    path: &DerivationPath,
+    data: &[u8],
+) -> Result<Vec<u8>> {
+    // one signer, followed by its path
+    let mut payload = vec![1];
+    payload.extend(path.serialize());
+
+    let (first, rest) = data.split_at(data.len().min(MAX_CHUNK_SIZE - payload.len()));
+    payload.extend_from_slice(first);
+
+    let p2 = if rest.is_empty() { 0 } else { P2_MORE };
+    let mut result = exchange(device, ins, P1_CONFIRM, p2, &payload)?;
+
+    let mut chunks = rest.chunks(MAX_CHUNK_SIZE).peekable();
+    while let Some(chunk) = chunks.next() {
+        let p2 = if chunks.peek().is_some() {
+            P2_EXTEND | P2_MORE
+        } else {
+            P2_EXTEND
+        };
+        result = exchange(device, ins, P1_CONFIRM, p2, chunk)?;
+    }
+
+    Ok(result)
+}
+
+/// Sends one APDU and returns the response data without its status word.
+fn exchange(device: &HidDevice, ins: u8, p1: u8, p2: u8, data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() > MAX_CHUNK_SIZE {
+        bail!("apdu payload of {} bytes is too large", data.len());
+    }
+
+    let mut apdu = vec![CLA, ins, p1, p2, data.len() as u8];
+    apdu.extend_from_slice(data);
+    write_apdu(device, &apdu)?;
+
+    let response = read_apdu(device)?;
+    let (data, status) = response.split_at(
+        response
+            .len()
+            .checked_sub(2)
+            .context("ledger response is missing its status word")?,
+    );
+
+    match u16::from_be_bytes([status[0], status[1]]) {
+        SW_OK => Ok(data.to_vec()),
+        status => Err(LedgerError(status).into()),
+    }
+}
+
+/// Frames `apdu` into HID packets: channel, tag, sequence number and, in the first
+/// packet, the total length.
+fn write_apdu(device: &HidDevice, apdu: &[u8]) -> Result<()> {
+    let mut framed = (apdu.len() as u16).to_be_bytes().to_vec();
+    framed.extend_from_slice(apdu);
+
+    for (sequence, chunk) in framed.chunks(HID_PACKET_SIZE - 5).enumerate() {
+        // leading zero report id, required by hidapi
+        let mut packet = vec![0];
+        packet.extend_from_slice(&CHANNEL);
+        packet.push(APDU_TAG);
+        packet.extend_from_slice(&(sequence as u16).to_be_bytes());
+        packet.extend_from_slice(chunk);
+        packet.resize(HID_PACKET_SIZE + 1, 0);
+
+        device.write(&packet).context("could not write to ledger")?;
+    }
+
+    Ok(())
+}
+
+fn read_apdu(device: &HidDevice) -> Result<Vec<u8>> {
+    let mut response = Vec::new();
+    let mut expected = None;
+    let mut sequence = 0u16;
+
+    loop {
+        let mut packet = [0; HID_PACKET_SIZE];
+        let read = device
+            .read_timeout(&mut packet, HID_TIMEOUT_MS)
+            .context("could not read from ledger")?;
+        if read == 0 {
+            bail!("timed out waiting for the ledger");
+        }
+        if read < 5 {
+            bail!("truncated ledger response packet");
+        }
+
+        if packet[..2] != CHANNEL
+            || packet[2] != APDU_TAG
+            || u16::from_be_bytes([packet[3], packet[4]]) != sequence
+        {
+            bail!("unexpected ledger response packet");
+        }
+
+        let mut data = &packet[5..read];
+        if sequence == 0 {
+            let [high, low, rest @ ..] = data else {
+                bail!("truncated ledger response packet");
+            };
+            expected = Some(u16::from_be_bytes([*high, *low]) as usize);
+            data = rest;
+        }
+        response.extend_from_slice(data);
+
+        let expected = expected.unwrap_or_default();
+        if response.len() >= expected {
+            response.truncate(expected);
+            return Ok(response);
+        }
+        sequence += 1;
+    }
+}
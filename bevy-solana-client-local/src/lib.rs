@@ -1,34 +1,221 @@
-use anyhow::{bail, Context};
-use bevy_solana_client_common::rpc_client::{RpcClient, RpcRequest, RpcResponse};
+#[cfg(feature = "ledger")]
+pub mod ledger;
+#[cfg(feature = "metrics-exporter")]
+pub mod metrics_exporter;
+pub mod runtime;
+#[cfg(feature = "tpu")]
+pub mod tpu;
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use bevy_solana_client_common::{
+    config::SolanaClientConfig,
+    endpoint::RpcEndpoints,
+    error::TransportError,
+    rpc_client::{RpcClient, RpcRequest},
+    state::RpcClientState,
+};
 
 pub struct LocalRpcClient {
-    pub url: String,
+    pub endpoints: RpcEndpoints,
+    pub config: SolanaClientConfig,
+    state: RpcClientState,
+    http: reqwest::Client,
+}
+
+impl LocalRpcClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_config(url, SolanaClientConfig::default())
+    }
+
+    pub fn with_config(url: impl Into<String>, config: SolanaClientConfig) -> Self {
+        Self::with_endpoints(RpcEndpoints::single(url), config)
+    }
+
+    /// Creates a client failing over between `endpoints` in order.
+    pub fn with_endpoints(endpoints: RpcEndpoints, config: SolanaClientConfig) -> Self {
+        Self::with_http_client(endpoints, config, reqwest::Client::new())
+    }
+
+    /// Creates a client sending every request through `http`, sharing its connection pool.
+    pub fn with_http_client(
+        endpoints: RpcEndpoints,
+        config: SolanaClientConfig,
+        http: reqwest::Client,
+    ) -> Self {
+        Self {
+            endpoints,
+            state: RpcClientState::new(&config),
+            config,
+            http,
+        }
+    }
+
+    pub fn builder(endpoints: RpcEndpoints) -> LocalRpcClientBuilder {
+        LocalRpcClientBuilder::new(endpoints)
+    }
+
+    /// The underlying http client, cheap to clone and share with other clients.
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http
+    }
+}
+
+/// Configures the http client of a [`LocalRpcClient`].
+pub struct LocalRpcClientBuilder {
+    endpoints: RpcEndpoints,
+    config: SolanaClientConfig,
+    proxy: Option<String>,
+    user_agent: Option<String>,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Option<Duration>>,
+}
+
+impl LocalRpcClientBuilder {
+    pub fn new(endpoints: RpcEndpoints) -> Self {
+        Self {
+            endpoints,
+            config: SolanaClientConfig::default(),
+            proxy: None,
+            user_agent: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+        }
+    }
+
+    pub fn config(mut self, config: SolanaClientConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Routes every request through the proxy at `url`.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Maximum number of idle connections kept open per endpoint host.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// How long idle connections are kept open, `None` keeps them forever.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    pub fn build(self) -> Result<LocalRpcClient> {
+        let mut http = reqwest::Client::builder();
+
+        if let Some(proxy) = &self.proxy {
+            http = http.proxy(
+                reqwest::Proxy::all(proxy).with_context(|| format!("invalid proxy {:?}", proxy))?,
+            );
+        }
+        if let Some(user_agent) = self.user_agent {
+            http = http.user_agent(user_agent);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            http = http.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            http = http.pool_idle_timeout(timeout);
+        }
+
+        let http = http.build().context("could not build http client")?;
+        Ok(LocalRpcClient::with_http_client(
+            self.endpoints,
+            self.config,
+            http,
+        ))
+    }
 }
 
 #[async_trait::async_trait(?Send)]
 impl RpcClient for LocalRpcClient {
-    async fn rpc_post<De: serde::de::DeserializeOwned>(
-        &self,
-        method: &str,
-        params: serde_json::Value,
-    ) -> anyhow::Result<De> {
-        let resp_str: String = reqwest::Client::new()
-            .post(&self.url)
-            .json(&RpcRequest::new(method, params))
+    fn config(&self) -> &SolanaClientConfig {
+        &self.config
+    }
+
+    fn endpoints(&self) -> &RpcEndpoints {
+        &self.endpoints
+    }
+
+    fn state(&self) -> &RpcClientState {
+        &self.state
+    }
+
+    async fn post(&self, url: &str, request: &RpcRequest<serde_json::Value>) -> Result<String> {
+        let mut req = self.http.post(self.config.request_url(url)?).json(request);
+        for (name, value) in &self.config.headers {
+            req = req.header(name, value);
+        }
+        let sticky = self.state.sticky_session.as_ref();
+        if let Some((name, value)) = sticky.and_then(|s| s.request_header(url)) {
+            req = req.header(name, value);
+        }
+
+        let resp = req
             .send()
-            .await?
+            .await
+            .map_err(|e| TransportError::Network(e.to_string()))?;
+
+        if let Some(sticky) = sticky {
+            let token = resp.headers().get(&sticky.config().response_header);
+            sticky.capture(url, token.and_then(|token| token.to_str().ok()));
+        }
+
+        let status = resp.status();
+        let body = resp
             .text()
-            .await?;
+            .await
+            .map_err(|e| TransportError::Network(e.to_string()))?;
 
-        log::debug!("resp_str: {:?}", resp_str);
-        let resp: RpcResponse<De> = serde_json::from_str(&resp_str)?;
+        if !status.is_success() {
+            return Err(TransportError::Http {
+                status: status.as_u16(),
+                body,
+            }
+            .into());
+        }
+
+        Ok(body)
+    }
 
-        if let Some(e) = resp.error {
-            bail!("rpc error: {:?}", e);
+    async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        let resp = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| TransportError::Network(e.to_string()))?;
+
+        let status = resp.status();
+        let body = resp
+            .bytes()
+            .await
+            .map_err(|e| TransportError::Network(e.to_string()))?;
+
+        if !status.is_success() {
+            return Err(TransportError::Http {
+                status: status.as_u16(),
+                body: String::from_utf8_lossy(&body).into_owned(),
+            }
+            .into());
         }
 
-        let result = resp.result.context("no result")?;
+        Ok(body.to_vec())
+    }
 
-        Ok(result)
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await
     }
 }
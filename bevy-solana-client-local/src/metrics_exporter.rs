@@ -0,0 +1,85 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use bevy_solana_client_common::rpc_client::RpcClient;
+
+const METRICS_PATH: &str = "/metrics";
+/// How long a client may take to send its request or read the response, so an idle
+/// connection cannot block the exporter.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Minimal HTTP endpoint serving a client's [`RpcMetrics`] to Prometheus scrapers.
+///
+/// Requests are answered one at a time on a dedicated thread, which is plenty for
+/// a scrape every few seconds and keeps game servers free of an HTTP framework.
+///
+/// [`RpcMetrics`]: bevy_solana_client_common::metrics::RpcMetrics
+pub struct MetricsExporter {
+    addr: SocketAddr,
+}
+
+impl MetricsExporter {
+    /// Binds `addr` and serves `GET /metrics` until the process exits.
+    pub fn spawn<C>(addr: impl ToSocketAddrs, client: Arc<C>) -> Result<Self>
+    where
+        C: RpcClient + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind(addr).context("could not bind metrics exporter")?;
+        let addr = listener.local_addr()?;
+
+        std::thread::Builder::new()
+            .name("solana-metrics".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    let result = stream
+                        .map_err(anyhow::Error::from)
+                        .and_then(|stream| handle_connection(stream, client.as_ref()));
+                    if let Err(err) = result {
+                        log::debug!("metrics exporter: {:?}", err);
+                    }
+                }
+            })
+            .context("could not spawn metrics exporter thread")?;
+
+        log::info!("serving rpc metrics on http://{}{}", addr, METRICS_PATH);
+        Ok(Self { addr })
+    }
+
+    /// The bound address, useful when binding port `0`.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+fn handle_connection<C: RpcClient>(mut stream: TcpStream, client: &C) -> Result<()> {
+    stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+    stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next(), parts.next());
+
+    let (status, body) = match (method, path) {
+        (Some("GET"), Some(path)) if path.split('?').next() == Some(METRICS_PATH) => {
+            ("200 OK", client.state().metrics.to_prometheus())
+        }
+        _ => ("404 Not Found", String::new()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
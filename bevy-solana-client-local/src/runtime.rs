@@ -0,0 +1,55 @@
+use std::{future::Future, pin::Pin, sync::OnceLock};
+
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+type Job = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()>>> + Send>;
+
+static LOCAL_TASK_RUNNER: OnceLock<LocalTaskRunner> = OnceLock::new();
+
+/// Runs `!Send` rpc futures on a dedicated current-thread tokio runtime.
+///
+/// [`RpcClient`](bevy_solana_client_common::rpc_client::RpcClient) futures are not `Send`,
+/// so instead of futures the runner receives `Send` closures that build them on its own thread.
+pub struct LocalTaskRunner {
+    sender: UnboundedSender<Job>,
+}
+
+impl LocalTaskRunner {
+    pub fn global() -> &'static LocalTaskRunner {
+        LOCAL_TASK_RUNNER.get_or_init(LocalTaskRunner::new)
+    }
+
+    fn new() -> Self {
+        let (sender, mut receiver) = unbounded_channel::<Job>();
+
+        std::thread::Builder::new()
+            .name("solana-rpc".to_string())
+            .spawn(move || {
+                let rt = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .expect("could not build rpc runtime");
+
+                let local = tokio::task::LocalSet::new();
+                local.block_on(&rt, async move {
+                    while let Some(job) = receiver.recv().await {
+                        tokio::task::spawn_local(job());
+                    }
+                });
+            })
+            .expect("could not spawn rpc thread");
+
+        Self { sender }
+    }
+
+    pub fn spawn<F, Fut>(&self, task: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        let job: Job = Box::new(move || Box::pin(task()));
+        if self.sender.send(job).is_err() {
+            log::error!("rpc runtime has shut down");
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use bevy_solana_client_common::rpc_client::RpcClient;
+use quinn::{ClientConfig, Endpoint};
+use rustls::{
+    client::{ServerCertVerified, ServerCertVerifier},
+    Certificate, PrivateKey, ServerName,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    transaction::Transaction,
+};
+
+const ALPN_TPU_PROTOCOL: &[u8] = b"solana-tpu";
+/// Validators that only advertise a UDP `tpu` address serve QUIC on this port offset.
+const QUIC_PORT_OFFSET: u16 = 6;
+
+#[derive(Debug, Clone)]
+pub struct TpuClientConfig {
+    /// How many upcoming slots' leaders receive each transaction.
+    pub fanout_slots: u64,
+    /// How long cluster contact info is reused before being fetched again.
+    pub cluster_nodes_ttl: Duration,
+    pub connect_timeout: Duration,
+}
+
+impl Default for TpuClientConfig {
+    fn default() -> Self {
+        Self {
+            fanout_slots: 12,
+            cluster_nodes_ttl: Duration::from_secs(60),
+            connect_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Sends transactions straight to the QUIC TPU ports of the upcoming leaders,
+/// skipping the rpc node's own forwarding.
+///
+/// The rpc client is only used to look up the leader schedule and the cluster's contact info.
+pub struct TpuClient<C: RpcClient> {
+    rpc: C,
+    config: TpuClientConfig,
+    endpoint: Endpoint,
+    connections: RwLock<HashMap<SocketAddr, quinn::Connection>>,
+    leader_tpus: RwLock<Option<(Instant, HashMap<Pubkey, SocketAddr>)>>,
+}
+
+impl<C: RpcClient> TpuClient<C> {
+    pub fn new(rpc: C, config: TpuClientConfig) -> Result<Self> {
+        Ok(Self {
+            rpc,
+            config,
+            endpoint: client_endpoint()?,
+            connections: RwLock::new(HashMap::new()),
+            leader_tpus: RwLock::new(None),
+        })
+    }
+
+    pub fn rpc(&self) -> &C {
+        &self.rpc
+    }
+
+    /// Sends a signed transaction to the leaders of the next `fanout_slots` slots.
+    ///
+    /// Succeeds if at least one leader accepted the stream.
+    pub async fn send_transaction(&self, tx: &Transaction) -> Result<Signature> {
+        let signature = *tx.signatures.first().context("transaction is not signed")?;
+        let wire_tx = bincode::serialize(tx)?;
+
+        let addrs = self.upcoming_leader_addrs().await?;
+        if addrs.is_empty() {
+            bail!("no tpu address known for the upcoming leaders");
+        }
+
+        let mut last_err = None;
+        let mut sent = 0;
+        for addr in addrs {
+            match self.send_wire_transaction(addr, &wire_tx).await {
+                Ok(()) => sent += 1,
+                Err(err) => {
+                    log::debug!("tpu send to {} failed: {:?}", addr, err);
+                    self.connections
+                        .write()
+                        .map_err(|e| anyhow!("{:?}", e))?
+                        .remove(&addr);
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        match last_err {
+            Some(err) if sent == 0 => Err(err.context("no leader accepted the transaction")),
+            _ => {
+                log::debug!("tx {} sent to {} leaders", signature, sent);
+                Ok(signature)
+            }
+        }
+    }
+
+    async fn send_wire_transaction(&self, addr: SocketAddr, wire_tx: &[u8]) -> Result<()> {
+        let connection = self.connection(addr).await?;
+        let mut stream = connection.open_uni().await?;
+        stream.write_all(wire_tx).await?;
+        stream.finish().await?;
+        Ok(())
+    }
+
+    async fn connection(&self, addr: SocketAddr) -> Result<quinn::Connection> {
+        let cached = self
+            .connections
+            .read()
+            .map_err(|e| anyhow!("{:?}", e))?
+            .get(&addr)
+            .filter(|c| c.close_reason().is_none())
+            .cloned();
+
+        if let Some(connection) = cached {
+            return Ok(connection);
+        }
+
+        let connecting = self.endpoint.connect(addr, "connect")?;
+        let connection = tokio::time::timeout(self.config.connect_timeout, connecting)
+            .await
+            .map_err(|_| anyhow!("quic connect to {} timed out", addr))??;
+
+        self.connections
+            .write()
+            .map_err(|e| anyhow!("{:?}", e))?
+            .insert(addr, connection.clone());
+
+        Ok(connection)
+    }
+
+    /// Resolves the QUIC TPU addresses of the upcoming leaders, in slot order.
+    pub async fn upcoming_leader_addrs(&self) -> Result<Vec<SocketAddr>> {
+        let slot = self
+            .rpc
+            .get_slot_with_commitment(CommitmentConfig::processed())
+            .await?;
+        let leaders = self
+            .rpc
+            .get_slot_leaders(slot, self.config.fanout_slots)
+            .await?;
+        let tpus = self.leader_tpus().await?;
+
+        let mut seen = HashSet::new();
+        Ok(leaders
+            .iter()
+            .filter_map(|leader| tpus.get(leader).copied())
+            .filter(|addr| seen.insert(*addr))
+            .collect())
+    }
+
+    async fn leader_tpus(&self) -> Result<HashMap<Pubkey, SocketAddr>> {
+        if let Some((fetched_at, tpus)) =
+            &*self.leader_tpus.read().map_err(|e| anyhow!("{:?}", e))?
+        {
+            if fetched_at.elapsed() < self.config.cluster_nodes_ttl {
+                return Ok(tpus.clone());
+            }
+        }
+
+        let tpus: HashMap<Pubkey, SocketAddr> = self
+            .rpc
+            .get_cluster_nodes()
+            .await?
+            .into_iter()
+            .filter_map(|node| {
+                let pubkey = Pubkey::from_str(&node.pubkey).ok()?;
+                // nodes whose QUIC port would overflow are skipped
+                let addr = node.tpu_quic.or_else(|| {
+                    let mut tpu = node.tpu?;
+                    tpu.set_port(tpu.port().checked_add(QUIC_PORT_OFFSET)?);
+                    Some(tpu)
+                })?;
+                Some((pubkey, addr))
+            })
+            .collect();
+
+        *self.leader_tpus.write().map_err(|e| anyhow!("{:?}", e))? =
+            Some((Instant::now(), tpus.clone()));
+
+        Ok(tpus)
+    }
+}
+
+fn client_endpoint() -> Result<Endpoint> {
+    // validators accept unstaked connections with any self-signed client certificate
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
+    let cert_der = Certificate(cert.serialize_der()?);
+    let key_der = PrivateKey(cert.serialize_private_key_der());
+
+    let mut crypto = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(SkipServerVerification))
+        .with_client_auth_cert(vec![cert_der], key_der)?;
+    crypto.alpn_protocols = vec![ALPN_TPU_PROTOCOL.to_vec()];
+
+    let mut endpoint = Endpoint::client(SocketAddr::from(([0, 0, 0, 0], 0)))?;
+    endpoint.set_default_client_config(ClientConfig::new(Arc::new(crypto)));
+    Ok(endpoint)
+}
+
+/// Validators present self-signed certificates, so there is nothing to verify against.
+struct SkipServerVerification;
+
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
@@ -0,0 +1,382 @@
+use std::{
+    any::TypeId,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    marker::PhantomData,
+    ops::RangeBounds,
+};
+
+use bevy::prelude::*;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{schedule::SolanaClientSet, subscription::TrackAccount, watcher::AccountWatcher};
+
+/// The address of an on-chain account mirrored into the ECS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct SolanaAccountPubkey(pub Pubkey);
+
+/// The latest known state of an on-chain account mirrored into the ECS.
+#[derive(Debug, Clone, Component)]
+pub struct SolanaAccountData(pub Account);
+
+/// Describes how to derive a secondary index key from a mirrored account.
+///
+/// Implement this on a marker type to get an [`AccountIndex`] for it, e.g. to
+/// look up "the account for match #123" by a field stored in the account data.
+pub trait AccountIndexer: Send + Sync + 'static {
+    type Key: Ord + Clone + Send + Sync + 'static;
+
+    /// Returns `None` if the account should not be part of this index.
+    fn extract(pubkey: &Pubkey, account: &Account) -> Option<Self::Key>;
+}
+
+/// Indexes accounts by the program that owns them.
+pub struct ByOwner;
+
+impl AccountIndexer for ByOwner {
+    type Key = Pubkey;
+
+    fn extract(_pubkey: &Pubkey, account: &Account) -> Option<Pubkey> {
+        Some(account.owner)
+    }
+}
+
+/// Indexes accounts by their leading 8 byte (anchor style) discriminator.
+pub struct ByDiscriminator;
+
+impl AccountIndexer for ByDiscriminator {
+    type Key = [u8; 8];
+
+    fn extract(_pubkey: &Pubkey, account: &Account) -> Option<[u8; 8]> {
+        account.data.get(..8)?.try_into().ok()
+    }
+}
+
+/// Sorted secondary index over entities carrying [`SolanaAccountData`].
+///
+/// The index is updated incrementally from change detection, so lookups are
+/// `O(log n)` instead of a scan over every mirrored account.
+#[derive(Resource)]
+pub struct AccountIndex<I: AccountIndexer> {
+    by_key: BTreeMap<I::Key, BTreeSet<Entity>>,
+    by_entity: HashMap<Entity, I::Key>,
+}
+
+impl<I: AccountIndexer> Default for AccountIndex<I> {
+    fn default() -> Self {
+        Self {
+            by_key: BTreeMap::new(),
+            by_entity: HashMap::new(),
+        }
+    }
+}
+
+impl<I: AccountIndexer> AccountIndex<I> {
+    /// Returns the first entity stored under `key`.
+    pub fn get(&self, key: &I::Key) -> Option<Entity> {
+        self.by_key.get(key)?.first().copied()
+    }
+
+    /// Returns every entity stored under `key`.
+    pub fn get_all(&self, key: &I::Key) -> impl Iterator<Item = Entity> + '_ {
+        self.by_key.get(key).into_iter().flatten().copied()
+    }
+
+    /// Iterates entities whose key falls within `range`, in key order.
+    pub fn range<R: RangeBounds<I::Key>>(
+        &self,
+        range: R,
+    ) -> impl Iterator<Item = (&I::Key, Entity)> + '_ {
+        self.by_key
+            .range(range)
+            .flat_map(|(key, entities)| entities.iter().map(move |e| (key, *e)))
+    }
+
+    /// Returns the key an entity is currently indexed under.
+    pub fn key_of(&self, entity: Entity) -> Option<&I::Key> {
+        self.by_entity.get(&entity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_entity.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_entity.is_empty()
+    }
+
+    /// Inserts or moves `entity` according to the key extracted from `account`.
+    pub fn upsert(&mut self, entity: Entity, pubkey: &Pubkey, account: &Account) {
+        let key = I::extract(pubkey, account);
+        if key.as_ref() == self.by_entity.get(&entity) {
+            return;
+        }
+
+        self.remove(entity);
+
+        if let Some(key) = key {
+            self.by_key.entry(key.clone()).or_default().insert(entity);
+            self.by_entity.insert(entity, key);
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        let Some(key) = self.by_entity.remove(&entity) else {
+            return;
+        };
+
+        if let Some(entities) = self.by_key.get_mut(&key) {
+            entities.remove(&entity);
+            if entities.is_empty() {
+                self.by_key.remove(&key);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.by_key.clear();
+        self.by_entity.clear();
+    }
+}
+
+/// Registers an [`AccountIndex<I>`] and the system keeping it up to date.
+pub struct AccountIndexPlugin<I: AccountIndexer>(PhantomData<fn() -> I>);
+
+impl<I: AccountIndexer> Default for AccountIndexPlugin<I> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<I: AccountIndexer> Plugin for AccountIndexPlugin<I> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AccountIndex<I>>();
+        app.add_systems(
+            PostUpdate,
+            update_account_index::<I>.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn update_account_index<I: AccountIndexer>(
+    mut index: ResMut<AccountIndex<I>>,
+    changed: Query<(Entity, &SolanaAccountPubkey, &SolanaAccountData), Changed<SolanaAccountData>>,
+    mut removed: RemovedComponents<SolanaAccountData>,
+) {
+    for entity in removed.read() {
+        index.remove(entity);
+    }
+
+    for (entity, pubkey, data) in &changed {
+        index.upsert(entity, &pubkey.0, &data.0);
+    }
+}
+
+/// A component naming the on-chain account its entity stands for.
+pub trait AccountAddress: Component {
+    fn address(&self) -> Pubkey;
+}
+
+impl AccountAddress for SolanaAccountPubkey {
+    fn address(&self) -> Pubkey {
+        self.0
+    }
+}
+
+/// Finds the entity of an account by its address in `O(1)`, e.g. to route an
+/// incoming `accountNotification` to the entity tracking it.
+///
+/// Entities are indexed under their [`SolanaAccountPubkey`],
+/// [`TrackAccount`](crate::subscription::TrackAccount) or
+/// [`AccountWatcher`](crate::watcher::AccountWatcher), which covers the accounts of every
+/// [`ProgramSync`](crate::sync::ProgramSync) too. If several entities share an address the
+/// last one added wins.
+#[derive(Debug, Default, Resource)]
+pub struct PubkeyIndex {
+    by_pubkey: HashMap<Pubkey, Entity>,
+    /// The address of every indexed component, an entity stays indexed as long as one
+    /// of them is left.
+    by_entity: HashMap<Entity, HashMap<TypeId, Pubkey>>,
+}
+
+impl PubkeyIndex {
+    pub fn get(&self, pubkey: &Pubkey) -> Option<Entity> {
+        self.by_pubkey.get(pubkey).copied()
+    }
+
+    /// The address `entity` is indexed under, any of them if its components disagree.
+    pub fn pubkey_of(&self, entity: Entity) -> Option<Pubkey> {
+        self.by_entity.get(&entity)?.values().next().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_pubkey.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_pubkey.is_empty()
+    }
+
+    /// Points the address of `component` at `entity`, replacing the address the
+    /// component had before and whatever entity was indexed for it.
+    pub fn insert<C: AccountAddress>(&mut self, entity: Entity, component: &C) {
+        let pubkey = component.address();
+        let entries = self.by_entity.entry(entity).or_default();
+        match entries.insert(TypeId::of::<C>(), pubkey) {
+            Some(old) if old == pubkey => return,
+            Some(old) => self.unlink(entity, old),
+            None => {}
+        }
+
+        if let Some(previous) = self.by_pubkey.insert(pubkey, entity) {
+            if previous != entity {
+                if let Some(entries) = self.by_entity.get_mut(&previous) {
+                    entries.retain(|_, address| *address != pubkey);
+                    if entries.is_empty() {
+                        self.by_entity.remove(&previous);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drops the address of `entity`'s component `C`, the entity stays indexed under
+    /// the addresses of its other components.
+    pub fn remove<C: AccountAddress>(&mut self, entity: Entity) {
+        let Some(entries) = self.by_entity.get_mut(&entity) else {
+            return;
+        };
+        let Some(pubkey) = entries.remove(&TypeId::of::<C>()) else {
+            return;
+        };
+        if entries.is_empty() {
+            self.by_entity.remove(&entity);
+        }
+        self.unlink(entity, pubkey);
+    }
+
+    pub fn clear(&mut self) {
+        self.by_pubkey.clear();
+        self.by_entity.clear();
+    }
+
+    /// Removes `pubkey -> entity` unless another component of `entity` still has it.
+    fn unlink(&mut self, entity: Entity, pubkey: Pubkey) {
+        let still_used = self
+            .by_entity
+            .get(&entity)
+            .is_some_and(|entries| entries.values().any(|address| *address == pubkey));
+        if !still_used && self.by_pubkey.get(&pubkey) == Some(&entity) {
+            self.by_pubkey.remove(&pubkey);
+        }
+    }
+}
+
+/// Registers the [`PubkeyIndex`] and keeps it up to date.
+pub struct PubkeyIndexPlugin;
+
+impl Plugin for PubkeyIndexPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PubkeyIndex>();
+        app.add_systems(
+            PostUpdate,
+            (
+                update_pubkey_index::<SolanaAccountPubkey>,
+                update_pubkey_index::<TrackAccount>,
+                update_pubkey_index::<AccountWatcher>,
+            )
+                .in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn update_pubkey_index<C: AccountAddress>(
+    mut index: ResMut<PubkeyIndex>,
+    changed: Query<(Entity, &C), Changed<C>>,
+    mut removed: RemovedComponents<C>,
+) {
+    for entity in removed.read() {
+        index.remove::<C>(entity);
+    }
+
+    for (entity, component) in &changed {
+        index.insert(entity, component);
+    }
+}
+
+/// Sudden changes to a mirrored account that usually point at a program upgrade or an exploit.
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub enum AccountAlert {
+    /// The account was reassigned to another program.
+    OwnerChanged {
+        entity: Entity,
+        pubkey: Pubkey,
+        old_owner: Pubkey,
+        new_owner: Pubkey,
+    },
+    /// The account no longer exists on chain: fetching a tracked account came back empty
+    /// or a [`ProgramSync`](crate::sync::ProgramSync) no longer found it, in which case
+    /// `entity` is already despawned.
+    Closed {
+        entity: Entity,
+        pubkey: Pubkey,
+        last_owner: Pubkey,
+    },
+}
+
+/// Sends [`AccountAlert::Closed`] for the mirrored account `pubkey` that last belonged to
+/// `last_owner`.
+pub(crate) fn send_account_closed(
+    world: &mut World,
+    entity: Entity,
+    pubkey: Pubkey,
+    last_owner: Pubkey,
+) {
+    warn!("tracked account {} was closed", pubkey);
+    world.send_event(AccountAlert::Closed {
+        entity,
+        pubkey,
+        last_owner,
+    });
+}
+
+pub struct AccountAlertPlugin;
+
+impl Plugin for AccountAlertPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AccountAlert>();
+        app.add_systems(
+            PostUpdate,
+            account_alert_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn account_alert_system(
+    mut known: Local<HashMap<Entity, Pubkey>>,
+    changed: Query<(Entity, &SolanaAccountPubkey, &SolanaAccountData), Changed<SolanaAccountData>>,
+    mut removed: RemovedComponents<SolanaAccountData>,
+    mut ev_writer: EventWriter<AccountAlert>,
+) {
+    // closed accounts are reported where their data is removed
+    for entity in removed.read() {
+        known.remove(&entity);
+    }
+
+    for (entity, pubkey, data) in &changed {
+        let owner = data.0.owner;
+        if let Some(old_owner) = known.insert(entity, owner) {
+            if old_owner != owner {
+                warn!(
+                    "tracked account {} changed owner from {} to {}",
+                    pubkey.0, old_owner, owner
+                );
+                ev_writer.send(AccountAlert::OwnerChanged {
+                    entity,
+                    pubkey: pubkey.0,
+                    old_owner,
+                    new_owner: owner,
+                });
+            }
+        }
+    }
+}
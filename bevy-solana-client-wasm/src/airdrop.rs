@@ -0,0 +1,181 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use bevy::prelude::*;
+use bevy_solana_client_common::{
+    catalog::ErrorCategory,
+    cluster::{is_loopback_url, Cluster},
+    rpc_client::RpcClient,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey,
+    signature::Signature,
+};
+
+use crate::{
+    balance::WalletBalancePoll,
+    bridge::AsyncBridge,
+    error::report_error,
+    rpc::{SolanaRpc, SolanaRpcClient},
+    schedule::SolanaClientSet,
+    Wallet, WalletEvent,
+};
+
+/// Airdrops SOL to freshly connected wallets with an empty balance, so playtests do not
+/// start with "insufficient funds" errors.
+///
+/// Only ever runs on devnet, testnet and local validators, never on mainnet.
+#[derive(Debug, Clone, Resource)]
+pub struct AutoAirdrop {
+    pub lamports: u64,
+    /// Airdrop requests made before giving up on a rate limited faucet, each a single
+    /// request as the rpc client does not retry `requestAirdrop` itself.
+    pub max_attempts: u32,
+    /// Wait before retrying a rate limited request, doubled on every attempt.
+    pub retry_delay: Duration,
+    /// How long to wait for the airdrop to be confirmed.
+    pub confirm_timeout: Duration,
+}
+
+impl Default for AutoAirdrop {
+    fn default() -> Self {
+        Self {
+            lamports: LAMPORTS_PER_SOL,
+            max_attempts: 4,
+            retry_delay: Duration::from_secs(2),
+            confirm_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub enum AutoAirdropEvent {
+    /// The wallet had no SOL, the airdrop was requested.
+    Requested { pubkey: Pubkey, lamports: u64 },
+    /// The airdrop was confirmed and the balance refreshed.
+    Confirmed {
+        pubkey: Pubkey,
+        lamports: u64,
+        signature: Signature,
+    },
+}
+
+/// Adds [`AutoAirdrop`], meant for development builds only.
+#[derive(Default)]
+pub struct AutoAirdropPlugin(pub AutoAirdrop);
+
+impl Plugin for AutoAirdropPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.0.clone());
+        app.add_event::<AutoAirdropEvent>();
+        app.add_systems(Update, auto_airdrop_system.in_set(SolanaClientSet::Network));
+    }
+}
+
+fn auto_airdrop_system(
+    mut ev_reader: EventReader<WalletEvent>,
+    config: Res<AutoAirdrop>,
+    wallet: Res<Wallet>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    let connected = ev_reader.read().any(|event| {
+        matches!(
+            event,
+            WalletEvent::Connected | WalletEvent::AccountChanged(_)
+        )
+    });
+    if !connected {
+        return;
+    }
+    let Some(pubkey) = wallet.info.as_ref().and_then(|info| info.pubkey().ok()) else {
+        return;
+    };
+
+    let config = config.clone();
+    let rpc = rpc.clone();
+    let bridge = bridge.clone();
+    bridge.clone().spawn(move || async move {
+        match needs_airdrop(&rpc, &pubkey).await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(err) => {
+                debug!("skipping auto airdrop: {:?}", err);
+                return;
+            }
+        }
+
+        let lamports = config.lamports;
+        bridge.send(move |world| {
+            world.send_event(AutoAirdropEvent::Requested { pubkey, lamports });
+        });
+
+        let result = airdrop_and_confirm(&rpc, &pubkey, &config).await;
+        bridge.send(move |world| match result {
+            Ok(signature) => {
+                info!("airdropped {} lamports to {}", lamports, pubkey);
+                world.resource_mut::<WalletBalancePoll>().refresh();
+                world.send_event(AutoAirdropEvent::Confirmed {
+                    pubkey,
+                    lamports,
+                    signature,
+                });
+            }
+            Err(err) => report_error(world, "auto airdrop", &err),
+        });
+    });
+}
+
+/// Whether `pubkey` is empty and on a cluster with a faucet.
+async fn needs_airdrop(rpc: &SolanaRpcClient, pubkey: &Pubkey) -> Result<bool> {
+    let cluster = Cluster::from_genesis_hash(&rpc.get_genesis_hash().await?);
+    let has_faucet = match cluster {
+        Cluster::Devnet | Cluster::Testnet => true,
+        Cluster::Mainnet => false,
+        // local validators have random genesis hashes
+        Cluster::Other(_) => is_loopback_url(rpc.endpoints.url(0)),
+    };
+
+    Ok(has_faucet && rpc.get_balance(pubkey).await? == 0)
+}
+
+async fn airdrop_and_confirm(
+    rpc: &SolanaRpcClient,
+    pubkey: &Pubkey,
+    config: &AutoAirdrop,
+) -> Result<Signature> {
+    let mut delay = config.retry_delay;
+    let mut attempt = 1;
+    // the only retry layer, the transport never retries `requestAirdrop` so a rate
+    // limited faucet sees at most `max_attempts` requests
+    let signature = loop {
+        match rpc.request_airdrop(pubkey, config.lamports).await {
+            Ok(signature) => break signature,
+            Err(err) if attempt < config.max_attempts && is_faucet_rate_limited(&err) => {
+                debug!("faucet rate limited, retrying in {:?}", delay);
+                rpc.sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    };
+
+    rpc.confirm_transaction(
+        &signature,
+        CommitmentConfig::confirmed(),
+        config.confirm_timeout,
+    )
+    .await?;
+    Ok(signature)
+}
+
+/// The public faucets answer rate limited requests with an internal error.
+fn is_faucet_rate_limited(err: &anyhow::Error) -> bool {
+    if ErrorCategory::of(err) == ErrorCategory::RateLimited {
+        return true;
+    }
+
+    let message = format!("{:#}", err).to_lowercase();
+    message.contains("rate limit") || message.contains("airdrop request failed")
+}
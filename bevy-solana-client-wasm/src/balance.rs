@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_solana_client_common::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    bridge::AsyncBridge, poll::PollTimer, rpc::SolanaRpc, schedule::SolanaClientSet, Wallet,
+    WalletEvent,
+};
+
+/// Polls the connected wallet's balance into [`WalletInfo::amount`](crate::WalletInfo::amount).
+///
+/// The balance is fetched right after connecting and then every `interval`,
+/// [`WalletEvent::BalanceChanged`] is sent whenever it changed.
+#[derive(Debug, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct WalletBalancePoll {
+    pub interval: Duration,
+    timer: PollTimer,
+}
+
+impl Default for WalletBalancePoll {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10))
+    }
+}
+
+impl WalletBalancePoll {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            timer: PollTimer::new(interval),
+        }
+    }
+
+    /// Fetches the balance on the next frame, e.g. after sending a transaction.
+    pub fn refresh(&mut self) {
+        self.timer.refresh();
+    }
+
+    /// Forgets the request in flight, its response was cancelled.
+    pub(crate) fn reset(&mut self) {
+        self.timer.reset();
+    }
+}
+
+pub struct WalletBalancePlugin;
+
+impl Plugin for WalletBalancePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WalletBalancePoll>();
+        app.register_type::<WalletBalancePoll>();
+        app.add_systems(
+            Update,
+            (wallet_balance_refresh_system, wallet_balance_system)
+                .chain()
+                .in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn wallet_balance_refresh_system(
+    mut ev_reader: EventReader<WalletEvent>,
+    mut poll: ResMut<WalletBalancePoll>,
+) {
+    for event in ev_reader.read() {
+        if let WalletEvent::Connected | WalletEvent::AccountChanged(_) = event {
+            poll.refresh();
+        }
+    }
+}
+
+fn wallet_balance_system(
+    time: Res<Time<Real>>,
+    mut poll: ResMut<WalletBalancePoll>,
+    wallet: Res<Wallet>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    let Some(pubkey) = wallet.info.as_ref().and_then(|info| info.pubkey().ok()) else {
+        return;
+    };
+
+    let interval = poll.interval;
+    if !poll.timer.tick(interval, time.delta()) {
+        return;
+    }
+
+    let rpc = rpc.clone();
+    let bridge = bridge.clone();
+    bridge.clone().spawn(move || async move {
+        let result = rpc.get_balance(&pubkey).await;
+        bridge.send(move |world| {
+            world.resource_mut::<WalletBalancePoll>().timer.finish();
+            match result {
+                Ok(lamports) => set_balance(world, pubkey, lamports),
+                Err(err) => debug!("could not fetch the wallet balance: {:?}", err),
+            }
+        });
+    });
+}
+
+fn set_balance(world: &mut World, pubkey: Pubkey, lamports: u64) {
+    let mut wallet = world.resource_mut::<Wallet>();
+    let Some(info) = wallet.info.as_mut() else {
+        return;
+    };
+    // the wallet switched accounts while the balance was fetched
+    if info.pubkey().ok() != Some(pubkey) || info.amount == lamports {
+        return;
+    }
+
+    info.amount = lamports;
+    world.send_event(WalletEvent::BalanceChanged(lamports));
+}
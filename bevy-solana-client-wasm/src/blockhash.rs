@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_solana_client_common::rpc_client::RpcClient;
+use solana_sdk::hash::Hash;
+
+use crate::{bridge::AsyncBridge, error::report_error, rpc::SolanaRpc, schedule::SolanaClientSet};
+
+/// Average slot time, used to estimate the block height between refreshes.
+const SLOT_DURATION: Duration = Duration::from_millis(400);
+
+#[derive(Debug, Clone, Copy)]
+struct CachedBlockhash {
+    blockhash: Hash,
+    last_valid_block_height: u64,
+    block_height: u64,
+    fetched_at: Duration,
+}
+
+/// A recent blockhash kept fresh in the background, so transactions can be built
+/// without waiting on the rpc.
+///
+/// Refreshed every `refresh` interval. Between refreshes the block height is estimated
+/// from the elapsed time and the blockhash is dropped once it would have expired.
+///
+/// The blockhash is shared with the rpc client as its
+/// [`RecentBlockhash`](bevy_solana_client_common::state::RecentBlockhash), so
+/// [`RpcClient::recent_blockhash`] and the transaction builders use it too. A send
+/// failing with `BlockhashNotFound` invalidates it.
+#[derive(Debug, Resource)]
+pub struct BlockhashCache {
+    pub refresh: Timer,
+    cached: Option<CachedBlockhash>,
+    now: Duration,
+    in_flight: bool,
+    /// The blockhash last shared with the rpc client.
+    shared: Option<Hash>,
+}
+
+impl Default for BlockhashCache {
+    fn default() -> Self {
+        let interval = Duration::from_secs(20);
+        let mut refresh = Timer::new(interval, TimerMode::Repeating);
+        refresh.set_elapsed(interval);
+
+        Self {
+            refresh,
+            cached: None,
+            now: Duration::ZERO,
+            in_flight: false,
+            shared: None,
+        }
+    }
+}
+
+impl BlockhashCache {
+    /// The cached blockhash, `None` before the first fetch or once it expired.
+    pub fn blockhash(&self) -> Option<Hash> {
+        self.valid().map(|cached| cached.blockhash)
+    }
+
+    /// The last block height transactions using [`blockhash`](Self::blockhash) can land at.
+    pub fn last_valid_block_height(&self) -> Option<u64> {
+        self.valid().map(|cached| cached.last_valid_block_height)
+    }
+
+    /// Estimated current block height.
+    pub fn block_height(&self) -> Option<u64> {
+        let cached = self.cached.as_ref()?;
+        let elapsed = self.now.saturating_sub(cached.fetched_at);
+        Some(cached.block_height + (elapsed.as_millis() / SLOT_DURATION.as_millis()) as u64)
+    }
+
+    /// Drops the cached blockhash and fetches a new one on the next frame, e.g. after a
+    /// transaction failed with `BlockhashNotFound`.
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+        let duration = self.refresh.duration();
+        self.refresh.set_elapsed(duration);
+    }
+
+    /// Forgets the blockhash and the request in flight, e.g. after switching clusters.
+    pub(crate) fn reset(&mut self) {
+        self.in_flight = false;
+        self.shared = None;
+        self.invalidate();
+    }
+
+    fn valid(&self) -> Option<&CachedBlockhash> {
+        let cached = self.cached.as_ref()?;
+        (self.block_height()? <= cached.last_valid_block_height).then_some(cached)
+    }
+}
+
+pub struct BlockhashCachePlugin;
+
+impl Plugin for BlockhashCachePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlockhashCache>();
+        app.add_systems(
+            PreUpdate,
+            refresh_blockhash_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn refresh_blockhash_system(
+    time: Res<Time<Real>>,
+    mut cache: ResMut<BlockhashCache>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    cache.now = time.elapsed();
+    let shared = &rpc.state().recent_blockhash;
+    if cache.shared.is_some() && shared.get().is_none() {
+        debug!("cached blockhash was not found by the cluster");
+        cache.invalidate();
+    }
+    if cache.cached.is_some() && cache.blockhash().is_none() {
+        debug!("cached blockhash expired");
+        cache.invalidate();
+    }
+    let blockhash = cache.blockhash();
+    if cache.shared != blockhash {
+        shared.set(blockhash);
+        cache.shared = blockhash;
+    }
+
+    cache.refresh.tick(time.delta());
+    if !cache.refresh.finished() || cache.in_flight {
+        return;
+    }
+    cache.refresh.reset();
+    cache.in_flight = true;
+
+    let rpc = rpc.clone();
+    let bridge = bridge.clone();
+    bridge.clone().spawn(move || async move {
+        let result = async {
+            let (blockhash, last_valid_block_height) = rpc
+                .get_latest_blockhash_with_expiry(rpc.commitment())
+                .await?;
+            let block_height = rpc.get_block_height().await?;
+            Ok::<_, anyhow::Error>((blockhash, last_valid_block_height, block_height))
+        }
+        .await;
+
+        bridge.send(move |world| {
+            let now = world.resource::<Time<Real>>().elapsed();
+            world.resource_mut::<BlockhashCache>().in_flight = false;
+
+            match result {
+                Ok((blockhash, last_valid_block_height, block_height)) => {
+                    world.resource_mut::<BlockhashCache>().cached = Some(CachedBlockhash {
+                        blockhash,
+                        last_valid_block_height,
+                        block_height,
+                        fetched_at: now,
+                    });
+                }
+                Err(err) => report_error(world, "refresh blockhash", &err),
+            }
+        });
+    });
+}
@@ -0,0 +1,202 @@
+use std::{
+    cell::Cell,
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
+
+use bevy::prelude::*;
+
+use crate::schedule::SolanaClientSet;
+
+type WorldCallback = Box<dyn FnOnce(&mut World) + Send>;
+
+struct QueuedCallback {
+    bytes: usize,
+    callback: WorldCallback,
+}
+
+/// Hands results of async rpc work back to the main thread.
+///
+/// Tasks are spawned with [`AsyncBridge::spawn`] and report back with
+/// [`AsyncBridge::send`], the callbacks are applied to the world in the order they were
+/// sent, as many per frame as the [`BridgeBudget`] allows.
+///
+/// [`AsyncBridge::cancel`] drops everything sent by tasks spawned before it.
+#[derive(Resource, Clone, Default)]
+pub struct AsyncBridge {
+    queue: Arc<Mutex<VecDeque<QueuedCallback>>>,
+    epoch: Arc<AtomicU64>,
+}
+
+thread_local! {
+    /// Epoch of the task being polled, see [`EpochScoped`].
+    static TASK_EPOCH: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Polls a task with its spawn epoch set, so callbacks it sends can be told apart from
+/// those of tasks spawned after a [`AsyncBridge::cancel`].
+struct EpochScoped<F: Future> {
+    epoch: u64,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for EpochScoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let previous = TASK_EPOCH.replace(Some(self.epoch));
+        let poll = self.future.as_mut().poll(cx);
+        TASK_EPOCH.set(previous);
+        poll
+    }
+}
+
+impl AsyncBridge {
+    pub fn send(&self, callback: impl FnOnce(&mut World) + Send + 'static) {
+        self.send_sized(0, callback);
+    }
+
+    /// Like [`send`](Self::send) for a callback applying `bytes` of account data, counted
+    /// against [`BridgeBudget::max_bytes`].
+    pub fn send_sized(&self, bytes: usize, callback: impl FnOnce(&mut World) + Send + 'static) {
+        if TASK_EPOCH.get().is_some_and(|epoch| epoch != self.epoch()) {
+            // the task was spawned before the bridge was cancelled
+            return;
+        }
+
+        match self.queue.lock() {
+            Ok(mut queue) => queue.push_back(QueuedCallback {
+                bytes,
+                callback: Box::new(callback),
+            }),
+            Err(err) => error!("async bridge poisoned: {:?}", err),
+        }
+    }
+
+    /// Number of callbacks waiting for the next frames.
+    pub fn pending(&self) -> usize {
+        self.queue.lock().map_or(0, |queue| queue.len())
+    }
+
+    /// Drops the queued callbacks and everything tasks spawned so far send from now on,
+    /// e.g. the responses of a cluster that was switched away from.
+    ///
+    /// The tasks themselves still run to completion.
+    pub fn cancel(&self) {
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+        match self.queue.lock() {
+            Ok(mut queue) => queue.clear(),
+            Err(err) => error!("async bridge poisoned: {:?}", err),
+        }
+    }
+
+    fn epoch(&self) -> u64 {
+        self.epoch.load(Ordering::SeqCst)
+    }
+
+    /// Spawns an rpc future on the platform's local executor.
+    ///
+    /// Rpc futures are not `Send`, so the task is passed as a closure building the future.
+    pub fn spawn<F, Fut>(&self, task: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        // tasks spawned by a cancelled task stay cancelled
+        let epoch = TASK_EPOCH.get().unwrap_or_else(|| self.epoch());
+        let task = move || EpochScoped {
+            epoch,
+            future: Box::pin(task()),
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(task());
+
+        #[cfg(not(target_arch = "wasm32"))]
+        bevy_solana_client_local::runtime::LocalTaskRunner::global().spawn(task);
+    }
+
+    /// Takes the callbacks fitting into `budget`, at least one so large ones still
+    /// make progress.
+    fn drain(&self, budget: &BridgeBudget) -> Vec<WorldCallback> {
+        let mut queue = match self.queue.lock() {
+            Ok(queue) => queue,
+            Err(err) => {
+                error!("async bridge poisoned: {:?}", err);
+                return vec![];
+            }
+        };
+
+        let mut callbacks = vec![];
+        let mut bytes: usize = 0;
+        while let Some(next) = queue.front() {
+            let within_budget = callbacks.len() < budget.max_callbacks
+                && bytes.saturating_add(next.bytes) <= budget.max_bytes;
+            if !within_budget && !callbacks.is_empty() {
+                break;
+            }
+
+            bytes = bytes.saturating_add(next.bytes);
+            callbacks.extend(queue.pop_front().map(|queued| queued.callback));
+        }
+        callbacks
+    }
+}
+
+/// How much async work [`AsyncBridge`] applies per frame, the rest spills over to the
+/// next frames instead of causing a hitch.
+#[derive(Debug, Clone, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct BridgeBudget {
+    /// Callbacks applied per frame.
+    pub max_callbacks: usize,
+    /// Bytes of account data applied per frame, see [`AsyncBridge::send_sized`].
+    pub max_bytes: usize,
+}
+
+impl Default for BridgeBudget {
+    fn default() -> Self {
+        Self {
+            max_callbacks: 64,
+            max_bytes: 1024 * 1024,
+        }
+    }
+}
+
+impl BridgeBudget {
+    /// Applies everything every frame, e.g. for headless servers and tests.
+    pub fn unlimited() -> Self {
+        Self {
+            max_callbacks: usize::MAX,
+            max_bytes: usize::MAX,
+        }
+    }
+}
+
+pub struct AsyncBridgePlugin;
+
+impl Plugin for AsyncBridgePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AsyncBridge>();
+        app.init_resource::<BridgeBudget>();
+        app.register_type::<BridgeBudget>();
+        app.add_systems(
+            PreUpdate,
+            apply_async_bridge_system.in_set(SolanaClientSet::Bridge),
+        );
+    }
+}
+
+fn apply_async_bridge_system(world: &mut World) {
+    let budget = world.resource::<BridgeBudget>().clone();
+    let callbacks = world.resource::<AsyncBridge>().drain(&budget);
+    for callback in callbacks {
+        callback(world);
+    }
+}
@@ -0,0 +1,265 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use bevy::prelude::*;
+use bevy_solana_client_common::{rpc_client::RpcClient, signer::GameSigner};
+use solana_sdk::{
+    bs58,
+    hash::hashv,
+    pubkey::Pubkey,
+    signature::{keypair_from_seed, Keypair, Signature},
+    signer::Signer,
+    transaction::{Transaction, VersionedTransaction},
+};
+
+use crate::{
+    error::report_error,
+    schedule::SolanaClientSet,
+    secretbox::{local_storage, open, random_bytes, seal},
+    session_key::sweep,
+    Wallet, WalletEvent,
+};
+
+/// `localStorage` key of the encrypted burner keypair.
+pub const BURNER_STORAGE_KEY: &str = "bevySolanaBurner";
+
+/// The burner keypair as kept in `localStorage`, sealed with a key derived from the
+/// game's secret and a random salt.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct StoredBurner {
+    pubkey: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl StoredBurner {
+    fn seal(keypair: &Keypair, secret: &str) -> Result<Self> {
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 24];
+        random_bytes(&mut salt)?;
+        random_bytes(&mut nonce)?;
+
+        let ciphertext = seal(&storage_key(secret, &salt), &nonce, &keypair.to_bytes());
+        Ok(Self {
+            pubkey: Signer::pubkey(keypair).to_string(),
+            salt: bs58::encode(salt).into_string(),
+            nonce: bs58::encode(nonce).into_string(),
+            ciphertext: bs58::encode(ciphertext).into_string(),
+        })
+    }
+
+    fn open(&self, secret: &str) -> Result<Keypair> {
+        let salt = bs58::decode(&self.salt).into_vec()?;
+        let nonce: [u8; 24] = bs58::decode(&self.nonce)
+            .into_vec()?
+            .try_into()
+            .map_err(|_| anyhow!("invalid burner nonce"))?;
+        let ciphertext = bs58::decode(&self.ciphertext).into_vec()?;
+
+        let bytes = open(&storage_key(secret, &salt), &nonce, &ciphertext)
+            .context("could not decrypt the burner wallet, was the secret changed?")?;
+        let keypair = Keypair::from_bytes(&bytes).context("invalid burner keypair")?;
+
+        if Signer::pubkey(&keypair).to_string() != self.pubkey {
+            return Err(anyhow!(
+                "burner keypair does not match its pubkey {}",
+                self.pubkey
+            ));
+        }
+        Ok(keypair)
+    }
+}
+
+fn storage_key(secret: &str, salt: &[u8]) -> [u8; 32] {
+    hashv(&[salt, secret.as_bytes()]).to_bytes()
+}
+
+/// Throwaway in-browser wallet for frictionless play, kept in `localStorage`.
+///
+/// The keypair is stored encrypted with [`BurnerWalletPlugin::secret`], so it is not
+/// lying around in plain text, but anything running on the page can still use it.
+/// Only keep small amounts on a burner and [`sweep`](Self::sweep) them to a real wallet.
+#[derive(Clone, Resource)]
+pub struct BurnerWallet {
+    keypair: Arc<Keypair>,
+}
+
+impl BurnerWallet {
+    /// Creates a fresh keypair from the browser's secure random source.
+    pub fn generate() -> Result<Self> {
+        let mut seed = [0u8; 32];
+        random_bytes(&mut seed)?;
+        let keypair = keypair_from_seed(&seed).map_err(|err| anyhow!("{}", err))?;
+
+        Ok(Self {
+            keypair: Arc::new(keypair),
+        })
+    }
+
+    /// The stored burner, `None` if none was saved yet.
+    pub fn load(secret: &str) -> Result<Option<Self>> {
+        let Some(json) = local_storage()?
+            .get_item(BURNER_STORAGE_KEY)
+            .map_err(|err| anyhow!("{err:?}"))?
+        else {
+            return Ok(None);
+        };
+
+        let stored: StoredBurner = serde_json::from_str(&json).context("invalid stored burner")?;
+        Ok(Some(Self {
+            keypair: Arc::new(stored.open(secret)?),
+        }))
+    }
+
+    pub fn save(&self, secret: &str) -> Result<()> {
+        let stored = StoredBurner::seal(&self.keypair, secret)?;
+        local_storage()?
+            .set_item(BURNER_STORAGE_KEY, &serde_json::to_string(&stored)?)
+            .map_err(|err| anyhow!("{err:?}"))
+    }
+
+    /// Deletes the stored keypair, funds left on it are lost.
+    pub fn forget() -> Result<()> {
+        local_storage()?
+            .remove_item(BURNER_STORAGE_KEY)
+            .map_err(|err| anyhow!("{err:?}"))
+    }
+
+    pub fn keypair(&self) -> &Keypair {
+        &self.keypair
+    }
+
+    /// Moves every lamport but the fee to `to`, e.g. after the player connected a real
+    /// wallet. `None` if there is nothing worth moving.
+    pub async fn sweep(&self, rpc: &impl RpcClient, to: &Pubkey) -> Result<Option<Signature>> {
+        sweep(rpc, &self.keypair, to).await
+    }
+}
+
+impl std::fmt::Debug for BurnerWallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BurnerWallet")
+            .field("pubkey", &self.keypair.pubkey())
+            .finish()
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl GameSigner for BurnerWallet {
+    fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    async fn sign_transaction(&self, tx: &mut Transaction) -> Result<()> {
+        self.keypair.as_ref().sign_transaction(tx).await
+    }
+
+    async fn sign_versioned_transaction(&self, tx: &mut VersionedTransaction) -> Result<()> {
+        self.keypair.as_ref().sign_versioned_transaction(tx).await
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(self.keypair.sign_message(message))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub enum BurnerWalletEvent {
+    Created(Pubkey),
+    Restored(Pubkey),
+    /// The player connected a real wallet while a burner exists, a good moment to
+    /// offer [`BurnerWallet::sweep`].
+    UpgradeAvailable {
+        burner: Pubkey,
+        wallet: Pubkey,
+    },
+}
+
+/// Connects the [`Wallet`] to a burner on startup, restoring the stored one or creating
+/// a new one.
+///
+/// Disconnecting and connecting a browser wallet afterwards is the upgrade path, the
+/// burner stays around until [`BurnerWallet::forget`] is called.
+pub struct BurnerWalletPlugin {
+    /// Game specific secret the stored keypair is encrypted with.
+    pub secret: String,
+}
+
+#[derive(Resource)]
+struct BurnerSecret(String);
+
+impl Plugin for BurnerWalletPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BurnerSecret(self.secret.clone()));
+        app.add_event::<BurnerWalletEvent>();
+        app.add_systems(Startup, load_burner_wallet_system);
+        app.add_systems(
+            Update,
+            burner_upgrade_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn load_burner_wallet_system(world: &mut World) {
+    let secret = world.resource::<BurnerSecret>().0.clone();
+
+    let result = BurnerWallet::load(&secret).and_then(|burner| match burner {
+        Some(burner) => Ok((burner, false)),
+        None => {
+            let burner = BurnerWallet::generate()?;
+            burner.save(&secret)?;
+            Ok((burner, true))
+        }
+    });
+
+    match result {
+        Ok((burner, created)) => {
+            let pubkey = burner.pubkey();
+            info!("burner wallet {}", pubkey);
+
+            let mut wallet = world.resource_mut::<Wallet>();
+            wallet.adapter = None;
+            wallet.connect(Arc::new(burner.clone()));
+
+            world.insert_resource(burner);
+            world.send_event(if created {
+                BurnerWalletEvent::Created(pubkey)
+            } else {
+                BurnerWalletEvent::Restored(pubkey)
+            });
+            world.send_event(WalletEvent::Connected);
+        }
+        Err(err) => report_error(world, "load burner wallet", &err),
+    }
+}
+
+fn burner_upgrade_system(
+    mut wallet_events: EventReader<WalletEvent>,
+    mut ev_writer: EventWriter<BurnerWalletEvent>,
+    wallet: Res<Wallet>,
+    burner: Option<Res<BurnerWallet>>,
+) {
+    let Some(burner) = burner else {
+        return;
+    };
+
+    for event in wallet_events.read() {
+        let WalletEvent::Connected = event else {
+            continue;
+        };
+        if wallet.adapter.is_none() {
+            continue;
+        }
+        let Some(pubkey) = wallet.info.as_ref().and_then(|info| info.pubkey().ok()) else {
+            continue;
+        };
+
+        if pubkey != burner.pubkey() {
+            ev_writer.send(BurnerWalletEvent::UpgradeAvailable {
+                burner: burner.pubkey(),
+                wallet: pubkey,
+            });
+        }
+    }
+}
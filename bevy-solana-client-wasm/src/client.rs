@@ -0,0 +1,236 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use bevy::{ecs::system::SystemParam, prelude::*};
+#[cfg(feature = "das")]
+use bevy_solana_client_common::{bubblegum, das};
+use bevy_solana_client_common::{
+    metadata::{self, Metadata},
+    rpc_client::RpcClient,
+    system, token,
+};
+use solana_sdk::{
+    account::Account, hash::Hash, instruction::Instruction, pubkey::Pubkey,
+    transaction::Transaction,
+};
+
+use crate::{
+    blockhash::BlockhashCache,
+    bridge::AsyncBridge,
+    rpc::{SolanaRpc, SolanaRpcClient},
+    Wallet, WalletNotConnected,
+};
+
+/// The result of an rpc call started from a system, polled from later frames.
+///
+/// Store it in a component or a `Local` and [`take`](Self::take) the result once it
+/// arrived. Dropping the handle does not cancel the call.
+#[derive(Debug, Component)]
+pub struct RpcTask<T> {
+    result: Arc<Mutex<Option<Result<T>>>>,
+}
+
+impl<T> RpcTask<T> {
+    fn ready(result: Result<T>) -> Self {
+        Self {
+            result: Arc::new(Mutex::new(Some(result))),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.result.lock().is_ok_and(|result| result.is_some())
+    }
+
+    /// The result, `None` while the call is still running or after it was taken.
+    pub fn take(&mut self) -> Option<Result<T>> {
+        self.result.lock().ok()?.take()
+    }
+}
+
+/// Everything a system needs to talk to Solana in one parameter.
+///
+/// Calls return an [`RpcTask`] right away, e.g. `let task = solana.get_balance(pubkey)`.
+/// Blockhashes come from the [`BlockhashCache`] while it holds a valid one, enable
+/// [`SolanaClientConfig::cache`](bevy_solana_client_common::config::SolanaClientConfig::cache)
+/// to serve repeated account reads from the response cache.
+#[derive(SystemParam)]
+pub struct Solana<'w> {
+    pub rpc: Res<'w, SolanaRpc>,
+    pub wallet: Res<'w, Wallet>,
+    pub bridge: Res<'w, AsyncBridge>,
+    pub blockhash: Res<'w, BlockhashCache>,
+}
+
+impl<'w> Solana<'w> {
+    /// Address of the connected wallet.
+    pub fn pubkey(&self) -> Option<Pubkey> {
+        self.wallet.info.as_ref()?.pubkey().ok()
+    }
+
+    /// Runs `call` with the rpc client on the platform's executor.
+    pub fn spawn<T, F, Fut>(&self, call: F) -> RpcTask<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(Arc<SolanaRpcClient>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T>> + 'static,
+    {
+        let result = Arc::new(Mutex::new(None));
+        let slot = result.clone();
+        let rpc = self.rpc.0.clone();
+
+        self.bridge.spawn(move || async move {
+            let value = call(rpc).await;
+            match slot.lock() {
+                Ok(mut slot) => *slot = Some(value),
+                Err(err) => error!("rpc task poisoned: {:?}", err),
+            }
+        });
+
+        RpcTask { result }
+    }
+
+    pub fn get_balance(&self, pubkey: Pubkey) -> RpcTask<u64> {
+        self.spawn(move |rpc| async move { rpc.get_balance(&pubkey).await })
+    }
+
+    pub fn get_account(&self, pubkey: Pubkey) -> RpcTask<Account> {
+        self.spawn(move |rpc| async move { rpc.get_account(&pubkey).await })
+    }
+
+    pub fn get_latest_blockhash(&self) -> RpcTask<Hash> {
+        if let Some(blockhash) = self.blockhash.blockhash() {
+            return RpcTask::ready(Ok(blockhash));
+        }
+        self.spawn(|rpc| async move { rpc.get_latest_blockhash().await })
+    }
+
+    /// Every asset held by `owner`, compressed NFTs included, see
+    /// [`das::get_all_assets_by_owner`].
+    #[cfg(feature = "das")]
+    pub fn get_assets_by_owner(&self, owner: Pubkey) -> RpcTask<Vec<das::DasAsset>> {
+        self.spawn(
+            move |rpc| async move { das::get_all_assets_by_owner(rpc.as_ref(), &owner).await },
+        )
+    }
+
+    /// An unsigned transfer of the compressed NFT `asset_id` from the connected wallet to
+    /// `new_owner`, see [`bubblegum::transfer_compressed_nft`].
+    #[cfg(feature = "das")]
+    pub fn transfer_compressed_nft(
+        &self,
+        asset_id: Pubkey,
+        new_owner: Pubkey,
+    ) -> RpcTask<Transaction> {
+        let Some(owner) = self.pubkey() else {
+            return RpcTask::ready(Err(WalletNotConnected.into()));
+        };
+        self.spawn(move |rpc| async move {
+            let tx =
+                bubblegum::transfer_compressed_nft(rpc.as_ref(), &asset_id, &new_owner).await?;
+            if tx.message.account_keys.first() != Some(&owner) {
+                anyhow::bail!("{} is not held by the connected wallet", asset_id);
+            }
+            Ok(tx)
+        })
+    }
+
+    /// Name, symbol and uri of the `mint`, see [`metadata::get_token_metadata`].
+    pub fn get_token_metadata(&self, mint: Pubkey) -> RpcTask<Metadata> {
+        self.spawn(
+            move |rpc| async move { metadata::get_token_metadata(rpc.as_ref(), &mint).await },
+        )
+    }
+
+    /// A rent exempt account of `space` bytes owned by `owner`, paid by the connected
+    /// wallet, see [`system::create_account`].
+    pub fn create_account(
+        &self,
+        new_account: Pubkey,
+        space: u64,
+        owner: Pubkey,
+    ) -> RpcTask<Instruction> {
+        let Some(payer) = self.pubkey() else {
+            return RpcTask::ready(Err(WalletNotConnected.into()));
+        };
+        self.spawn(move |rpc| async move {
+            system::create_account(rpc.as_ref(), &payer, &new_account, space, &owner).await
+        })
+    }
+
+    /// The account derived from the connected wallet and `seed`, paid by the wallet, see
+    /// [`system::create_account_with_seed`].
+    pub fn create_account_with_seed(
+        &self,
+        seed: String,
+        space: u64,
+        owner: Pubkey,
+    ) -> RpcTask<(Instruction, Pubkey)> {
+        let Some(payer) = self.pubkey() else {
+            return RpcTask::ready(Err(WalletNotConnected.into()));
+        };
+        self.spawn(move |rpc| async move {
+            system::create_account_with_seed(rpc.as_ref(), &payer, &payer, &seed, space, &owner)
+                .await
+        })
+    }
+
+    /// Grows the system owned `account`, topped up by the connected wallet, see
+    /// [`system::allocate`].
+    pub fn allocate(&self, account: Pubkey, space: u64) -> RpcTask<Vec<Instruction>> {
+        let Some(payer) = self.pubkey() else {
+            return RpcTask::ready(Err(WalletNotConnected.into()));
+        };
+        self.spawn(move |rpc| async move {
+            system::allocate(rpc.as_ref(), &payer, &account, space).await
+        })
+    }
+
+    /// Creates `mint` paid by the connected wallet, see [`token::create_mint`].
+    pub fn create_mint(
+        &self,
+        mint: Pubkey,
+        config: token::MintConfig,
+    ) -> RpcTask<Vec<Instruction>> {
+        let Some(payer) = self.pubkey() else {
+            return RpcTask::ready(Err(WalletNotConnected.into()));
+        };
+        self.spawn(move |rpc| async move {
+            token::create_mint(rpc.as_ref(), &payer, &mint, &config).await
+        })
+    }
+
+    /// Tops `account` up to rent exemption from the connected wallet, see
+    /// [`system::fund_rent_exempt`].
+    pub fn fund_rent_exempt(&self, account: Pubkey, space: u64) -> RpcTask<Option<Instruction>> {
+        let Some(payer) = self.pubkey() else {
+            return RpcTask::ready(Err(WalletNotConnected.into()));
+        };
+        self.spawn(move |rpc| async move {
+            system::fund_rent_exempt(rpc.as_ref(), &payer, &account, space).await
+        })
+    }
+
+    /// An unsigned transfer of `amount` raw tokens of `mint` from the connected wallet to
+    /// `to_owner`, see [`token::transfer_token`].
+    pub fn transfer_token(
+        &self,
+        mint: Pubkey,
+        to_owner: Pubkey,
+        amount: u64,
+    ) -> RpcTask<Transaction> {
+        let Some(owner) = self.pubkey() else {
+            return RpcTask::ready(Err(WalletNotConnected.into()));
+        };
+        self.spawn(move |rpc| async move {
+            token::transfer_token(rpc.as_ref(), &mint, &owner, &to_owner, amount).await
+        })
+    }
+
+    /// See [`RpcClient::view_call`].
+    pub fn view_call(&self, program_id: Pubkey, ix: Instruction) -> RpcTask<Vec<u8>> {
+        self.spawn(move |rpc| async move { rpc.view_call(&program_id, ix).await })
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use bevy_solana_client_common::cluster::ClusterConfig;
+
+use crate::{
+    account::SolanaAccountData,
+    balance::WalletBalancePoll,
+    blockhash::BlockhashCache,
+    bridge::AsyncBridge,
+    check_rpc_cluster,
+    cooldown::ChainClock,
+    fetch_node_version,
+    leaderboard::Leaderboard,
+    rpc::{SolanaRpc, SolanaRpcClient},
+    schedule::SolanaClientSet,
+    signature_status::SignatureStatusCache,
+    subscription::AccountSubscriptionPoll,
+    sync::ProgramSync,
+    token_balance::TokenBalancePoll,
+    treasury::{TreasuryView, ViewTreasury},
+    watch_only::WatchOnlyRefresh,
+    watcher::{AccountWatcher, WatchedAccount},
+    RpcCluster, WalletConnectSettings,
+};
+
+/// Moves the client to another cluster at runtime, e.g. from a devnet/mainnet toggle in
+/// the settings menu.
+///
+/// Responses still in flight are dropped, cached state and mirrored accounts of the old
+/// cluster are cleared and every tracked account is fetched again from the new one.
+#[derive(Debug, Clone, Event)]
+pub struct SwitchCluster(pub ClusterConfig);
+
+/// The client switched clusters, sent after everything of the old one was cleared.
+#[derive(Debug, Clone, Event)]
+pub struct ClusterSwitched(pub ClusterConfig);
+
+pub struct ClusterSwitchPlugin;
+
+impl Plugin for ClusterSwitchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SwitchCluster>();
+        app.add_event::<ClusterSwitched>();
+        // before the bridge so nothing is applied against the old cluster this frame
+        app.add_systems(
+            PreUpdate,
+            switch_cluster_system.before(SolanaClientSet::Bridge),
+        );
+    }
+}
+
+fn switch_cluster_system(world: &mut World) {
+    let Some(SwitchCluster(config)) = world.resource_mut::<Events<SwitchCluster>>().drain().last()
+    else {
+        return;
+    };
+    info!("switching to {}", config.urls.join(", "));
+
+    world.resource::<AsyncBridge>().cancel();
+    let rpc = SolanaRpc::new(SolanaRpcClient::with_endpoints(
+        config.endpoints(),
+        config.client.clone(),
+    ));
+    world.insert_resource(rpc.clone());
+
+    reset_caches(world);
+    clear_accounts(world);
+
+    if config.cluster.is_some() {
+        world.resource_mut::<WalletConnectSettings>().cluster = config.cluster;
+    }
+    world.resource_mut::<RpcCluster>().0 = None;
+    fetch_node_version(&rpc, world.resource::<AsyncBridge>());
+    check_rpc_cluster(&rpc, world.resource::<AsyncBridge>(), config.cluster);
+
+    world.send_event(ClusterSwitched(config));
+}
+
+fn reset_caches(world: &mut World) {
+    world.resource_mut::<BlockhashCache>().reset();
+    world.resource_mut::<SignatureStatusCache>().reset();
+    world.resource_mut::<WalletBalancePoll>().reset();
+    // re-fetches every tracked account, their data is cleared below
+    world.resource_mut::<AccountSubscriptionPoll>().reset();
+    world.resource_mut::<ChainClock>().reset();
+
+    if let Some(mut leaderboard) = world.get_resource_mut::<Leaderboard>() {
+        leaderboard.reset();
+    }
+    if let Some(mut poll) = world.get_resource_mut::<TokenBalancePoll>() {
+        poll.reset();
+    }
+    if let Some(mut refresh) = world.get_resource_mut::<WatchOnlyRefresh>() {
+        let duration = refresh.timer.duration();
+        refresh.timer.set_elapsed(duration);
+    }
+    if let Some(view) = world.get_resource::<TreasuryView>() {
+        let address = view.address;
+        world.send_event(ViewTreasury(address));
+    }
+}
+
+/// Despawns the accounts of every [`ProgramSync`] and forgets the state of all other
+/// mirrored accounts.
+fn clear_accounts(world: &mut World) {
+    let mut despawn = vec![];
+    let mut syncs = world.query::<&mut ProgramSync>();
+    for mut sync in syncs.iter_mut(world) {
+        despawn.extend(sync.reset());
+    }
+    for entity in despawn {
+        if let Some(entity) = world.get_entity_mut(entity) {
+            entity.despawn_recursive();
+        }
+    }
+
+    let mut watchers = world.query::<(Entity, &mut AccountWatcher)>();
+    let watched: Vec<Entity> = watchers
+        .iter_mut(world)
+        .map(|(entity, mut watcher)| {
+            watcher.reset();
+            entity
+        })
+        .collect();
+    for entity in watched {
+        world.entity_mut(entity).remove::<WatchedAccount>();
+    }
+
+    let mut mirrored = world.query_filtered::<Entity, With<SolanaAccountData>>();
+    let mirrored: Vec<Entity> = mirrored.iter(world).collect();
+    for entity in mirrored {
+        world.entity_mut(entity).remove::<SolanaAccountData>();
+    }
+}
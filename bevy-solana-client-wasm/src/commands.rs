@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use bevy_solana_client_common::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+use crate::{bridge::AsyncBridge, error::report_error, rpc::SolanaRpc, Wallet};
+
+/// Completion of a call made through [`SolanaCommandsExt::solana`], failures are sent
+/// as [`SolanaError`](crate::error::SolanaError)s instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub enum SolanaCommandEvent {
+    Airdropped {
+        pubkey: Pubkey,
+        lamports: u64,
+        signature: Signature,
+    },
+    BalanceRefreshed {
+        pubkey: Pubkey,
+        lamports: u64,
+    },
+}
+
+/// Fire-and-forget rpc calls queued through [`Commands`].
+pub struct SolanaCommands<'c, 'w, 's> {
+    commands: &'c mut Commands<'w, 's>,
+}
+
+pub trait SolanaCommandsExt<'w, 's> {
+    /// Rpc calls answered with [`SolanaCommandEvent`]s, e.g.
+    /// `commands.solana().airdrop(pubkey, LAMPORTS_PER_SOL)`.
+    fn solana(&mut self) -> SolanaCommands<'_, 'w, 's>;
+}
+
+impl<'w, 's> SolanaCommandsExt<'w, 's> for Commands<'w, 's> {
+    fn solana(&mut self) -> SolanaCommands<'_, 'w, 's> {
+        SolanaCommands { commands: self }
+    }
+}
+
+impl<'c, 'w, 's> SolanaCommands<'c, 'w, 's> {
+    /// Requests `lamports` from the cluster's faucet, devnet, testnet and local only.
+    pub fn airdrop(&mut self, pubkey: Pubkey, lamports: u64) -> &mut Self {
+        self.commands.add(move |world: &mut World| {
+            let rpc = world.resource::<SolanaRpc>().clone();
+            let bridge = world.resource::<AsyncBridge>().clone();
+
+            bridge.clone().spawn(move || async move {
+                let result = rpc.request_airdrop(&pubkey, lamports).await;
+                bridge.send(move |world| match result {
+                    Ok(signature) => {
+                        world.send_event(SolanaCommandEvent::Airdropped {
+                            pubkey,
+                            lamports,
+                            signature,
+                        });
+                    }
+                    Err(err) => report_error(world, "airdrop", &err),
+                });
+            });
+        });
+        self
+    }
+
+    /// Fetches the SOL balance of `pubkey`.
+    pub fn refresh_balance_of(&mut self, pubkey: Pubkey) -> &mut Self {
+        self.commands
+            .add(move |world: &mut World| refresh_balance(world, pubkey));
+        self
+    }
+
+    /// Fetches the SOL balance of the connected wallet, nothing happens while disconnected.
+    pub fn refresh_balance(&mut self) -> &mut Self {
+        self.commands.add(|world: &mut World| {
+            let wallet = world.resource::<Wallet>();
+            if let Some(pubkey) = wallet.info.as_ref().and_then(|info| info.pubkey().ok()) {
+                refresh_balance(world, pubkey);
+            }
+        });
+        self
+    }
+}
+
+fn refresh_balance(world: &mut World, pubkey: Pubkey) {
+    let rpc = world.resource::<SolanaRpc>().clone();
+    let bridge = world.resource::<AsyncBridge>().clone();
+
+    bridge.clone().spawn(move || async move {
+        let result = rpc.get_balance(&pubkey).await;
+        bridge.send(move |world| match result {
+            Ok(lamports) => {
+                world.send_event(SolanaCommandEvent::BalanceRefreshed { pubkey, lamports });
+            }
+            Err(err) => report_error(world, "refresh balance", &err),
+        });
+    });
+}
+
+pub struct SolanaCommandsPlugin;
+
+impl Plugin for SolanaCommandsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SolanaCommandEvent>();
+    }
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+use bevy_solana_client_common::{cluster::Cluster, rpc_client::RpcClient};
+
+use crate::{rpc::SolanaRpc, RpcCluster, Wallet};
+
+/// Runs while a wallet is connected, e.g. `place_bet_system.run_if(wallet_connected())`.
+pub fn wallet_connected() -> impl FnMut(Res<Wallet>) -> bool + Clone {
+    |wallet: Res<Wallet>| wallet.info.is_some()
+}
+
+/// Runs once the rpc is known to be on `cluster`, see [`RpcCluster`].
+pub fn cluster_is(cluster: Cluster) -> impl FnMut(Res<RpcCluster>) -> bool + Clone {
+    move |rpc_cluster: Res<RpcCluster>| rpc_cluster.0 == Some(cluster)
+}
+
+/// Runs while at least one rpc endpoint is available, i.e. not cooling down after
+/// repeated failures.
+pub fn rpc_healthy() -> impl FnMut(Res<SolanaRpc>) -> bool + Clone {
+    |rpc: Res<SolanaRpc>| rpc.endpoints().has_available()
+}
@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_solana_client_common::rpc_client::RpcClient;
+use solana_sdk::{account::Account, clock::Clock, sysvar};
+
+use crate::{
+    account::SolanaAccountData,
+    bridge::AsyncBridge,
+    error::report_error,
+    rpc::{SolanaRpc, SolanaRpcClient},
+    schedule::SolanaClientSet,
+};
+
+/// Estimate of the cluster's `Clock` sysvar timestamp, refreshed while cooldowns exist.
+///
+/// Between refreshes the estimate advances with the local clock, so player devices with
+/// a skewed system time still agree with the programs checking the cooldowns.
+#[derive(Debug, Resource)]
+pub struct ChainClock {
+    pub refresh: Timer,
+    sample: Option<(Clock, Duration)>,
+    requested: bool,
+    pending: bool,
+}
+
+impl Default for ChainClock {
+    fn default() -> Self {
+        Self {
+            refresh: Timer::new(Duration::from_secs(30), TimerMode::Repeating),
+            sample: None,
+            requested: false,
+            pending: false,
+        }
+    }
+}
+
+impl ChainClock {
+    /// The last fetched `Clock` sysvar.
+    pub fn clock(&self) -> Option<&Clock> {
+        self.sample.as_ref().map(|(clock, _)| clock)
+    }
+
+    /// Estimated on-chain unix timestamp at `time`, `None` until the clock was fetched once.
+    pub fn now_unix(&self, time: &Time<Real>) -> Option<i64> {
+        let (clock, fetched_at) = self.sample.as_ref()?;
+        let since = time.elapsed().saturating_sub(*fetched_at);
+        Some(clock.unix_timestamp + since.as_secs() as i64)
+    }
+
+    /// Forgets the clock of the previous cluster and the request in flight.
+    pub(crate) fn reset(&mut self) {
+        self.sample = None;
+        self.requested = false;
+        self.pending = false;
+    }
+}
+
+/// An action locked until the on-chain clock reaches `until_unix`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct ChainCooldown {
+    pub until_unix: i64,
+    elapsed: bool,
+}
+
+impl ChainCooldown {
+    pub fn new(until_unix: i64) -> Self {
+        Self {
+            until_unix,
+            elapsed: false,
+        }
+    }
+
+    pub fn is_elapsed(&self) -> bool {
+        self.elapsed
+    }
+
+    /// Time left according to the chain clock estimate, zero once elapsed.
+    pub fn remaining(&self, clock: &ChainClock, time: &Time<Real>) -> Option<Duration> {
+        let now = clock.now_unix(time)?;
+        Some(Duration::from_secs(
+            self.until_unix.saturating_sub(now).max(0) as u64,
+        ))
+    }
+}
+
+/// Keeps the [`ChainCooldown`] of this entity in sync with a timestamp field of the
+/// [`SolanaAccountData`] on the same entity, e.g. a player's `next_attack_at`.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct CooldownSource {
+    pub extract: fn(&Account) -> Option<i64>,
+}
+
+/// Sent once when the chain clock passes the [`ChainCooldown`] of `entity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct CooldownElapsed {
+    pub entity: Entity,
+    pub until_unix: i64,
+}
+
+pub struct ChainCooldownPlugin;
+
+impl Plugin for ChainCooldownPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChainClock>();
+        app.add_event::<CooldownElapsed>();
+        app.add_systems(
+            Update,
+            (
+                refresh_chain_clock_system,
+                sync_cooldown_source_system,
+                cooldown_elapsed_system,
+            )
+                .chain()
+                .in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn refresh_chain_clock_system(
+    time: Res<Time<Real>>,
+    mut clock: ResMut<ChainClock>,
+    cooldowns: Query<(), With<ChainCooldown>>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    let due = clock.refresh.tick(time.delta()).just_finished() || !clock.requested;
+    if !due || clock.pending || cooldowns.is_empty() {
+        return;
+    }
+    clock.requested = true;
+    clock.pending = true;
+
+    let rpc = rpc.clone();
+    let bridge = bridge.clone();
+    bridge.clone().spawn(move || async move {
+        let result = fetch_clock(&rpc).await;
+        bridge.send(move |world| {
+            let fetched_at = world.resource::<Time<Real>>().elapsed();
+            let mut clock = world.resource_mut::<ChainClock>();
+            clock.pending = false;
+
+            match result {
+                Ok(sysvar) => clock.sample = Some((sysvar, fetched_at)),
+                Err(err) => report_error(world, "fetch chain clock", &err),
+            }
+        });
+    });
+}
+
+async fn fetch_clock(rpc: &SolanaRpcClient) -> Result<Clock> {
+    let account = rpc.get_account(&sysvar::clock::id()).await?;
+    bincode::deserialize(&account.data).context("invalid clock sysvar")
+}
+
+fn sync_cooldown_source_system(
+    mut commands: Commands,
+    mut sources: Query<
+        (
+            Entity,
+            &CooldownSource,
+            &SolanaAccountData,
+            Option<&mut ChainCooldown>,
+        ),
+        Changed<SolanaAccountData>,
+    >,
+) {
+    for (entity, source, data, cooldown) in &mut sources {
+        let Some(until_unix) = (source.extract)(&data.0) else {
+            continue;
+        };
+
+        match cooldown {
+            Some(mut cooldown) if cooldown.until_unix != until_unix => {
+                *cooldown = ChainCooldown::new(until_unix);
+            }
+            Some(_) => {}
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(ChainCooldown::new(until_unix));
+            }
+        }
+    }
+}
+
+fn cooldown_elapsed_system(
+    time: Res<Time<Real>>,
+    clock: Res<ChainClock>,
+    mut cooldowns: Query<(Entity, &mut ChainCooldown)>,
+    mut ev_writer: EventWriter<CooldownElapsed>,
+) {
+    let Some(now) = clock.now_unix(&time) else {
+        return;
+    };
+
+    for (entity, mut cooldown) in &mut cooldowns {
+        if !cooldown.elapsed && now >= cooldown.until_unix {
+            cooldown.elapsed = true;
+            ev_writer.send(CooldownElapsed {
+                entity,
+                until_unix: cooldown.until_unix,
+            });
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, Result};
+use bevy::{
+    ecs::system::EntityCommands,
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, TaskPool},
+};
+use bevy_solana_client_common::idl::Idl;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{
+    account::{SolanaAccountData, SolanaAccountPubkey},
+    schedule::SolanaClientSet,
+};
+
+/// Decodes account data into a component, e.g. with borsh or bincode.
+pub type AccountDecoder<T> = fn(&[u8]) -> Result<T>;
+
+/// Applies a decoded value, or removes the stale one if decoding failed.
+type ApplyDecoded = Box<dyn FnOnce(&mut EntityCommands) + Send>;
+
+type ErasedDecoder = Arc<dyn Fn(&[u8]) -> (ApplyDecoded, Option<anyhow::Error>) + Send + Sync>;
+
+/// Decoders turning the raw data of mirrored accounts into typed components.
+///
+/// Every entity with [`SolanaAccountData`], e.g. the ones spawned by a
+/// [`ProgramSync`](crate::sync::ProgramSync), gets the decoded component inserted next
+/// to the raw account whenever the data changes. A decoder registered for an account's
+/// 8 byte discriminator takes precedence over one registered for its whole program.
+///
+/// Accounts larger than `offload_above` bytes are decoded off the frame as set by
+/// `strategy`, their component shows up once decoding finished, usually a frame later.
+/// Smaller ones are decoded right away.
+#[derive(Resource)]
+pub struct AccountDecoderRegistry {
+    pub offload_above: usize,
+    pub strategy: DecodeStrategy,
+    by_discriminator: HashMap<(Pubkey, [u8; 8]), ErasedDecoder>,
+    by_program: HashMap<Pubkey, ErasedDecoder>,
+}
+
+impl Default for AccountDecoderRegistry {
+    fn default() -> Self {
+        Self {
+            offload_above: 4096,
+            strategy: DecodeStrategy::default(),
+            by_discriminator: HashMap::new(),
+            by_program: HashMap::new(),
+        }
+    }
+}
+
+/// How accounts above [`AccountDecoderRegistry::offload_above`] are decoded.
+///
+/// Decoders are plain functions living in the app's wasm module, running them in a web
+/// worker would need a shared-memory build of the whole app, so the wasm default
+/// spreads decoding over frames instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeStrategy {
+    /// On the [`AsyncComputeTaskPool`], in parallel to the frame.
+    TaskPool,
+    /// On the main thread, at most `bytes_per_frame` bytes of account data per frame
+    /// but at least one account, the rest waits for the next frames.
+    FrameBudget { bytes_per_frame: usize },
+}
+
+impl Default for DecodeStrategy {
+    fn default() -> Self {
+        // the task pool has no threads on wasm and would decode everything at once
+        if cfg!(target_arch = "wasm32") {
+            DecodeStrategy::FrameBudget {
+                bytes_per_frame: 256 * 1024,
+            }
+        } else {
+            DecodeStrategy::TaskPool
+        }
+    }
+}
+
+impl AccountDecoderRegistry {
+    /// Decodes every account owned by `program_id` with `decode`.
+    pub fn register_program<T: Component>(
+        &mut self,
+        program_id: Pubkey,
+        decode: AccountDecoder<T>,
+    ) -> &mut Self {
+        self.by_program.insert(program_id, erase(decode));
+        self
+    }
+
+    /// Decodes the accounts of `program_id` starting with `discriminator` with `decode`.
+    pub fn register_discriminator<T: Component>(
+        &mut self,
+        program_id: Pubkey,
+        discriminator: [u8; 8],
+        decode: AccountDecoder<T>,
+    ) -> &mut Self {
+        self.by_discriminator
+            .insert((program_id, discriminator), erase(decode));
+        self
+    }
+
+    /// Decodes the accounts of IDL account type `name`, e.g. from an
+    /// [`IdlAsset`](crate::idl::IdlAsset), owned by `program_id` with `decode`.
+    pub fn register_idl_account<T: Component>(
+        &mut self,
+        program_id: Pubkey,
+        idl: &Idl,
+        name: &str,
+        decode: AccountDecoder<T>,
+    ) -> Result<&mut Self> {
+        let account = idl
+            .account(name)
+            .with_context(|| format!("IDL {} has no account {}", idl.name, name))?;
+        Ok(self.register_discriminator(program_id, account.discriminator, decode))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_discriminator.is_empty() && self.by_program.is_empty()
+    }
+
+    fn get(&self, account: &Account) -> Option<&ErasedDecoder> {
+        let discriminator = account
+            .data
+            .get(..8)
+            .and_then(|bytes| <[u8; 8]>::try_from(bytes).ok());
+
+        discriminator
+            .and_then(|discriminator| self.by_discriminator.get(&(account.owner, discriminator)))
+            .or_else(|| self.by_program.get(&account.owner))
+    }
+}
+
+fn erase<T: Component>(decode: AccountDecoder<T>) -> ErasedDecoder {
+    Arc::new(move |data| match decode(data) {
+        Ok(value) => (
+            Box::new(move |entity: &mut EntityCommands| {
+                entity.insert(value);
+            }),
+            None,
+        ),
+        // stale values would look like the current state of the account
+        Err(err) => (
+            Box::new(|entity: &mut EntityCommands| {
+                entity.remove::<T>();
+            }),
+            Some(err),
+        ),
+    })
+}
+
+type DecodeResult = Arc<Mutex<Option<(ApplyDecoded, Option<anyhow::Error>)>>>;
+
+struct DecodeTask {
+    pubkey: Option<Pubkey>,
+    result: DecodeResult,
+}
+
+/// An account waiting for its turn under [`DecodeStrategy::FrameBudget`].
+struct QueuedDecode {
+    entity: Entity,
+    pubkey: Option<Pubkey>,
+    decode: ErasedDecoder,
+    bytes: Vec<u8>,
+}
+
+pub struct AccountDecoderPlugin;
+
+impl Plugin for AccountDecoderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AccountDecoderRegistry>();
+        app.add_systems(
+            PostUpdate,
+            decode_accounts_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn decode_accounts_system(
+    mut commands: Commands,
+    mut pending: Local<HashMap<Entity, DecodeTask>>,
+    mut queued: Local<VecDeque<QueuedDecode>>,
+    registry: Res<AccountDecoderRegistry>,
+    changed: Query<
+        (Entity, &SolanaAccountData, Option<&SolanaAccountPubkey>),
+        Changed<SolanaAccountData>,
+    >,
+) {
+    if registry.is_empty() {
+        return;
+    }
+
+    for (entity, data, pubkey) in &changed {
+        let Some(decode) = registry.get(&data.0) else {
+            continue;
+        };
+        let pubkey = pubkey.map(|pubkey| pubkey.0);
+
+        // a newer value replaces whatever was still being decoded
+        pending.remove(&entity);
+        queued.retain(|queued| queued.entity != entity);
+
+        if data.0.data.len() <= registry.offload_above {
+            let (apply, err) = decode(&data.0.data);
+            apply_decoded(&mut commands, entity, pubkey, apply, err);
+            continue;
+        }
+
+        let decode = decode.clone();
+        let bytes = data.0.data.clone();
+        if let DecodeStrategy::FrameBudget { .. } = registry.strategy {
+            queued.push_back(QueuedDecode {
+                entity,
+                pubkey,
+                decode,
+                bytes,
+            });
+            continue;
+        }
+
+        let result = DecodeResult::default();
+        let slot = result.clone();
+        AsyncComputeTaskPool::get_or_init(TaskPool::default)
+            .spawn(async move {
+                let decoded = decode(&bytes);
+                if let Ok(mut slot) = slot.lock() {
+                    *slot = Some(decoded);
+                }
+            })
+            .detach();
+        // the result of an outdated task is dropped with its slot
+        pending.insert(entity, DecodeTask { pubkey, result });
+    }
+
+    pending.retain(|entity, decoding| {
+        let decoded = match decoding.result.lock() {
+            Ok(mut slot) => slot.take(),
+            Err(_) => return false,
+        };
+        let Some((apply, err)) = decoded else {
+            return true;
+        };
+        apply_decoded(&mut commands, *entity, decoding.pubkey, apply, err);
+        false
+    });
+
+    // whatever is left over from a switch to the task pool is decoded right away
+    let mut budget = match registry.strategy {
+        DecodeStrategy::FrameBudget { bytes_per_frame } => bytes_per_frame,
+        DecodeStrategy::TaskPool => usize::MAX,
+    };
+    let mut decoded_any = false;
+    while let Some(next) = queued.front() {
+        if decoded_any && next.bytes.len() > budget {
+            break;
+        }
+        let Some(next) = queued.pop_front() else {
+            break;
+        };
+        budget = budget.saturating_sub(next.bytes.len());
+        decoded_any = true;
+
+        let (apply, err) = (next.decode)(&next.bytes);
+        apply_decoded(&mut commands, next.entity, next.pubkey, apply, err);
+    }
+}
+
+fn apply_decoded(
+    commands: &mut Commands,
+    entity: Entity,
+    pubkey: Option<Pubkey>,
+    apply: ApplyDecoded,
+    err: Option<anyhow::Error>,
+) {
+    // the account entity may have been despawned while decoding
+    let Some(mut entity_commands) = commands.get_entity(entity) else {
+        return;
+    };
+    apply(&mut entity_commands);
+
+    if let Some(err) = err {
+        match pubkey {
+            Some(pubkey) => warn!("could not decode account {}: {:#}", pubkey, err),
+            None => warn!("could not decode account of {:?}: {:#}", entity, err),
+        }
+    }
+}
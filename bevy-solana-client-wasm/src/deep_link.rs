@@ -0,0 +1,638 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use bevy::prelude::*;
+use bevy_solana_client_common::signer::GameSigner;
+use salsa20::{
+    cipher::{consts::U10, generic_array::GenericArray},
+    hsalsa,
+};
+use serde_json::{json, Value};
+use solana_sdk::{bs58, pubkey::Pubkey, signature::Signature, transaction::Transaction};
+use wasm_bindgen::JsValue;
+use web_sys::UrlSearchParams;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::{
+    error::report_error,
+    schedule::SolanaClientSet,
+    secretbox::{local_storage, open, random_bytes, seal},
+    wallet_adapter::WalletAdapter,
+    Wallet, WalletEvent,
+};
+
+/// `localStorage` key of the persisted [`DeepLinkSession`].
+pub const DEEP_LINK_STORAGE_KEY: &str = "bevySolanaDeepLink";
+
+/// Query parameter added to redirect links, names the method the wallet answers.
+const ACTION_PARAM: &str = "bevySolanaAction";
+
+/// Mobile wallets reachable through universal links, for browsers without an injected provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum DeepLinkWallet {
+    Phantom,
+    Solflare,
+}
+
+impl DeepLinkWallet {
+    pub fn name(self) -> &'static str {
+        match self {
+            DeepLinkWallet::Phantom => "Phantom",
+            DeepLinkWallet::Solflare => "Solflare",
+        }
+    }
+
+    fn base_url(self) -> &'static str {
+        match self {
+            DeepLinkWallet::Phantom => "https://phantom.app/ul/v1/",
+            DeepLinkWallet::Solflare => "https://solflare.com/ul/v1/",
+        }
+    }
+
+    /// Query parameter carrying the wallet's encryption key in the connect redirect.
+    fn encryption_key_param(self) -> &'static str {
+        match self {
+            DeepLinkWallet::Phantom => "phantom_encryption_public_key",
+            DeepLinkWallet::Solflare => "solflare_encryption_public_key",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeepLinkMethod {
+    Connect,
+    Disconnect,
+    SignTransaction,
+    SignAndSendTransaction,
+    SignMessage,
+}
+
+impl DeepLinkMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeepLinkMethod::Connect => "connect",
+            DeepLinkMethod::Disconnect => "disconnect",
+            DeepLinkMethod::SignTransaction => "signTransaction",
+            DeepLinkMethod::SignAndSendTransaction => "signAndSendTransaction",
+            DeepLinkMethod::SignMessage => "signMessage",
+        }
+    }
+
+    fn parse(method: &str) -> Option<Self> {
+        [
+            DeepLinkMethod::Connect,
+            DeepLinkMethod::Disconnect,
+            DeepLinkMethod::SignTransaction,
+            DeepLinkMethod::SignAndSendTransaction,
+            DeepLinkMethod::SignMessage,
+        ]
+        .into_iter()
+        .find(|m| m.as_str() == method)
+    }
+}
+
+/// Encryption keys and session of a deep link connection.
+///
+/// Every request leaves the page, so this is kept in `localStorage` to survive the
+/// reload that brings the player back from the wallet app.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct DeepLinkSession {
+    pub wallet: DeepLinkWallet,
+    /// The game's x25519 secret, bs58 encoded.
+    secret: String,
+    /// The wallet's x25519 public key, known once connected.
+    wallet_encryption_key: Option<String>,
+    /// Opaque session token the wallet issued on connect.
+    session: Option<String>,
+    pub public_key: Option<Pubkey>,
+}
+
+impl std::fmt::Debug for DeepLinkSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeepLinkSession")
+            .field("wallet", &self.wallet)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DeepLinkSession {
+    fn new(wallet: DeepLinkWallet) -> Result<Self> {
+        let mut secret = [0u8; 32];
+        random_bytes(&mut secret)?;
+        Ok(Self {
+            wallet,
+            secret: bs58::encode(secret).into_string(),
+            wallet_encryption_key: None,
+            session: None,
+            public_key: None,
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.session.is_some() && self.public_key.is_some()
+    }
+
+    fn secret(&self) -> Result<StaticSecret> {
+        let bytes: [u8; 32] = decode_key(&self.secret).context("invalid deep link secret")?;
+        Ok(StaticSecret::from(bytes))
+    }
+
+    fn encryption_public_key(&self) -> Result<String> {
+        let public_key = PublicKey::from(&self.secret()?);
+        Ok(bs58::encode(public_key.as_bytes()).into_string())
+    }
+
+    /// The `crypto_box` key shared with the wallet.
+    fn shared_key(&self, wallet_encryption_key: &str) -> Result<[u8; 32]> {
+        let wallet_key: [u8; 32] =
+            decode_key(wallet_encryption_key).context("invalid wallet encryption key")?;
+        let shared = self.secret()?.diffie_hellman(&PublicKey::from(wallet_key));
+        Ok(hsalsa::<U10>(
+            GenericArray::from_slice(shared.as_bytes()),
+            &GenericArray::default(),
+        )
+        .into())
+    }
+
+    fn session_key(&self) -> Result<[u8; 32]> {
+        let wallet_key = self
+            .wallet_encryption_key
+            .as_deref()
+            .context("deep link wallet is not connected")?;
+        self.shared_key(wallet_key)
+    }
+
+    pub fn load() -> Option<Self> {
+        let json = local_storage()
+            .ok()?
+            .get_item(DEEP_LINK_STORAGE_KEY)
+            .ok()??;
+        serde_json::from_str(&json)
+            .inspect_err(|err| warn!("ignoring invalid deep link session: {}", err))
+            .ok()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        local_storage()?
+            .set_item(DEEP_LINK_STORAGE_KEY, &serde_json::to_string(self)?)
+            .map_err(|err| anyhow!("{err:?}"))
+    }
+
+    pub fn clear() {
+        if let Ok(storage) = local_storage() {
+            let _ = storage.remove_item(DEEP_LINK_STORAGE_KEY);
+        }
+    }
+
+    /// The universal link starting the connection, the wallet answers at `redirect_link`.
+    pub fn connect_url(&self, app_url: &str, redirect_link: &str, cluster: &str) -> Result<String> {
+        let params = UrlSearchParams::new().map_err(|err| anyhow!("{err:?}"))?;
+        params.append("app_url", app_url);
+        params.append("dapp_encryption_public_key", &self.encryption_public_key()?);
+        params.append("redirect_link", redirect_link);
+        params.append("cluster", cluster);
+        Ok(format!(
+            "{}{}?{}",
+            self.wallet.base_url(),
+            DeepLinkMethod::Connect.as_str(),
+            String::from(params.to_string())
+        ))
+    }
+
+    /// The universal link of an encrypted request, `payload` gets the session added.
+    pub fn request_url(
+        &self,
+        method: DeepLinkMethod,
+        mut payload: Value,
+        redirect_link: &str,
+    ) -> Result<String> {
+        payload["session"] = json!(self
+            .session
+            .as_deref()
+            .context("deep link wallet is not connected")?);
+
+        let mut nonce = [0u8; 24];
+        random_bytes(&mut nonce)?;
+        let sealed = seal(&self.session_key()?, &nonce, payload.to_string().as_bytes());
+
+        let params = UrlSearchParams::new().map_err(|err| anyhow!("{err:?}"))?;
+        params.append("dapp_encryption_public_key", &self.encryption_public_key()?);
+        params.append("nonce", &bs58::encode(nonce).into_string());
+        params.append("redirect_link", redirect_link);
+        params.append("payload", &bs58::encode(sealed).into_string());
+        Ok(format!(
+            "{}{}?{}",
+            self.wallet.base_url(),
+            method.as_str(),
+            String::from(params.to_string())
+        ))
+    }
+
+    /// Decrypts the `data` of a redirect, encrypted with `wallet_encryption_key` or the
+    /// key of the current connection.
+    fn open_response(
+        &self,
+        params: &UrlSearchParams,
+        wallet_encryption_key: Option<&str>,
+    ) -> Result<Value> {
+        let key = match wallet_encryption_key {
+            Some(wallet_key) => self.shared_key(wallet_key)?,
+            None => self.session_key()?,
+        };
+        let nonce: [u8; 24] = decode_key(&params.get("nonce").context("missing nonce")?)?;
+        let data = bs58::decode(params.get("data").context("missing data")?)
+            .into_vec()
+            .map_err(|err| anyhow!("invalid response data: {}", err))?;
+
+        let plaintext = open(&key, &nonce, &data)?;
+        serde_json::from_slice(&plaintext).context("invalid response payload")
+    }
+}
+
+fn decode_key<const N: usize>(encoded: &str) -> Result<[u8; N]> {
+    bs58::decode(encoded)
+        .into_vec()
+        .map_err(|err| anyhow!("{}", err))?
+        .try_into()
+        .map_err(|_| anyhow!("expected {} bytes", N))
+}
+
+/// Where wallets send the player back to, the current page plus the answered method.
+fn redirect_link(method: DeepLinkMethod) -> Result<String> {
+    let location = web_sys::window()
+        .context("could not get window")?
+        .location();
+    let origin = location.origin().map_err(|err| anyhow!("{err:?}"))?;
+    let path = location.pathname().map_err(|err| anyhow!("{err:?}"))?;
+    Ok(format!(
+        "{}{}?{}={}",
+        origin,
+        path,
+        ACTION_PARAM,
+        method.as_str()
+    ))
+}
+
+/// Which wallet app deep links go to and the cluster it connects to.
+#[derive(Debug, Clone, Resource)]
+pub struct DeepLinkSettings {
+    pub wallet: DeepLinkWallet,
+    /// `mainnet-beta`, `testnet` or `devnet`.
+    pub cluster: String,
+    /// Shown by the wallet for the connect request, the page origin by default.
+    pub app_url: Option<String>,
+}
+
+impl Default for DeepLinkSettings {
+    fn default() -> Self {
+        Self {
+            wallet: DeepLinkWallet::Phantom,
+            cluster: "mainnet-beta".to_string(),
+            app_url: None,
+        }
+    }
+}
+
+/// Leaves the page for the wallet app, answered by a [`DeepLinkResponse`] after the
+/// wallet redirected back and the game restarted.
+///
+/// Transactions must carry a recent blockhash, the wallet adds its signature only.
+#[derive(Debug, Clone, Event)]
+pub enum DeepLinkRequest {
+    Connect,
+    Disconnect,
+    SignTransaction(Transaction),
+    SignAndSendTransaction(Transaction),
+    SignMessage(Vec<u8>),
+}
+
+#[derive(Debug, Clone, Event)]
+pub enum DeepLinkResponse {
+    Connected(Pubkey),
+    Disconnected,
+    TransactionSigned(Transaction),
+    TransactionSent(Signature),
+    MessageSigned(Signature),
+    /// The player declined in the wallet app or the request was invalid.
+    Rejected {
+        method: DeepLinkMethod,
+        code: i64,
+        message: String,
+    },
+}
+
+/// A signing request left the page for the wallet app, see [`DeepLinkSigner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeepLinkRedirect(pub DeepLinkMethod);
+
+impl std::fmt::Display for DeepLinkRedirect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} continues in the wallet app, its result arrives as a deep link response",
+            self.0.as_str()
+        )
+    }
+}
+
+impl std::error::Error for DeepLinkRedirect {}
+
+/// The [`Wallet`] signer of a deep link connection.
+///
+/// Every request leaves the page, so signing never finishes in place: the deep link is
+/// opened and the call fails with [`DeepLinkRedirect`], the result arrives as a
+/// [`DeepLinkResponse`] once the wallet sent the player back.
+#[derive(Debug, Clone, Copy)]
+pub struct DeepLinkSigner {
+    pub pubkey: Pubkey,
+}
+
+impl DeepLinkSigner {
+    /// Opens the deep link of `request`, the error the signing call fails with.
+    fn redirect(&self, request: DeepLinkRequest) -> anyhow::Error {
+        let method = match request {
+            DeepLinkRequest::SignTransaction(_) => DeepLinkMethod::SignTransaction,
+            DeepLinkRequest::SignAndSendTransaction(_) => DeepLinkMethod::SignAndSendTransaction,
+            DeepLinkRequest::SignMessage(_) => DeepLinkMethod::SignMessage,
+            _ => unreachable!("only signing requests go through the signer"),
+        };
+        match session_request_url(&request).and_then(|url| open_deep_link(&url)) {
+            Ok(()) => DeepLinkRedirect(method).into(),
+            Err(err) => err,
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl GameSigner for DeepLinkSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_transaction(&self, tx: &mut Transaction) -> Result<()> {
+        Err(self.redirect(DeepLinkRequest::SignTransaction(tx.clone())))
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Err(self.redirect(DeepLinkRequest::SignMessage(message.to_vec())))
+    }
+
+    async fn sign_and_submit_transaction(&self, tx: &mut Transaction) -> Result<Option<Signature>> {
+        Err(self.redirect(DeepLinkRequest::SignAndSendTransaction(tx.clone())))
+    }
+}
+
+/// Connects and signs through Phantom or Solflare universal links on mobile browsers,
+/// where no wallet injects a provider.
+///
+/// The connect button uses deep links when [`WalletAdapter::detect_all`] finds nothing,
+/// and a connection restored from `localStorage` connects the [`Wallet`] on startup.
+#[derive(Default)]
+pub struct DeepLinkPlugin {
+    pub settings: DeepLinkSettings,
+}
+
+impl Plugin for DeepLinkPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone());
+        app.add_event::<DeepLinkRequest>();
+        app.add_event::<DeepLinkResponse>();
+        app.add_systems(Startup, deep_link_redirect_system);
+        app.add_systems(
+            Update,
+            (deep_link_connect_button_system, deep_link_request_system)
+                .chain()
+                .in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn deep_link_connect_button_system(
+    mut wallet_events: EventReader<WalletEvent>,
+    mut ev_writer: EventWriter<DeepLinkRequest>,
+    wallet: Res<Wallet>,
+) {
+    for event in wallet_events.read() {
+        match event {
+            WalletEvent::ConnectBtnClick
+                if wallet.info.is_none() && WalletAdapter::detect_all().is_empty() =>
+            {
+                ev_writer.send(DeepLinkRequest::Connect);
+            }
+            WalletEvent::Disconnected => DeepLinkSession::clear(),
+            _ => {}
+        }
+    }
+}
+
+fn deep_link_request_system(
+    mut ev_reader: EventReader<DeepLinkRequest>,
+    settings: Res<DeepLinkSettings>,
+    mut commands: Commands,
+) {
+    // the page is left, later requests of the same frame are dropped
+    let Some(request) = ev_reader.read().last().cloned() else {
+        return;
+    };
+
+    let result = deep_link_url(&request, &settings).and_then(|url| {
+        debug!("opening deep link for {:?}", request);
+        open_deep_link(&url)
+    });
+
+    if let Err(err) = result {
+        commands.add(move |world: &mut World| {
+            report_error(world, "open wallet deep link", &err);
+        });
+    }
+}
+
+fn deep_link_url(request: &DeepLinkRequest, settings: &DeepLinkSettings) -> Result<String> {
+    if let DeepLinkRequest::Connect = request {
+        // a fresh key pair for every connection
+        let session = DeepLinkSession::new(settings.wallet)?;
+        session.save()?;
+
+        let app_url = match &settings.app_url {
+            Some(app_url) => app_url.clone(),
+            None => web_sys::window()
+                .context("could not get window")?
+                .location()
+                .origin()
+                .map_err(|err| anyhow!("{err:?}"))?,
+        };
+        return session.connect_url(
+            &app_url,
+            &redirect_link(DeepLinkMethod::Connect)?,
+            &settings.cluster,
+        );
+    }
+
+    session_request_url(request)
+}
+
+/// The deep link of a request within the stored connection.
+fn session_request_url(request: &DeepLinkRequest) -> Result<String> {
+    let session = DeepLinkSession::load().context("deep link wallet is not connected")?;
+    let (method, payload) = match request {
+        DeepLinkRequest::Connect => unreachable!(),
+        DeepLinkRequest::Disconnect => (DeepLinkMethod::Disconnect, json!({})),
+        DeepLinkRequest::SignTransaction(tx) => (
+            DeepLinkMethod::SignTransaction,
+            json!({"transaction": bs58::encode(bincode::serialize(tx)?).into_string()}),
+        ),
+        DeepLinkRequest::SignAndSendTransaction(tx) => (
+            DeepLinkMethod::SignAndSendTransaction,
+            json!({"transaction": bs58::encode(bincode::serialize(tx)?).into_string()}),
+        ),
+        DeepLinkRequest::SignMessage(message) => (
+            DeepLinkMethod::SignMessage,
+            json!({"message": bs58::encode(message).into_string()}),
+        ),
+    };
+    session.request_url(method, payload, &redirect_link(method)?)
+}
+
+/// Restores a stored connection and applies the answer of a wallet redirect.
+fn deep_link_redirect_system(world: &mut World) {
+    let Some(mut session) = DeepLinkSession::load() else {
+        return;
+    };
+
+    if let Some(public_key) = session.public_key.filter(|_| session.is_connected()) {
+        connect(world, public_key);
+    }
+
+    let Some(params) = redirect_params() else {
+        return;
+    };
+    let Some(method) = params
+        .get(ACTION_PARAM)
+        .as_deref()
+        .and_then(DeepLinkMethod::parse)
+    else {
+        return;
+    };
+    clear_redirect_params();
+
+    if let Some(code) = params.get("errorCode") {
+        let message = params.get("errorMessage").unwrap_or_default();
+        warn!(
+            "{} {} failed: {} {}",
+            session.wallet.name(),
+            method.as_str(),
+            code,
+            message
+        );
+        world.send_event(DeepLinkResponse::Rejected {
+            method,
+            code: code.parse().unwrap_or_default(),
+            message,
+        });
+        if method == DeepLinkMethod::Connect {
+            world.send_event(WalletEvent::Disconnected);
+        }
+        return;
+    }
+
+    let result = apply_response(world, &mut session, method, &params);
+    if let Err(err) = result {
+        report_error(
+            world,
+            format!("{} {}", session.wallet.name(), method.as_str()),
+            &err,
+        );
+    }
+}
+
+fn apply_response(
+    world: &mut World,
+    session: &mut DeepLinkSession,
+    method: DeepLinkMethod,
+    params: &UrlSearchParams,
+) -> Result<()> {
+    let response = match method {
+        DeepLinkMethod::Connect => {
+            let wallet_key = params
+                .get(session.wallet.encryption_key_param())
+                .context("missing wallet encryption key")?;
+            let data = session.open_response(params, Some(&wallet_key))?;
+            let public_key: Pubkey = string_field(&data, "public_key")?.parse()?;
+
+            session.wallet_encryption_key = Some(wallet_key);
+            session.session = Some(string_field(&data, "session")?.to_string());
+            session.public_key = Some(public_key);
+            session.save()?;
+
+            connect(world, public_key);
+            DeepLinkResponse::Connected(public_key)
+        }
+        DeepLinkMethod::Disconnect => {
+            DeepLinkSession::clear();
+            crate::wallet_disconnected(world);
+            DeepLinkResponse::Disconnected
+        }
+        DeepLinkMethod::SignTransaction => {
+            let data = session.open_response(params, None)?;
+            let bytes = bs58::decode(string_field(&data, "transaction")?)
+                .into_vec()
+                .map_err(|err| anyhow!("invalid signed transaction: {}", err))?;
+            DeepLinkResponse::TransactionSigned(bincode::deserialize(&bytes)?)
+        }
+        DeepLinkMethod::SignAndSendTransaction => {
+            let data = session.open_response(params, None)?;
+            DeepLinkResponse::TransactionSent(string_field(&data, "signature")?.parse()?)
+        }
+        DeepLinkMethod::SignMessage => {
+            let data = session.open_response(params, None)?;
+            DeepLinkResponse::MessageSigned(string_field(&data, "signature")?.parse()?)
+        }
+    };
+
+    world.send_event(response);
+    Ok(())
+}
+
+fn open_deep_link(url: &str) -> Result<()> {
+    web_sys::window()
+        .context("could not get window")?
+        .location()
+        .set_href(url)
+        .map_err(|err| anyhow!("{err:?}"))
+}
+
+fn string_field<'a>(data: &'a Value, field: &str) -> Result<&'a str> {
+    data.get(field)
+        .and_then(Value::as_str)
+        .with_context(|| format!("wallet response is missing {:?}", field))
+}
+
+fn connect(world: &mut World, public_key: Pubkey) {
+    let mut wallet = world.resource_mut::<Wallet>();
+    if wallet.info.as_ref().and_then(|info| info.pubkey().ok()) == Some(public_key) {
+        return;
+    }
+
+    wallet.adapter = None;
+    wallet.connect(Arc::new(DeepLinkSigner { pubkey: public_key }));
+    world.send_event(WalletEvent::Connected);
+}
+
+fn redirect_params() -> Option<UrlSearchParams> {
+    let search = web_sys::window()?.location().search().ok()?;
+    UrlSearchParams::new_with_str(&search).ok()
+}
+
+/// Drops the response from the address bar, so a reload does not apply it twice.
+fn clear_redirect_params() {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let Ok(path) = window.location().pathname() else {
+        return;
+    };
+    if let Ok(history) = window.history() {
+        let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&path));
+    }
+}
@@ -0,0 +1,187 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use bevy::{
+    diagnostic::{
+        Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore, RegisterDiagnostic,
+    },
+    prelude::*,
+    utils::Instant,
+};
+use bevy_solana_client_common::{
+    metrics::{LatencyHistogram, MethodMetrics},
+    rpc_client::RpcClient,
+};
+
+use crate::{rpc::SolanaRpc, schedule::SolanaClientSet};
+
+/// Publishes the request metrics of [`SolanaRpc`] to the [`DiagnosticsStore`].
+///
+/// Every `interval` the number of requests and errors and the mean and p99 latency
+/// of that window are recorded, both combined and per method under
+/// `rpc/method/<method>/...`, so e.g. `LogDiagnosticsPlugin` can show RPC health.
+pub struct RpcDiagnosticsPlugin {
+    pub interval: Duration,
+}
+
+impl Default for RpcDiagnosticsPlugin {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RpcDiagnosticsPlugin {
+    pub const REQUESTS: DiagnosticPath = DiagnosticPath::const_new("rpc/requests");
+    pub const ERRORS: DiagnosticPath = DiagnosticPath::const_new("rpc/errors");
+    pub const LATENCY: DiagnosticPath = DiagnosticPath::const_new("rpc/latency");
+    pub const LATENCY_P99: DiagnosticPath = DiagnosticPath::const_new("rpc/latency_p99");
+    /// Mean send to confirmation time of the transactions confirmed in the window.
+    pub const CONFIRMATION_TIME: DiagnosticPath =
+        DiagnosticPath::const_new("rpc/confirmation_time");
+
+    /// Path of a per method diagnostic, `metric` is one of `requests`, `errors`,
+    /// `latency` or `latency_p99`.
+    pub fn method_path(method: &str, metric: &str) -> DiagnosticPath {
+        DiagnosticPath::from_components(["rpc", "method", method, metric])
+    }
+}
+
+impl Plugin for RpcDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(Self::REQUESTS))
+            .register_diagnostic(Diagnostic::new(Self::ERRORS))
+            .register_diagnostic(Diagnostic::new(Self::LATENCY).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(Self::LATENCY_P99).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(Self::CONFIRMATION_TIME).with_suffix("ms"));
+
+        app.init_resource::<RpcMetricsSnapshot>();
+        app.insert_resource(RpcDiagnosticsTimer(Timer::new(
+            self.interval,
+            TimerMode::Repeating,
+        )));
+        app.add_systems(
+            Update,
+            rpc_diagnostics_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+/// The cumulative request metrics of [`SolanaRpc`] as of the last diagnostics update.
+#[derive(Debug, Default, Resource)]
+pub struct RpcMetricsSnapshot {
+    pub methods: BTreeMap<String, MethodMetrics>,
+    /// Send to confirmation times, see [`LatencyHistogram::quantile`] for percentiles.
+    pub confirmation_times: LatencyHistogram,
+}
+
+impl RpcMetricsSnapshot {
+    /// The counters of every method combined.
+    pub fn total(&self) -> MethodMetrics {
+        self.methods
+            .values()
+            .fold(MethodMetrics::default(), |mut total, m| {
+                total.merge(m);
+                total
+            })
+    }
+}
+
+#[derive(Resource)]
+struct RpcDiagnosticsTimer(Timer);
+
+fn rpc_diagnostics_system(
+    time: Res<Time>,
+    mut timer: ResMut<RpcDiagnosticsTimer>,
+    mut snapshot: ResMut<RpcMetricsSnapshot>,
+    mut store: ResMut<DiagnosticsStore>,
+    rpc: Res<SolanaRpc>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let current = rpc.state().metrics.snapshot();
+    let mut total = MethodMetrics::default();
+
+    for (method, metrics) in &current {
+        let window = match snapshot.methods.get(method) {
+            Some(previous) => metrics.since(previous),
+            None => metrics.clone(),
+        };
+        total.merge(&window);
+
+        let [requests, errors, latency, latency_p99] =
+            ["requests", "errors", "latency", "latency_p99"]
+                .map(|metric| RpcDiagnosticsPlugin::method_path(method, metric));
+
+        if store.get(&requests).is_none() {
+            store.add(Diagnostic::new(requests.clone()));
+            store.add(Diagnostic::new(errors.clone()));
+            store.add(Diagnostic::new(latency.clone()).with_suffix("ms"));
+            store.add(Diagnostic::new(latency_p99.clone()).with_suffix("ms"));
+        }
+
+        record_window(
+            &mut store,
+            &window,
+            [&requests, &errors, &latency, &latency_p99],
+        );
+    }
+
+    record_window(
+        &mut store,
+        &total,
+        [
+            &RpcDiagnosticsPlugin::REQUESTS,
+            &RpcDiagnosticsPlugin::ERRORS,
+            &RpcDiagnosticsPlugin::LATENCY,
+            &RpcDiagnosticsPlugin::LATENCY_P99,
+        ],
+    );
+
+    let confirmation_times = rpc.state().metrics.confirmation_times();
+    let confirmations = confirmation_times.since(&snapshot.confirmation_times);
+    if let Some(mean) = confirmations.mean() {
+        if let Some(diagnostic) = store
+            .get_mut(&RpcDiagnosticsPlugin::CONFIRMATION_TIME)
+            .filter(|d| d.is_enabled)
+        {
+            diagnostic.add_measurement(DiagnosticMeasurement {
+                time: Instant::now(),
+                value: mean.as_secs_f64() * 1000.0,
+            });
+        }
+    }
+
+    snapshot.methods = current;
+    snapshot.confirmation_times = confirmation_times;
+}
+
+/// Records the requests, errors, mean and p99 latency of one window.
+fn record_window(
+    store: &mut DiagnosticsStore,
+    window: &MethodMetrics,
+    paths: [&DiagnosticPath; 4],
+) {
+    let [requests, errors, latency, latency_p99] = paths;
+    let mut add = |path: &DiagnosticPath, value: f64| {
+        if let Some(diagnostic) = store.get_mut(path).filter(|d| d.is_enabled) {
+            diagnostic.add_measurement(DiagnosticMeasurement {
+                time: Instant::now(),
+                value,
+            });
+        }
+    };
+
+    add(requests, window.requests as f64);
+    add(errors, window.errors as f64);
+
+    // without requests in this window there is no latency to report
+    if let Some(mean) = window.latency.mean() {
+        add(latency, mean.as_secs_f64() * 1000.0);
+    }
+    if let Some(p99) = window.latency.quantile(0.99) {
+        add(latency_p99, p99.as_secs_f64() * 1000.0);
+    }
+}
@@ -0,0 +1,66 @@
+use std::ops::{Deref, DerefMut};
+
+use bevy::prelude::*;
+use bevy_solana_client_common::catalog::{ErrorCatalog, ErrorCategory, ErrorExplanation};
+
+/// The [`ErrorCatalog`] used to explain [`SolanaError`]s, replace or edit it to
+/// change the language or override texts.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct SolanaErrorCatalog(pub ErrorCatalog);
+
+impl Deref for SolanaErrorCatalog {
+    type Target = ErrorCatalog;
+
+    fn deref(&self) -> &ErrorCatalog {
+        &self.0
+    }
+}
+
+impl DerefMut for SolanaErrorCatalog {
+    fn deref_mut(&mut self) -> &mut ErrorCatalog {
+        &mut self.0
+    }
+}
+
+/// A failed request or transaction of the plugin, explained for players.
+#[derive(Debug, Clone, Event)]
+pub struct SolanaError {
+    /// What the plugin was doing, e.g. `"connect wallet"`.
+    pub context: String,
+    pub category: ErrorCategory,
+    pub explanation: ErrorExplanation,
+    /// The underlying error, for logs.
+    pub message: String,
+}
+
+impl SolanaError {
+    pub fn new(catalog: &ErrorCatalog, context: impl Into<String>, err: &anyhow::Error) -> Self {
+        let (category, explanation) = catalog.explain(err);
+        Self {
+            context: context.into(),
+            category,
+            explanation,
+            message: format!("{:#}", err),
+        }
+    }
+}
+
+/// Sends a [`SolanaError`] for `err`, e.g. from an [`AsyncBridge`](crate::bridge::AsyncBridge) callback.
+pub fn report_error(world: &mut World, context: impl Into<String>, err: &anyhow::Error) {
+    let event = match world.get_resource::<SolanaErrorCatalog>() {
+        Some(catalog) => SolanaError::new(catalog, context, err),
+        None => SolanaError::new(&ErrorCatalog::default(), context, err),
+    };
+
+    debug!("{}: {}", event.context, event.message);
+    world.send_event(event);
+}
+
+pub struct SolanaErrorPlugin;
+
+impl Plugin for SolanaErrorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SolanaErrorCatalog>();
+        app.add_event::<SolanaError>();
+    }
+}
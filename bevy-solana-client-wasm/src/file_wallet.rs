@@ -0,0 +1,154 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, Context, Result};
+use bevy::prelude::*;
+use bevy_solana_client_common::signer::GameSigner;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature},
+    signer::Signer,
+    transaction::{Transaction, VersionedTransaction},
+};
+
+use crate::{error::report_error, schedule::SolanaClientSet, Wallet, WalletEvent};
+
+/// Local keypair wallet of desktop builds, loaded from a Solana CLI keypair file.
+///
+/// While present, the connect button connects to it instead of looking for browser wallets.
+#[derive(Clone, Resource)]
+pub struct FileWallet {
+    pub path: PathBuf,
+    keypair: Arc<Keypair>,
+}
+
+impl FileWallet {
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let keypair = read_keypair_file(&path)
+            .map_err(|err| anyhow!("{}", err))
+            .with_context(|| format!("could not read keypair file {}", path.display()))?;
+
+        Ok(Self {
+            path,
+            keypair: Arc::new(keypair),
+        })
+    }
+
+    /// Wraps a keypair that was read from `path` by other means, e.g. a decrypted keystore.
+    pub fn from_keypair(path: impl Into<PathBuf>, keypair: Keypair) -> Self {
+        Self {
+            path: path.into(),
+            keypair: Arc::new(keypair),
+        }
+    }
+
+    /// `~/.config/solana/id.json`, the default keypair of the Solana CLI.
+    pub fn default_path() -> Option<PathBuf> {
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+        Some(PathBuf::from(home).join(".config/solana/id.json"))
+    }
+
+    pub fn keypair(&self) -> &Keypair {
+        &self.keypair
+    }
+}
+
+impl std::fmt::Debug for FileWallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileWallet")
+            .field("path", &self.path)
+            .field("pubkey", &self.keypair.pubkey())
+            .finish()
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl GameSigner for FileWallet {
+    fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    async fn sign_transaction(&self, tx: &mut Transaction) -> Result<()> {
+        self.keypair.as_ref().sign_transaction(tx).await
+    }
+
+    async fn sign_versioned_transaction(&self, tx: &mut VersionedTransaction) -> Result<()> {
+        self.keypair.as_ref().sign_versioned_transaction(tx).await
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(self.keypair.sign_message(message))
+    }
+}
+
+/// Loads a [`FileWallet`] at startup and connects the [`Wallet`] resource to it.
+///
+/// `path` defaults to [`FileWallet::default_path`].
+#[derive(Default)]
+pub struct FileWalletPlugin {
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Resource)]
+struct FileWalletPath(Option<PathBuf>);
+
+impl Plugin for FileWalletPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FileWalletPath(self.path.clone()));
+        app.add_systems(Startup, load_file_wallet_system);
+        app.add_systems(
+            Update,
+            file_wallet_connect_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn load_file_wallet_system(world: &mut World) {
+    let path = world
+        .resource::<FileWalletPath>()
+        .0
+        .clone()
+        .or_else(FileWallet::default_path);
+
+    let result = path
+        .context("no keypair path configured and no home directory found")
+        .and_then(FileWallet::load);
+
+    match result {
+        Ok(file_wallet) => {
+            info!(
+                "loaded wallet {} from {}",
+                file_wallet.pubkey(),
+                file_wallet.path.display()
+            );
+            connect(&mut world.resource_mut::<Wallet>(), &file_wallet);
+            world.insert_resource(file_wallet);
+            world.send_event(WalletEvent::Connected);
+        }
+        Err(err) => report_error(world, "load keypair file", &err),
+    }
+}
+
+fn file_wallet_connect_system(
+    mut ev_reader: EventReader<WalletEvent>,
+    mut ev_writer: EventWriter<WalletEvent>,
+    mut wallet: ResMut<Wallet>,
+    file_wallet: Option<Res<FileWallet>>,
+) {
+    let Some(file_wallet) = file_wallet else {
+        return;
+    };
+
+    let clicked = ev_reader
+        .read()
+        .any(|event| matches!(event, WalletEvent::ConnectBtnClick));
+    if clicked && wallet.info.is_none() {
+        connect(&mut wallet, &file_wallet);
+        ev_writer.send(WalletEvent::Connected);
+    }
+}
+
+pub(crate) fn connect(wallet: &mut Wallet, file_wallet: &FileWallet) {
+    wallet.adapter = None;
+    wallet.connect(Arc::new(file_wallet.clone()));
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use bevy_solana_client_common::idl::Idl;
+
+/// An Anchor IDL loaded from `*.idl.json`, copy `target/idl/<program>.json` into the assets
+/// folder under that name.
+///
+/// Feeds [`AccountDecoderRegistry::register_idl_account`](crate::decoder::AccountDecoderRegistry::register_idl_account)
+/// and [`IdlInstruction::build`](bevy_solana_client_common::idl::IdlInstruction::build).
+#[derive(Debug, Clone, Asset, TypePath, Deref)]
+pub struct IdlAsset(pub Idl);
+
+#[derive(Default)]
+pub struct IdlAssetLoader;
+
+impl AssetLoader for IdlAssetLoader {
+    type Asset = IdlAsset;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<IdlAsset> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        Idl::from_slice(&bytes).map(IdlAsset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["idl.json"]
+    }
+}
+
+/// Registers [`IdlAsset`] and its loader, needs bevy's `AssetPlugin`.
+pub struct IdlAssetPlugin;
+
+impl Plugin for IdlAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<IdlAsset>();
+        app.init_asset_loader::<IdlAssetLoader>();
+    }
+}
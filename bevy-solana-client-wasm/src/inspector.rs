@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{
+    account::{SolanaAccountData, SolanaAccountPubkey},
+    schedule::SolanaClientSet,
+};
+
+const BYTES_PER_LINE: usize = 16;
+
+const TEXT_COLOR: Color = Color::linear_rgb(0.9, 0.9, 0.9);
+const OFFSET_COLOR: Color = Color::linear_rgb(0.4, 0.4, 0.4);
+const CHANGED_COLOR: Color = Color::linear_rgb(1.0, 0.8, 0.0);
+
+/// Decodes account data of one program into a human readable form, e.g. via `{:#?}`.
+pub type AccountDecoder = fn(&Account) -> Option<String>;
+
+/// Developer panel showing the raw bytes of a mirrored account, highlighting the bytes
+/// changed by the last update, next to its decoded form.
+#[derive(Debug, Resource)]
+pub struct AccountInspector {
+    /// The mirrored account entity to show, `None` hides the panel.
+    pub selected: Option<Entity>,
+    /// Only the first `max_bytes` bytes are dumped.
+    pub max_bytes: usize,
+    decoders: HashMap<Pubkey, AccountDecoder>,
+    shown: Option<Entity>,
+    current: Vec<u8>,
+    previous: Option<Vec<u8>>,
+}
+
+impl Default for AccountInspector {
+    fn default() -> Self {
+        Self {
+            selected: None,
+            max_bytes: 512,
+            decoders: HashMap::new(),
+            shown: None,
+            current: vec![],
+            previous: None,
+        }
+    }
+}
+
+impl AccountInspector {
+    /// Registers the decoder for accounts owned by `program_id`.
+    pub fn with_decoder(mut self, program_id: Pubkey, decoder: AccountDecoder) -> Self {
+        self.decoders.insert(program_id, decoder);
+        self
+    }
+
+    pub fn select(&mut self, entity: Option<Entity>) {
+        self.selected = entity;
+    }
+
+    fn decode(&self, account: &Account) -> String {
+        match self.decoders.get(&account.owner) {
+            Some(decoder) => decoder(account).unwrap_or_else(|| "could not decode".to_string()),
+            None => format!("no decoder for owner {}", account.owner),
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+struct InspectorPanel;
+
+#[derive(Debug, Component)]
+struct InspectorHexdump;
+
+#[derive(Debug, Component)]
+struct InspectorDecoded;
+
+pub struct AccountInspectorPlugin;
+
+impl Plugin for AccountInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AccountInspector>();
+        app.add_systems(Startup, setup_inspector_panel);
+        app.add_systems(Update, account_inspector_system.in_set(SolanaClientSet::Ui));
+    }
+}
+
+/// Formats `data` as hex lines, one section per run of equally highlighted bytes.
+///
+/// Bytes differing from `previous`, or beyond its end, are highlighted.
+pub fn hexdump_sections(
+    data: &[u8],
+    previous: Option<&[u8]>,
+    style: &TextStyle,
+) -> Vec<TextSection> {
+    let section = |value: String, color: Color| {
+        TextSection::new(
+            value,
+            TextStyle {
+                color,
+                ..style.clone()
+            },
+        )
+    };
+    let changed = |i: usize| previous.is_some_and(|prev| prev.get(i) != data.get(i));
+
+    let mut sections = vec![];
+    for (line, chunk) in data.chunks(BYTES_PER_LINE).enumerate() {
+        let offset = line * BYTES_PER_LINE;
+        sections.push(section(format!("{:08x}  ", offset), OFFSET_COLOR));
+
+        let mut run = String::new();
+        let mut run_changed = false;
+        for (i, byte) in chunk.iter().enumerate() {
+            let is_changed = changed(offset + i);
+            if is_changed != run_changed && !run.is_empty() {
+                let color = if run_changed {
+                    CHANGED_COLOR
+                } else {
+                    TEXT_COLOR
+                };
+                sections.push(section(std::mem::take(&mut run), color));
+            }
+            run_changed = is_changed;
+            run.push_str(&format!("{:02x} ", byte));
+        }
+        run.push_str(&"   ".repeat(BYTES_PER_LINE - chunk.len()));
+
+        let ascii: String = chunk
+            .iter()
+            .map(|b| {
+                if b.is_ascii_graphic() {
+                    *b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+
+        let color = if run_changed {
+            CHANGED_COLOR
+        } else {
+            TEXT_COLOR
+        };
+        sections.push(section(run, color));
+        sections.push(section(format!(" {}\n", ascii), OFFSET_COLOR));
+    }
+
+    sections
+}
+
+fn setup_inspector_panel(mut commands: Commands) {
+    let style = inspector_text_style();
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                top: Val::Px(10.0),
+                column_gap: Val::Px(20.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                display: Display::None,
+                ..default()
+            },
+            background_color: Color::linear_rgba(0.0, 0.0, 0.0, 0.8).into(),
+            z_index: ZIndex::Global(50),
+            ..default()
+        })
+        .insert(InspectorPanel)
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section("", style.clone()))
+                .insert(InspectorHexdump);
+            parent
+                .spawn(TextBundle::from_section("", style))
+                .insert(InspectorDecoded);
+        });
+}
+
+fn inspector_text_style() -> TextStyle {
+    TextStyle {
+        font_size: 14.0,
+        color: TEXT_COLOR,
+        ..Default::default()
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn account_inspector_system(
+    mut inspector: ResMut<AccountInspector>,
+    accounts: Query<(&SolanaAccountPubkey, Ref<SolanaAccountData>)>,
+    mut panel: Query<&mut Style, With<InspectorPanel>>,
+    mut hexdump: Query<&mut Text, (With<InspectorHexdump>, Without<InspectorDecoded>)>,
+    mut decoded: Query<&mut Text, (With<InspectorDecoded>, Without<InspectorHexdump>)>,
+) {
+    let selected = inspector
+        .selected
+        .and_then(|entity| Some((entity, accounts.get(entity).ok()?)));
+
+    let Ok(mut panel) = panel.get_single_mut() else {
+        return;
+    };
+    let Some((entity, (pubkey, data))) = selected else {
+        panel.display = Display::None;
+        inspector.shown = None;
+        return;
+    };
+
+    let switched = inspector.shown != Some(entity);
+    if !switched && !data.is_changed() && !inspector.is_changed() {
+        return;
+    }
+
+    if switched {
+        inspector.previous = None;
+    } else if data.is_changed() && inspector.current != data.0.data {
+        inspector.previous = Some(std::mem::take(&mut inspector.current));
+    }
+    inspector.shown = Some(entity);
+    inspector.current = data.0.data.clone();
+    panel.display = Display::Flex;
+
+    let max_bytes = inspector.max_bytes.min(data.0.data.len());
+    let previous = inspector
+        .previous
+        .as_deref()
+        .map(|prev| &prev[..prev.len().min(max_bytes)]);
+
+    let style = inspector_text_style();
+    let mut sections = vec![TextSection::new(
+        format!("{} ({} bytes)\n", pubkey.0, data.0.data.len()),
+        style.clone(),
+    )];
+    sections.extend(hexdump_sections(
+        &data.0.data[..max_bytes],
+        previous,
+        &style,
+    ));
+
+    if let Ok(mut text) = hexdump.get_single_mut() {
+        text.sections = sections;
+    }
+    if let Ok(mut text) = decoded.get_single_mut() {
+        text.sections = vec![TextSection::new(inspector.decode(&data.0), style)];
+    }
+}
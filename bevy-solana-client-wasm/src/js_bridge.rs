@@ -0,0 +1,300 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use bevy::prelude::*;
+use bevy_solana_client_common::rpc_client::ConfirmationStatus;
+use serde_json::Value;
+use solana_sdk::signature::Signature;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+use crate::{
+    error::SolanaError,
+    schedule::SolanaClientSet,
+    signature_status::{SignatureStatusCache, TrackedTransaction},
+    tracker::TransactionTracker,
+    wallet_adapter::WalletAdapter,
+    Wallet, WalletEvent,
+};
+
+/// Name of the object installed on `window`.
+pub const JS_BRIDGE_GLOBAL: &str = "bevySolana";
+
+/// Commands the surrounding web app posts with `window.bevySolana.postMessage(...)`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum JsCommand {
+    /// Connects to `wallet` by name, or opens the wallet selection modal.
+    Connect {
+        wallet: Option<String>,
+    },
+    Disconnect,
+    /// Tracks the status of a transaction the web app has sent itself, until it is
+    /// finalized, failed or expired.
+    TrackTransaction {
+        signature: String,
+    },
+    /// Asks for a [`JsMessage::State`] snapshot, e.g. after the web app mounted.
+    GetState,
+}
+
+/// Messages passed to the listeners added with `window.bevySolana.addListener(...)`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum JsMessage {
+    #[serde(rename_all = "camelCase")]
+    State {
+        connected: bool,
+        address: Option<String>,
+        wallet: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    WalletConnected {
+        address: String,
+        wallet: Option<String>,
+    },
+    WalletDisconnected,
+    #[serde(rename_all = "camelCase")]
+    WalletAccountChanged {
+        address: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    WalletConnectionFailed {
+        wallet: String,
+        reason: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    TransactionStatus {
+        signature: String,
+        confirmation_status: Option<ConfirmationStatus>,
+        err: Option<Value>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Error {
+        context: String,
+        category: String,
+        summary: String,
+        hint: String,
+        message: String,
+    },
+}
+
+thread_local! {
+    static INBOX: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    static LISTENERS: RefCell<Vec<js_sys::Function>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Passes a message to every JS listener.
+pub fn post_to_js(message: &JsMessage) {
+    let json = match serde_json::to_string(message) {
+        Ok(json) => json,
+        Err(err) => return error!("could not serialize {:?}: {}", message, err),
+    };
+    let Ok(value) = js_sys::JSON::parse(&json) else {
+        return;
+    };
+
+    // clone first, listeners may add or remove listeners
+    let listeners = LISTENERS.with(|listeners| listeners.borrow().clone());
+    for listener in listeners {
+        if let Err(err) = listener.call1(&JsValue::NULL, &value) {
+            warn!("js bridge listener failed: {:?}", err);
+        }
+    }
+}
+
+/// Mirrors wallet, transaction and error events to JS, and applies commands from JS,
+/// for games embedded in a larger web app.
+///
+/// Installs `window.bevySolana` with `postMessage(command)`, `addListener(fn)` and
+/// `removeListener(fn)`, see [`JsCommand`] and [`JsMessage`] for the message shapes.
+pub struct JsBridgePlugin;
+
+impl Plugin for JsBridgePlugin {
+    fn build(&self, app: &mut App) {
+        if let Err(err) = install_global() {
+            error!("could not install window.{}: {:?}", JS_BRIDGE_GLOBAL, err);
+        }
+
+        app.add_systems(
+            Update,
+            (
+                js_command_system,
+                mirror_wallet_events_system,
+                mirror_errors_system,
+                mirror_transaction_status_system,
+            )
+                .in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn install_global() -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let global = js_sys::Object::new();
+
+    let post_message = Closure::<dyn FnMut(JsValue)>::new(|message: JsValue| {
+        let json = match message.as_string() {
+            Some(json) => Some(json),
+            None => js_sys::JSON::stringify(&message)
+                .ok()
+                .and_then(|s| s.as_string()),
+        };
+        if let Some(json) = json {
+            INBOX.with(|inbox| inbox.borrow_mut().push(json));
+        }
+    });
+    let add_listener = Closure::<dyn FnMut(JsValue)>::new(|listener: JsValue| {
+        if let Ok(listener) = listener.dyn_into::<js_sys::Function>() {
+            LISTENERS.with(|listeners| listeners.borrow_mut().push(listener));
+        }
+    });
+    let remove_listener = Closure::<dyn FnMut(JsValue)>::new(|listener: JsValue| {
+        LISTENERS.with(|listeners| {
+            listeners
+                .borrow_mut()
+                .retain(|l| !JsValue::from(l.clone()).eq(&listener));
+        });
+    });
+
+    for (name, closure) in [
+        ("postMessage", post_message),
+        ("addListener", add_listener),
+        ("removeListener", remove_listener),
+    ] {
+        js_sys::Reflect::set(&global, &JsValue::from_str(name), closure.as_ref())?;
+        // the global lives as long as the page
+        closure.forget();
+    }
+
+    js_sys::Reflect::set(&window, &JsValue::from_str(JS_BRIDGE_GLOBAL), &global)?;
+    Ok(())
+}
+
+fn js_command_system(
+    mut commands: Commands,
+    mut ev_writer: EventWriter<WalletEvent>,
+    wallet: Res<Wallet>,
+) {
+    let inbox = INBOX.with(|inbox| std::mem::take(&mut *inbox.borrow_mut()));
+
+    for json in inbox {
+        let command: JsCommand = match serde_json::from_str(&json) {
+            Ok(command) => command,
+            Err(err) => {
+                warn!("invalid js bridge command {}: {}", json, err);
+                continue;
+            }
+        };
+        debug!("js bridge command: {:?}", command);
+
+        match command {
+            JsCommand::Connect { wallet: None } => {
+                ev_writer.send(WalletEvent::ConnectBtnClick);
+            }
+            JsCommand::Connect { wallet: Some(name) } => {
+                match WalletAdapter::ALL
+                    .into_iter()
+                    .find(|adapter| adapter.name().eq_ignore_ascii_case(&name))
+                {
+                    Some(adapter) => {
+                        ev_writer.send(WalletEvent::AdapterSelected(adapter));
+                    }
+                    None => warn!("js bridge: unknown wallet {:?}", name),
+                }
+            }
+            JsCommand::Disconnect => {
+                ev_writer.send(WalletEvent::DisconnectBtnClick);
+            }
+            JsCommand::TrackTransaction { signature } => match signature.parse::<Signature>() {
+                Ok(signature) => {
+                    commands.spawn(TransactionTracker::new(signature));
+                }
+                Err(err) => warn!("js bridge: invalid signature {:?}: {}", signature, err),
+            },
+            JsCommand::GetState => post_to_js(&state_message(&wallet)),
+        }
+    }
+}
+
+fn state_message(wallet: &Wallet) -> JsMessage {
+    JsMessage::State {
+        connected: wallet.info.is_some(),
+        address: wallet.info.as_ref().map(|info| info.address.clone()),
+        wallet: wallet.adapter.map(|adapter| adapter.name().to_string()),
+    }
+}
+
+fn mirror_wallet_events_system(mut ev_reader: EventReader<WalletEvent>, wallet: Res<Wallet>) {
+    for event in ev_reader.read() {
+        let failed = |adapter: &WalletAdapter, reason: &str| JsMessage::WalletConnectionFailed {
+            wallet: adapter.name().to_string(),
+            reason: reason.to_string(),
+        };
+
+        let message = match event {
+            WalletEvent::Connected => match &wallet.info {
+                Some(info) => JsMessage::WalletConnected {
+                    address: info.address.clone(),
+                    wallet: wallet.adapter.map(|adapter| adapter.name().to_string()),
+                },
+                None => continue,
+            },
+            WalletEvent::Disconnected => JsMessage::WalletDisconnected,
+            WalletEvent::AccountChanged(pubkey) => JsMessage::WalletAccountChanged {
+                address: pubkey.to_string(),
+            },
+            WalletEvent::ConnectionTimedOut(adapter) => failed(adapter, "timedOut"),
+            WalletEvent::ConnectionFailed(adapter) => failed(adapter, "failed"),
+            WalletEvent::PopupLikelyBlocked(adapter) => failed(adapter, "popupBlocked"),
+            _ => continue,
+        };
+        post_to_js(&message);
+    }
+}
+
+fn mirror_errors_system(mut ev_reader: EventReader<SolanaError>) {
+    for error in ev_reader.read() {
+        post_to_js(&JsMessage::Error {
+            context: error.context.clone(),
+            category: format!("{:?}", error.category),
+            summary: error.explanation.summary.to_string(),
+            hint: error.explanation.hint.to_string(),
+            message: error.message.clone(),
+        });
+    }
+}
+
+fn mirror_transaction_status_system(
+    mut sent: Local<HashMap<Signature, Option<ConfirmationStatus>>>,
+    cache: Res<SignatureStatusCache>,
+    tracked: Query<&TrackedTransaction>,
+    // a failed transaction's tracker stops polling, the final status is still posted
+    trackers: Query<&TransactionTracker>,
+) {
+    if !cache.is_changed() {
+        return;
+    }
+
+    let signatures = || {
+        tracked
+            .iter()
+            .map(|tracked| tracked.signature)
+            .chain(trackers.iter().map(|tracker| tracker.signature))
+    };
+    for signature in signatures() {
+        let Some(status) = cache.get(&signature) else {
+            continue;
+        };
+        if sent.get(&signature) == Some(&status.confirmation_status) {
+            continue;
+        }
+
+        sent.insert(signature, status.confirmation_status);
+        post_to_js(&JsMessage::TransactionStatus {
+            signature: signature.to_string(),
+            confirmation_status: status.confirmation_status,
+            err: status.err.clone(),
+        });
+    }
+
+    sent.retain(|signature, _| signatures().any(|tracked| tracked == *signature));
+}
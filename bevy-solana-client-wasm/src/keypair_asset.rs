@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use bevy_solana_client_common::signer::GameSigner;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{read_keypair, Keypair, Signature},
+    signer::Signer,
+    transaction::{Transaction, VersionedTransaction},
+};
+
+/// A keypair loaded from a Solana CLI keypair file, e.g. `assets/keys/game.keypair.json`.
+///
+/// With bevy's `file_watcher` feature edits of the file are picked up while the game runs,
+/// handles see the new keypair on the next [`AssetEvent::Modified`].
+#[derive(Clone, Asset, TypePath)]
+pub struct KeypairAsset {
+    keypair: Arc<Keypair>,
+}
+
+impl KeypairAsset {
+    pub fn keypair(&self) -> &Keypair {
+        &self.keypair
+    }
+}
+
+impl std::fmt::Debug for KeypairAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeypairAsset")
+            .field("pubkey", &self.keypair.pubkey())
+            .finish()
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl GameSigner for KeypairAsset {
+    fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    async fn sign_transaction(&self, tx: &mut Transaction) -> Result<()> {
+        self.keypair.as_ref().sign_transaction(tx).await
+    }
+
+    async fn sign_versioned_transaction(&self, tx: &mut VersionedTransaction) -> Result<()> {
+        self.keypair.as_ref().sign_versioned_transaction(tx).await
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(self.keypair.sign_message(message))
+    }
+}
+
+/// Loads `*.keypair.json` files as [`KeypairAsset`]s.
+#[derive(Default)]
+pub struct KeypairAssetLoader;
+
+impl AssetLoader for KeypairAssetLoader {
+    type Asset = KeypairAsset;
+    type Settings = ();
+    type Error = anyhow::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<KeypairAsset> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        let keypair = read_keypair(&mut bytes.as_slice())
+            .map_err(|err| anyhow!("{}", err))
+            .with_context(|| format!("invalid keypair file {}", load_context.path().display()))?;
+
+        Ok(KeypairAsset {
+            keypair: Arc::new(keypair),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["keypair.json"]
+    }
+}
+
+/// Registers [`KeypairAsset`] and its loader, needs bevy's `AssetPlugin`.
+pub struct KeypairAssetPlugin;
+
+impl Plugin for KeypairAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<KeypairAsset>();
+        app.init_asset_loader::<KeypairAssetLoader>();
+    }
+}
@@ -0,0 +1,475 @@
+use std::path::{Path, PathBuf};
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, TaskPool},
+};
+use solana_sdk::{
+    bs58,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use crate::{
+    bridge::AsyncBridge,
+    error::report_error,
+    file_wallet::{self, FileWallet},
+    schedule::SolanaClientSet,
+    Wallet, WalletEvent,
+};
+
+const KEYSTORE_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+
+/// Shortest password a new keystore accepts.
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+/// Upper bounds of the argon2 parameters read from a keystore file, so a tampered file
+/// cannot make unlocking take gigabytes of memory or minutes of time.
+const MAX_M_COST: u32 = 256 * 1024;
+const MAX_T_COST: u32 = 16;
+const MAX_P_COST: u32 = 8;
+
+/// A keypair encrypted with a password, as stored on disk.
+///
+/// The key is derived with argon2id and the keypair sealed with AES-256-GCM, so a copied
+/// keystore file is useless without the password.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    /// Address of the encrypted keypair, readable without the password.
+    pub pubkey: String,
+    pub kdf: KeystoreKdf,
+    /// Base58 encoded AES-GCM nonce.
+    pub nonce: String,
+    /// Base58 encoded encrypted keypair bytes and tag.
+    pub ciphertext: String,
+}
+
+/// Argon2id parameters of a [`Keystore`].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct KeystoreKdf {
+    /// Base58 encoded salt.
+    pub salt: String,
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Keystore {
+    pub fn encrypt(keypair: &Keypair, password: &str) -> Result<Self> {
+        let mut salt = [0; SALT_LEN];
+        getrandom(&mut salt)?;
+
+        let params = Params::default();
+        let kdf = KeystoreKdf {
+            salt: bs58::encode(salt).into_string(),
+            m_cost: params.m_cost(),
+            t_cost: params.t_cost(),
+            p_cost: params.p_cost(),
+        };
+
+        let cipher = kdf.cipher(password)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, keypair.to_bytes().as_slice())
+            .map_err(|_| anyhow!("could not encrypt keypair"))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            pubkey: keypair.pubkey().to_string(),
+            kdf,
+            nonce: bs58::encode(nonce).into_string(),
+            ciphertext: bs58::encode(ciphertext).into_string(),
+        })
+    }
+
+    /// Fails with [`WrongPassword`] if `password` does not open the keystore.
+    pub fn decrypt(&self, password: &str) -> Result<Keypair> {
+        if self.version != KEYSTORE_VERSION {
+            bail!("unsupported keystore version {}", self.version);
+        }
+
+        let nonce = bs58::decode(&self.nonce).into_vec()?;
+        if nonce.len() != 12 {
+            bail!("invalid keystore nonce");
+        }
+        let ciphertext = bs58::decode(&self.ciphertext).into_vec()?;
+
+        let bytes = self
+            .kdf
+            .cipher(password)?
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| WrongPassword)?;
+        let keypair = Keypair::from_bytes(&bytes).context("invalid keystore keypair")?;
+
+        if keypair.pubkey().to_string() != self.pubkey {
+            bail!("keystore keypair does not match its pubkey {}", self.pubkey);
+        }
+        Ok(keypair)
+    }
+
+    pub fn pubkey(&self) -> Result<Pubkey> {
+        self.pubkey.parse().context("invalid keystore pubkey")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("could not read keystore {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("invalid keystore {}", path.display()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("could not write keystore {}", path.display()))
+    }
+}
+
+impl KeystoreKdf {
+    fn cipher(&self, password: &str) -> Result<Aes256Gcm> {
+        let salt = bs58::decode(&self.salt).into_vec()?;
+        let params = Params::new(
+            self.m_cost.clamp(Params::MIN_M_COST, MAX_M_COST),
+            self.t_cost.clamp(Params::MIN_T_COST, MAX_T_COST),
+            self.p_cost.clamp(Params::MIN_P_COST, MAX_P_COST),
+            Some(32),
+        )
+        .map_err(|err| anyhow!("invalid keystore kdf params: {}", err))?;
+
+        let mut key = [0; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &salt, &mut key)
+            .map_err(|err| anyhow!("could not derive keystore key: {}", err))?;
+
+        Ok(Aes256Gcm::new(&key.into()))
+    }
+}
+
+fn getrandom(buf: &mut [u8]) -> Result<()> {
+    use aes_gcm::aead::rand_core::RngCore;
+    OsRng
+        .try_fill_bytes(buf)
+        .map_err(|err| anyhow!("could not generate salt: {}", err))
+}
+
+/// The password did not open the keystore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrongPassword;
+
+impl std::fmt::Display for WrongPassword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "wrong keystore password")
+    }
+}
+
+impl std::error::Error for WrongPassword {}
+
+/// Where the per-game keystore lives and whether it exists yet.
+#[derive(Debug, Clone, Resource)]
+pub struct KeystoreStatus {
+    pub path: PathBuf,
+    /// Address of the stored wallet, `None` until one was created.
+    pub pubkey: Option<Pubkey>,
+    /// The key is being derived, further unlock requests are ignored until it is done.
+    pub unlocking: bool,
+}
+
+/// Unlocks the keystore with `password`, creating a fresh wallet if none exists yet.
+///
+/// Creating one needs the password twice in `confirmation` and at least
+/// [`MIN_PASSWORD_LEN`] characters, a typo would lock the wallet for good.
+#[derive(Debug, Clone, Event)]
+pub struct UnlockKeystore {
+    pub password: String,
+    pub confirmation: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub enum KeystoreEvent {
+    Created(Pubkey),
+    Unlocked(Pubkey),
+    WrongPassword,
+    /// The password of a new keystore and its confirmation differ.
+    PasswordMismatch,
+    /// The password of a new keystore is shorter than [`MIN_PASSWORD_LEN`].
+    PasswordTooShort,
+}
+
+/// Embedded, password protected wallet of desktop games.
+///
+/// Pressing the connect button opens a password prompt, games with their own UI can
+/// send [`UnlockKeystore`] instead and set `prompt` to `false`.
+pub struct KeystoreWalletPlugin {
+    pub path: PathBuf,
+    pub prompt: bool,
+}
+
+impl KeystoreWalletPlugin {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            prompt: true,
+        }
+    }
+}
+
+impl Plugin for KeystoreWalletPlugin {
+    fn build(&self, app: &mut App) {
+        let pubkey = Keystore::load(&self.path)
+            .ok()
+            .and_then(|keystore| keystore.pubkey().ok());
+        app.insert_resource(KeystoreStatus {
+            path: self.path.clone(),
+            pubkey,
+            unlocking: false,
+        });
+        app.add_event::<UnlockKeystore>();
+        app.add_event::<KeystoreEvent>();
+        app.add_systems(
+            Update,
+            unlock_keystore_system.in_set(SolanaClientSet::Network),
+        );
+
+        if self.prompt {
+            app.add_systems(
+                Update,
+                (open_password_prompt_system, password_prompt_system).in_set(SolanaClientSet::Ui),
+            );
+        }
+    }
+}
+
+fn unlock_keystore_system(world: &mut World) {
+    let requests: Vec<_> = world
+        .resource_mut::<Events<UnlockKeystore>>()
+        .drain()
+        .collect();
+
+    for UnlockKeystore {
+        password,
+        confirmation,
+    } in requests
+    {
+        let status = world.resource::<KeystoreStatus>();
+        if status.unlocking {
+            continue;
+        }
+        let path = status.path.clone();
+
+        let create = !path.exists();
+        if create && password.chars().count() < MIN_PASSWORD_LEN {
+            world.send_event(KeystoreEvent::PasswordTooShort);
+            continue;
+        }
+        if create && confirmation.as_deref() != Some(password.as_str()) {
+            world.send_event(KeystoreEvent::PasswordMismatch);
+            continue;
+        }
+
+        world.resource_mut::<KeystoreStatus>().unlocking = true;
+        let bridge = world.resource::<AsyncBridge>().clone();
+        // deriving the key takes long enough to drop frames
+        AsyncComputeTaskPool::get_or_init(TaskPool::default)
+            .spawn(async move {
+                let result = if create {
+                    let keypair = Keypair::new();
+                    Keystore::encrypt(&keypair, &password)
+                        .and_then(|keystore| keystore.save(&path))
+                        .map(|_| (keypair, true))
+                } else {
+                    Keystore::load(&path)
+                        .and_then(|keystore| keystore.decrypt(&password))
+                        .map(|keypair| (keypair, false))
+                };
+                bridge.send(move |world| keystore_opened(world, path, result));
+            })
+            .detach();
+    }
+}
+
+fn keystore_opened(world: &mut World, path: PathBuf, result: Result<(Keypair, bool)>) {
+    world.resource_mut::<KeystoreStatus>().unlocking = false;
+
+    match result {
+        Ok((keypair, created)) => {
+            let pubkey = keypair.pubkey();
+            info!("opened keystore wallet {}", pubkey);
+
+            let wallet = FileWallet::from_keypair(&path, keypair);
+            file_wallet::connect(&mut world.resource_mut::<Wallet>(), &wallet);
+            world.insert_resource(wallet);
+            world.resource_mut::<KeystoreStatus>().pubkey = Some(pubkey);
+            world.send_event(if created {
+                KeystoreEvent::Created(pubkey)
+            } else {
+                KeystoreEvent::Unlocked(pubkey)
+            });
+            world.send_event(WalletEvent::Connected);
+        }
+        Err(err) if err.downcast_ref::<WrongPassword>().is_some() => {
+            world.send_event(KeystoreEvent::WrongPassword);
+        }
+        Err(err) => report_error(world, "open keystore", &err),
+    }
+}
+
+#[derive(Debug, Default, Component)]
+struct PasswordPrompt {
+    password: String,
+    /// Whether the password is chosen for a new keystore and has to be repeated.
+    create: bool,
+    /// The first entry of a new password, while waiting for its confirmation.
+    first: Option<String>,
+}
+
+#[derive(Debug, Component)]
+struct PasswordPromptTitle;
+
+#[derive(Debug, Component)]
+struct PasswordPromptText;
+
+fn open_password_prompt_system(
+    mut commands: Commands,
+    mut ev_reader: EventReader<WalletEvent>,
+    mut keystore_events: EventReader<KeystoreEvent>,
+    status: Res<KeystoreStatus>,
+    wallet: Option<Res<FileWallet>>,
+    prompts: Query<Entity, With<PasswordPrompt>>,
+) {
+    let clicked = ev_reader
+        .read()
+        .any(|event| matches!(event, WalletEvent::ConnectBtnClick));
+    let retry = keystore_events
+        .read()
+        .filter_map(|event| match event {
+            KeystoreEvent::WrongPassword => Some("Wrong password, try again".to_string()),
+            KeystoreEvent::PasswordMismatch => {
+                Some("The passwords did not match, choose again".to_string())
+            }
+            KeystoreEvent::PasswordTooShort => Some(format!(
+                "Choose a password of at least {} characters",
+                MIN_PASSWORD_LEN
+            )),
+            _ => None,
+        })
+        .last();
+    if !(clicked || retry.is_some()) || wallet.is_some() || !prompts.is_empty() {
+        return;
+    }
+
+    let create = !status.path.exists();
+    let title = match (retry, status.pubkey) {
+        (Some(retry), _) => retry,
+        (None, Some(pubkey)) if !create => format!("Password for {}", pubkey),
+        (None, _) => "Choose a password for your new wallet".to_string(),
+    };
+    let text_style = TextStyle {
+        font_size: 30.0,
+        color: Color::linear_rgb(0.9, 0.9, 0.9),
+        ..Default::default()
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            background_color: Color::linear_rgba(0.0, 0.0, 0.0, 0.8).into(),
+            z_index: ZIndex::Global(100),
+            ..default()
+        })
+        .insert(PasswordPrompt {
+            create,
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(title, text_style.clone()))
+                .insert(PasswordPromptTitle);
+            parent
+                .spawn(TextBundle::from_section("_", text_style))
+                .insert(PasswordPromptText);
+        });
+}
+
+fn password_prompt_system(
+    mut commands: Commands,
+    mut keys: EventReader<KeyboardInput>,
+    mut prompts: Query<(Entity, &mut PasswordPrompt)>,
+    mut texts: Query<&mut Text, (With<PasswordPromptText>, Without<PasswordPromptTitle>)>,
+    mut titles: Query<&mut Text, With<PasswordPromptTitle>>,
+    mut ev_writer: EventWriter<UnlockKeystore>,
+) {
+    let Ok((entity, mut prompt)) = prompts.get_single_mut() else {
+        keys.clear();
+        return;
+    };
+
+    for key in keys.read() {
+        if key.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &key.logical_key {
+            Key::Character(c) => prompt.password.push_str(c),
+            Key::Space => prompt.password.push(' '),
+            Key::Backspace => {
+                prompt.password.pop();
+            }
+            Key::Enter => {
+                let entry = std::mem::take(&mut prompt.password);
+                if prompt.create && prompt.first.is_none() {
+                    prompt.first = Some(entry);
+                    if let Ok(mut title) = titles.get_single_mut() {
+                        title.sections[0].value = "Repeat the password".to_string();
+                    }
+                    continue;
+                }
+
+                let (password, confirmation) = match prompt.first.take() {
+                    Some(first) => (first, Some(entry)),
+                    None => (entry, None),
+                };
+                ev_writer.send(UnlockKeystore {
+                    password,
+                    confirmation,
+                });
+                commands.entity(entity).despawn_recursive();
+                return;
+            }
+            Key::Escape => {
+                commands.entity(entity).despawn_recursive();
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    if prompt.is_changed() {
+        if let Ok(mut text) = texts.get_single_mut() {
+            text.sections[0].value = format!("{}_", "*".repeat(prompt.password.chars().count()));
+        }
+    }
+}
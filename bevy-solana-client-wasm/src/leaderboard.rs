@@ -0,0 +1,355 @@
+use std::{cmp::Ordering, collections::HashMap, time::Duration};
+
+use bevy::prelude::*;
+use bevy_solana_client_common::scan::ProgramScan;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{
+    account::{SolanaAccountData, SolanaAccountPubkey},
+    scan::{ProgramScanEvent, ScanProgramAccounts},
+    schedule::SolanaClientSet,
+    subscription::TrackAccount,
+};
+
+/// Reads the score out of an account, `None` excludes the account from the leaderboard.
+pub type ScoreExtractor = fn(&Account) -> Option<u64>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    /// Highest score first.
+    #[default]
+    Descending,
+    /// Lowest score first, e.g. speedrun times.
+    Ascending,
+}
+
+impl SortOrder {
+    fn compare(self, a: &LeaderboardEntry, b: &LeaderboardEntry) -> Ordering {
+        let by_score = match self {
+            SortOrder::Descending => b.score.cmp(&a.score),
+            SortOrder::Ascending => a.score.cmp(&b.score),
+        };
+        // ties are broken by address so every client agrees on the ranks
+        by_score.then_with(|| a.pubkey.cmp(&b.pubkey))
+    }
+}
+
+/// Which accounts of a program make up a leaderboard and how they are ranked.
+#[derive(Debug, Clone)]
+pub struct LeaderboardConfig {
+    pub program_id: Pubkey,
+    /// The scan used to find every candidate, e.g. with a discriminator filter.
+    pub scan: ProgramScan,
+    pub score: ScoreExtractor,
+    pub order: SortOrder,
+    /// How many entries are kept and subscribed to.
+    pub top: usize,
+    /// How often the whole program is rescanned to pick up accounts outside the top.
+    pub refresh: Duration,
+}
+
+impl LeaderboardConfig {
+    pub fn new(program_id: Pubkey, scan: ProgramScan, score: ScoreExtractor) -> Self {
+        Self {
+            program_id,
+            scan,
+            score,
+            order: SortOrder::default(),
+            top: 10,
+            refresh: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_order(mut self, order: SortOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn with_top(mut self, top: usize) -> Self {
+        self.top = top;
+        self
+    }
+
+    pub fn with_refresh(mut self, refresh: Duration) -> Self {
+        self.refresh = refresh;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeaderboardEntry {
+    pub pubkey: Pubkey,
+    pub score: u64,
+    /// The entity mirroring and tracking the account while it is on the leaderboard.
+    pub entity: Entity,
+}
+
+/// The top accounts of a program, sorted by [`LeaderboardConfig::order`].
+///
+/// Filled by periodic program scans. In between, each entry's account is tracked and
+/// re-fetched every [`AccountSubscriptionPoll::interval`](crate::subscription::AccountSubscriptionPoll::interval),
+/// a changed score reorders the entries right away.
+#[derive(Debug, Resource)]
+pub struct Leaderboard {
+    pub config: LeaderboardConfig,
+    entries: Vec<LeaderboardEntry>,
+    refresh: Timer,
+    scanning: bool,
+}
+
+impl Leaderboard {
+    pub fn new(config: LeaderboardConfig) -> Self {
+        let mut refresh = Timer::new(config.refresh, TimerMode::Repeating);
+        // scan right away instead of after the first interval
+        refresh.set_elapsed(config.refresh);
+
+        Self {
+            config,
+            entries: Vec::new(),
+            refresh,
+            scanning: false,
+        }
+    }
+
+    /// Entries in rank order.
+    pub fn entries(&self) -> &[LeaderboardEntry] {
+        &self.entries
+    }
+
+    /// The 1-based rank of `pubkey`, `None` if it is not in the top.
+    pub fn rank_of(&self, pubkey: &Pubkey) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.pubkey == *pubkey)
+            .map(|i| i + 1)
+    }
+
+    /// The entry at 1-based `rank`.
+    pub fn get(&self, rank: usize) -> Option<&LeaderboardEntry> {
+        self.entries.get(rank.checked_sub(1)?)
+    }
+
+    /// Forgets the scan in flight and scans again on the next frame.
+    pub(crate) fn reset(&mut self) {
+        self.scanning = false;
+        let duration = self.refresh.duration();
+        self.refresh.set_elapsed(duration);
+    }
+
+    /// Starts a rescan on the next frame.
+    pub fn refresh(&mut self) {
+        let duration = self.refresh.duration();
+        self.refresh.set_elapsed(duration);
+    }
+
+    fn ranks(&self) -> HashMap<Pubkey, usize> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.pubkey, i + 1))
+            .collect()
+    }
+
+    fn sort(&mut self) {
+        let order = self.config.order;
+        self.entries.sort_by(|a, b| order.compare(a, b));
+    }
+}
+
+/// An account entered, left or moved within the [`Leaderboard`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct LeaderboardRankChanged {
+    pub pubkey: Pubkey,
+    /// 1-based rank before the change, `None` if the account was not in the top.
+    pub old_rank: Option<usize>,
+    /// 1-based rank after the change, `None` if the account dropped out of the top.
+    pub new_rank: Option<usize>,
+}
+
+/// Marks the entities spawned for [`Leaderboard`] entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct LeaderboardMember;
+
+/// Maintains a [`Leaderboard`], relies on the default [`ProgramScanPlugin`](crate::scan::ProgramScanPlugin)
+/// and [`AccountSubscriptionPlugin`](crate::subscription::AccountSubscriptionPlugin).
+pub struct LeaderboardPlugin(pub LeaderboardConfig);
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Leaderboard::new(self.0.clone()));
+        app.add_event::<LeaderboardRankChanged>();
+        app.add_systems(
+            Update,
+            (
+                leaderboard_refresh_system,
+                leaderboard_scan_system,
+                leaderboard_update_system,
+            )
+                .chain()
+                .in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn leaderboard_refresh_system(
+    mut leaderboard: ResMut<Leaderboard>,
+    time: Res<Time<Real>>,
+    mut ev_writer: EventWriter<ScanProgramAccounts>,
+) {
+    leaderboard.refresh.tick(time.delta());
+    if !leaderboard.refresh.finished() || leaderboard.scanning {
+        return;
+    }
+
+    leaderboard.scanning = true;
+    ev_writer.send(ScanProgramAccounts {
+        program_id: leaderboard.config.program_id,
+        scan: leaderboard.config.scan.clone(),
+    });
+}
+
+fn leaderboard_scan_system(
+    mut commands: Commands,
+    mut leaderboard: ResMut<Leaderboard>,
+    mut ev_reader: EventReader<ProgramScanEvent>,
+    mut ev_writer: EventWriter<LeaderboardRankChanged>,
+) {
+    for event in ev_reader.read() {
+        let accounts = match event {
+            ProgramScanEvent::Completed {
+                program_id,
+                accounts,
+            } if *program_id == leaderboard.config.program_id => accounts,
+            ProgramScanEvent::Failed { program_id }
+                if *program_id == leaderboard.config.program_id =>
+            {
+                leaderboard.scanning = false;
+                continue;
+            }
+            _ => continue,
+        };
+        leaderboard.scanning = false;
+
+        let old_ranks = leaderboard.ranks();
+        let mut entities: HashMap<Pubkey, Entity> = leaderboard
+            .entries
+            .drain(..)
+            .map(|entry| (entry.pubkey, entry.entity))
+            .collect();
+
+        let score = leaderboard.config.score;
+        let mut candidates: Vec<(LeaderboardEntry, &Account)> = accounts
+            .iter()
+            .filter_map(|(account, pubkey)| {
+                let entry = LeaderboardEntry {
+                    pubkey: *pubkey,
+                    score: score(account)?,
+                    entity: Entity::PLACEHOLDER,
+                };
+                Some((entry, account))
+            })
+            .collect();
+        let order = leaderboard.config.order;
+        candidates.sort_by(|(a, _), (b, _)| order.compare(a, b));
+        candidates.truncate(leaderboard.config.top);
+
+        for (mut entry, account) in candidates {
+            let data = SolanaAccountData(account.clone());
+            entry.entity = match entities.remove(&entry.pubkey) {
+                Some(entity) => {
+                    commands.entity(entity).insert(data);
+                    entity
+                }
+                None => commands
+                    .spawn((
+                        LeaderboardMember,
+                        SolanaAccountPubkey(entry.pubkey),
+                        TrackAccount(entry.pubkey),
+                        data,
+                    ))
+                    .id(),
+            };
+            leaderboard.entries.push(entry);
+        }
+
+        // entries that dropped out of the top stop being tracked
+        for entity in entities.into_values() {
+            commands.entity(entity).despawn();
+        }
+
+        send_rank_changes(&old_ranks, &leaderboard, &mut ev_writer);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn leaderboard_update_system(
+    mut leaderboard: ResMut<Leaderboard>,
+    changed: Query<
+        (&SolanaAccountPubkey, &SolanaAccountData),
+        (With<LeaderboardMember>, Changed<SolanaAccountData>),
+    >,
+    mut ev_writer: EventWriter<LeaderboardRankChanged>,
+) {
+    if changed.is_empty() {
+        return;
+    }
+
+    let old_ranks = leaderboard.ranks();
+    let score = leaderboard.config.score;
+    // an account that no longer qualifies sinks to the bottom until the next scan removes it
+    let disqualified = match leaderboard.config.order {
+        SortOrder::Descending => u64::MIN,
+        SortOrder::Ascending => u64::MAX,
+    };
+    let mut moved = false;
+
+    for (pubkey, data) in &changed {
+        let Some(entry) = leaderboard
+            .entries
+            .iter_mut()
+            .find(|entry| entry.pubkey == pubkey.0)
+        else {
+            continue;
+        };
+
+        let score = score(&data.0).unwrap_or(disqualified);
+        if score != entry.score {
+            entry.score = score;
+            moved = true;
+        }
+    }
+
+    if moved {
+        leaderboard.sort();
+        send_rank_changes(&old_ranks, &leaderboard, &mut ev_writer);
+    }
+}
+
+fn send_rank_changes(
+    old_ranks: &HashMap<Pubkey, usize>,
+    leaderboard: &Leaderboard,
+    ev_writer: &mut EventWriter<LeaderboardRankChanged>,
+) {
+    let new_ranks = leaderboard.ranks();
+
+    for (pubkey, new_rank) in &new_ranks {
+        let old_rank = old_ranks.get(pubkey).copied();
+        if old_rank != Some(*new_rank) {
+            ev_writer.send(LeaderboardRankChanged {
+                pubkey: *pubkey,
+                old_rank,
+                new_rank: Some(*new_rank),
+            });
+        }
+    }
+
+    for (pubkey, old_rank) in old_ranks {
+        if !new_ranks.contains_key(pubkey) {
+            ev_writer.send(LeaderboardRankChanged {
+                pubkey: *pubkey,
+                old_rank: Some(*old_rank),
+                new_rank: None,
+            });
+        }
+    }
+}
@@ -1,40 +1,209 @@
-use std::sync::{Arc, OnceLock, RwLock, RwLockWriteGuard};
-
-use anyhow::{anyhow, bail, Context, Result};
+pub mod account;
+pub mod airdrop;
+pub mod balance;
+pub mod blockhash;
+pub mod bridge;
+#[cfg(target_arch = "wasm32")]
+pub mod burner;
+pub mod client;
+pub mod cluster;
+pub mod commands;
+pub mod conditions;
+pub mod cooldown;
+pub mod decoder;
+#[cfg(target_arch = "wasm32")]
+pub mod deep_link;
+pub mod diagnostics;
+pub mod error;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod file_wallet;
+pub mod idl;
+pub mod inspector;
+#[cfg(target_arch = "wasm32")]
+pub mod js_bridge;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod keypair_asset;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod keystore;
+pub mod leaderboard;
+pub mod nft;
+pub mod nft_image;
+pub mod optimistic;
+pub mod poll;
+pub mod recorder;
+pub mod rpc;
+pub mod scan;
+pub mod schedule;
+#[cfg(target_arch = "wasm32")]
+mod secretbox;
+pub mod session_key;
+pub mod session_summary;
+pub mod signature_status;
+pub mod subscription;
+pub mod sync;
+pub mod token_balance;
+pub mod tracker;
+pub mod treasury;
+pub mod tween;
+pub mod wallet_adapter;
+pub mod wallet_modal;
+#[cfg(target_arch = "wasm32")]
+pub mod wallet_session;
+pub mod wallet_state;
+pub mod watch_only;
+pub mod watcher;
+
+use std::{
+    str::FromStr,
+    sync::{Arc, OnceLock, RwLock, RwLockWriteGuard},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
 use bevy::prelude::*;
-use bevy_solana_client_common::rpc_client::{RpcClient, RpcRequest, RpcResponse};
+use bevy_solana_client_common::{
+    cluster::Cluster,
+    config::SolanaClientConfig,
+    endpoint::RpcEndpoints,
+    error::TransportError,
+    rpc_client::{RpcClient, RpcRequest},
+    signer::GameSigner,
+    state::RpcClientState,
+};
 use gloo_net::http::Request;
+use schedule::SolanaClientSet;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use wallet_adapter::{ConnectTimedOut, PopupLikelyBlocked, WalletAdapter, WalletSigner};
 use wasm_bindgen::JsValue;
 
 pub struct WasmRpcClient {
-    pub url: String,
+    pub endpoints: RpcEndpoints,
+    pub config: SolanaClientConfig,
+    state: RpcClientState,
+}
+
+impl WasmRpcClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_config(url, SolanaClientConfig::default())
+    }
+
+    pub fn with_config(url: impl Into<String>, config: SolanaClientConfig) -> Self {
+        Self::with_endpoints(RpcEndpoints::single(url), config)
+    }
+
+    /// Creates a client failing over between `endpoints` in order.
+    pub fn with_endpoints(endpoints: RpcEndpoints, config: SolanaClientConfig) -> Self {
+        Self {
+            endpoints,
+            state: RpcClientState::new(&config),
+            config,
+        }
+    }
 }
 
 #[async_trait::async_trait(?Send)]
 impl RpcClient for WasmRpcClient {
-    async fn rpc_post<De: serde::de::DeserializeOwned>(
-        &self,
-        method: &str,
-        params: serde_json::Value,
-    ) -> Result<De> {
-        let resp_str: String = Request::post(&self.url)
+    fn config(&self) -> &SolanaClientConfig {
+        &self.config
+    }
+
+    fn endpoints(&self) -> &RpcEndpoints {
+        &self.endpoints
+    }
+
+    fn state(&self) -> &RpcClientState {
+        &self.state
+    }
+
+    async fn post(&self, url: &str, request: &RpcRequest<serde_json::Value>) -> Result<String> {
+        // aborts the fetch if this future is dropped, e.g. on timeout
+        let abort = AbortOnDrop::new();
+
+        let mut req = Request::post(&self.config.request_url(url)?)
             .header("Content-Type", "application/json")
-            .json(&RpcRequest::new(method, params))?
+            .abort_signal(abort.signal().as_ref());
+        for (name, value) in &self.config.headers {
+            req = req.header(name, value);
+        }
+        let sticky = self.state.sticky_session.as_ref();
+        if let Some((name, value)) = sticky.and_then(|s| s.request_header(url)) {
+            req = req.header(name, &value);
+        }
+
+        let resp = req
+            .json(request)?
             .send()
-            .await?
+            .await
+            .map_err(|e| TransportError::Network(e.to_string()))?;
+
+        if let Some(sticky) = sticky {
+            let token = resp.headers().get(&sticky.config().response_header);
+            sticky.capture(url, token.as_deref());
+        }
+
+        let body = resp
             .text()
-            .await?;
+            .await
+            .map_err(|e| TransportError::Network(e.to_string()))?;
+
+        if !resp.ok() {
+            return Err(TransportError::Http {
+                status: resp.status(),
+                body,
+            }
+            .into());
+        }
+
+        Ok(body)
+    }
 
-        log::debug!("resp_str: {:?}", resp_str);
-        let resp: RpcResponse<De> = serde_json::from_str(&resp_str)?;
+    async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        let abort = AbortOnDrop::new();
 
-        if let Some(e) = resp.error {
-            bail!("rpc error: {:?}", e);
+        let resp = Request::get(url)
+            .abort_signal(abort.signal().as_ref())
+            .send()
+            .await
+            .map_err(|e| TransportError::Network(e.to_string()))?;
+
+        let body = resp
+            .binary()
+            .await
+            .map_err(|e| TransportError::Network(e.to_string()))?;
+
+        if !resp.ok() {
+            return Err(TransportError::Http {
+                status: resp.status(),
+                body: String::from_utf8_lossy(&body).into_owned(),
+            }
+            .into());
         }
 
-        let result = resp.result.context("no result")?;
+        Ok(body)
+    }
 
-        Ok(result)
+    async fn sleep(&self, duration: Duration) {
+        gloo_timers::future::sleep(duration).await
+    }
+}
+
+struct AbortOnDrop(Option<web_sys::AbortController>);
+
+impl AbortOnDrop {
+    fn new() -> Self {
+        Self(web_sys::AbortController::new().ok())
+    }
+
+    fn signal(&self) -> Option<web_sys::AbortSignal> {
+        self.0.as_ref().map(|controller| controller.signal())
+    }
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        if let Some(controller) = &self.0 {
+            controller.abort();
+        }
     }
 }
 
@@ -42,21 +211,92 @@ pub struct BevySolanaClientWasm;
 
 impl Plugin for BevySolanaClientWasm {
     fn build(&self, app: &mut App) {
+        app.init_resource::<rpc::SolanaRpc>();
+        app.add_plugins((
+            account::AccountAlertPlugin,
+            bridge::AsyncBridgePlugin,
+            commands::SolanaCommandsPlugin,
+            cooldown::ChainCooldownPlugin,
+            error::SolanaErrorPlugin,
+            optimistic::OptimisticPlugin,
+            scan::ProgramScanPlugin,
+            signature_status::SignatureStatusPlugin,
+            subscription::AccountSubscriptionPlugin,
+            sync::ProgramSyncPlugin,
+            tween::AccountValueTweenPlugin,
+            wallet_modal::WalletModalPlugin,
+            wallet_state::WalletStatePlugin,
+            watch_only::WatchOnlyPlugin,
+            watcher::AccountWatcherPlugin,
+        ));
+        app.add_plugins((
+            account::PubkeyIndexPlugin,
+            balance::WalletBalancePlugin,
+            blockhash::BlockhashCachePlugin,
+            cluster::ClusterSwitchPlugin,
+            decoder::AccountDecoderPlugin,
+            schedule::SolanaClientSetPlugin,
+            tracker::TransactionTrackerPlugin,
+        ));
         app.add_event::<WalletEvent>();
-        app.insert_resource(Wallet { info: None });
-        app.add_systems(Startup, setup_wallet_menu);
+        app.init_resource::<WalletConnectSettings>();
+        app.init_resource::<RpcCluster>();
+        app.register_type::<Wallet>();
+        app.register_type::<WalletConnectSettings>();
+        app.init_resource::<Wallet>();
+        app.add_systems(Startup, (setup_wallet_menu, rpc_cluster_startup_system));
         app.add_systems(
             Update,
             (
-                wallet_menu_interaction_system,
-                wallet_event_system,
-                wallet_menu_system,
-                async_wallet_event_system,
+                (wallet_menu_interaction_system, wallet_menu_system).in_set(SolanaClientSet::Ui),
+                async_wallet_event_system.in_set(SolanaClientSet::Bridge),
+                (
+                    wallet_event_system,
+                    wallet_network_check_system,
+                    wallet_cluster_check_system,
+                )
+                    .in_set(SolanaClientSet::Network),
+                (wallet_button_state_system, wallet_button_render_system)
+                    .chain()
+                    .in_set(SolanaClientSet::Ui),
             ),
         );
     }
 }
 
+/// Applies an `accountChanged` event of the connected wallet's provider.
+fn wallet_account_changed(world: &mut World, pubkey: Pubkey) {
+    let mut wallet = world.resource_mut::<Wallet>();
+    let Some(info) = wallet.info.as_mut() else {
+        return;
+    };
+
+    debug!("wallet account changed to {}", pubkey);
+    *info = WalletInfo {
+        amount: 0,
+        address: pubkey.to_string(),
+    };
+    if let Some(adapter) = wallet.adapter {
+        wallet.signer = Some(Arc::new(WalletSigner { adapter, pubkey }));
+    }
+    world.send_event(WalletEvent::AccountChanged(pubkey));
+}
+
+/// Forgets the connected wallet once it has disconnected, by request or on its own.
+fn wallet_disconnected(world: &mut World) {
+    let mut wallet = world.resource_mut::<Wallet>();
+    if wallet.info.is_none() {
+        return;
+    }
+
+    debug!("wallet disconnected");
+    wallet.info = None;
+    wallet.adapter = None;
+    wallet.signer = None;
+    wallet_adapter::stop_listening_to_provider();
+    world.send_event(WalletEvent::Disconnected);
+}
+
 fn reflect_get(target: &JsValue, key: &JsValue) -> Result<JsValue> {
     let result = js_sys::Reflect::get(target, key).map_err(|e| anyhow!("{:?}", e))?;
     debug!("reflect_get: {:?}", result);
@@ -94,53 +334,261 @@ impl AsyncWalletEventQueue {
     }
 }
 
-#[derive(Debug, Resource)]
+/// A signer shared between the [`Wallet`] and the tasks signing with it.
+pub type SharedSigner = Arc<dyn GameSigner + Send + Sync>;
+
+#[derive(Default, Resource, Reflect)]
+#[reflect(Resource)]
 pub struct Wallet {
     pub info: Option<WalletInfo>,
+    /// The browser wallet the connection was made through.
+    pub adapter: Option<WalletAdapter>,
+    #[reflect(ignore)]
+    signer: Option<SharedSigner>,
 }
 
-#[derive(Debug)]
+impl std::fmt::Debug for Wallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Wallet")
+            .field("info", &self.info)
+            .field("adapter", &self.adapter)
+            .field(
+                "signer",
+                &self.signer.as_ref().map(|signer| signer.pubkey()),
+            )
+            .finish()
+    }
+}
+
+impl Wallet {
+    pub fn is_connected(&self) -> bool {
+        self.info.is_some()
+    }
+
+    /// Connects `signer` as the player's wallet, whatever it is backed by: a browser
+    /// wallet, a burner or keystore, a keypair file natively.
+    pub fn connect(&mut self, signer: SharedSigner) {
+        self.info = Some(WalletInfo {
+            amount: 0,
+            address: signer.pubkey().to_string(),
+        });
+        self.signer = Some(signer);
+    }
+
+    /// Signer of the connected wallet, `None` while disconnected.
+    pub fn signer(&self) -> Option<SharedSigner> {
+        self.signer.clone()
+    }
+
+    /// Like [`Wallet::signer`], but fails with [`WalletNotConnected`] for actions that
+    /// cannot go on without a signature.
+    pub fn require_signer(&self) -> Result<SharedSigner> {
+        self.signer().ok_or_else(|| WalletNotConnected.into())
+    }
+}
+
+/// A transaction or message had to be signed while no browser wallet was connected,
+/// e.g. in spectator mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalletNotConnected;
+
+impl std::fmt::Display for WalletNotConnected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no wallet is connected, connect one to sign")
+    }
+}
+
+impl std::error::Error for WalletNotConnected {}
+
+/// How wallet connections are made.
+#[derive(Debug, Clone, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct WalletConnectSettings {
+    /// Give up on a connect request the wallet never answers, `None` waits forever.
+    pub timeout: Option<Duration>,
+    /// The cluster the game is deployed on, the rpc is checked against it on every
+    /// connect. `None` skips the check.
+    #[reflect(ignore)]
+    pub cluster: Option<Cluster>,
+    /// Spectator mode, the built-in connect button and wallet modal are not shown.
+    ///
+    /// Reading the chain and syncing accounts work without a wallet either way, signing
+    /// fails with [`WalletNotConnected`].
+    pub read_only: bool,
+}
+
+impl Default for WalletConnectSettings {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(60)),
+            cluster: None,
+            read_only: false,
+        }
+    }
+}
+
+/// The cluster the rpc is on, by its genesis hash. `None` until it was fetched at startup.
+#[derive(Debug, Default, Clone, Copy, Resource)]
+pub struct RpcCluster(pub Option<Cluster>);
+
+#[derive(Debug, Reflect)]
 pub struct WalletInfo {
-    pub amount: u32,
+    /// Balance in lamports, kept up to date by the [`balance::WalletBalancePoll`].
+    pub amount: u64,
     pub address: String,
 }
 
+impl WalletInfo {
+    pub fn pubkey(&self) -> Result<Pubkey> {
+        Pubkey::from_str(&self.address)
+            .with_context(|| format!("invalid wallet address {:?}", self.address))
+    }
+
+    /// Checks that `signature` was produced by this wallet signing `message`, see
+    /// [`WalletAdapter::sign_message`].
+    pub fn verify_message(&self, message: &[u8], signature: &Signature) -> bool {
+        self.pubkey()
+            .is_ok_and(|pubkey| wallet_adapter::verify_message(&pubkey, message, signature))
+    }
+}
+
 #[derive(Debug, Event)]
 pub enum WalletEvent {
     ConnectBtnClick,
     DisconnectBtnClick,
     Connected,
+    /// The wallet has revoked the connection, after [`WalletEvent::DisconnectBtnClick`]
+    /// or from within the wallet.
     Disconnected,
+    /// A wallet was picked in the selection modal, connecting to it starts.
+    AdapterSelected(WalletAdapter),
+    /// The wallet did not answer within [`WalletConnectSettings::timeout`], send
+    /// [`WalletEvent::AdapterSelected`] again to retry.
+    ConnectionTimedOut(WalletAdapter),
+    /// The wallet rejected or failed the connect request.
+    ConnectionFailed(WalletAdapter),
+    /// The connect request failed as if the wallet popup was blocked, players should
+    /// allow popups or open the wallet from the browser toolbar.
+    PopupLikelyBlocked(WalletAdapter),
+    /// The player switched to another account in the wallet, [`Wallet::info`] already
+    /// points at it.
+    AccountChanged(Pubkey),
+    /// The connected wallet's balance changed, in lamports.
+    BalanceChanged(u64),
+    /// The connected wallet has neither history nor balance on the configured
+    /// cluster, it is most likely set to a different network.
+    NetworkMismatchSuspected,
+    /// The rpc the client talks to is not on [`WalletConnectSettings::cluster`],
+    /// transactions built for the game's programs would fail there.
+    ClusterMismatch {
+        expected: Cluster,
+        actual: Cluster,
+    },
 }
 
 pub enum AsyncWalletEvent {
-    ConnectionCompleted(Result<String>),
+    ConnectionCompleted(WalletAdapter, Result<String>),
 }
 
-#[derive(Debug, Component)]
-pub enum WalletButtonType {
-    Connect,
-    Disconnect,
+/// State of a connect / disconnect button, driven by [`WalletEvent`]s.
+///
+/// Every entity carrying this component is kept in sync, so custom wallet UIs can
+/// add it to their own entities and render it however they like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Component)]
+pub enum WalletButtonState {
+    /// Not connected, pressing the button starts connecting.
+    #[default]
+    Idle,
+    /// Waiting for the wallet to answer a connect request.
+    Connecting,
+    /// Connected, pressing the button disconnects.
+    Connected,
+    /// Ignores presses and events until set back to [`WalletButtonState::Idle`].
+    Disabled,
 }
 
+impl WalletButtonState {
+    /// The state after `event`.
+    pub fn next(self, event: &WalletEvent) -> Self {
+        match (self, event) {
+            (WalletButtonState::Disabled, _) => WalletButtonState::Disabled,
+            (_, WalletEvent::AdapterSelected(_)) => WalletButtonState::Connecting,
+            (_, WalletEvent::Connected) => WalletButtonState::Connected,
+            (
+                _,
+                WalletEvent::ConnectionTimedOut(_)
+                | WalletEvent::ConnectionFailed(_)
+                | WalletEvent::PopupLikelyBlocked(_)
+                | WalletEvent::Disconnected,
+            ) => WalletButtonState::Idle,
+            (state, _) => state,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WalletButtonState::Idle | WalletButtonState::Disabled => "Connect",
+            WalletButtonState::Connecting => "...",
+            WalletButtonState::Connected => "Disconnect",
+        }
+    }
+
+    /// The event a press of the button sends, if it accepts presses at all.
+    pub fn press_event(self) -> Option<WalletEvent> {
+        match self {
+            WalletButtonState::Idle => Some(WalletEvent::ConnectBtnClick),
+            WalletButtonState::Connected => Some(WalletEvent::DisconnectBtnClick),
+            WalletButtonState::Connecting | WalletButtonState::Disabled => None,
+        }
+    }
+}
+
+/// The built-in connect / disconnect button.
+#[derive(Debug, Component)]
+pub struct WalletButton;
+
 #[derive(Debug, Component)]
 pub struct WalletMenu;
 
 const NORMAL_BUTTON: Color = Color::linear_rgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::linear_rgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::linear_rgb(0.35, 0.75, 0.35);
+const DISABLED_BUTTON: Color = Color::linear_rgb(0.08, 0.08, 0.08);
 
-fn async_wallet_event_system(mut ev_writer: EventWriter<WalletEvent>, mut wallet: ResMut<Wallet>) {
+fn async_wallet_event_system(
+    mut ev_writer: EventWriter<WalletEvent>,
+    mut err_writer: EventWriter<error::SolanaError>,
+    mut wallet: ResMut<Wallet>,
+    catalog: Res<error::SolanaErrorCatalog>,
+    bridge: Res<bridge::AsyncBridge>,
+) {
     if let Ok(Some(event)) = AsyncWalletEventQueue::pop() {
         match event {
-            AsyncWalletEvent::ConnectionCompleted(result) => match result {
+            AsyncWalletEvent::ConnectionCompleted(adapter, result) => match result {
                 Ok(address) => {
                     debug!("WalletEvent::ConnectionCompleted: {:?}", address);
-                    wallet.info = Some(WalletInfo { amount: 0, address });
+                    let info = WalletInfo { amount: 0, address };
+                    wallet.signer = info
+                        .pubkey()
+                        .ok()
+                        .map(|pubkey| Arc::new(WalletSigner { adapter, pubkey }) as SharedSigner);
+                    wallet.info = Some(info);
+                    wallet.adapter = Some(adapter);
+                    if let Err(err) = wallet_adapter::listen_to_provider(adapter, bridge.clone()) {
+                        debug!("could not listen to {} events: {:?}", adapter.name(), err);
+                    }
                     ev_writer.send(WalletEvent::Connected);
                 }
                 Err(err) => {
                     debug!("WalletEvent::ConnectionCompleted: {:?}", err);
+                    if err.downcast_ref::<ConnectTimedOut>().is_some() {
+                        ev_writer.send(WalletEvent::ConnectionTimedOut(adapter));
+                    } else if err.downcast_ref::<PopupLikelyBlocked>().is_some() {
+                        ev_writer.send(WalletEvent::PopupLikelyBlocked(adapter));
+                    } else {
+                        ev_writer.send(WalletEvent::ConnectionFailed(adapter));
+                    }
+                    err_writer.send(error::SolanaError::new(&catalog, "connect wallet", &err));
                 }
             },
         }
@@ -149,48 +597,103 @@ fn async_wallet_event_system(mut ev_writer: EventWriter<WalletEvent>, mut wallet
 
 fn wallet_menu_system(
     mut ev_reader: EventReader<WalletEvent>,
-    mut wallet_menu_query: Query<&mut Text, (With<WalletMenu>, Without<ConnectDisconnectBtnText>)>,
+    mut wallet_menu_query: Query<&mut Text, With<WalletMenu>>,
     mut wallet: ResMut<Wallet>,
-    mut toggle_connect_btn: Query<&mut WalletButtonType, With<WalletButtonType>>,
-    mut toggle_connect_btn_text: Query<
-        &mut Text,
-        (With<ConnectDisconnectBtnText>, Without<WalletMenu>),
-    >,
+    bridge: Res<bridge::AsyncBridge>,
 ) {
     for event in ev_reader.read() {
         match event {
             WalletEvent::Connected => {
                 debug!("WalletEvent::Connected");
-                if let Some(info) = &wallet.info {
-                    wallet_menu_query.single_mut().sections[0].value = info.address.clone();
+                // there is no wallet menu in read-only mode
+                if let (Some(info), Ok(mut text)) =
+                    (&wallet.info, wallet_menu_query.get_single_mut())
+                {
+                    text.sections[0].value = info.address.clone();
                 }
-                toggle_connect_btn_text.single_mut().sections[0].value = "Disconnect".to_string();
-                *toggle_connect_btn.single_mut() = WalletButtonType::Disconnect;
+            }
+            WalletEvent::AccountChanged(_) | WalletEvent::Disconnected => {
+                let Ok(mut text) = wallet_menu_query.get_single_mut() else {
+                    continue;
+                };
+                let address = wallet.info.as_ref().map(|info| info.address.clone());
+                text.sections[0].value = address.unwrap_or_default();
             }
             WalletEvent::DisconnectBtnClick => {
                 debug!("WalletEvent::DisconnectBtnClick");
-                wallet.info = None;
-                wallet_menu_query.single_mut().sections[0].value = String::new();
-                toggle_connect_btn_text.single_mut().sections[0].value = "Connect".to_string();
-                *toggle_connect_btn.single_mut() = WalletButtonType::Connect;
+                wallet_adapter::stop_listening_to_provider();
+                let Some(adapter) = wallet.adapter.take() else {
+                    bridge.send(wallet_disconnected);
+                    continue;
+                };
+
+                // `Disconnected` follows once the wallet has revoked the connection,
+                // so a reconnect does not race the pending disconnect
+                let bridge = bridge.clone();
+                bridge.clone().spawn(move || async move {
+                    let result = adapter.disconnect().await;
+                    bridge.send(move |world| {
+                        if let Err(err) = result {
+                            error::report_error(world, "disconnect wallet", &err);
+                        }
+                        wallet_disconnected(world);
+                    });
+                });
             }
             _ => {}
         }
     }
 }
 
+/// The only place button states change, so they can not race each other.
+fn wallet_button_state_system(
+    mut ev_reader: EventReader<WalletEvent>,
+    mut buttons: Query<&mut WalletButtonState>,
+) {
+    for event in ev_reader.read() {
+        for mut state in &mut buttons {
+            let next = state.next(event);
+            state.set_if_neq(next);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn wallet_button_render_system(
+    mut buttons: Query<
+        (&WalletButtonState, &Children, &mut BackgroundColor),
+        (With<WalletButton>, Changed<WalletButtonState>),
+    >,
+    mut texts: Query<&mut Text, With<ConnectDisconnectBtnText>>,
+) {
+    for (state, children, mut color) in &mut buttons {
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = state.label().to_string();
+            }
+        }
+
+        *color = match state {
+            WalletButtonState::Disabled => DISABLED_BUTTON,
+            _ => NORMAL_BUTTON,
+        }
+        .into();
+    }
+}
+
 fn wallet_event_system(
-    mut _commands: Commands,
     mut ev_reader: EventReader<WalletEvent>,
-    mut _wallet: ResMut<Wallet>,
+    settings: Res<WalletConnectSettings>,
 ) {
     for event in ev_reader.read() {
-        if let WalletEvent::ConnectBtnClick = event {
-            debug!("WalletEvent::ConnectBtnClick");
+        if let WalletEvent::AdapterSelected(adapter) = *event {
+            debug!("WalletEvent::AdapterSelected: {}", adapter.name());
+            let timeout = settings.timeout;
 
             wasm_bindgen_futures::spawn_local(async move {
                 AsyncWalletEventQueue::push(AsyncWalletEvent::ConnectionCompleted(
-                    connect_to_phantom().await,
+                    adapter,
+                    adapter.connect_with_timeout(timeout).await,
                 ))
                 .unwrap();
             });
@@ -198,52 +701,108 @@ fn wallet_event_system(
     }
 }
 
-async fn connect_to_phantom() -> Result<String> {
-    debug!("connect_to_wallet");
-    let window = web_sys::window().context("could not get window")?;
-    if let Some(solana) = window.get("solana") {
-        let is_phantom = reflect_get(&solana, &wasm_bindgen::JsValue::from_str("isPhantom"))?;
-
-        if is_phantom == true {
-            let connect_str = wasm_bindgen::JsValue::from_str("connect");
-            let connect: js_sys::Function = reflect_get(&solana, &connect_str)?.into();
-
-            debug!("{:?}", connect.to_string());
-
-            let resp = connect.call0(&solana).map_err(|err| anyhow!("{err:?}"))?;
-            let promise = js_sys::Promise::resolve(&resp);
-
-            let result = wasm_bindgen_futures::JsFuture::from(promise)
-                .await
-                .map_err(|err| anyhow!("{err:?}"))?;
-
-            debug!("{:?}", result);
-
-            let pubkey_str = wasm_bindgen::JsValue::from_str("publicKey");
-            let pubkey_obj: js_sys::Object = reflect_get(&result, &pubkey_str)?.into();
-
-            let bn_str = wasm_bindgen::JsValue::from_str("toString");
-            let to_string_fn: js_sys::Function = reflect_get(&pubkey_obj, &bn_str)?.into();
-
-            debug!("pubkey_obj: {:?}", to_string_fn.call0(&pubkey_obj));
+fn wallet_network_check_system(
+    mut ev_reader: EventReader<WalletEvent>,
+    wallet: Res<Wallet>,
+    rpc: Res<rpc::SolanaRpc>,
+    bridge: Res<bridge::AsyncBridge>,
+) {
+    for event in ev_reader.read() {
+        let WalletEvent::Connected = event else {
+            continue;
+        };
+        let Some(info) = &wallet.info else {
+            continue;
+        };
+        let Ok(pubkey) = info.address.parse::<Pubkey>() else {
+            continue;
+        };
+
+        let rpc = rpc.clone();
+        let bridge = bridge.clone();
+        bridge.clone().spawn(move || async move {
+            // injected providers don't report their selected network, so look
+            // for traces of the wallet on the cluster the game talks to instead
+            match is_unknown_on_cluster(&rpc, &pubkey).await {
+                Ok(true) => {
+                    warn!(
+                        "wallet {} has no history on {}, it is likely set to another network",
+                        pubkey,
+                        rpc.endpoints.url(0)
+                    );
+                    bridge.send(|world| {
+                        world.send_event(WalletEvent::NetworkMismatchSuspected);
+                    });
+                }
+                Ok(false) => {}
+                Err(err) => debug!("wallet network check failed: {:?}", err),
+            }
+        });
+    }
+}
 
-            let pubkey = to_string_fn
-                .call0(&pubkey_obj)
-                .map_err(|err| anyhow!("{:?}", err))?;
+fn rpc_cluster_startup_system(rpc: Res<rpc::SolanaRpc>, bridge: Res<bridge::AsyncBridge>) {
+    fetch_node_version(&rpc, &bridge);
+    check_rpc_cluster(&rpc, &bridge, None);
+}
 
-            let public_key = pubkey
-                .as_string()
-                .context("could not convert pubkey to string")?;
+/// Learns the node's version up front, so requests adapt to old nodes from the start.
+fn fetch_node_version(rpc: &rpc::SolanaRpc, bridge: &bridge::AsyncBridge) {
+    let rpc = rpc.clone();
+    bridge.clone().spawn(move || async move {
+        match rpc.get_version().await {
+            Ok(version) => debug!("rpc node runs solana-core {}", version),
+            Err(err) => debug!("could not fetch the node version: {:?}", err),
+        }
+    });
+}
 
-            debug!("pubkey: {:?}", public_key);
+fn wallet_cluster_check_system(
+    mut ev_reader: EventReader<WalletEvent>,
+    settings: Res<WalletConnectSettings>,
+    rpc: Res<rpc::SolanaRpc>,
+    bridge: Res<bridge::AsyncBridge>,
+) {
+    for event in ev_reader.read() {
+        if let WalletEvent::Connected = event {
+            check_rpc_cluster(&rpc, &bridge, settings.cluster);
+        }
+    }
+}
 
-            return Ok(public_key);
+/// Updates [`RpcCluster`] and sends [`WalletEvent::ClusterMismatch`] if it is not `expected`.
+fn check_rpc_cluster(
+    rpc: &rpc::SolanaRpc,
+    bridge: &bridge::AsyncBridge,
+    expected: Option<Cluster>,
+) {
+    let rpc = rpc.clone();
+    let bridge = bridge.clone();
+    bridge.clone().spawn(move || async move {
+        match rpc.get_genesis_hash().await {
+            Ok(hash) => bridge.send(move |world| {
+                let actual = Cluster::from_genesis_hash(&hash);
+                world.resource_mut::<RpcCluster>().0 = Some(actual);
+
+                if let Some(expected) = expected.filter(|expected| *expected != actual) {
+                    warn!("game expects {} but the rpc is on {}", expected, actual);
+                    world.send_event(WalletEvent::ClusterMismatch { expected, actual });
+                }
+            }),
+            Err(err) if expected.is_some() => bridge.send(move |world| {
+                error::report_error(world, "check cluster", &err);
+            }),
+            Err(err) => debug!("could not fetch the genesis hash: {:?}", err),
         }
+    });
+}
 
-        debug!("isPhantom: {:?}", is_phantom);
+async fn is_unknown_on_cluster(rpc: &rpc::SolanaRpcClient, pubkey: &Pubkey) -> Result<bool> {
+    if !rpc.get_signatures_for_address(pubkey, 1).await?.is_empty() {
+        return Ok(false);
     }
 
-    Err(anyhow!("could not connect to wallet"))
+    Ok(rpc.get_balance(pubkey).await? == 0)
 }
 
 #[allow(clippy::type_complexity)]
@@ -253,13 +812,17 @@ pub fn wallet_menu_interaction_system(
             &Interaction,
             &mut BackgroundColor,
             &mut BorderColor,
-            &WalletButtonType,
+            &WalletButtonState,
         ),
-        (Changed<Interaction>, With<WalletButtonType>),
+        (Changed<Interaction>, With<WalletButton>),
     >,
     mut ev_writer: EventWriter<WalletEvent>,
 ) {
-    for (interaction, mut color, mut border_color, button_type) in &mut interaction_query {
+    for (interaction, mut color, mut border_color, state) in &mut interaction_query {
+        if *state == WalletButtonState::Disabled {
+            continue;
+        }
+
         // styling
 
         match *interaction {
@@ -278,16 +841,12 @@ pub fn wallet_menu_interaction_system(
         }
 
         match *interaction {
-            Interaction::Pressed => match button_type {
-                WalletButtonType::Connect => {
-                    println!("Connect button clicked");
-                    ev_writer.send(WalletEvent::ConnectBtnClick);
-                }
-                WalletButtonType::Disconnect => {
-                    println!("Disconnect button clicked");
-                    ev_writer.send(WalletEvent::DisconnectBtnClick);
+            Interaction::Pressed => {
+                if let Some(event) = state.press_event() {
+                    debug!("wallet button pressed: {:?}", event);
+                    ev_writer.send(event);
                 }
-            },
+            }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
                 border_color.0 = Color::WHITE;
@@ -300,7 +859,11 @@ pub fn wallet_menu_interaction_system(
 #[derive(Debug, Component)]
 pub struct ConnectDisconnectBtnText;
 
-pub fn setup_wallet_menu(mut commands: Commands) {
+pub fn setup_wallet_menu(mut commands: Commands, settings: Res<WalletConnectSettings>) {
+    if settings.read_only {
+        return;
+    }
+
     // setup connect button
     commands
         .spawn(NodeBundle {
@@ -355,7 +918,7 @@ pub fn setup_wallet_menu(mut commands: Commands) {
                         ))
                         .insert(ConnectDisconnectBtnText);
                 })
-                .insert(WalletButtonType::Connect);
+                .insert((WalletButton, WalletButtonState::Idle));
         });
 
     // setup address display
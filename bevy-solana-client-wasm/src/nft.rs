@@ -0,0 +1,228 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use bevy::prelude::*;
+use bevy_solana_client_common::{
+    catalog::ErrorCategory,
+    metadata::{get_off_chain_metadata, get_token_metadata, resolve_uri, Metadata, NftAttribute},
+    token::TokenAccountBalance,
+};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    bridge::AsyncBridge,
+    rpc::{SolanaRpc, SolanaRpcClient},
+    schedule::SolanaClientSet,
+    token_balance::{TokenBalanceChanged, TokenBalancePlugin, TokenBalances},
+};
+
+/// An NFT held by the connected wallet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nft {
+    pub mint: Pubkey,
+    /// The token account holding it.
+    pub token_account: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    /// Gateway url of the image, `None` if the off-chain JSON has none or could not be
+    /// fetched.
+    pub image_url: Option<String>,
+    pub attributes: Vec<NftAttribute>,
+    pub metadata: Metadata,
+}
+
+impl Nft {
+    /// The value of the attribute named `trait_type`.
+    pub fn attribute(&self, trait_type: &str) -> Option<&serde_json::Value> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.trait_type == trait_type)
+            .map(|attribute| &attribute.value)
+    }
+}
+
+/// The connected wallet's NFTs with Metaplex metadata, by mint.
+///
+/// Follows [`TokenBalances`], items show up once their metadata was fetched. Metadata
+/// that failed to load, e.g. because of a rate limit, is fetched again after the next
+/// balance refresh.
+#[derive(Debug, Default, Resource)]
+pub struct NftInventory {
+    items: BTreeMap<Pubkey, Nft>,
+    loading: HashSet<Pubkey>,
+    failed: HashSet<Pubkey>,
+}
+
+impl NftInventory {
+    pub fn get(&self, mint: &Pubkey) -> Option<&Nft> {
+        self.items.get(mint)
+    }
+
+    pub fn contains(&self, mint: &Pubkey) -> bool {
+        self.items.contains_key(mint)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Nft> {
+        self.items.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Items of the verified `collection`, e.g. to gate content on holding one.
+    pub fn in_collection<'a>(&'a self, collection: &'a Pubkey) -> impl Iterator<Item = &'a Nft> {
+        self.iter()
+            .filter(move |nft| nft.metadata.verified_collection() == Some(*collection))
+    }
+
+    /// Whether metadata of NFTs the wallet holds is still being fetched.
+    pub fn is_loading(&self) -> bool {
+        !self.loading.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub enum NftInventoryEvent {
+    Added(Pubkey),
+    Removed(Pubkey),
+}
+
+/// Keeps the [`NftInventory`] of the connected wallet, adds the [`TokenBalancePlugin`]
+/// if missing.
+pub struct NftInventoryPlugin;
+
+impl Plugin for NftInventoryPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<TokenBalancePlugin>() {
+            app.add_plugins(TokenBalancePlugin);
+        }
+        app.init_resource::<NftInventory>();
+        app.add_event::<NftInventoryEvent>();
+        app.add_systems(
+            Update,
+            nft_inventory_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn nft_inventory_system(
+    mut ev_reader: EventReader<TokenBalanceChanged>,
+    mut ev_writer: EventWriter<NftInventoryEvent>,
+    mut inventory: ResMut<NftInventory>,
+    balances: Res<TokenBalances>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    let mut fetch = vec![];
+    for event in ev_reader.read() {
+        let held = event
+            .current
+            .is_some_and(|amount| amount.amount == 1 && amount.decimals == 0);
+        if !held {
+            inventory.loading.remove(&event.mint);
+            inventory.failed.remove(&event.mint);
+            if inventory.items.remove(&event.mint).is_some() {
+                ev_writer.send(NftInventoryEvent::Removed(event.mint));
+            }
+            continue;
+        }
+        fetch.push(event.mint);
+    }
+
+    // the balances were refreshed, retry the metadata that failed to load
+    if balances.is_changed() {
+        fetch.extend(std::mem::take(&mut inventory.failed));
+    }
+
+    for mint in fetch {
+        if inventory.contains(&mint) || !inventory.loading.insert(mint) {
+            continue;
+        }
+
+        let Some(account) = balances
+            .accounts()
+            .iter()
+            .find(|account| account.mint == mint && account.is_nft())
+            .cloned()
+        else {
+            inventory.loading.remove(&mint);
+            continue;
+        };
+
+        let rpc = rpc.clone();
+        let bridge = bridge.clone();
+        bridge.clone().spawn(move || async move {
+            let result = fetch_nft(&rpc, account).await;
+            bridge.send(move |world| add_nft(world, result));
+        });
+    }
+}
+
+async fn fetch_nft(rpc: &SolanaRpcClient, account: TokenAccountBalance) -> (Pubkey, Result<Nft>) {
+    let mint = account.mint;
+    let metadata = match get_token_metadata(rpc, &mint).await {
+        Ok(metadata) => metadata,
+        Err(err) => return (mint, Err(err)),
+    };
+
+    // the item is still listed with its on-chain name if the JSON is unreachable
+    let off_chain = match get_off_chain_metadata(rpc, &metadata.uri).await {
+        Ok(off_chain) => Some(off_chain),
+        Err(err) => {
+            debug!("could not fetch the metadata json of {}: {:?}", mint, err);
+            None
+        }
+    };
+    let (image_url, attributes) = off_chain
+        .map(|off_chain| {
+            (
+                off_chain.image.as_deref().map(resolve_uri),
+                off_chain.attributes,
+            )
+        })
+        .unwrap_or_default();
+
+    let nft = Nft {
+        mint,
+        token_account: account.address,
+        name: metadata.name.clone(),
+        symbol: metadata.symbol.clone(),
+        image_url,
+        attributes,
+        metadata,
+    };
+    (mint, Ok(nft))
+}
+
+fn add_nft(world: &mut World, (mint, result): (Pubkey, Result<Nft>)) {
+    let mut inventory = world.resource_mut::<NftInventory>();
+    // sold or disconnected while loading
+    if !inventory.loading.remove(&mint) {
+        return;
+    }
+
+    match result {
+        Ok(nft) => {
+            inventory.items.insert(mint, nft);
+            world.send_event(NftInventoryEvent::Added(mint));
+        }
+        Err(err) => match ErrorCategory::of(&err) {
+            ErrorCategory::RateLimited
+            | ErrorCategory::NodeBehind
+            | ErrorCategory::Timeout
+            | ErrorCategory::Network => {
+                warn!(
+                    "could not fetch the metadata of {}, retrying: {:?}",
+                    mint, err
+                );
+                inventory.failed.insert(mint);
+            }
+            _ => debug!("{} is not listed in the inventory: {:?}", mint, err),
+        },
+    }
+}
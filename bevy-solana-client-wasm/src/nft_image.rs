@@ -0,0 +1,160 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        texture::{CompressedImageFormats, ImageSampler, ImageType},
+    },
+};
+use bevy_solana_client_common::{metadata::resolve_uri, rpc_client::RpcClient};
+
+use crate::{
+    bridge::AsyncBridge,
+    nft::{Nft, NftInventory, NftInventoryEvent},
+    rpc::{SolanaRpc, SolanaRpcClient},
+    schedule::SolanaClientSet,
+};
+
+/// NFT art loaded into [`Image`] assets, by url.
+///
+/// [`load`](Self::load) hands out the handle right away, the image appears in
+/// `Assets<Image>` once it was downloaded and decoded. Which formats decode depends on
+/// the enabled bevy image features, png is on by default.
+#[derive(Debug, Default, Resource)]
+pub struct NftImages {
+    handles: HashMap<String, Handle<Image>>,
+    queued: Vec<(String, Handle<Image>)>,
+    failed: HashSet<String>,
+}
+
+impl NftImages {
+    /// The image at `uri`, an `ipfs://`, `ar://` or https uri. Each url is fetched once.
+    pub fn load(&mut self, uri: &str, images: &Assets<Image>) -> Handle<Image> {
+        let url = resolve_uri(uri);
+        if let Some(handle) = self.handles.get(&url) {
+            return handle.clone();
+        }
+
+        let handle = images.reserve_handle();
+        self.handles.insert(url.clone(), handle.clone());
+        self.queued.push((url, handle.clone()));
+        handle
+    }
+
+    /// The image of `nft`, `None` if its metadata has no image.
+    pub fn load_nft(&mut self, nft: &Nft, images: &Assets<Image>) -> Option<Handle<Image>> {
+        Some(self.load(nft.image_url.as_deref()?, images))
+    }
+
+    pub fn get(&self, uri: &str) -> Option<&Handle<Image>> {
+        self.handles.get(&resolve_uri(uri))
+    }
+
+    /// Whether the image at `uri` could not be fetched or decoded, its handle stays empty.
+    pub fn is_failed(&self, uri: &str) -> bool {
+        self.failed.contains(&resolve_uri(uri))
+    }
+
+    /// Drops all images, e.g. when leaving the inventory screen.
+    pub fn clear(&mut self) {
+        self.handles.clear();
+        self.queued.clear();
+        self.failed.clear();
+    }
+}
+
+/// Loads images for [`NftImages`], and the art of every item added to the
+/// [`NftInventory`] if the inventory is used.
+pub struct NftImagePlugin;
+
+impl Plugin for NftImagePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NftImages>();
+        app.add_systems(
+            Update,
+            (
+                nft_inventory_image_system.run_if(resource_exists::<NftInventory>),
+                nft_image_fetch_system,
+            )
+                .chain()
+                .in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn nft_inventory_image_system(
+    mut ev_reader: EventReader<NftInventoryEvent>,
+    inventory: Res<NftInventory>,
+    mut nft_images: ResMut<NftImages>,
+    images: Res<Assets<Image>>,
+) {
+    for event in ev_reader.read() {
+        if let NftInventoryEvent::Added(mint) = event {
+            if let Some(nft) = inventory.get(mint) {
+                nft_images.load_nft(nft, &images);
+            }
+        }
+    }
+}
+
+fn nft_image_fetch_system(
+    mut nft_images: ResMut<NftImages>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    for (url, handle) in nft_images.queued.drain(..) {
+        let rpc = rpc.clone();
+        let bridge = bridge.clone();
+        bridge.clone().spawn(move || async move {
+            let result = fetch_image(&rpc, &url).await;
+            bridge.send(move |world| {
+                let mut nft_images = world.resource_mut::<NftImages>();
+                // cleared while loading
+                if nft_images.handles.get(&url) != Some(&handle) {
+                    return;
+                }
+                match result {
+                    Ok(image) => {
+                        world.resource_mut::<Assets<Image>>().insert(&handle, image);
+                    }
+                    Err(err) => {
+                        debug!("could not load the nft image {}: {:?}", url, err);
+                        nft_images.failed.insert(url);
+                    }
+                }
+            });
+        });
+    }
+}
+
+async fn fetch_image(rpc: &SolanaRpcClient, url: &str) -> Result<Image> {
+    let bytes = match rpc.config().timeout {
+        Some(timeout) => rpc.get_with_timeout(url, timeout).await?,
+        None => rpc.get(url).await?,
+    };
+    let mime_type = sniff_mime_type(&bytes).ok_or_else(|| anyhow!("unknown image format"))?;
+
+    Ok(Image::from_buffer(
+        &bytes,
+        ImageType::MimeType(mime_type),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::default(),
+    )?)
+}
+
+/// Gateways often serve art without a proper content type, tell the format from the
+/// file signature instead.
+fn sniff_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [0xff, 0xd8, 0xff, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        [b'B', b'M', ..] => Some("image/bmp"),
+        _ => None,
+    }
+}
@@ -0,0 +1,285 @@
+use std::time::Duration;
+
+use bevy::{
+    ecs::{component::Tick, system::SystemChangeTick},
+    prelude::*,
+};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, signature::Signature};
+
+use crate::{
+    account::SolanaAccountData,
+    schedule::SolanaClientSet,
+    signature_status::{SignatureStatusCache, TrackedTransaction},
+};
+
+/// Requests a predicted state to be applied to a mirrored account until `signature` resolves.
+///
+/// A new prediction for an entity that is already [`Optimistic`] supersedes the previous
+/// one, rolling back still restores the last confirmed state.
+#[derive(Debug, Clone, Event)]
+pub struct PredictAccount {
+    pub entity: Entity,
+    pub signature: Signature,
+    pub predicted: Account,
+}
+
+/// Marks a [`SolanaAccountData`] that holds a predicted, not yet confirmed state.
+#[derive(Debug, Clone, Component)]
+pub struct Optimistic {
+    pub signature: Signature,
+    /// The last confirmed state, restored on rollback.
+    pub confirmed: Account,
+    pub predicted: Account,
+    /// [`Time::elapsed`] when the prediction was applied.
+    pub applied_at: Duration,
+    applied_tick: Tick,
+    tracker: Entity,
+}
+
+/// What to do when a real account update diverges from a pending prediction.
+///
+/// Updates equal to [`Optimistic::confirmed`] that arrive before the transaction has a
+/// status are stale fetches, not divergences, and keep the prediction in place.
+#[derive(Debug, Default, Clone, Copy)]
+pub enum ConflictStrategy {
+    /// Accept the chain state and drop the prediction.
+    #[default]
+    KeepServer,
+    /// Re-apply the transaction's effect on top of the new chain state, the account stays
+    /// [`Optimistic`] until the transaction resolves. `None` falls back to the chain state.
+    Resimulate(fn(&Account) -> Option<Account>),
+    /// Merge the chain state and the prediction into a new prediction.
+    Merge(fn(&Divergence) -> Account),
+}
+
+/// The states involved in a divergence between chain and prediction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The real account update that just arrived.
+    pub server: Account,
+    pub predicted: Account,
+    /// The confirmed state the prediction was based on.
+    pub confirmed: Account,
+}
+
+/// Overrides [`OptimisticSettings::conflict_strategy`] for this entity.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Reconcile(pub ConflictStrategy);
+
+/// Emitted whenever a real account update diverges from a prediction.
+#[derive(Debug, Clone, Event)]
+pub struct OptimisticDivergence {
+    pub entity: Entity,
+    pub signature: Signature,
+    pub divergence: Divergence,
+    /// The state applied to the account after reconciling.
+    pub resolved: Account,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollbackReason {
+    /// A real account update arrived that differs from the prediction and was kept.
+    Diverged,
+    /// The transaction landed but failed.
+    TransactionFailed,
+    /// The transaction did not land within [`OptimisticSettings::timeout`].
+    Expired,
+}
+
+/// How a prediction was resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub enum OptimisticOutcome {
+    Confirmed {
+        entity: Entity,
+        signature: Signature,
+    },
+    RolledBack {
+        entity: Entity,
+        signature: Signature,
+        reason: RollbackReason,
+    },
+}
+
+#[derive(Debug, Clone, Resource)]
+pub struct OptimisticSettings {
+    /// Commitment at which the transaction confirms the prediction.
+    pub commitment: CommitmentConfig,
+    /// Roll back if the transaction has not landed by then, roughly a blockhash lifetime.
+    pub timeout: Duration,
+    /// Applied to entities without a [`Reconcile`] component.
+    pub conflict_strategy: ConflictStrategy,
+}
+
+impl Default for OptimisticSettings {
+    fn default() -> Self {
+        Self {
+            commitment: CommitmentConfig::confirmed(),
+            timeout: Duration::from_secs(90),
+            conflict_strategy: ConflictStrategy::default(),
+        }
+    }
+}
+
+pub struct OptimisticPlugin;
+
+impl Plugin for OptimisticPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PredictAccount>();
+        app.add_event::<OptimisticOutcome>();
+        app.add_event::<OptimisticDivergence>();
+        app.init_resource::<OptimisticSettings>();
+        app.add_systems(
+            Update,
+            apply_predictions_system.in_set(SolanaClientSet::Network),
+        );
+        app.add_systems(
+            PostUpdate,
+            resolve_predictions_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn apply_predictions_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    ticks: SystemChangeTick,
+    mut events: EventReader<PredictAccount>,
+    mut accounts: Query<(&mut SolanaAccountData, Option<&Optimistic>)>,
+) {
+    for event in events.read() {
+        let Ok((mut data, previous)) = accounts.get_mut(event.entity) else {
+            warn!(
+                "cannot predict {:?}, it is not a mirrored account",
+                event.entity
+            );
+            continue;
+        };
+
+        let confirmed = match previous {
+            Some(previous) => {
+                commands.entity(previous.tracker).despawn();
+                previous.confirmed.clone()
+            }
+            None => data.0.clone(),
+        };
+
+        data.0 = event.predicted.clone();
+
+        let tracker = commands
+            .spawn(TrackedTransaction {
+                signature: event.signature,
+            })
+            .id();
+
+        commands.entity(event.entity).insert(Optimistic {
+            signature: event.signature,
+            confirmed,
+            predicted: event.predicted.clone(),
+            applied_at: time.elapsed(),
+            applied_tick: ticks.this_run(),
+            tracker,
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn resolve_predictions_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    ticks: SystemChangeTick,
+    settings: Res<OptimisticSettings>,
+    statuses: Res<SignatureStatusCache>,
+    mut accounts: Query<(
+        Entity,
+        &mut SolanaAccountData,
+        &mut Optimistic,
+        Option<&Reconcile>,
+    )>,
+    mut ev_outcome: EventWriter<OptimisticOutcome>,
+    mut ev_divergence: EventWriter<OptimisticDivergence>,
+) {
+    for (entity, mut data, mut optimistic, reconcile) in &mut accounts {
+        let signature = optimistic.signature;
+        let status = statuses.get(&signature);
+        let landed =
+            status.is_some_and(|s| s.err.is_some() || s.satisfies_commitment(settings.commitment));
+
+        let overwritten = data.last_changed() != optimistic.applied_tick;
+        // a fetch still returning the state from before the transaction, which has not
+        // been seen by the cluster yet, keeps the prediction
+        if overwritten && status.is_none() && data.0 == optimistic.confirmed {
+            data.0 = optimistic.predicted.clone();
+            optimistic.applied_tick = ticks.this_run();
+            continue;
+        }
+
+        // anything else written after the prediction is a real update from the chain
+        let outcome = if overwritten {
+            if data.0 == optimistic.predicted {
+                OptimisticOutcome::Confirmed { entity, signature }
+            } else {
+                let divergence = Divergence {
+                    server: data.0.clone(),
+                    predicted: optimistic.predicted.clone(),
+                    confirmed: optimistic.confirmed.clone(),
+                };
+                let strategy = reconcile.map_or(settings.conflict_strategy, |r| r.0);
+
+                // once the transaction landed the chain state already includes it
+                let repredicted = match strategy {
+                    _ if landed => None,
+                    ConflictStrategy::KeepServer => None,
+                    ConflictStrategy::Resimulate(simulate) => simulate(&divergence.server),
+                    ConflictStrategy::Merge(merge) => Some(merge(&divergence)),
+                };
+
+                ev_divergence.send(OptimisticDivergence {
+                    entity,
+                    signature,
+                    resolved: repredicted
+                        .clone()
+                        .unwrap_or_else(|| divergence.server.clone()),
+                    divergence: divergence.clone(),
+                });
+
+                if let Some(predicted) = repredicted {
+                    debug!("optimistic update of {:?} re-predicted", entity);
+                    data.0 = predicted.clone();
+                    optimistic.confirmed = divergence.server;
+                    optimistic.predicted = predicted;
+                    optimistic.applied_tick = ticks.this_run();
+                    continue;
+                }
+
+                OptimisticOutcome::RolledBack {
+                    entity,
+                    signature,
+                    reason: RollbackReason::Diverged,
+                }
+            }
+        } else if status.is_some_and(|s| s.err.is_some()) {
+            data.0 = optimistic.confirmed.clone();
+            OptimisticOutcome::RolledBack {
+                entity,
+                signature,
+                reason: RollbackReason::TransactionFailed,
+            }
+        } else if landed {
+            OptimisticOutcome::Confirmed { entity, signature }
+        } else if time.elapsed().saturating_sub(optimistic.applied_at) > settings.timeout {
+            data.0 = optimistic.confirmed.clone();
+            OptimisticOutcome::RolledBack {
+                entity,
+                signature,
+                reason: RollbackReason::Expired,
+            }
+        } else {
+            continue;
+        };
+
+        debug!("optimistic update resolved: {:?}", outcome);
+        commands.entity(optimistic.tracker).despawn();
+        commands.entity(entity).remove::<Optimistic>();
+        ev_outcome.send(outcome);
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+/// When to fetch polled state: right away, then every interval, but never while the
+/// previous fetch is still in flight.
+///
+/// Owners keep their `interval` public and pass it to [`tick`](Self::tick), so changing
+/// it takes effect on the next frame.
+#[derive(Debug, Clone, Reflect)]
+pub struct PollTimer {
+    timer: Timer,
+    due: bool,
+    in_flight: bool,
+}
+
+impl PollTimer {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            timer: Timer::new(interval, TimerMode::Repeating),
+            due: true,
+            in_flight: false,
+        }
+    }
+
+    /// Advances the timer by `delta`, returns `true` if a fetch should start now. The
+    /// fetch counts as in flight until [`finish`](Self::finish).
+    pub fn tick(&mut self, interval: Duration, delta: Duration) -> bool {
+        if self.timer.duration() != interval {
+            self.timer.set_duration(interval);
+        }
+        self.timer.tick(delta);
+        if self.in_flight || !(self.due || self.timer.finished()) {
+            return false;
+        }
+
+        self.timer.reset();
+        self.due = false;
+        self.in_flight = true;
+        true
+    }
+
+    /// The fetch started by [`tick`](Self::tick) completed.
+    pub fn finish(&mut self) {
+        self.in_flight = false;
+    }
+
+    pub fn is_in_flight(&self) -> bool {
+        self.in_flight
+    }
+
+    /// Fetches on the next frame, or right after the fetch in flight.
+    pub fn refresh(&mut self) {
+        self.due = true;
+    }
+
+    /// Forgets the fetch in flight, its response was cancelled, and fetches on the next
+    /// frame.
+    pub fn reset(&mut self) {
+        self.in_flight = false;
+        self.due = true;
+    }
+}
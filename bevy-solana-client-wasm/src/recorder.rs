@@ -0,0 +1,269 @@
+use std::{
+    collections::VecDeque,
+    io::{Read, Write},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+use bevy::prelude::*;
+use bevy_solana_client_common::rpc_client::{ConfirmationStatus, RpcSignatureStatus};
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature};
+
+use crate::{
+    account::{PubkeyIndex, PubkeyIndexPlugin, SolanaAccountData, SolanaAccountPubkey},
+    schedule::SolanaClientSet,
+    signature_status::{SignatureStatusCache, SignatureStatusChanged},
+    Wallet, WalletEvent, WalletInfo,
+};
+
+/// A single chain related event of a recorded session.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum ChainRecord {
+    AccountUpdate {
+        pubkey: Pubkey,
+        account: Account,
+    },
+    TransactionStatus {
+        signature: Signature,
+        slot: u64,
+        confirmation_status: Option<ConfirmationStatus>,
+        /// The `TransactionError` as JSON.
+        err: Option<String>,
+    },
+    WalletConnected {
+        address: String,
+    },
+    WalletDisconnected,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RecordedEvent {
+    /// Seconds since the app started.
+    pub elapsed: f64,
+    pub record: ChainRecord,
+}
+
+/// Appends every account update, transaction status and wallet event of a session to a sink.
+///
+/// Records are bincode encoded back to back, read them with [`read_session`]. The sink is
+/// flushed after every frame that recorded something, so a crash loses at most that frame.
+#[derive(Resource, Clone)]
+pub struct SessionRecorder {
+    sink: Arc<Mutex<dyn Write + Send>>,
+}
+
+impl SessionRecorder {
+    pub fn new(sink: impl Write + Send + 'static) -> Self {
+        Self {
+            sink: Arc::new(Mutex::new(sink)),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let file = std::fs::File::create(path)?;
+        Ok(Self::new(std::io::BufWriter::new(file)))
+    }
+
+    pub fn record(&self, elapsed: f64, record: ChainRecord) -> Result<()> {
+        let mut sink = self.sink.lock().map_err(|e| anyhow!("{:?}", e))?;
+        bincode::serialize_into(&mut *sink, &RecordedEvent { elapsed, record })?;
+        Ok(())
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        self.sink.lock().map_err(|e| anyhow!("{:?}", e))?.flush()?;
+        Ok(())
+    }
+}
+
+/// Reads every record written by a [`SessionRecorder`].
+pub fn read_session(mut reader: impl Read) -> Result<Vec<RecordedEvent>> {
+    let mut events = vec![];
+    loop {
+        match bincode::deserialize_from(&mut reader) {
+            Ok(event) => events.push(event),
+            Err(err) => match *err {
+                bincode::ErrorKind::Io(ref io)
+                    if io.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    return Ok(events)
+                }
+                _ => return Err(err.into()),
+            },
+        }
+    }
+}
+
+/// Records the session into the [`SessionRecorder`] resource.
+pub struct SessionRecorderPlugin {
+    pub recorder: SessionRecorder,
+}
+
+impl Plugin for SessionRecorderPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.recorder.clone());
+        app.add_event::<SignatureStatusChanged>();
+        app.add_systems(Last, record_session_system);
+    }
+}
+
+fn record_session_system(
+    time: Res<Time>,
+    recorder: Res<SessionRecorder>,
+    accounts: Query<(&SolanaAccountPubkey, &SolanaAccountData), Changed<SolanaAccountData>>,
+    mut statuses: EventReader<SignatureStatusChanged>,
+    mut wallet_events: EventReader<WalletEvent>,
+    wallet: Option<Res<Wallet>>,
+) {
+    let elapsed = time.elapsed_seconds_f64();
+    let mut records = vec![];
+
+    for (pubkey, data) in &accounts {
+        records.push(ChainRecord::AccountUpdate {
+            pubkey: pubkey.0,
+            account: data.0.clone(),
+        });
+    }
+
+    for SignatureStatusChanged { signature, status } in statuses.read() {
+        records.push(ChainRecord::TransactionStatus {
+            signature: *signature,
+            slot: status.slot,
+            confirmation_status: status.confirmation_status,
+            err: status.err.as_ref().map(|err| err.to_string()),
+        });
+    }
+
+    for event in wallet_events.read() {
+        match event {
+            WalletEvent::Connected => {
+                if let Some(info) = wallet.as_ref().and_then(|w| w.info.as_ref()) {
+                    records.push(ChainRecord::WalletConnected {
+                        address: info.address.clone(),
+                    });
+                }
+            }
+            // the click is followed by `Disconnected` once the wallet let go
+            WalletEvent::Disconnected => {
+                records.push(ChainRecord::WalletDisconnected);
+            }
+            _ => {}
+        }
+    }
+
+    if records.is_empty() {
+        return;
+    }
+    for record in records {
+        if let Err(err) = recorder.record(elapsed, record) {
+            error!("could not record session event: {:?}", err);
+        }
+    }
+    if let Err(err) = recorder.flush() {
+        error!("could not flush the session recording: {:?}", err);
+    }
+}
+
+/// Feeds a recorded session back into the app, typically a headless one, on its original schedule.
+#[derive(Resource)]
+pub struct SessionReplay {
+    events: VecDeque<RecordedEvent>,
+}
+
+impl SessionReplay {
+    pub fn new(events: Vec<RecordedEvent>) -> Self {
+        Self {
+            events: events.into(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+pub struct SessionReplayPlugin;
+
+impl Plugin for SessionReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WalletEvent>();
+        app.init_resource::<SignatureStatusCache>();
+        if !app.is_plugin_added::<PubkeyIndexPlugin>() {
+            app.add_plugins(PubkeyIndexPlugin);
+        }
+        app.add_systems(
+            PreUpdate,
+            replay_session_system.in_set(SolanaClientSet::Bridge),
+        );
+    }
+}
+
+fn replay_session_system(world: &mut World) {
+    let elapsed = world.resource::<Time>().elapsed_seconds_f64();
+
+    let mut due = vec![];
+    {
+        let Some(mut replay) = world.get_resource_mut::<SessionReplay>() else {
+            return;
+        };
+        while replay.events.front().is_some_and(|e| e.elapsed <= elapsed) {
+            due.extend(replay.events.pop_front());
+        }
+    }
+
+    for event in due {
+        apply_record(world, event.record);
+    }
+}
+
+fn apply_record(world: &mut World, record: ChainRecord) {
+    match record {
+        ChainRecord::AccountUpdate { pubkey, account } => {
+            let existing = world
+                .resource::<PubkeyIndex>()
+                .get(&pubkey)
+                .and_then(|entity| world.get_entity_mut(entity));
+
+            match existing {
+                Some(mut entity) => {
+                    entity.insert(SolanaAccountData(account));
+                }
+                None => {
+                    let address = SolanaAccountPubkey(pubkey);
+                    let entity = world.spawn((address, SolanaAccountData(account))).id();
+                    // the index catches up in `PostUpdate`, a second update this frame
+                    // has to find the entity already
+                    world.resource_mut::<PubkeyIndex>().insert(entity, &address);
+                }
+            }
+        }
+        ChainRecord::TransactionStatus {
+            signature,
+            slot,
+            confirmation_status,
+            err,
+        } => {
+            let status = RpcSignatureStatus {
+                slot,
+                confirmations: None,
+                err: err.and_then(|err| serde_json::from_str(&err).ok()),
+                confirmation_status,
+            };
+            world
+                .resource_mut::<SignatureStatusCache>()
+                .insert(signature, status);
+        }
+        ChainRecord::WalletConnected { address } => {
+            world.insert_resource(Wallet {
+                info: Some(WalletInfo { amount: 0, address }),
+                ..default()
+            });
+            world.send_event(WalletEvent::Connected);
+        }
+        ChainRecord::WalletDisconnected => {
+            world.insert_resource(Wallet::default());
+            world.send_event(WalletEvent::Disconnected);
+        }
+    }
+}
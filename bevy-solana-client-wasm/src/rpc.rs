@@ -0,0 +1,34 @@
+use std::{ops::Deref, sync::Arc};
+
+use bevy::prelude::*;
+use bevy_solana_client_common::rpc_client::SOLANA_DEVNET_URL;
+
+#[cfg(target_arch = "wasm32")]
+pub type SolanaRpcClient = crate::WasmRpcClient;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub type SolanaRpcClient = bevy_solana_client_local::LocalRpcClient;
+
+/// The rpc client used by the plugin's systems, devnet unless inserted before the plugin.
+#[derive(Resource, Clone)]
+pub struct SolanaRpc(pub Arc<SolanaRpcClient>);
+
+impl SolanaRpc {
+    pub fn new(client: SolanaRpcClient) -> Self {
+        Self(Arc::new(client))
+    }
+}
+
+impl Default for SolanaRpc {
+    fn default() -> Self {
+        Self::new(SolanaRpcClient::new(SOLANA_DEVNET_URL))
+    }
+}
+
+impl Deref for SolanaRpc {
+    type Target = SolanaRpcClient;
+
+    fn deref(&self) -> &SolanaRpcClient {
+        &self.0
+    }
+}
@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use bevy_solana_client_common::{
+    rpc_client::RpcClient,
+    scan::{ProgramScan, ScanProgress},
+};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{bridge::AsyncBridge, error::report_error, rpc::SolanaRpc, schedule::SolanaClientSet};
+
+/// Starts a chunked scan of `program_id`, answered by [`ProgramScanEvent`]s.
+#[derive(Debug, Clone, Event)]
+pub struct ScanProgramAccounts {
+    pub program_id: Pubkey,
+    pub scan: ProgramScan,
+}
+
+#[derive(Debug, Clone, Event)]
+pub enum ProgramScanEvent {
+    Progress {
+        program_id: Pubkey,
+        progress: ScanProgress,
+    },
+    Completed {
+        program_id: Pubkey,
+        accounts: Vec<(Account, Pubkey)>,
+    },
+    /// The scan was aborted, the error is reported as a [`SolanaError`](crate::error::SolanaError).
+    Failed { program_id: Pubkey },
+}
+
+pub struct ProgramScanPlugin;
+
+impl Plugin for ProgramScanPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ScanProgramAccounts>();
+        app.add_event::<ProgramScanEvent>();
+        app.add_systems(
+            Update,
+            scan_program_accounts_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn scan_program_accounts_system(
+    mut ev_reader: EventReader<ScanProgramAccounts>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    for ScanProgramAccounts { program_id, scan } in ev_reader.read().cloned() {
+        let rpc = rpc.clone();
+        let bridge = bridge.clone();
+        bridge.clone().spawn(move || async move {
+            let progress_bridge = bridge.clone();
+            let mut on_progress = move |progress| {
+                progress_bridge.send(move |world| {
+                    world.send_event(ProgramScanEvent::Progress {
+                        program_id,
+                        progress,
+                    });
+                });
+            };
+
+            let result = rpc
+                .scan_program_accounts(&program_id, &scan, &mut on_progress)
+                .await;
+            bridge.send(move |world| match result {
+                Ok(accounts) => {
+                    world.send_event(ProgramScanEvent::Completed {
+                        program_id,
+                        accounts,
+                    });
+                }
+                Err(err) => {
+                    report_error(world, format!("scan program {}", program_id), &err);
+                    world.send_event(ProgramScanEvent::Failed { program_id });
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+
+/// The sets every system of the client runs in, in `PreUpdate`, `Update` and `PostUpdate`.
+///
+/// Within each schedule they run in the order `Bridge`, `Network`, `Ui`. Order game
+/// systems against them, e.g. `my_system.after(SolanaClientSet::Network)` to see the
+/// accounts mirrored this frame, or pause the client with
+/// `app.configure_sets(Update, SolanaClientSet::Network.run_if(in_state(Paused::No)))`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
+pub enum SolanaClientSet {
+    /// Applies the results of async work, rpc responses and wallet callbacks, to the world.
+    Bridge,
+    /// Sends requests and keeps the mirrored accounts, transactions and wallet in sync.
+    Network,
+    /// The wallet menu, modal and the other built-in panels.
+    Ui,
+}
+
+/// Orders the [`SolanaClientSet`]s.
+pub struct SolanaClientSetPlugin;
+
+impl Plugin for SolanaClientSetPlugin {
+    fn build(&self, app: &mut App) {
+        let sets = || {
+            (
+                SolanaClientSet::Bridge,
+                SolanaClientSet::Network,
+                SolanaClientSet::Ui,
+            )
+                .chain()
+        };
+        app.configure_sets(PreUpdate, sets());
+        app.configure_sets(Update, sets());
+        app.configure_sets(PostUpdate, sets());
+    }
+}
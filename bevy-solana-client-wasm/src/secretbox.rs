@@ -0,0 +1,61 @@
+use anyhow::{anyhow, bail, Context, Result};
+use poly1305::{universal_hash::KeyInit, Poly1305};
+use salsa20::{
+    cipher::{KeyIvInit, StreamCipher},
+    XSalsa20,
+};
+
+/// NaCl `secretbox`: XSalsa20 keystream, the first 32 bytes key Poly1305, the tag comes first.
+pub(crate) fn seal(key: &[u8; 32], nonce: &[u8; 24], plaintext: &[u8]) -> Vec<u8> {
+    let mut cipher = XSalsa20::new(key.into(), nonce.into());
+    let mut mac_key = [0u8; 32];
+    cipher.apply_keystream(&mut mac_key);
+
+    let mut ciphertext = plaintext.to_vec();
+    cipher.apply_keystream(&mut ciphertext);
+    let tag = Poly1305::new(&mac_key.into()).compute_unpadded(&ciphertext);
+
+    [tag.as_slice(), &ciphertext].concat()
+}
+
+pub(crate) fn open(key: &[u8; 32], nonce: &[u8; 24], sealed: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < 16 {
+        bail!("encrypted payload is too short");
+    }
+    let (tag, ciphertext) = sealed.split_at(16);
+
+    let mut cipher = XSalsa20::new(key.into(), nonce.into());
+    let mut mac_key = [0u8; 32];
+    cipher.apply_keystream(&mut mac_key);
+
+    let expected = Poly1305::new(&mac_key.into()).compute_unpadded(ciphertext);
+    let diff = expected
+        .iter()
+        .zip(tag)
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b));
+    if diff != 0 {
+        bail!("could not decrypt the wallet response");
+    }
+
+    let mut plaintext = ciphertext.to_vec();
+    cipher.apply_keystream(&mut plaintext);
+    Ok(plaintext)
+}
+
+pub(crate) fn random_bytes(buf: &mut [u8]) -> Result<()> {
+    web_sys::window()
+        .context("could not get window")?
+        .crypto()
+        .map_err(|err| anyhow!("{err:?}"))?
+        .get_random_values_with_u8_array(buf)
+        .map_err(|err| anyhow!("{err:?}"))?;
+    Ok(())
+}
+
+pub(crate) fn local_storage() -> Result<web_sys::Storage> {
+    web_sys::window()
+        .context("could not get window")?
+        .local_storage()
+        .map_err(|err| anyhow!("{err:?}"))?
+        .context("local storage is not available")
+}
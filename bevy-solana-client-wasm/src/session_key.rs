@@ -0,0 +1,231 @@
+use std::{sync::Arc, time::Duration};
+
+#[cfg(target_arch = "wasm32")]
+use anyhow::anyhow;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_solana_client_common::{
+    rpc_client::RpcClient,
+    session::{now_unix, SessionKey},
+    signer::{sign_and_send_transaction, GameSigner},
+};
+use solana_sdk::{
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction,
+    transaction::Transaction,
+};
+
+use crate::{
+    bridge::AsyncBridge, error::report_error, rpc::SolanaRpc, schedule::SolanaClientSet, Wallet,
+    WalletEvent,
+};
+
+/// Fee of a single signature transfer, kept back when sweeping.
+const SWEEP_FEE: u64 = 5_000;
+
+/// Asks the connected wallet to approve a new session key, answered by
+/// [`SessionEvent::Started`].
+#[derive(Debug, Clone, Event)]
+pub struct StartSession {
+    /// Programs the session key may call.
+    pub programs: Vec<Pubkey>,
+    pub duration: Duration,
+    /// Lamports the wallet sends to the session key for fees, skipped if zero.
+    ///
+    /// Funding is a second wallet prompt right after the approval.
+    pub fund_lamports: u64,
+}
+
+/// Ends the current session and returns its remaining lamports to the wallet.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct RevokeSession;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub enum SessionEvent {
+    Started(Pubkey),
+    Expired(Pubkey),
+    /// Revoked with [`RevokeSession`] or because the wallet disconnected or switched accounts.
+    Revoked(Pubkey),
+}
+
+/// The active session key, sign gameplay transactions with it instead of the wallet.
+#[derive(Debug, Clone, Resource)]
+pub struct GameSession(pub Arc<SessionKey>);
+
+/// Popup-free gameplay transactions: the wallet approves an ephemeral key once, game
+/// systems sign with [`GameSession`] until it expires or is revoked.
+pub struct SessionKeyPlugin;
+
+impl Plugin for SessionKeyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StartSession>();
+        app.add_event::<RevokeSession>();
+        app.add_event::<SessionEvent>();
+        app.add_systems(
+            Update,
+            (
+                start_session_system,
+                revoke_session_system,
+                session_expiry_system,
+            )
+                .in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn start_session_system(
+    mut ev_reader: EventReader<StartSession>,
+    wallet: Res<Wallet>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+    mut commands: Commands,
+) {
+    for request in ev_reader.read().cloned() {
+        let signer = match wallet.require_signer() {
+            Ok(signer) => signer,
+            Err(err) => {
+                commands.add(move |world: &mut World| {
+                    report_error(world, "start session", &err);
+                });
+                continue;
+            }
+        };
+
+        let rpc = rpc.clone();
+        let bridge = bridge.clone();
+        bridge.clone().spawn(move || async move {
+            let expires_at = now_unix() + request.duration.as_secs() as i64;
+            let result = async {
+                let keypair = generate_keypair()?;
+                let session =
+                    SessionKey::approve(signer.as_ref(), keypair, request.programs, expires_at)
+                        .await?;
+
+                if request.fund_lamports > 0 {
+                    let ix = system_instruction::transfer(
+                        &signer.pubkey(),
+                        &session.grant.session_key,
+                        request.fund_lamports,
+                    );
+                    let mut tx =
+                        Transaction::new_unsigned(Message::new(&[ix], Some(&signer.pubkey())));
+                    tx.message.recent_blockhash = rpc.recent_blockhash().await?;
+                    sign_and_send_transaction(signer.as_ref(), &mut tx, rpc.0.as_ref())
+                        .await
+                        .context("could not fund the session key")?;
+                }
+
+                Ok::<_, anyhow::Error>(session)
+            }
+            .await;
+
+            bridge.send(move |world| match result {
+                Ok(session) => {
+                    let pubkey = session.pubkey();
+                    info!(
+                        "session key {} active until {}",
+                        pubkey, session.grant.expires_at
+                    );
+                    if let Some(previous) = world.remove_resource::<GameSession>() {
+                        previous.0.revoke();
+                    }
+                    world.insert_resource(GameSession(Arc::new(session)));
+                    world.send_event(SessionEvent::Started(pubkey));
+                }
+                Err(err) => report_error(world, "start session", &err),
+            });
+        });
+    }
+}
+
+fn revoke_session_system(
+    mut revoke: EventReader<RevokeSession>,
+    mut wallet_events: EventReader<WalletEvent>,
+    session: Option<Res<GameSession>>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+    mut commands: Commands,
+    mut ev_writer: EventWriter<SessionEvent>,
+) {
+    let wallet_changed = wallet_events.read().any(|event| {
+        matches!(
+            event,
+            WalletEvent::Disconnected | WalletEvent::AccountChanged(_)
+        )
+    });
+    let requested = revoke.read().last().is_some();
+    let Some(session) = session.filter(|_| requested || wallet_changed) else {
+        return;
+    };
+
+    session.0.revoke();
+    commands.remove_resource::<GameSession>();
+    ev_writer.send(SessionEvent::Revoked(session.0.pubkey()));
+    return_funds(&session.0, &rpc, &bridge);
+}
+
+fn session_expiry_system(
+    session: Option<Res<GameSession>>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+    mut commands: Commands,
+    mut ev_writer: EventWriter<SessionEvent>,
+) {
+    let Some(session) = session.filter(|session| !session.0.is_active()) else {
+        return;
+    };
+
+    debug!("session key {} expired", session.0.pubkey());
+    commands.remove_resource::<GameSession>();
+    ev_writer.send(SessionEvent::Expired(session.0.pubkey()));
+    return_funds(&session.0, &rpc, &bridge);
+}
+
+/// Sends what is left on an ended session key back to the wallet that approved it.
+fn return_funds(session: &Arc<SessionKey>, rpc: &SolanaRpc, bridge: &AsyncBridge) {
+    let session = session.clone();
+    let rpc = rpc.clone();
+    let bridge = bridge.clone();
+    bridge.clone().spawn(move || async move {
+        let result = sweep(rpc.0.as_ref(), session.keypair(), &session.grant.authority).await;
+        if let Err(err) = result {
+            bridge.send(move |world| report_error(world, "return session funds", &err));
+        }
+    });
+}
+
+/// Moves every lamport but the fee from `keypair` to `to`, `None` if there is nothing
+/// worth moving.
+pub(crate) async fn sweep(
+    rpc: &impl RpcClient,
+    keypair: &Keypair,
+    to: &Pubkey,
+) -> Result<Option<Signature>> {
+    let from = Signer::pubkey(keypair);
+    let balance = rpc.get_balance(&from).await?;
+    if balance <= SWEEP_FEE {
+        return Ok(None);
+    }
+
+    let ix = system_instruction::transfer(&from, to, balance - SWEEP_FEE);
+    let mut tx = Transaction::new_unsigned(Message::new(&[ix], Some(&from)));
+    tx.try_sign(&[keypair], rpc.recent_blockhash().await?)?;
+    rpc.send_transaction(&tx).await?;
+
+    Ok(tx.signatures.first().copied())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn generate_keypair() -> Result<Keypair> {
+    let mut seed = [0u8; 32];
+    crate::secretbox::random_bytes(&mut seed)?;
+    solana_sdk::signature::keypair_from_seed(&seed).map_err(|err| anyhow!("{}", err))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn generate_keypair() -> Result<Keypair> {
+    Ok(Keypair::new())
+}
@@ -0,0 +1,302 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+};
+
+use bevy::prelude::*;
+use bevy_solana_client_common::{
+    rpc_client::{RpcClient, RpcConfirmedTransaction},
+    token::{RpcTransactionTokenBalance, TokenAmount},
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+
+use crate::{
+    bridge::AsyncBridge,
+    rpc::SolanaRpc,
+    schedule::SolanaClientSet,
+    signature_status::{SignatureStatusCache, TrackedTransaction},
+};
+
+/// Lamports per SOL, as decimals of a [`TokenAmount`].
+const SOL_DECIMALS: u8 = 9;
+
+/// What the player did on chain during a session, e.g. for a post-match screen.
+///
+/// Built from every [`TrackedTransaction`], token flows and fees are those of each
+/// transaction's fee payer.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Resource, serde::Serialize, serde::Deserialize)]
+pub struct SessionSummary {
+    pub transactions_sent: u64,
+    pub transactions_confirmed: u64,
+    pub transactions_failed: u64,
+    /// Total fees in lamports, failed transactions are charged too.
+    pub fees_paid: u64,
+    pub tokens: Vec<TokenFlow>,
+    /// Mints of NFTs minted to or received by the player.
+    pub nfts_acquired: Vec<Pubkey>,
+    /// Mints of NFTs the player sent away or burned.
+    pub nfts_transferred: Vec<Pubkey>,
+}
+
+/// Raw amounts of a fungible token that came in and went out during a session.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TokenFlow {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub gained: u64,
+    pub spent: u64,
+}
+
+impl TokenFlow {
+    pub fn gained(&self) -> TokenAmount {
+        TokenAmount::new(self.gained, self.decimals)
+    }
+
+    pub fn spent(&self) -> TokenAmount {
+        TokenAmount::new(self.spent, self.decimals)
+    }
+}
+
+impl SessionSummary {
+    /// Adds the fees and token balance changes of a confirmed transaction.
+    pub fn apply_transaction(&mut self, tx: &RpcConfirmedTransaction) {
+        let (Some(meta), Some(owner)) = (&tx.meta, tx.fee_payer()) else {
+            return;
+        };
+        self.fees_paid += meta.fee;
+
+        let owner = owner.to_string();
+        let pre = owned_balances(&meta.pre_token_balances, &owner);
+        let post = owned_balances(&meta.post_token_balances, &owner);
+
+        let mints: HashSet<&String> = pre.keys().chain(post.keys()).collect();
+        for mint in mints {
+            let Ok(mint_pubkey) = mint.parse::<Pubkey>() else {
+                continue;
+            };
+            let (pre_amount, decimals) = pre.get(mint).copied().unwrap_or((0, 0));
+            let (post_amount, post_decimals) = post.get(mint).copied().unwrap_or((0, decimals));
+            let decimals = decimals.max(post_decimals);
+
+            if decimals == 0 && pre_amount == 0 && post_amount == 1 {
+                self.nfts_acquired.push(mint_pubkey);
+            } else if decimals == 0 && pre_amount == 1 && post_amount == 0 {
+                self.nfts_transferred.push(mint_pubkey);
+            } else if pre_amount != post_amount {
+                let flow = match self.tokens.iter_mut().find(|f| f.mint == mint_pubkey) {
+                    Some(flow) => flow,
+                    None => {
+                        self.tokens.push(TokenFlow {
+                            mint: mint_pubkey,
+                            decimals,
+                            gained: 0,
+                            spent: 0,
+                        });
+                        self.tokens.last_mut().unwrap()
+                    }
+                };
+
+                if post_amount > pre_amount {
+                    flow.gained = flow.gained.saturating_add(post_amount - pre_amount);
+                } else {
+                    flow.spent = flow.spent.saturating_add(pre_amount - post_amount);
+                }
+            }
+        }
+    }
+}
+
+/// Sums the raw balances of `owner` per mint.
+pub(crate) fn owned_balances(
+    balances: &[RpcTransactionTokenBalance],
+    owner: &str,
+) -> BTreeMap<String, (u64, u8)> {
+    let mut owned = BTreeMap::new();
+    for balance in balances {
+        if balance.owner.as_deref() != Some(owner) {
+            continue;
+        }
+        let Ok(amount) = TokenAmount::try_from(balance.ui_token_amount.clone()) else {
+            continue;
+        };
+
+        let entry = owned
+            .entry(balance.mint.clone())
+            .or_insert((0u64, amount.decimals));
+        entry.0 = entry.0.saturating_add(amount.amount);
+    }
+    owned
+}
+
+impl fmt::Display for SessionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "transactions: {} sent, {} confirmed, {} failed",
+            self.transactions_sent, self.transactions_confirmed, self.transactions_failed
+        )?;
+        writeln!(
+            f,
+            "fees: {} SOL",
+            TokenAmount::new(self.fees_paid, SOL_DECIMALS)
+        )?;
+        for flow in &self.tokens {
+            writeln!(f, "{}: +{} -{}", flow.mint, flow.gained(), flow.spent())?;
+        }
+        writeln!(
+            f,
+            "nfts: {} acquired, {} transferred",
+            self.nfts_acquired.len(),
+            self.nfts_transferred.len()
+        )
+    }
+}
+
+/// Ends the current session, answered with a [`SessionReport`].
+#[derive(Debug, Clone, Copy, Event)]
+pub struct FinishSession;
+
+/// The summary of a finished session, the [`SessionSummary`] resource starts over.
+#[derive(Debug, Clone, Event)]
+pub struct SessionReport(pub SessionSummary);
+
+/// Marks the text of the optional summary panel.
+#[derive(Debug, Component)]
+pub struct SessionSummaryPanel;
+
+#[derive(Default)]
+pub struct SessionSummaryPlugin {
+    /// Spawn a text panel showing the live summary.
+    pub panel: bool,
+}
+
+impl Plugin for SessionSummaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SessionSummary>();
+        app.add_event::<FinishSession>();
+        app.add_event::<SessionReport>();
+        app.add_systems(
+            Update,
+            (session_summary_system, finish_session_system)
+                .chain()
+                .in_set(SolanaClientSet::Network),
+        );
+
+        if self.panel {
+            app.add_systems(Startup, setup_session_summary_panel);
+            app.add_systems(
+                Update,
+                session_summary_panel_system.in_set(SolanaClientSet::Ui),
+            );
+        }
+    }
+}
+
+#[derive(Default)]
+struct SessionTransactions {
+    sent: HashSet<Signature>,
+    landed: HashSet<Signature>,
+}
+
+fn session_summary_system(
+    mut transactions: Local<SessionTransactions>,
+    mut summary: ResMut<SessionSummary>,
+    mut finished: EventReader<FinishSession>,
+    tracked: Query<Ref<TrackedTransaction>>,
+    statuses: Res<SignatureStatusCache>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    if !finished.is_empty() {
+        finished.clear();
+        *transactions = SessionTransactions::default();
+    }
+
+    for tracked in tracked.iter().filter(|t| t.is_added()) {
+        if transactions.sent.insert(tracked.signature) {
+            summary.transactions_sent += 1;
+        }
+    }
+
+    if !statuses.is_changed() {
+        return;
+    }
+
+    let landed: Vec<Signature> = transactions
+        .sent
+        .iter()
+        .filter(|signature| !transactions.landed.contains(*signature))
+        .filter(|signature| {
+            statuses.get(signature).is_some_and(|s| {
+                s.err.is_some() || s.satisfies_commitment(CommitmentConfig::confirmed())
+            })
+        })
+        .copied()
+        .collect();
+
+    for signature in landed {
+        transactions.landed.insert(signature);
+        if statuses.get(&signature).is_some_and(|s| s.err.is_some()) {
+            summary.transactions_failed += 1;
+        } else {
+            summary.transactions_confirmed += 1;
+        }
+
+        let rpc = rpc.clone();
+        let bridge = bridge.clone();
+        bridge.clone().spawn(move || async move {
+            match rpc.get_transaction(&signature).await {
+                Ok(Some(tx)) => bridge.send(move |world| {
+                    world
+                        .resource_mut::<SessionSummary>()
+                        .apply_transaction(&tx);
+                }),
+                Ok(None) => debug!("transaction {} not found for session summary", signature),
+                Err(err) => debug!("could not fetch transaction {}: {:?}", signature, err),
+            }
+        });
+    }
+}
+
+fn finish_session_system(
+    mut finished: EventReader<FinishSession>,
+    mut summary: ResMut<SessionSummary>,
+    mut ev_writer: EventWriter<SessionReport>,
+) {
+    if finished.read().last().is_some() {
+        ev_writer.send(SessionReport(std::mem::take(&mut *summary)));
+    }
+}
+
+fn setup_session_summary_panel(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::linear_rgb(0.9, 0.9, 0.9),
+                ..Default::default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            ..default()
+        }),
+        SessionSummaryPanel,
+    ));
+}
+
+fn session_summary_panel_system(
+    summary: Res<SessionSummary>,
+    mut panels: Query<&mut Text, With<SessionSummaryPanel>>,
+) {
+    if !summary.is_changed() {
+        return;
+    }
+
+    for mut text in &mut panels {
+        text.sections[0].value = summary.to_string();
+    }
+}
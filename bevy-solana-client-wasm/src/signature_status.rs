@@ -0,0 +1,279 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use bevy_solana_client_common::rpc_client::{RpcClient, RpcSignatureStatus};
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+
+use crate::{bridge::AsyncBridge, error::report_error, rpc::SolanaRpc, schedule::SolanaClientSet};
+
+/// A submitted transaction whose status is polled until it is finalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct TrackedTransaction {
+    pub signature: Signature,
+}
+
+/// Latest statuses of every [`TrackedTransaction`], shared by all trackers.
+///
+/// All pending signatures are polled together on one timer, batched into as
+/// few `getSignatureStatuses` requests as possible.
+#[derive(Debug, Resource)]
+pub struct SignatureStatusCache {
+    statuses: HashMap<Signature, RpcSignatureStatus>,
+    timer: Timer,
+    in_flight: bool,
+}
+
+impl Default for SignatureStatusCache {
+    fn default() -> Self {
+        Self::with_interval(Duration::from_millis(1000))
+    }
+}
+
+impl SignatureStatusCache {
+    /// Forgets every status and the poll in flight.
+    pub(crate) fn reset(&mut self) {
+        self.statuses.clear();
+        self.in_flight = false;
+    }
+
+    pub fn with_interval(interval: Duration) -> Self {
+        Self {
+            statuses: HashMap::new(),
+            timer: Timer::new(interval, TimerMode::Repeating),
+            in_flight: false,
+        }
+    }
+
+    pub fn get(&self, signature: &Signature) -> Option<&RpcSignatureStatus> {
+        self.statuses.get(signature)
+    }
+
+    pub fn is_finalized(&self, signature: &Signature) -> bool {
+        self.get(signature)
+            .is_some_and(|s| s.satisfies_commitment(CommitmentConfig::finalized()))
+    }
+
+    /// Overrides the status of `signature`, e.g. when replaying a recorded session.
+    pub fn insert(&mut self, signature: Signature, status: RpcSignatureStatus) {
+        self.statuses.insert(signature, status);
+    }
+
+    /// Iterates every known status.
+    pub fn iter(&self) -> impl Iterator<Item = (&Signature, &RpcSignatureStatus)> {
+        self.statuses.iter()
+    }
+
+    /// Stores the fetched statuses, returns those that changed.
+    fn apply(
+        &mut self,
+        signatures: Vec<Signature>,
+        statuses: Vec<Option<RpcSignatureStatus>>,
+    ) -> Vec<SignatureStatusChanged> {
+        let mut changed = vec![];
+        for (signature, status) in signatures.into_iter().zip(statuses) {
+            let Some(status) = status else {
+                continue;
+            };
+            if self.statuses.get(&signature) != Some(&status) {
+                self.statuses.insert(signature, status.clone());
+                changed.push(SignatureStatusChanged { signature, status });
+            }
+        }
+        changed
+    }
+}
+
+/// A polled status of a [`TrackedTransaction`] changed.
+#[derive(Debug, Clone, PartialEq, Event)]
+pub struct SignatureStatusChanged {
+    pub signature: Signature,
+    pub status: RpcSignatureStatus,
+}
+
+/// When to report degraded send to confirmation times.
+#[derive(Debug, Clone, Resource)]
+pub struct ConfirmationTimeSettings {
+    /// Confirmation times above this are considered degraded.
+    pub threshold: Duration,
+    /// Number of recent transactions the quantile is computed over.
+    pub window: usize,
+    /// Quantile of the window compared against the threshold, e.g. `0.9` for p90.
+    pub quantile: f64,
+}
+
+impl Default for ConfirmationTimeSettings {
+    fn default() -> Self {
+        Self {
+            threshold: Duration::from_secs(8),
+            window: 20,
+            quantile: 0.9,
+        }
+    }
+}
+
+/// Emitted when recent confirmation times cross [`ConfirmationTimeSettings::threshold`],
+/// e.g. to raise priority fees while the cluster is congested.
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub enum ConfirmationTimeEvent {
+    Degraded {
+        /// The rpc url of the cluster the transactions were sent to.
+        cluster: String,
+        latency: Duration,
+        threshold: Duration,
+    },
+    Recovered {
+        cluster: String,
+        latency: Duration,
+        threshold: Duration,
+    },
+}
+
+pub struct SignatureStatusPlugin;
+
+impl Plugin for SignatureStatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SignatureStatusCache>();
+        app.init_resource::<ConfirmationTimeSettings>();
+        app.add_event::<ConfirmationTimeEvent>();
+        app.add_event::<SignatureStatusChanged>();
+        app.add_systems(
+            Update,
+            (poll_signature_statuses_system, confirmation_time_system)
+                .in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn poll_signature_statuses_system(
+    time: Res<Time>,
+    mut cache: ResMut<SignatureStatusCache>,
+    tracked: Query<&TrackedTransaction>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    if !cache.timer.tick(time.delta()).just_finished() || cache.in_flight {
+        return;
+    }
+
+    // forget statuses of transactions nobody tracks anymore
+    let tracked_signatures: HashSet<Signature> = tracked.iter().map(|t| t.signature).collect();
+    cache
+        .statuses
+        .retain(|signature, _| tracked_signatures.contains(signature));
+
+    let pending: Vec<Signature> = tracked_signatures
+        .into_iter()
+        .filter(|signature| !cache.is_finalized(signature))
+        .collect();
+
+    if pending.is_empty() {
+        return;
+    }
+
+    cache.in_flight = true;
+
+    let rpc = rpc.clone();
+    let bridge = bridge.clone();
+    bridge.clone().spawn(move || async move {
+        let result = rpc.get_signature_statuses(&pending).await;
+
+        bridge.send(move |world| {
+            let mut cache = world.resource_mut::<SignatureStatusCache>();
+            cache.in_flight = false;
+
+            match result {
+                Ok(statuses) => {
+                    let changed = cache.apply(pending, statuses);
+                    world.send_event_batch(changed);
+                }
+                Err(err) => report_error(world, "poll signature statuses", &err),
+            }
+        });
+    });
+}
+
+#[derive(Default)]
+struct ConfirmationTimes {
+    sent_at: HashMap<Signature, Duration>,
+    recent: VecDeque<Duration>,
+    degraded: bool,
+}
+
+fn confirmation_time_system(
+    mut times: Local<ConfirmationTimes>,
+    time: Res<Time>,
+    settings: Res<ConfirmationTimeSettings>,
+    cache: Res<SignatureStatusCache>,
+    tracked: Query<Ref<TrackedTransaction>>,
+    rpc: Res<SolanaRpc>,
+    mut ev_writer: EventWriter<ConfirmationTimeEvent>,
+) {
+    for tracked in tracked.iter().filter(|t| t.is_added()) {
+        times
+            .sent_at
+            .entry(tracked.signature)
+            .or_insert(time.elapsed());
+    }
+
+    if !cache.is_changed() {
+        return;
+    }
+
+    let tracked_signatures: HashSet<Signature> = tracked.iter().map(|t| t.signature).collect();
+    let mut confirmed = vec![];
+    times.sent_at.retain(|signature, sent_at| {
+        let landed = cache
+            .get(signature)
+            .is_some_and(|s| s.satisfies_commitment(CommitmentConfig::confirmed()));
+        if landed {
+            confirmed.push(time.elapsed().saturating_sub(*sent_at));
+        }
+        !landed && tracked_signatures.contains(signature)
+    });
+
+    if confirmed.is_empty() {
+        return;
+    }
+
+    for elapsed in confirmed {
+        rpc.state().metrics.record_confirmation(elapsed);
+        times.recent.push_back(elapsed);
+    }
+    while times.recent.len() > settings.window.max(1) {
+        times.recent.pop_front();
+    }
+
+    let mut recent: Vec<Duration> = times.recent.iter().copied().collect();
+    recent.sort();
+    let rank = ((recent.len() as f64) * settings.quantile.clamp(0.0, 1.0)).ceil() as usize;
+    let latency = recent[rank.clamp(1, recent.len()) - 1];
+
+    let degraded = latency > settings.threshold;
+    if degraded == times.degraded {
+        return;
+    }
+    times.degraded = degraded;
+
+    let cluster = rpc.endpoints.url(0).to_string();
+    let threshold = settings.threshold;
+    if degraded {
+        warn!(
+            "confirmation times on {} degraded to {:?}, above {:?}",
+            cluster, latency, threshold
+        );
+        ev_writer.send(ConfirmationTimeEvent::Degraded {
+            cluster,
+            latency,
+            threshold,
+        });
+    } else {
+        ev_writer.send(ConfirmationTimeEvent::Recovered {
+            cluster,
+            latency,
+            threshold,
+        });
+    }
+}
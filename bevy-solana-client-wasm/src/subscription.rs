@@ -0,0 +1,259 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::Result;
+use bevy::prelude::*;
+use bevy_solana_client_common::{catalog::ErrorCategory, rpc_client::RpcClient};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{
+    account::{send_account_closed, AccountAddress, SolanaAccountData, SolanaAccountPubkey},
+    bridge::AsyncBridge,
+    poll::PollTimer,
+    rpc::SolanaRpc,
+    schedule::SolanaClientSet,
+};
+
+/// Marks an entity as interested in updates for an on-chain account.
+///
+/// Any number of entities may track the same account; the underlying
+/// subscription is only opened once and torn down with the last tracker.
+///
+/// The account is mirrored into [`SolanaAccountPubkey`] and [`SolanaAccountData`] on the
+/// entity, see [`AccountSubscriptionPoll`]. The data is removed while the account does
+/// not exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct TrackAccount(pub Pubkey);
+
+impl AccountAddress for TrackAccount {
+    fn address(&self) -> Pubkey {
+        self.0
+    }
+}
+
+/// Emitted when the first tracker of an account appears or the last one goes away.
+///
+/// [`AccountSubscriptionPoll`] fetches a new subscription right away, other transport
+/// backends listen to this to open and close the actual RPC/WS subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub enum AccountSubscriptionEvent {
+    Subscribe(Pubkey),
+    Unsubscribe(Pubkey),
+}
+
+/// Reference counts of tracked accounts.
+#[derive(Debug, Default, Resource)]
+pub struct AccountSubscriptions {
+    ref_counts: HashMap<Pubkey, usize>,
+    trackers: HashMap<Entity, Pubkey>,
+}
+
+impl AccountSubscriptions {
+    /// Increments the reference count, returns `true` if this is the first reference.
+    pub fn acquire(&mut self, pubkey: Pubkey) -> bool {
+        let count = self.ref_counts.entry(pubkey).or_default();
+        *count += 1;
+        *count == 1
+    }
+
+    /// Decrements the reference count, returns `true` if this was the last reference.
+    pub fn release(&mut self, pubkey: &Pubkey) -> bool {
+        let Some(count) = self.ref_counts.get_mut(pubkey) else {
+            return false;
+        };
+
+        *count -= 1;
+        if *count == 0 {
+            self.ref_counts.remove(pubkey);
+            return true;
+        }
+        false
+    }
+
+    pub fn ref_count(&self, pubkey: &Pubkey) -> usize {
+        self.ref_counts.get(pubkey).copied().unwrap_or_default()
+    }
+
+    pub fn is_subscribed(&self, pubkey: &Pubkey) -> bool {
+        self.ref_counts.contains_key(pubkey)
+    }
+
+    /// Iterates every account that currently has at least one tracker.
+    pub fn subscribed(&self) -> impl Iterator<Item = &Pubkey> {
+        self.ref_counts.keys()
+    }
+}
+
+/// How often the accounts of [`TrackAccount`] entities are fetched. A new subscription
+/// is fetched right away.
+#[derive(Debug, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct AccountSubscriptionPoll {
+    pub interval: Duration,
+    timer: PollTimer,
+}
+
+impl Default for AccountSubscriptionPoll {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(5))
+    }
+}
+
+impl AccountSubscriptionPoll {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            timer: PollTimer::new(interval),
+        }
+    }
+
+    /// Fetches every tracked account on the next frame.
+    pub fn refresh(&mut self) {
+        self.timer.refresh();
+    }
+
+    /// Forgets the request in flight, its response was cancelled.
+    pub(crate) fn reset(&mut self) {
+        self.timer.reset();
+    }
+}
+
+pub struct AccountSubscriptionPlugin;
+
+impl Plugin for AccountSubscriptionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AccountSubscriptionEvent>();
+        app.init_resource::<AccountSubscriptions>();
+        app.init_resource::<AccountSubscriptionPoll>();
+        app.register_type::<AccountSubscriptionPoll>();
+        app.add_systems(
+            PreUpdate,
+            account_subscription_system.in_set(SolanaClientSet::Network),
+        );
+        app.add_systems(
+            Update,
+            account_subscription_poll_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn account_subscription_system(
+    mut subscriptions: ResMut<AccountSubscriptions>,
+    changed: Query<(Entity, &TrackAccount), Changed<TrackAccount>>,
+    mut removed: RemovedComponents<TrackAccount>,
+    mut ev_writer: EventWriter<AccountSubscriptionEvent>,
+) {
+    for entity in removed.read() {
+        if let Some(pubkey) = subscriptions.trackers.remove(&entity) {
+            if subscriptions.release(&pubkey) {
+                debug!("unsubscribe account: {}", pubkey);
+                ev_writer.send(AccountSubscriptionEvent::Unsubscribe(pubkey));
+            }
+        }
+    }
+
+    for (entity, track) in &changed {
+        let previous = subscriptions.trackers.insert(entity, track.0);
+        if previous == Some(track.0) {
+            continue;
+        }
+
+        if let Some(previous) = previous {
+            if subscriptions.release(&previous) {
+                debug!("unsubscribe account: {}", previous);
+                ev_writer.send(AccountSubscriptionEvent::Unsubscribe(previous));
+            }
+        }
+
+        if subscriptions.acquire(track.0) {
+            debug!("subscribe account: {}", track.0);
+            ev_writer.send(AccountSubscriptionEvent::Subscribe(track.0));
+        }
+    }
+}
+
+fn account_subscription_poll_system(
+    time: Res<Time<Real>>,
+    mut ev_reader: EventReader<AccountSubscriptionEvent>,
+    mut poll: ResMut<AccountSubscriptionPoll>,
+    subscriptions: Res<AccountSubscriptions>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    if ev_reader
+        .read()
+        .any(|event| matches!(event, AccountSubscriptionEvent::Subscribe(_)))
+    {
+        poll.refresh();
+    }
+    if subscriptions.ref_counts.is_empty() {
+        return;
+    }
+
+    let interval = poll.interval;
+    if !poll.timer.tick(interval, time.delta()) {
+        return;
+    }
+
+    let pubkeys: Vec<Pubkey> = subscriptions.subscribed().copied().collect();
+    let rpc = rpc.clone();
+    let bridge = bridge.clone();
+    bridge.clone().spawn(move || async move {
+        let mut results = Vec::with_capacity(pubkeys.len());
+        for pubkey in pubkeys {
+            let result = rpc.get_account(&pubkey).await;
+            results.push((pubkey, result));
+        }
+        bridge.send(move |world| {
+            world
+                .resource_mut::<AccountSubscriptionPoll>()
+                .timer
+                .finish();
+            for (pubkey, result) in results {
+                set_tracked_account(world, pubkey, result);
+            }
+        });
+    });
+}
+
+/// Writes a fetched account onto its trackers, a transport error keeps the last state.
+fn set_tracked_account(world: &mut World, pubkey: Pubkey, result: Result<Account>) {
+    let account = match result {
+        Ok(account) => Some(account),
+        Err(err) if ErrorCategory::of(&err) == ErrorCategory::AccountNotFound => None,
+        Err(err) => {
+            debug!("could not fetch tracked account {}: {:?}", pubkey, err);
+            return;
+        }
+    };
+
+    let trackers: Vec<Entity> = world
+        .resource::<AccountSubscriptions>()
+        .trackers
+        .iter()
+        .filter(|(_, tracked)| **tracked == pubkey)
+        .map(|(entity, _)| *entity)
+        .collect();
+    for entity in trackers {
+        let Some(mut entity) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        match &account {
+            Some(account) => {
+                // only replace changed accounts so `Changed<SolanaAccountData>` means an
+                // on-chain change
+                if entity.get::<SolanaAccountData>().map(|data| &data.0) != Some(account) {
+                    entity.insert((
+                        SolanaAccountPubkey(pubkey),
+                        SolanaAccountData(account.clone()),
+                    ));
+                }
+            }
+            None => {
+                let id = entity.id();
+                if let Some(SolanaAccountData(last)) = entity.take::<SolanaAccountData>() {
+                    send_account_closed(world, id, pubkey, last.owner);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use bevy_solana_client_common::{rpc_client::RpcClient, scan::RpcFilter};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{
+    account::{send_account_closed, SolanaAccountData, SolanaAccountPubkey},
+    bridge::AsyncBridge,
+    error::report_error,
+    poll::PollTimer,
+    rpc::SolanaRpc,
+    schedule::SolanaClientSet,
+};
+
+/// Accounts applied per bridge callback.
+const RECONCILE_CHUNK: usize = 256;
+
+/// Mirrors every account of `program_id` matching `filters` into an entity of its own,
+/// spawning, updating and despawning them as the program's accounts change.
+///
+/// The accounts are reconciled with a `getProgramAccounts` call every `interval`, large
+/// responses are applied over several frames within the [`BridgeBudget`](crate::bridge::BridgeBudget).
+/// Despawning the sync entity despawns its accounts. Register a decoder in the
+/// [`AccountDecoderRegistry`](crate::decoder::AccountDecoderRegistry) to get the accounts
+/// as typed components.
+#[derive(Debug, Clone, Component)]
+pub struct ProgramSync {
+    pub program_id: Pubkey,
+    pub filters: Vec<RpcFilter>,
+    pub interval: Duration,
+    timer: PollTimer,
+    accounts: HashMap<Pubkey, Entity>,
+    /// Spawned and updated accounts of the sync in flight.
+    progress: (usize, usize),
+}
+
+impl ProgramSync {
+    pub fn new(program_id: Pubkey) -> Self {
+        let interval = Duration::from_secs(10);
+
+        Self {
+            program_id,
+            filters: Vec::new(),
+            interval,
+            timer: PollTimer::new(interval),
+            accounts: HashMap::new(),
+            progress: (0, 0),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: RpcFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The entity mirroring `pubkey`.
+    pub fn get(&self, pubkey: &Pubkey) -> Option<Entity> {
+        self.accounts.get(pubkey).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Forgets the sync in flight and hands back the account entities to despawn, the
+    /// sync starts over on the next frame.
+    pub(crate) fn reset(&mut self) -> Vec<Entity> {
+        self.timer.reset();
+        self.progress = (0, 0);
+        self.accounts.drain().map(|(_, entity)| entity).collect()
+    }
+}
+
+/// An account entity spawned by the [`ProgramSync`] on `sync`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct SyncedAccount {
+    pub sync: Entity,
+}
+
+/// A [`ProgramSync`] finished reconciling its accounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct ProgramSynced {
+    pub sync: Entity,
+    pub spawned: usize,
+    pub updated: usize,
+    pub despawned: usize,
+}
+
+pub struct ProgramSyncPlugin;
+
+impl Plugin for ProgramSyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ProgramSynced>();
+        app.add_systems(
+            Update,
+            (program_sync_system, program_sync_removed_system).in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn program_sync_system(
+    time: Res<Time<Real>>,
+    mut syncs: Query<(Entity, &mut ProgramSync)>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    for (entity, mut sync) in &mut syncs {
+        let interval = sync.interval;
+        if !sync.timer.tick(interval, time.delta()) {
+            continue;
+        }
+
+        let program_id = sync.program_id;
+        let filters = sync.filters.clone();
+        let rpc = rpc.clone();
+        let bridge = bridge.clone();
+        bridge.clone().spawn(move || async move {
+            let accounts = match rpc
+                .get_program_accounts_with_filters(&program_id, &filters, rpc.commitment())
+                .await
+            {
+                Ok(accounts) => accounts,
+                Err(err) => {
+                    bridge.send(move |world| {
+                        if let Some(mut sync) = world.get_mut::<ProgramSync>(entity) {
+                            sync.timer.finish();
+                        }
+                        report_error(world, format!("sync program {}", program_id), &err);
+                    });
+                    return;
+                }
+            };
+
+            // large programs are applied over several frames within the bridge budget
+            let fetched: HashSet<Pubkey> = accounts.iter().map(|(_, pubkey)| *pubkey).collect();
+            let mut accounts = accounts.into_iter().peekable();
+            while accounts.peek().is_some() {
+                let chunk: Vec<_> = accounts.by_ref().take(RECONCILE_CHUNK).collect();
+                let bytes = chunk.iter().map(|(account, _)| account.data.len()).sum();
+                bridge.send_sized(bytes, move |world| upsert_accounts(world, entity, chunk));
+            }
+            bridge.send(move |world| finish_sync(world, entity, fetched));
+        });
+    }
+}
+
+fn upsert_accounts(world: &mut World, sync_entity: Entity, accounts: Vec<(Account, Pubkey)>) {
+    let Some(mut sync) = world.get_mut::<ProgramSync>(sync_entity) else {
+        return;
+    };
+    let mut known = std::mem::take(&mut sync.accounts);
+    let (mut spawned, mut updated) = (0, 0);
+
+    for (account, pubkey) in accounts {
+        let existing = known
+            .get(&pubkey)
+            .and_then(|entity| world.get_entity_mut(*entity));
+
+        let entity = match existing {
+            Some(mut entity) => {
+                if entity.get::<SolanaAccountData>().map(|data| &data.0) != Some(&account) {
+                    entity.insert(SolanaAccountData(account));
+                    updated += 1;
+                }
+                entity.id()
+            }
+            None => {
+                spawned += 1;
+                world
+                    .spawn((
+                        SyncedAccount { sync: sync_entity },
+                        SolanaAccountPubkey(pubkey),
+                        SolanaAccountData(account),
+                    ))
+                    .id()
+            }
+        };
+        known.insert(pubkey, entity);
+    }
+
+    if let Some(mut sync) = world.get_mut::<ProgramSync>(sync_entity) {
+        sync.accounts = known;
+        sync.progress.0 += spawned;
+        sync.progress.1 += updated;
+    }
+}
+
+/// Despawns the accounts missing from the `fetched` ones once every chunk was applied.
+fn finish_sync(world: &mut World, sync_entity: Entity, fetched: HashSet<Pubkey>) {
+    let Some(mut sync) = world.get_mut::<ProgramSync>(sync_entity) else {
+        return;
+    };
+    sync.timer.finish();
+    let (spawned, updated) = std::mem::take(&mut sync.progress);
+    let mut event = ProgramSynced {
+        sync: sync_entity,
+        spawned,
+        updated,
+        despawned: 0,
+    };
+
+    let mut gone = vec![];
+    sync.accounts.retain(|pubkey, entity| {
+        let keep = fetched.contains(pubkey);
+        if !keep {
+            gone.push((*pubkey, *entity));
+        }
+        keep
+    });
+
+    for (pubkey, entity) in gone {
+        let Some(entity) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        let id = entity.id();
+        let last_owner = entity.get::<SolanaAccountData>().map(|data| data.0.owner);
+        entity.despawn_recursive();
+        event.despawned += 1;
+        if let Some(last_owner) = last_owner {
+            send_account_closed(world, id, pubkey, last_owner);
+        }
+    }
+    world.send_event(event);
+}
+
+fn program_sync_removed_system(
+    mut commands: Commands,
+    mut removed: RemovedComponents<ProgramSync>,
+    synced: Query<(Entity, &SyncedAccount)>,
+) {
+    let removed: HashSet<Entity> = removed.read().collect();
+    if removed.is_empty() {
+        return;
+    }
+
+    for (entity, account) in &synced {
+        if removed.contains(&account.sync) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
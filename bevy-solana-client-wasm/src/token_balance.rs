@@ -0,0 +1,243 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::Result;
+use bevy::prelude::*;
+use bevy_solana_client_common::{
+    rpc_client::RpcClient,
+    token::{TokenAccountBalance, TokenAmount, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID},
+};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    bridge::AsyncBridge,
+    poll::PollTimer,
+    rpc::{SolanaRpc, SolanaRpcClient},
+    schedule::SolanaClientSet,
+    tracker::TransactionEvent,
+    Wallet, WalletEvent,
+};
+
+/// The connected wallet's token holdings under both token programs, by mint.
+///
+/// Filled by the [`TokenBalancePlugin`] right after connecting, then every
+/// [`TokenBalancePoll::interval`] and after each confirmed transaction. Empty while no
+/// wallet is connected.
+#[derive(Debug, Default, Resource)]
+pub struct TokenBalances {
+    owner: Option<Pubkey>,
+    by_mint: HashMap<Pubkey, TokenAmount>,
+    accounts: Vec<TokenAccountBalance>,
+}
+
+impl TokenBalances {
+    /// The wallet the balances belong to, `None` before the first fetch.
+    pub fn owner(&self) -> Option<Pubkey> {
+        self.owner
+    }
+
+    /// Total held of `mint` across the wallet's token accounts.
+    pub fn get(&self, mint: &Pubkey) -> Option<TokenAmount> {
+        self.by_mint.get(mint).copied()
+    }
+
+    /// Raw amount held of `mint`, 0 if the wallet has none.
+    pub fn amount(&self, mint: &Pubkey) -> u64 {
+        self.get(mint).map_or(0, |amount| amount.amount)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Pubkey, &TokenAmount)> {
+        self.by_mint.iter()
+    }
+
+    /// Every token account of the wallet, including empty ones.
+    pub fn accounts(&self) -> &[TokenAccountBalance] {
+        &self.accounts
+    }
+
+    fn clear(&mut self) -> Vec<(Pubkey, TokenAmount)> {
+        self.owner = None;
+        self.accounts.clear();
+        self.by_mint.drain().collect()
+    }
+}
+
+/// A balance of [`TokenBalances`] changed, `None` when the wallet holds no account of
+/// the mint.
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub struct TokenBalanceChanged {
+    pub mint: Pubkey,
+    pub previous: Option<TokenAmount>,
+    pub current: Option<TokenAmount>,
+}
+
+/// How often [`TokenBalances`] is refreshed.
+#[derive(Debug, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct TokenBalancePoll {
+    pub interval: Duration,
+    timer: PollTimer,
+}
+
+impl Default for TokenBalancePoll {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(15))
+    }
+}
+
+impl TokenBalancePoll {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            timer: PollTimer::new(interval),
+        }
+    }
+
+    /// Fetches the balances on the next frame.
+    pub fn refresh(&mut self) {
+        self.timer.refresh();
+    }
+
+    /// Forgets the request in flight, its response was cancelled.
+    pub(crate) fn reset(&mut self) {
+        self.timer.reset();
+    }
+}
+
+pub struct TokenBalancePlugin;
+
+impl Plugin for TokenBalancePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TokenBalances>();
+        app.init_resource::<TokenBalancePoll>();
+        app.register_type::<TokenBalancePoll>();
+        app.add_event::<TokenBalanceChanged>();
+        app.add_systems(
+            Update,
+            (token_balance_refresh_system, token_balance_system)
+                .chain()
+                .in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn token_balance_refresh_system(
+    mut wallet_events: EventReader<WalletEvent>,
+    mut tx_events: EventReader<TransactionEvent>,
+    mut poll: ResMut<TokenBalancePoll>,
+    mut balances: ResMut<TokenBalances>,
+    mut changed: EventWriter<TokenBalanceChanged>,
+) {
+    for event in wallet_events.read() {
+        match event {
+            WalletEvent::Connected | WalletEvent::AccountChanged(_) => poll.refresh(),
+            WalletEvent::Disconnected => {
+                for (mint, previous) in balances.clear() {
+                    changed.send(TokenBalanceChanged {
+                        mint,
+                        previous: Some(previous),
+                        current: None,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if tx_events
+        .read()
+        .any(|event| matches!(event, TransactionEvent::Confirmed { .. }))
+    {
+        poll.refresh();
+    }
+}
+
+fn token_balance_system(
+    time: Res<Time<Real>>,
+    mut poll: ResMut<TokenBalancePoll>,
+    wallet: Res<Wallet>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    let Some(owner) = wallet.info.as_ref().and_then(|info| info.pubkey().ok()) else {
+        return;
+    };
+
+    let interval = poll.interval;
+    if !poll.timer.tick(interval, time.delta()) {
+        return;
+    }
+
+    let rpc = rpc.clone();
+    let bridge = bridge.clone();
+    bridge.clone().spawn(move || async move {
+        let result = fetch_token_accounts(&rpc, &owner).await;
+        bridge.send(move |world| {
+            world.resource_mut::<TokenBalancePoll>().timer.finish();
+            match result {
+                Ok(accounts) => set_balances(world, owner, accounts),
+                Err(err) => debug!("could not fetch the wallet's token accounts: {:?}", err),
+            }
+        });
+    });
+}
+
+async fn fetch_token_accounts(
+    rpc: &SolanaRpcClient,
+    owner: &Pubkey,
+) -> Result<Vec<TokenAccountBalance>> {
+    let mut accounts = rpc
+        .get_token_accounts_by_owner(owner, &TOKEN_PROGRAM_ID)
+        .await?;
+    accounts.extend(
+        rpc.get_token_accounts_by_owner(owner, &TOKEN_2022_PROGRAM_ID)
+            .await?,
+    );
+    Ok(accounts)
+}
+
+fn set_balances(world: &mut World, owner: Pubkey, accounts: Vec<TokenAccountBalance>) {
+    // the wallet switched accounts while the balances were fetched
+    let connected = world
+        .resource::<Wallet>()
+        .info
+        .as_ref()
+        .and_then(|info| info.pubkey().ok());
+    if connected != Some(owner) {
+        return;
+    }
+
+    let mut by_mint: HashMap<Pubkey, TokenAmount> = HashMap::new();
+    for account in &accounts {
+        by_mint
+            .entry(account.mint)
+            .and_modify(|total| total.amount = total.amount.saturating_add(account.amount.amount))
+            .or_insert(account.amount);
+    }
+
+    let mut balances = world.resource_mut::<TokenBalances>();
+    let previous = std::mem::replace(&mut balances.by_mint, by_mint);
+    balances.owner = Some(owner);
+    balances.accounts = accounts;
+
+    let mut events: Vec<TokenBalanceChanged> = balances
+        .by_mint
+        .iter()
+        .filter(|(mint, amount)| previous.get(*mint) != Some(*amount))
+        .map(|(mint, amount)| TokenBalanceChanged {
+            mint: *mint,
+            previous: previous.get(mint).copied(),
+            current: Some(*amount),
+        })
+        .collect();
+    events.extend(
+        previous
+            .into_iter()
+            .filter(|(mint, _)| !balances.by_mint.contains_key(mint))
+            .map(|(mint, amount)| TokenBalanceChanged {
+                mint,
+                previous: Some(amount),
+                current: None,
+            }),
+    );
+    world.send_event_batch(events);
+}
@@ -0,0 +1,261 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_solana_client_common::{
+    error::decode_transaction_error,
+    program_error::{
+        transaction_program_ids, versioned_transaction_program_ids, ProgramErrorEntry,
+    },
+    rpc_client::{ConfirmationStatus, RpcSignatureStatus},
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{Transaction, TransactionError, VersionedTransaction},
+};
+
+use crate::{
+    error::SolanaErrorCatalog,
+    schedule::SolanaClientSet,
+    signature_status::{SignatureStatusCache, TrackedTransaction},
+};
+
+/// Where a submitted transaction is on its way to being finalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+pub enum TransactionState {
+    /// Sent, the cluster has not reported it yet.
+    Submitted,
+    Processed,
+    Confirmed,
+    Finalized,
+    /// Landed with an error, nothing but the fee was applied.
+    Failed,
+    /// Never showed up before its blockhash ran out, it can no longer land.
+    Expired,
+}
+
+impl TransactionState {
+    /// Whether the state can no longer change.
+    pub fn is_final(self) -> bool {
+        matches!(
+            self,
+            TransactionState::Finalized | TransactionState::Failed | TransactionState::Expired
+        )
+    }
+
+    fn from_status(status: &RpcSignatureStatus) -> Self {
+        if status.err.is_some() {
+            return TransactionState::Failed;
+        }
+        match status.confirmation_status {
+            Some(ConfirmationStatus::Processed) => TransactionState::Processed,
+            Some(ConfirmationStatus::Confirmed) => TransactionState::Confirmed,
+            Some(ConfirmationStatus::Finalized) => TransactionState::Finalized,
+            // old nodes only report the confirmation count, `None` once rooted
+            None if status.confirmations.is_none() => TransactionState::Finalized,
+            None => TransactionState::Confirmed,
+        }
+    }
+}
+
+/// Follows a submitted transaction through its [`TransactionState`]s, spawn it right
+/// after sending.
+///
+/// The status is polled through the [`SignatureStatusCache`], changes are sent as
+/// [`TransactionStateChanged`] events.
+#[derive(Debug, Clone, PartialEq, Eq, Component, Reflect)]
+#[reflect(Component)]
+pub struct TransactionTracker {
+    #[reflect(ignore)]
+    pub signature: Signature,
+    pub state: TransactionState,
+    /// How long the transaction may go unseen before it is considered expired, a
+    /// blockhash is valid for about 150 blocks.
+    pub expires_after: Duration,
+    /// The programs of the transaction's instructions in order, resolves custom errors
+    /// to the program that failed, see
+    /// [`ProgramErrorTable::describe`](bevy_solana_client_common::program_error::ProgramErrorTable::describe).
+    #[reflect(ignore)]
+    pub program_ids: Vec<Pubkey>,
+    submitted_at: Option<Duration>,
+}
+
+impl TransactionTracker {
+    pub fn new(signature: Signature) -> Self {
+        Self {
+            signature,
+            state: TransactionState::Submitted,
+            expires_after: Duration::from_secs(90),
+            program_ids: Vec::new(),
+            submitted_at: None,
+        }
+    }
+
+    /// Tracks `tx`, with the programs of its instructions.
+    pub fn for_transaction(tx: &Transaction) -> Self {
+        Self::new(tx.signatures.first().copied().unwrap_or_default())
+            .with_program_ids(transaction_program_ids(tx))
+    }
+
+    /// Tracks a versioned `tx`, with the programs of its instructions.
+    pub fn for_versioned_transaction(tx: &VersionedTransaction) -> Self {
+        Self::new(tx.signatures.first().copied().unwrap_or_default())
+            .with_program_ids(versioned_transaction_program_ids(tx))
+    }
+
+    pub fn with_expiry(mut self, expires_after: Duration) -> Self {
+        self.expires_after = expires_after;
+        self
+    }
+
+    pub fn with_program_ids(mut self, program_ids: Vec<Pubkey>) -> Self {
+        self.program_ids = program_ids;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct TransactionStateChanged {
+    pub entity: Entity,
+    pub signature: Signature,
+    pub old: TransactionState,
+    pub new: TransactionState,
+}
+
+/// Lifecycle of every [`TransactionTracker`], for toasts and gameplay reacting to
+/// transactions without looking at their states.
+#[derive(Debug, Clone, PartialEq, Event)]
+pub enum TransactionEvent {
+    Submitted {
+        signature: Signature,
+    },
+    /// Confirmed by a supermajority of the cluster, sent once even if the transaction
+    /// skips straight to finalized.
+    Confirmed {
+        signature: Signature,
+        slot: u64,
+    },
+    Failed {
+        signature: Signature,
+        slot: u64,
+        /// The decoded error, e.g. `InstructionError(0, Custom(6000))` for an Anchor
+        /// error code, `None` if it could not be decoded.
+        error: Option<TransactionError>,
+        /// The error as reported by the node.
+        raw_error: serde_json::Value,
+        /// The custom error of `error` in the catalog's
+        /// [`ProgramErrorTable`](bevy_solana_client_common::program_error::ProgramErrorTable).
+        program_error: Option<ProgramErrorEntry>,
+    },
+    Expired {
+        signature: Signature,
+    },
+}
+
+pub struct TransactionTrackerPlugin;
+
+impl Plugin for TransactionTrackerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TransactionStateChanged>();
+        app.add_event::<TransactionEvent>();
+        app.register_type::<TransactionTracker>();
+        app.add_systems(
+            Update,
+            transaction_tracker_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn transaction_tracker_system(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    cache: Res<SignatureStatusCache>,
+    catalog: Res<SolanaErrorCatalog>,
+    mut trackers: Query<(Entity, &mut TransactionTracker, Option<&TrackedTransaction>)>,
+    mut ev_writer: EventWriter<TransactionStateChanged>,
+    mut lifecycle_writer: EventWriter<TransactionEvent>,
+) {
+    for (entity, mut tracker, tracked) in &mut trackers {
+        if tracker.state.is_final() {
+            continue;
+        }
+
+        let submitted_at = match tracker.submitted_at {
+            Some(submitted_at) => submitted_at,
+            None => {
+                tracker.submitted_at = Some(time.elapsed());
+                lifecycle_writer.send(TransactionEvent::Submitted {
+                    signature: tracker.signature,
+                });
+                time.elapsed()
+            }
+        };
+        if tracked.map(|t| t.signature) != Some(tracker.signature) {
+            commands.entity(entity).insert(TrackedTransaction {
+                signature: tracker.signature,
+            });
+        }
+
+        let status = cache.get(&tracker.signature);
+        let new = match status {
+            Some(status) => TransactionState::from_status(status),
+            None if time.elapsed() - submitted_at > tracker.expires_after => {
+                TransactionState::Expired
+            }
+            None => TransactionState::Submitted,
+        };
+        // statuses only move forward, a lagging node must not undo a confirmation
+        if new <= tracker.state {
+            continue;
+        }
+
+        let old = tracker.state;
+        tracker.state = new;
+        debug!("transaction {} {:?} -> {:?}", tracker.signature, old, new);
+
+        if matches!(new, TransactionState::Failed | TransactionState::Expired) {
+            // nothing left to poll for
+            commands.entity(entity).remove::<TrackedTransaction>();
+        }
+        ev_writer.send(TransactionStateChanged {
+            entity,
+            signature: tracker.signature,
+            old,
+            new,
+        });
+
+        let signature = tracker.signature;
+        let lifecycle = match (new, status) {
+            (TransactionState::Failed, Some(status)) => {
+                let raw_error = status.err.clone().unwrap_or_default();
+                let error = decode_transaction_error(&raw_error);
+                let program_error = error.as_ref().and_then(|error| {
+                    catalog
+                        .program_errors()
+                        .describe(error, &tracker.program_ids)
+                        .cloned()
+                });
+                Some(TransactionEvent::Failed {
+                    signature,
+                    slot: status.slot,
+                    error,
+                    raw_error,
+                    program_error,
+                })
+            }
+            (TransactionState::Confirmed | TransactionState::Finalized, Some(status))
+                if old < TransactionState::Confirmed =>
+            {
+                Some(TransactionEvent::Confirmed {
+                    signature,
+                    slot: status.slot,
+                })
+            }
+            (TransactionState::Expired, _) => Some(TransactionEvent::Expired { signature }),
+            _ => None,
+        };
+        if let Some(event) = lifecycle {
+            lifecycle_writer.send(event);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::{str::FromStr, time::Duration};
+
+use anyhow::Result;
+use bevy::prelude::*;
+use bevy_solana_client_common::{
+    rpc_client::{RpcClient, RpcConfirmedTransaction},
+    token::TokenAmount,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+use crate::{
+    bridge::AsyncBridge,
+    error::report_error,
+    rpc::{SolanaRpc, SolanaRpcClient},
+    schedule::SolanaClientSet,
+    session_summary::owned_balances,
+    watch_only::{fetch_holdings, WalletHoldings},
+};
+
+/// Lamports and tokens that left the treasury in one transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreasuryOutflow {
+    pub signature: Signature,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Lamports the treasury lost, including the fee if it paid it.
+    pub lamports: u64,
+    /// Tokens the treasury's token accounts lost, per mint.
+    pub tokens: Vec<(Pubkey, TokenAmount)>,
+}
+
+impl TreasuryOutflow {
+    /// The outflow of `treasury` in `tx`, `None` if nothing left it.
+    pub fn from_transaction(
+        tx: &RpcConfirmedTransaction,
+        signature: Signature,
+        treasury: &Pubkey,
+    ) -> Option<Self> {
+        let meta = tx.meta.as_ref()?;
+        let treasury_str = treasury.to_string();
+
+        let lamports = tx
+            .account_keys()
+            .position(|key| key == treasury_str)
+            .and_then(|i| {
+                Some(
+                    meta.pre_balances
+                        .get(i)?
+                        .saturating_sub(*meta.post_balances.get(i)?),
+                )
+            })
+            .unwrap_or_default();
+
+        let pre = owned_balances(&meta.pre_token_balances, &treasury_str);
+        let post = owned_balances(&meta.post_token_balances, &treasury_str);
+        let tokens: Vec<(Pubkey, TokenAmount)> = pre
+            .iter()
+            .filter_map(|(mint, (pre_amount, decimals))| {
+                let post_amount = post.get(mint).map_or(0, |(amount, _)| *amount);
+                let spent = pre_amount
+                    .checked_sub(post_amount)
+                    .filter(|spent| *spent > 0)?;
+                Some((mint.parse().ok()?, TokenAmount::new(spent, *decimals)))
+            })
+            .collect();
+
+        if lamports == 0 && tokens.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            signature,
+            slot: tx.slot,
+            block_time: tx.block_time,
+            lamports,
+            tokens,
+        })
+    }
+}
+
+/// A shared bank (multisig vault, guild or DAO treasury) hydrated for display.
+///
+/// Set the address with [`ViewTreasury`], balances and recent outflows are refreshed
+/// periodically while an address is set.
+#[derive(Debug, Resource)]
+pub struct TreasuryView {
+    pub address: Option<Pubkey>,
+    pub holdings: WalletHoldings,
+    /// Most recent outflows, newest first.
+    pub outflows: Vec<TreasuryOutflow>,
+    /// How many recent transactions are inspected for outflows.
+    pub history: usize,
+    pub refresh: Timer,
+    /// Whether the data belongs to `address` yet.
+    loaded: bool,
+}
+
+impl Default for TreasuryView {
+    fn default() -> Self {
+        Self {
+            address: None,
+            holdings: WalletHoldings::default(),
+            outflows: Vec::new(),
+            history: 20,
+            refresh: Timer::new(Duration::from_secs(30), TimerMode::Repeating),
+            loaded: false,
+        }
+    }
+}
+
+impl TreasuryView {
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+}
+
+/// Switches the [`TreasuryView`] to another address, `None` clears it.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct ViewTreasury(pub Option<Pubkey>);
+
+pub struct TreasuryPlugin;
+
+impl Plugin for TreasuryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TreasuryView>();
+        app.add_event::<ViewTreasury>();
+        app.add_systems(
+            Update,
+            treasury_refresh_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn treasury_refresh_system(
+    time: Res<Time>,
+    mut view: ResMut<TreasuryView>,
+    mut ev_reader: EventReader<ViewTreasury>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    let switched = ev_reader.read().last().copied();
+    if let Some(ViewTreasury(address)) = switched {
+        *view = TreasuryView {
+            address,
+            history: view.history,
+            refresh: view.refresh.clone(),
+            ..Default::default()
+        };
+        view.refresh.reset();
+    }
+
+    let due = view.refresh.tick(time.delta()).just_finished() || switched.is_some();
+    let Some(address) = view.address.filter(|_| due) else {
+        return;
+    };
+
+    let history = view.history;
+    let rpc = rpc.clone();
+    let bridge = bridge.clone();
+    bridge.clone().spawn(move || async move {
+        let result = fetch_treasury(&rpc, &address, history).await;
+        bridge.send(move |world| match result {
+            Ok((holdings, outflows)) => {
+                let mut view = world.resource_mut::<TreasuryView>();
+                // ignore responses for an address the player navigated away from
+                if view.address == Some(address) {
+                    view.holdings = holdings;
+                    view.outflows = outflows;
+                    view.loaded = true;
+                }
+            }
+            Err(err) => report_error(world, format!("refresh treasury {}", address), &err),
+        });
+    });
+}
+
+async fn fetch_treasury(
+    rpc: &SolanaRpcClient,
+    address: &Pubkey,
+    history: usize,
+) -> Result<(WalletHoldings, Vec<TreasuryOutflow>)> {
+    let holdings = fetch_holdings(rpc, address).await?;
+
+    let mut outflows = Vec::new();
+    for confirmed in rpc.get_signatures_for_address(address, history).await? {
+        let Ok(signature) = Signature::from_str(&confirmed.signature) else {
+            continue;
+        };
+        // one missing transaction must not hide the others
+        match rpc.get_transaction(&signature).await {
+            Ok(Some(tx)) => {
+                outflows.extend(TreasuryOutflow::from_transaction(&tx, signature, address))
+            }
+            Ok(None) => {}
+            Err(err) => warn!(
+                "could not fetch treasury transaction {}: {:#}",
+                signature, err
+            ),
+        }
+    }
+
+    Ok((holdings, outflows))
+}
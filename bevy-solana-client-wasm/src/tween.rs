@@ -0,0 +1,195 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use solana_sdk::account::Account;
+
+use crate::{account::SolanaAccountData, schedule::SolanaClientSet};
+
+/// Easing curve applied while an [`AnimatedValue`] moves to a new on-chain value.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    #[default]
+    EaseOutCubic,
+    EaseInOutCubic,
+}
+
+impl Easing {
+    /// Maps linear progress `t` in `0..=1` onto the curve.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOutCubic if t < 0.5 => 4.0 * t * t * t,
+            Easing::EaseInOutCubic => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+        }
+    }
+}
+
+/// Binds a numeric field of a mirrored account to the [`AnimatedValue`] of this entity.
+///
+/// `source` is the entity carrying the account's [`SolanaAccountData`], e.g. a health bar
+/// bound to the on-chain HP field of a player account.
+#[derive(Debug, Clone, Component)]
+pub struct AccountValueBinding {
+    pub source: Entity,
+    pub extract: fn(&Account) -> Option<f64>,
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+impl AccountValueBinding {
+    pub fn new(source: Entity, extract: fn(&Account) -> Option<f64>) -> Self {
+        Self {
+            source,
+            extract,
+            duration: Duration::from_millis(300),
+            easing: Easing::default(),
+        }
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+}
+
+/// A value that tweens towards its latest target instead of snapping to it.
+///
+/// The first value seen is applied immediately, later ones are interpolated from
+/// wherever the previous animation currently is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Component)]
+pub struct AnimatedValue {
+    current: f64,
+    start: f64,
+    target: f64,
+    elapsed: Duration,
+    duration: Duration,
+    easing: Easing,
+    initialized: bool,
+}
+
+impl AnimatedValue {
+    /// The interpolated value for this frame.
+    pub fn get(&self) -> f64 {
+        self.current
+    }
+
+    /// The value being animated towards.
+    pub fn target(&self) -> f64 {
+        self.target
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.initialized && self.elapsed < self.duration
+    }
+
+    /// Starts animating from the current value to `target`.
+    pub fn animate_to(&mut self, target: f64, duration: Duration, easing: Easing) {
+        if !self.initialized || duration.is_zero() {
+            self.snap_to(target);
+            return;
+        }
+
+        self.start = self.current;
+        self.target = target;
+        self.elapsed = Duration::ZERO;
+        self.duration = duration;
+        self.easing = easing;
+    }
+
+    /// Jumps to `value` without animating.
+    pub fn snap_to(&mut self, value: f64) {
+        *self = Self {
+            current: value,
+            start: value,
+            target: value,
+            initialized: true,
+            ..Default::default()
+        };
+    }
+
+    fn tick(&mut self, delta: Duration) {
+        if !self.is_animating() {
+            return;
+        }
+
+        self.elapsed = (self.elapsed + delta).min(self.duration);
+        let t = self.elapsed.as_secs_f64() / self.duration.as_secs_f64();
+        self.current = self.start + (self.target - self.start) * self.easing.apply(t);
+    }
+}
+
+/// Reads a little endian `u64` at `offset` of the account data.
+pub fn read_u64_le(account: &Account, offset: usize) -> Option<u64> {
+    let bytes = account.data.get(offset..offset + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Reads a little endian `u32` at `offset` of the account data.
+pub fn read_u32_le(account: &Account, offset: usize) -> Option<u32> {
+    let bytes = account.data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+pub struct AccountValueTweenPlugin;
+
+impl Plugin for AccountValueTweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (update_bound_values_system, tween_values_system)
+                .chain()
+                .in_set(SolanaClientSet::Ui),
+        );
+    }
+}
+
+fn update_bound_values_system(
+    mut commands: Commands,
+    mut bindings: Query<(Entity, Ref<AccountValueBinding>, Option<&mut AnimatedValue>)>,
+    accounts: Query<Ref<SolanaAccountData>>,
+) {
+    for (entity, binding, value) in &mut bindings {
+        let Ok(account) = accounts.get(binding.source) else {
+            continue;
+        };
+        if !account.is_changed() && !binding.is_changed() {
+            continue;
+        }
+        let Some(target) = (binding.extract)(&account.0) else {
+            continue;
+        };
+
+        match value {
+            Some(mut value) if value.target() != target || !value.is_initialized() => {
+                value.animate_to(target, binding.duration, binding.easing)
+            }
+            Some(_) => {}
+            None => {
+                let mut value = AnimatedValue::default();
+                value.snap_to(target);
+                commands.entity(entity).insert(value);
+            }
+        }
+    }
+}
+
+fn tween_values_system(time: Res<Time>, mut values: Query<&mut AnimatedValue>) {
+    for mut value in &mut values {
+        // only touch animating values, so change detection stays meaningful
+        if value.is_animating() {
+            value.tick(time.delta());
+        }
+    }
+}
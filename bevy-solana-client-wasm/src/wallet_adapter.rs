@@ -0,0 +1,614 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    pin::pin,
+    rc::Rc,
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use bevy::prelude::*;
+use bevy_solana_client_common::{
+    rpc_client::RpcClient, signer::GameSigner, versioned::signer_index,
+};
+use futures_util::future::{select, Either};
+use solana_sdk::{
+    bs58,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{Transaction, VersionedTransaction},
+};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+use crate::{bridge::AsyncBridge, reflect_get};
+
+/// Browser wallets injected into `window` that the plugin can talk to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum WalletAdapter {
+    /// `window.phantom.solana`, or `window.solana` with `isPhantom` set.
+    Phantom,
+    /// `window.solflare` with `isSolflare` set.
+    Solflare,
+}
+
+impl WalletAdapter {
+    /// Every supported wallet, in order of preference.
+    pub const ALL: [WalletAdapter; 2] = [WalletAdapter::Phantom, WalletAdapter::Solflare];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            WalletAdapter::Phantom => "Phantom",
+            WalletAdapter::Solflare => "Solflare",
+        }
+    }
+
+    /// Brand color, used where no icon is available.
+    pub fn color(self) -> Color {
+        match self {
+            WalletAdapter::Phantom => Color::srgb_u8(0xab, 0x9f, 0xf2),
+            WalletAdapter::Solflare => Color::srgb_u8(0xfc, 0x7d, 0x1a),
+        }
+    }
+
+    /// Returns the first installed wallet of [`WalletAdapter::ALL`].
+    pub fn detect() -> Option<WalletAdapter> {
+        Self::ALL.into_iter().find(|adapter| adapter.is_installed())
+    }
+
+    /// Returns every installed wallet, in order of preference.
+    pub fn detect_all() -> Vec<WalletAdapter> {
+        Self::ALL
+            .into_iter()
+            .filter(|adapter| adapter.is_installed())
+            .collect()
+    }
+
+    pub fn is_installed(self) -> bool {
+        self.provider().is_ok()
+    }
+
+    /// The injected provider object of this wallet.
+    pub fn provider(self) -> Result<JsValue> {
+        let window = web_sys::window().context("could not get window")?;
+
+        let (candidates, flag): (&[&str], _) = match self {
+            WalletAdapter::Phantom => (&["phantom.solana", "solana"], "isPhantom"),
+            WalletAdapter::Solflare => (&["solflare"], "isSolflare"),
+        };
+
+        for path in candidates {
+            let mut provider: JsValue = window.clone().into();
+            for key in path.split('.') {
+                if !provider.is_object() {
+                    break;
+                }
+                provider = reflect_get(&provider, &JsValue::from_str(key))?;
+            }
+
+            if provider.is_object() && reflect_get(&provider, &JsValue::from_str(flag))? == true {
+                return Ok(provider);
+            }
+        }
+
+        bail!("{} wallet is not installed", self.name())
+    }
+
+    /// Asks the wallet to connect and returns the base58 address of the selected account.
+    pub async fn connect(self) -> Result<String> {
+        debug!("connect to {}", self.name());
+        self.connect_with_args(&[]).await
+    }
+
+    /// Reconnects without a popup if the player approved the game before, fails if
+    /// they did not.
+    pub async fn connect_trusted(self) -> Result<String> {
+        debug!("connect to {} if trusted", self.name());
+        let options = js_sys::Object::new();
+        js_sys::Reflect::set(
+            &options,
+            &JsValue::from_str("onlyIfTrusted"),
+            &JsValue::TRUE,
+        )
+        .map_err(|err| anyhow!("{err:?}"))?;
+
+        self.connect_with_args(&[options.into()]).await
+    }
+
+    async fn connect_with_args(self, args: &[JsValue]) -> Result<String> {
+        let provider = self.provider()?;
+
+        // phantom resolves with `{ publicKey }`, solflare with a boolean and sets
+        // `publicKey` on the provider instead
+        let result = call_method(&provider, "connect", args).await?;
+        let public_key = match reflect_get(&result, &JsValue::from_str("publicKey")) {
+            Ok(public_key) if !public_key.is_undefined() => public_key,
+            _ => reflect_get(&provider, &JsValue::from_str("publicKey"))?,
+        };
+
+        if public_key.is_null() || public_key.is_undefined() {
+            bail!("{} did not return a public key", self.name());
+        }
+
+        let address = public_key_to_string(&public_key)?;
+
+        debug!("connected to {}: {}", self.name(), address);
+        Ok(address)
+    }
+
+    /// Like [`WalletAdapter::connect`], but fails with [`ConnectTimedOut`] if the wallet
+    /// does not answer in time, e.g. because its popup was closed.
+    ///
+    /// Rejections that look like a blocked popup fail with [`PopupLikelyBlocked`].
+    pub async fn connect_with_timeout(self, timeout: Option<Duration>) -> Result<String> {
+        // a missing wallet fails just as fast as a blocked popup
+        self.provider()?;
+
+        let focus = FocusWatcher::new();
+        let started = js_sys::Date::now();
+
+        let result = match timeout {
+            Some(timeout) => {
+                let connect = pin!(self.connect());
+                let sleep = pin!(gloo_timers::future::sleep(timeout));
+
+                match select(connect, sleep).await {
+                    Either::Left((result, _)) => result,
+                    Either::Right(_) => {
+                        return Err(ConnectTimedOut {
+                            adapter: self,
+                            timeout,
+                        }
+                        .into())
+                    }
+                }
+            }
+            None => self.connect().await,
+        };
+
+        let elapsed = Duration::from_millis((js_sys::Date::now() - started).max(0.0) as u64);
+        result.map_err(|err| {
+            if is_popup_likely_blocked(&err, elapsed, focus.lost()) {
+                PopupLikelyBlocked {
+                    adapter: self,
+                    reason: format!("{:#}", err),
+                }
+                .into()
+            } else {
+                err
+            }
+        })
+    }
+
+    pub async fn disconnect(self) -> Result<()> {
+        debug!("disconnect from {}", self.name());
+        call_method(&self.provider()?, "disconnect", &[]).await?;
+        Ok(())
+    }
+
+    /// Signs arbitrary bytes off-chain, e.g. a server nonce to authenticate the player.
+    ///
+    /// Check the result with [`verify_message`] before trusting it.
+    pub async fn sign_message(self, message: &[u8]) -> Result<Signature> {
+        let provider = self.provider()?;
+        let message: JsValue = js_sys::Uint8Array::from(message).into();
+        let result = call_method(&provider, "signMessage", &[message]).await?;
+
+        // phantom resolves with `{ signature }`, solflare with the signature itself
+        let signature = if result.is_instance_of::<js_sys::Uint8Array>() {
+            result
+        } else {
+            reflect_get(&result, &JsValue::from_str("signature"))?
+        };
+
+        if !signature.is_instance_of::<js_sys::Uint8Array>() {
+            bail!("{} returned an invalid signature", self.name());
+        }
+
+        let signature = js_sys::Uint8Array::from(signature).to_vec();
+        Signature::try_from(signature)
+            .map_err(|_| anyhow!("{} returned a signature of invalid length", self.name()))
+    }
+
+    /// Has the wallet sign `tx` and submit it itself, returning the signature.
+    ///
+    /// Providers without `signAndSendTransaction` only sign, and the transaction is
+    /// sent through `rpc` instead.
+    pub async fn sign_and_send_transaction(
+        self,
+        tx: &mut Transaction,
+        rpc: &impl RpcClient,
+    ) -> Result<Signature> {
+        match self.sign_and_submit_transaction(tx).await? {
+            Some(signature) => Ok(signature),
+            None => rpc.send_transaction(tx).await,
+        }
+    }
+
+    /// Has the wallet sign and submit `tx`, or only sign it if the provider lacks
+    /// `signAndSendTransaction` or `tx` already carries signatures of other signers, which
+    /// the wallet would drop. Returns the signature if the wallet sent `tx`.
+    pub async fn sign_and_submit_transaction(
+        self,
+        tx: &mut Transaction,
+    ) -> Result<Option<Signature>> {
+        let provider = self.provider()?;
+
+        if has_signatures(&tx.signatures) || !has_method(&provider, "signAndSendTransaction")? {
+            debug!("{} signs, the transaction is sent separately", self.name());
+            self.sign_transaction(tx).await?;
+            return Ok(None);
+        }
+
+        let result = request(&provider, "signAndSendTransaction", &tx.message_data()).await?;
+        let (signature, signer) = parse_signature(&result)?;
+        if let Some(index) = tx
+            .message
+            .signer_keys()
+            .iter()
+            .position(|key| **key == signer)
+        {
+            tx.signatures[index] = signature;
+        }
+        Ok(Some(signature))
+    }
+
+    /// Has the wallet add its signature to `tx`.
+    pub async fn sign_transaction(self, tx: &mut Transaction) -> Result<()> {
+        let provider = self.provider()?;
+        let result = request(&provider, "signTransaction", &tx.message_data()).await?;
+        let (signature, signer) = parse_signature(&result)?;
+
+        let index = tx
+            .message
+            .signer_keys()
+            .iter()
+            .position(|key| **key == signer)
+            .with_context(|| format!("{} is not a signer of the transaction", signer))?;
+        tx.signatures[index] = signature;
+        Ok(())
+    }
+
+    /// [`sign_and_send_transaction`](Self::sign_and_send_transaction) for legacy and v0
+    /// messages.
+    pub async fn sign_and_send_versioned_transaction(
+        self,
+        tx: &mut VersionedTransaction,
+        rpc: &impl RpcClient,
+    ) -> Result<Signature> {
+        match self.sign_and_submit_versioned_transaction(tx).await? {
+            Some(signature) => Ok(signature),
+            None => rpc.send_versioned_transaction(tx).await,
+        }
+    }
+
+    /// [`sign_and_submit_transaction`](Self::sign_and_submit_transaction) for legacy and
+    /// v0 messages.
+    pub async fn sign_and_submit_versioned_transaction(
+        self,
+        tx: &mut VersionedTransaction,
+    ) -> Result<Option<Signature>> {
+        let provider = self.provider()?;
+
+        if has_signatures(&tx.signatures) || !has_method(&provider, "signAndSendTransaction")? {
+            self.sign_versioned_transaction(tx).await?;
+            return Ok(None);
+        }
+
+        let result = request(&provider, "signAndSendTransaction", &tx.message.serialize()).await?;
+        let (signature, signer) = parse_signature(&result)?;
+        if let Some(index) = signer_index(&tx.message, &signer) {
+            tx.signatures[index] = signature;
+        }
+        Ok(Some(signature))
+    }
+
+    /// Has the wallet add its signature to a legacy or v0 `tx`.
+    pub async fn sign_versioned_transaction(self, tx: &mut VersionedTransaction) -> Result<()> {
+        let provider = self.provider()?;
+        let result = request(&provider, "signTransaction", &tx.message.serialize()).await?;
+        let (signature, signer) = parse_signature(&result)?;
+
+        let index = signer_index(&tx.message, &signer)
+            .with_context(|| format!("{} is not a signer of the transaction", signer))?;
+        tx.signatures[index] = signature;
+        Ok(())
+    }
+}
+
+/// [`GameSigner`] signing through the browser wallet connected as `pubkey`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalletSigner {
+    pub adapter: WalletAdapter,
+    pub pubkey: Pubkey,
+}
+
+#[async_trait::async_trait(?Send)]
+impl GameSigner for WalletSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_transaction(&self, tx: &mut Transaction) -> Result<()> {
+        self.adapter.sign_transaction(tx).await
+    }
+
+    async fn sign_versioned_transaction(&self, tx: &mut VersionedTransaction) -> Result<()> {
+        self.adapter.sign_versioned_transaction(tx).await
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        self.adapter.sign_message(message).await
+    }
+
+    async fn sign_and_submit_transaction(&self, tx: &mut Transaction) -> Result<Option<Signature>> {
+        self.adapter.sign_and_submit_transaction(tx).await
+    }
+
+    async fn sign_and_submit_versioned_transaction(
+        &self,
+        tx: &mut VersionedTransaction,
+    ) -> Result<Option<Signature>> {
+        self.adapter.sign_and_submit_versioned_transaction(tx).await
+    }
+}
+
+/// Whether any signature is already present, `signAndSendTransaction` only takes the
+/// message and would send the transaction without them.
+fn has_signatures(signatures: &[Signature]) -> bool {
+    signatures
+        .iter()
+        .any(|signature| *signature != Signature::default())
+}
+
+/// Sends a `request` style call with the base58 encoded transaction message.
+async fn request(provider: &JsValue, method: &str, message: &[u8]) -> Result<JsValue> {
+    let params = js_sys::Object::new();
+    js_sys::Reflect::set(
+        &params,
+        &JsValue::from_str("message"),
+        &JsValue::from_str(&bs58::encode(message).into_string()),
+    )
+    .map_err(|err| anyhow!("{err:?}"))?;
+
+    let args = js_sys::Object::new();
+    js_sys::Reflect::set(
+        &args,
+        &JsValue::from_str("method"),
+        &JsValue::from_str(method),
+    )
+    .map_err(|err| anyhow!("{err:?}"))?;
+    js_sys::Reflect::set(&args, &JsValue::from_str("params"), &params)
+        .map_err(|err| anyhow!("{err:?}"))?;
+
+    call_method(provider, "request", &[args.into()]).await
+}
+
+fn has_method(provider: &JsValue, method: &str) -> Result<bool> {
+    Ok(reflect_get(provider, &JsValue::from_str(method))?.is_function())
+}
+
+/// Parses the `{ signature, publicKey }` object wallets answer signing requests with.
+fn parse_signature(result: &JsValue) -> Result<(Signature, Pubkey)> {
+    let signature = reflect_get(result, &JsValue::from_str("signature"))?
+        .as_string()
+        .context("wallet did not return a signature")?;
+    let public_key = reflect_get(result, &JsValue::from_str("publicKey"))?;
+    let public_key = match public_key.as_string() {
+        Some(public_key) => public_key,
+        None => public_key_to_string(&public_key)?,
+    };
+
+    Ok((
+        Signature::from_str(&signature).context("wallet returned an invalid signature")?,
+        Pubkey::from_str(&public_key).context("wallet returned an invalid public key")?,
+    ))
+}
+
+/// Converts a web3.js `PublicKey` into its base58 address.
+fn public_key_to_string(public_key: &JsValue) -> Result<String> {
+    let to_string: js_sys::Function =
+        reflect_get(public_key, &JsValue::from_str("toString"))?.into();
+    to_string
+        .call0(public_key)
+        .map_err(|err| anyhow!("{:?}", err))?
+        .as_string()
+        .context("could not convert pubkey to string")
+}
+
+thread_local! {
+    static PROVIDER_LISTENERS: RefCell<Option<ProviderListeners>> = const { RefCell::new(None) };
+}
+
+type ProviderCallback = Closure<dyn FnMut(JsValue)>;
+
+/// JS listeners registered on the provider of the connected wallet.
+struct ProviderListeners {
+    provider: JsValue,
+    listeners: Vec<(&'static str, ProviderCallback)>,
+}
+
+impl Drop for ProviderListeners {
+    fn drop(&mut self) {
+        for (event, closure) in &self.listeners {
+            let closure: &JsValue = closure.as_ref();
+            if let Err(err) = call_sync(
+                &self.provider,
+                "removeListener",
+                &[JsValue::from_str(event), closure.clone()],
+            ) {
+                debug!("could not remove {} listener: {:?}", event, err);
+            }
+        }
+    }
+}
+
+/// Forwards the provider's `accountChanged` and `disconnect` events as
+/// [`WalletEvent`]s, replacing the listeners of a previous connection.
+pub(crate) fn listen_to_provider(adapter: WalletAdapter, bridge: AsyncBridge) -> Result<()> {
+    stop_listening_to_provider();
+    let provider = adapter.provider()?;
+
+    let account_bridge = bridge.clone();
+    let account_changed = ProviderCallback::new(move |public_key: JsValue| {
+        // `null` when the player switched to an account that has not approved the app yet
+        let pubkey = if public_key.is_null() || public_key.is_undefined() {
+            None
+        } else {
+            public_key_to_string(&public_key)
+                .and_then(|address| Pubkey::from_str(&address).map_err(Into::into))
+                .inspect_err(|err| debug!("invalid accountChanged pubkey: {:?}", err))
+                .ok()
+        };
+
+        account_bridge.send(move |world| match pubkey {
+            Some(pubkey) => crate::wallet_account_changed(world, pubkey),
+            None => crate::wallet_disconnected(world),
+        });
+    });
+
+    let disconnect = ProviderCallback::new(move |_: JsValue| {
+        bridge.send(crate::wallet_disconnected);
+    });
+
+    let mut listeners = ProviderListeners {
+        provider: provider.clone(),
+        listeners: Vec::new(),
+    };
+    for (event, closure) in [
+        ("accountChanged", account_changed),
+        ("disconnect", disconnect),
+    ] {
+        let callback: &JsValue = closure.as_ref();
+        call_sync(
+            &provider,
+            "on",
+            &[JsValue::from_str(event), callback.clone()],
+        )?;
+        listeners.listeners.push((event, closure));
+    }
+
+    PROVIDER_LISTENERS.with(|cell| *cell.borrow_mut() = Some(listeners));
+    Ok(())
+}
+
+/// Removes the listeners registered by [`listen_to_provider`].
+pub(crate) fn stop_listening_to_provider() {
+    // take first, dropping calls into JS which must not happen while borrowed
+    let listeners = PROVIDER_LISTENERS.with(|cell| cell.borrow_mut().take());
+    drop(listeners);
+}
+
+/// Checks that `signature` is a valid ed25519 signature of `message` by `signer`.
+pub fn verify_message(signer: &Pubkey, message: &[u8], signature: &Signature) -> bool {
+    signature.verify(signer.as_ref(), message)
+}
+
+/// The wallet did not answer a connect request in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectTimedOut {
+    pub adapter: WalletAdapter,
+    pub timeout: Duration,
+}
+
+impl fmt::Display for ConnectTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} did not answer the connect request within {:?}",
+            self.adapter.name(),
+            self.timeout
+        )
+    }
+}
+
+impl std::error::Error for ConnectTimedOut {}
+
+/// A connect request failed in a way that suggests the wallet popup never showed up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PopupLikelyBlocked {
+    pub adapter: WalletAdapter,
+    /// The error reported by the wallet.
+    pub reason: String,
+}
+
+impl fmt::Display for PopupLikelyBlocked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the {} popup was likely blocked: {}",
+            self.adapter.name(),
+            self.reason
+        )
+    }
+}
+
+impl std::error::Error for PopupLikelyBlocked {}
+
+/// Rejections faster than this can't have been made by a player in a popup.
+const IMMEDIATE_REJECTION: Duration = Duration::from_millis(500);
+
+fn is_popup_likely_blocked(err: &anyhow::Error, elapsed: Duration, lost_focus: bool) -> bool {
+    let message = format!("{:#}", err).to_ascii_lowercase();
+    if message.contains("popup") && message.contains("block") {
+        return true;
+    }
+
+    // an opened popup takes the focus from the page, a blocked one never does
+    !lost_focus && elapsed < IMMEDIATE_REJECTION
+}
+
+/// Records whether the page lost focus while it is alive.
+struct FocusWatcher {
+    lost: Rc<Cell<bool>>,
+    listener: Option<(web_sys::Window, Closure<dyn FnMut()>)>,
+}
+
+impl FocusWatcher {
+    fn new() -> Self {
+        let lost = Rc::new(Cell::new(false));
+
+        let listener = web_sys::window().and_then(|window| {
+            let flag = lost.clone();
+            let closure = Closure::<dyn FnMut()>::new(move || flag.set(true));
+            window
+                .add_event_listener_with_callback("blur", closure.as_ref().unchecked_ref())
+                .ok()?;
+            Some((window, closure))
+        });
+
+        Self { lost, listener }
+    }
+
+    fn lost(&self) -> bool {
+        self.lost.get()
+    }
+}
+
+impl Drop for FocusWatcher {
+    fn drop(&mut self) {
+        if let Some((window, closure)) = &self.listener {
+            let _ = window
+                .remove_event_listener_with_callback("blur", closure.as_ref().unchecked_ref());
+        }
+    }
+}
+
+/// Calls `provider[method](...args)` without awaiting the result.
+fn call_sync(provider: &JsValue, method: &str, args: &[JsValue]) -> Result<JsValue> {
+    let function: js_sys::Function = reflect_get(provider, &JsValue::from_str(method))?.into();
+    let args: js_sys::Array = args.iter().collect();
+
+    function
+        .apply(provider, &args)
+        .map_err(|err| anyhow!("{err:?}"))
+}
+
+/// Calls `provider[method](...args)` and awaits the result if it is a promise.
+async fn call_method(provider: &JsValue, method: &str, args: &[JsValue]) -> Result<JsValue> {
+    let resp = call_sync(provider, method, args)?;
+
+    wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&resp))
+        .await
+        .map_err(|err| anyhow!("{err:?}"))
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+use crate::deep_link::DeepLinkSettings;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{file_wallet::FileWallet, keystore::KeystoreStatus};
+use crate::{
+    schedule::SolanaClientSet, wallet_adapter::WalletAdapter, WalletConnectSettings, WalletEvent,
+    HOVERED_BUTTON, NORMAL_BUTTON,
+};
+
+/// Optional icons shown next to the wallet names, injected providers carry none.
+///
+/// Wallets without an icon get a badge in their brand color instead.
+#[derive(Debug, Default, Resource)]
+pub struct WalletIcons(pub HashMap<WalletAdapter, Handle<Image>>);
+
+/// Root node of the wallet selection modal.
+#[derive(Debug, Component)]
+pub struct WalletModal;
+
+/// A button of the wallet selection modal, `None` closes it.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct WalletModalOption(pub Option<WalletAdapter>);
+
+pub struct WalletModalPlugin;
+
+impl Plugin for WalletModalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WalletIcons>();
+        app.add_systems(
+            Update,
+            (open_wallet_modal_system, wallet_modal_interaction_system).in_set(SolanaClientSet::Ui),
+        );
+    }
+}
+
+fn open_wallet_modal_system(
+    mut commands: Commands,
+    mut ev_reader: EventReader<WalletEvent>,
+    icons: Res<WalletIcons>,
+    modals: Query<Entity, With<WalletModal>>,
+    settings: Res<WalletConnectSettings>,
+    #[cfg(not(target_arch = "wasm32"))] file_wallet: Option<Res<FileWallet>>,
+    #[cfg(not(target_arch = "wasm32"))] keystore: Option<Res<KeystoreStatus>>,
+    #[cfg(target_arch = "wasm32")] deep_link: Option<Res<DeepLinkSettings>>,
+) {
+    let clicked = ev_reader
+        .read()
+        .any(|event| matches!(event, WalletEvent::ConnectBtnClick));
+    if !clicked || !modals.is_empty() || settings.read_only {
+        return;
+    }
+
+    // desktop builds with a keypair file or keystore connect to it directly
+    #[cfg(not(target_arch = "wasm32"))]
+    if file_wallet.is_some() || keystore.is_some() {
+        return;
+    }
+
+    let adapters = WalletAdapter::detect_all();
+    debug!("detected wallets: {:?}", adapters);
+
+    // mobile browsers without an injected provider connect through deep links
+    #[cfg(target_arch = "wasm32")]
+    if adapters.is_empty() && deep_link.is_some() {
+        return;
+    }
+
+    spawn_wallet_modal(&mut commands, &adapters, &icons);
+}
+
+fn spawn_wallet_modal(commands: &mut Commands, adapters: &[WalletAdapter], icons: &WalletIcons) {
+    let text_style = TextStyle {
+        font_size: 30.0,
+        color: Color::linear_rgb(0.9, 0.9, 0.9),
+        ..Default::default()
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: Color::linear_rgba(0.0, 0.0, 0.0, 0.6).into(),
+            z_index: ZIndex::Global(100),
+            ..default()
+        })
+        .insert(WalletModal)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(10.0),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: NORMAL_BUTTON.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let title = if adapters.is_empty() {
+                        "No supported wallet found"
+                    } else {
+                        "Select a wallet"
+                    };
+                    parent.spawn(TextBundle::from_section(title, text_style.clone()));
+
+                    for adapter in adapters {
+                        spawn_option(
+                            parent,
+                            WalletModalOption(Some(*adapter)),
+                            adapter.name(),
+                            Some((adapter.color(), icons.0.get(adapter).cloned())),
+                            &text_style,
+                        );
+                    }
+
+                    spawn_option(parent, WalletModalOption(None), "Cancel", None, &text_style);
+                });
+        });
+}
+
+fn spawn_option(
+    parent: &mut ChildBuilder,
+    option: WalletModalOption,
+    label: &str,
+    icon: Option<(Color, Option<Handle<Image>>)>,
+    text_style: &TextStyle,
+) {
+    parent
+        .spawn(ButtonBundle {
+            style: Style {
+                width: Val::Px(300.0),
+                height: Val::Px(50.0),
+                column_gap: Val::Px(10.0),
+                padding: UiRect::horizontal(Val::Px(10.0)),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: NORMAL_BUTTON.into(),
+            ..default()
+        })
+        .insert(option)
+        .with_children(|parent| {
+            let icon_style = Style {
+                width: Val::Px(32.0),
+                height: Val::Px(32.0),
+                ..default()
+            };
+
+            match icon {
+                Some((_, Some(image))) => {
+                    parent.spawn(ImageBundle {
+                        style: icon_style,
+                        image: UiImage::new(image),
+                        ..default()
+                    });
+                }
+                Some((color, None)) => {
+                    parent.spawn(NodeBundle {
+                        style: icon_style,
+                        background_color: color.into(),
+                        ..default()
+                    });
+                }
+                None => {}
+            }
+
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn wallet_modal_interaction_system(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &WalletModalOption),
+        Changed<Interaction>,
+    >,
+    modals: Query<Entity, With<WalletModal>>,
+    mut ev_writer: EventWriter<WalletEvent>,
+) {
+    for (interaction, mut color, option) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                if let Some(adapter) = option.0 {
+                    debug!("WalletEvent::AdapterSelected: {}", adapter.name());
+                    ev_writer.send(WalletEvent::AdapterSelected(adapter));
+                }
+                for modal in &modals {
+                    commands.entity(modal).despawn_recursive();
+                }
+            }
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::{anyhow, Context, Result};
+use bevy::prelude::*;
+
+use crate::{
+    schedule::SolanaClientSet, secretbox::local_storage, wallet_adapter::WalletAdapter,
+    AsyncWalletEvent, AsyncWalletEventQueue, Wallet, WalletEvent,
+};
+
+/// `localStorage` key of the last wallet connection.
+pub const WALLET_SESSION_STORAGE_KEY: &str = "bevySolanaWalletSession";
+
+/// The browser wallet a player was connected with, kept in `localStorage` so a page
+/// reload does not ask them to connect again.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct WalletSession {
+    /// [`WalletAdapter::name`] of the wallet.
+    pub adapter: String,
+    pub address: String,
+}
+
+impl WalletSession {
+    /// The stored session, `None` if the player was not connected.
+    pub fn load() -> Result<Option<Self>> {
+        let Some(json) = local_storage()?
+            .get_item(WALLET_SESSION_STORAGE_KEY)
+            .map_err(|err| anyhow!("{err:?}"))?
+        else {
+            return Ok(None);
+        };
+
+        serde_json::from_str(&json)
+            .context("invalid stored wallet session")
+            .map(Some)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        local_storage()?
+            .set_item(WALLET_SESSION_STORAGE_KEY, &serde_json::to_string(self)?)
+            .map_err(|err| anyhow!("{err:?}"))
+    }
+
+    pub fn forget() -> Result<()> {
+        local_storage()?
+            .remove_item(WALLET_SESSION_STORAGE_KEY)
+            .map_err(|err| anyhow!("{err:?}"))
+    }
+
+    pub fn wallet_adapter(&self) -> Option<WalletAdapter> {
+        WalletAdapter::ALL
+            .into_iter()
+            .find(|adapter| adapter.name() == self.adapter)
+    }
+}
+
+/// Remembers the connected browser wallet and silently reconnects to it after a page
+/// reload, using the wallet's `onlyIfTrusted` connect.
+///
+/// If the wallet no longer trusts the game the session is dropped and the player
+/// connects as usual. The [`BurnerWalletPlugin`](crate::burner::BurnerWalletPlugin)
+/// restores its keypair on its own.
+pub struct WalletSessionPlugin;
+
+impl Plugin for WalletSessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, restore_wallet_session_system);
+        app.add_systems(
+            Update,
+            store_wallet_session_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn restore_wallet_session_system() {
+    let session = match WalletSession::load() {
+        Ok(Some(session)) => session,
+        Ok(None) => return,
+        Err(err) => {
+            warn!("could not load the wallet session: {:?}", err);
+            return;
+        }
+    };
+    let Some(adapter) = session.wallet_adapter().filter(|a| a.is_installed()) else {
+        debug!("wallet {} of the stored session is gone", session.adapter);
+        return;
+    };
+
+    wasm_bindgen_futures::spawn_local(async move {
+        match adapter.connect_trusted().await {
+            Ok(address) => {
+                debug!("restored {} session of {}", adapter.name(), address);
+                if let Err(err) = AsyncWalletEventQueue::push(
+                    AsyncWalletEvent::ConnectionCompleted(adapter, Ok(address)),
+                ) {
+                    error!("could not restore the wallet session: {:?}", err);
+                }
+            }
+            Err(err) => {
+                debug!("{} no longer trusts the game: {:?}", adapter.name(), err);
+                if let Err(err) = WalletSession::forget() {
+                    warn!("could not forget the wallet session: {:?}", err);
+                }
+            }
+        }
+    });
+}
+
+fn store_wallet_session_system(mut ev_reader: EventReader<WalletEvent>, wallet: Res<Wallet>) {
+    for event in ev_reader.read() {
+        let result = match event {
+            WalletEvent::Connected | WalletEvent::AccountChanged(_) => {
+                // burners and file wallets have no browser wallet to reconnect to
+                let (Some(adapter), Some(info)) = (wallet.adapter, &wallet.info) else {
+                    continue;
+                };
+                WalletSession {
+                    adapter: adapter.name().to_string(),
+                    address: info.address.clone(),
+                }
+                .save()
+            }
+            WalletEvent::Disconnected => WalletSession::forget(),
+            _ => continue,
+        };
+
+        if let Err(err) = result {
+            warn!("could not store the wallet session: {:?}", err);
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+use crate::{schedule::SolanaClientSet, WalletEvent};
+
+/// The wallet connection as a Bevy state, for `OnEnter(WalletState::Connected)`
+/// schedules and `in_state` run conditions.
+///
+/// Follows the [`WalletEvent`]s, so it is updated in the frame after the event was sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, States, Reflect)]
+pub enum WalletState {
+    #[default]
+    Disconnected,
+    /// A wallet was picked and is asked to connect.
+    Connecting,
+    Connected,
+    /// The last connect request failed, timed out or was blocked. Picking a wallet
+    /// again moves on to [`WalletState::Connecting`].
+    Error,
+}
+
+impl WalletState {
+    /// The state after `event`.
+    pub fn next(self, event: &WalletEvent) -> Self {
+        match event {
+            WalletEvent::AdapterSelected(_) => WalletState::Connecting,
+            WalletEvent::Connected | WalletEvent::AccountChanged(_) => WalletState::Connected,
+            WalletEvent::Disconnected => WalletState::Disconnected,
+            WalletEvent::ConnectionTimedOut(_)
+            | WalletEvent::ConnectionFailed(_)
+            | WalletEvent::PopupLikelyBlocked(_) => WalletState::Error,
+            _ => self,
+        }
+    }
+}
+
+/// Keeps [`WalletState`] in sync with the wallet, requires Bevy's `StatesPlugin`
+/// which is part of the `DefaultPlugins`.
+pub struct WalletStatePlugin;
+
+impl Plugin for WalletStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<WalletState>();
+        app.register_type::<State<WalletState>>();
+        app.add_systems(Update, wallet_state_system.in_set(SolanaClientSet::Network));
+    }
+}
+
+fn wallet_state_system(
+    mut ev_reader: EventReader<WalletEvent>,
+    state: Res<State<WalletState>>,
+    mut next_state: ResMut<NextState<WalletState>>,
+) {
+    let current = *state.get();
+    let next = ev_reader
+        .read()
+        .fold(current, |state, event| state.next(event));
+
+    if next != current {
+        debug!("wallet state {:?} -> {:?}", current, next);
+        next_state.set(next);
+    }
+}
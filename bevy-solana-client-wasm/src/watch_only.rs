@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use bevy::prelude::*;
+use bevy_solana_client_common::{
+    rpc_client::RpcClient,
+    token::{TokenAccountBalance, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID},
+};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    bridge::AsyncBridge,
+    error::report_error,
+    rpc::{SolanaRpc, SolanaRpcClient},
+    schedule::SolanaClientSet,
+};
+
+/// Marks a wallet entity that can be displayed but never used for signing,
+/// e.g. a ledger or cold wallet address added by the player.
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct WatchOnly;
+
+/// The address of a wallet represented by an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct WalletAddress(pub Pubkey);
+
+/// SOL and token balances of a [`WalletAddress`], filled in by the watch-only refresh.
+#[derive(Debug, Default, Clone, Component)]
+pub struct WalletHoldings {
+    pub lamports: Option<u64>,
+    pub tokens: Vec<TokenAccountBalance>,
+}
+
+impl WalletHoldings {
+    pub fn nfts(&self) -> impl Iterator<Item = &TokenAccountBalance> {
+        self.tokens.iter().filter(|t| t.is_nft())
+    }
+
+    pub fn fungible(&self) -> impl Iterator<Item = &TokenAccountBalance> {
+        self.tokens.iter().filter(|t| !t.is_nft())
+    }
+}
+
+#[derive(Bundle)]
+pub struct WatchOnlyWalletBundle {
+    pub watch_only: WatchOnly,
+    pub address: WalletAddress,
+    pub holdings: WalletHoldings,
+}
+
+impl WatchOnlyWalletBundle {
+    pub fn new(address: Pubkey) -> Self {
+        Self {
+            watch_only: WatchOnly,
+            address: WalletAddress(address),
+            holdings: WalletHoldings::default(),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct WatchOnlyRefresh {
+    pub timer: Timer,
+}
+
+impl Default for WatchOnlyRefresh {
+    fn default() -> Self {
+        Self {
+            timer: Timer::new(Duration::from_secs(30), TimerMode::Repeating),
+        }
+    }
+}
+
+pub struct WatchOnlyPlugin;
+
+impl Plugin for WatchOnlyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WatchOnlyRefresh>();
+        app.add_systems(
+            Update,
+            refresh_watch_only_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn refresh_watch_only_system(
+    time: Res<Time>,
+    mut refresh: ResMut<WatchOnlyRefresh>,
+    all: Query<(Entity, &WalletAddress), With<WatchOnly>>,
+    added: Query<(Entity, &WalletAddress), Added<WatchOnly>>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    let due: Vec<(Entity, Pubkey)> = if refresh.timer.tick(time.delta()).just_finished() {
+        all.iter().map(|(e, a)| (e, a.0)).collect()
+    } else {
+        added.iter().map(|(e, a)| (e, a.0)).collect()
+    };
+
+    for (entity, address) in due {
+        let rpc = rpc.clone();
+        let bridge = bridge.clone();
+        bridge.clone().spawn(move || async move {
+            match fetch_holdings(&rpc, &address).await {
+                Ok(holdings) => bridge.send(move |world| {
+                    if let Some(mut entity) = world.get_entity_mut(entity) {
+                        entity.insert(holdings);
+                    }
+                }),
+                Err(err) => bridge.send(move |world| {
+                    report_error(
+                        world,
+                        format!("refresh watch-only wallet {}", address),
+                        &err,
+                    );
+                }),
+            }
+        });
+    }
+}
+
+pub(crate) async fn fetch_holdings(
+    rpc: &SolanaRpcClient,
+    address: &Pubkey,
+) -> Result<WalletHoldings> {
+    let lamports = rpc.get_balance(address).await?;
+
+    let mut tokens = rpc
+        .get_token_accounts_by_owner(address, &TOKEN_PROGRAM_ID)
+        .await?;
+    tokens.extend(
+        rpc.get_token_accounts_by_owner(address, &TOKEN_2022_PROGRAM_ID)
+            .await?,
+    );
+
+    Ok(WalletHoldings {
+        lamports: Some(lamports),
+        tokens,
+    })
+}
@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_solana_client_common::{catalog::ErrorCategory, rpc_client::RpcClient};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{
+    account::AccountAddress, bridge::AsyncBridge, poll::PollTimer, rpc::SolanaRpc,
+    schedule::SolanaClientSet,
+};
+
+/// Keeps a sibling [`WatchedAccount`] in sync with an on-chain account by polling it
+/// every `interval`.
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct AccountWatcher {
+    #[reflect(ignore)]
+    pub pubkey: Pubkey,
+    pub interval: Duration,
+    timer: PollTimer,
+}
+
+impl AccountWatcher {
+    /// Fetches `pubkey` right away and then every `interval`.
+    pub fn new(pubkey: Pubkey, interval: Duration) -> Self {
+        Self {
+            pubkey,
+            interval,
+            timer: PollTimer::new(interval),
+        }
+    }
+
+    /// Forgets the fetch in flight and fetches again on the next frame.
+    pub(crate) fn reset(&mut self) {
+        self.timer.reset();
+    }
+}
+
+impl AccountAddress for AccountWatcher {
+    fn address(&self) -> Pubkey {
+        self.pubkey
+    }
+}
+
+/// The latest state of the account of an [`AccountWatcher`], only replaced when the
+/// account changed so `Changed<WatchedAccount>` means an on-chain change.
+///
+/// Removed while the account does not exist, other fetch errors keep the last state.
+#[derive(Debug, Clone, PartialEq, Component, Reflect)]
+#[reflect(Component)]
+pub struct WatchedAccount(#[reflect(ignore)] pub Account);
+
+pub struct AccountWatcherPlugin;
+
+impl Plugin for AccountWatcherPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<AccountWatcher>();
+        app.register_type::<WatchedAccount>();
+        app.add_systems(
+            Update,
+            account_watcher_system.in_set(SolanaClientSet::Network),
+        );
+    }
+}
+
+fn account_watcher_system(
+    time: Res<Time<Real>>,
+    mut watchers: Query<(Entity, &mut AccountWatcher)>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+) {
+    for (entity, mut watcher) in &mut watchers {
+        let interval = watcher.interval;
+        if !watcher.timer.tick(interval, time.delta()) {
+            continue;
+        }
+
+        let pubkey = watcher.pubkey;
+        let rpc = rpc.clone();
+        let bridge = bridge.clone();
+        bridge.clone().spawn(move || async move {
+            let result = rpc.get_account(&pubkey).await;
+            bridge.send(move |world| {
+                let Some(mut entity) = world.get_entity_mut(entity) else {
+                    return;
+                };
+                let Some(mut watcher) = entity.get_mut::<AccountWatcher>() else {
+                    return;
+                };
+                watcher.timer.finish();
+                // the watcher was pointed at another account in the meantime
+                if watcher.pubkey != pubkey {
+                    return;
+                }
+
+                match result {
+                    Ok(account) => {
+                        if entity.get::<WatchedAccount>().map(|w| &w.0) != Some(&account) {
+                            entity.insert(WatchedAccount(account));
+                        }
+                    }
+                    Err(err) if ErrorCategory::of(&err) == ErrorCategory::AccountNotFound => {
+                        entity.remove::<WatchedAccount>();
+                    }
+                    // keep the last state through rate limits and network failures
+                    Err(err) => debug!("could not fetch watched account {}: {:?}", pubkey, err),
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,248 @@
+//! Press space to bump your on-chain counter and climb the leaderboard of every counter.
+//!
+//! Expects an Anchor counter program at [`COUNTER_PROGRAM_ID`] with an `increment`
+//! instruction creating the counter on first use. In the browser a session key signs
+//! the increments, so the wallet only prompts once.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_solana_client_common::scan::{ProgramScan, RpcFilter};
+use bevy_solana_client_wasm::{
+    account::SolanaAccountData,
+    leaderboard::{Leaderboard, LeaderboardConfig, LeaderboardPlugin},
+    session_key::{GameSession, SessionKeyPlugin, StartSession},
+    subscription::TrackAccount,
+    Wallet, WalletEvent,
+};
+use solana_sdk::{
+    account::Account,
+    ed25519_program,
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+use wasm_client_example::{scene_app, send_instructions, wallet_signer, SceneSigner};
+
+/// Replace with the id of your deployed counter program.
+const COUNTER_PROGRAM_ID: Pubkey = pubkey!("HuZeNHKpqDNjERB7VZBZjMQs9gScufCRg9bK1bUUyL6A");
+
+/// `Counter { authority: Pubkey, count: u64 }` behind the 8 byte discriminator.
+const AUTHORITY_OFFSET: usize = 8;
+const COUNT_OFFSET: usize = 40;
+
+/// The counter of the connected wallet.
+#[derive(Component)]
+struct PlayerCounter(Pubkey);
+
+#[derive(Component)]
+struct CounterText;
+
+#[derive(Component)]
+struct LeaderboardText;
+
+fn main() {
+    let discriminator = &hash(b"account:Counter").to_bytes()[..8];
+    let leaderboard = LeaderboardConfig::new(
+        COUNTER_PROGRAM_ID,
+        ProgramScan::new(AUTHORITY_OFFSET).with_filter(RpcFilter::memcmp(0, discriminator)),
+        count,
+    )
+    .with_top(5)
+    .with_refresh(Duration::from_secs(30));
+
+    scene_app("On-chain counter")
+        .add_plugins((SessionKeyPlugin, LeaderboardPlugin(leaderboard)))
+        .add_systems(Startup, setup_counter)
+        .add_systems(
+            Update,
+            (
+                player_counter_system,
+                increment_system,
+                counter_render_system,
+                leaderboard_render_system,
+            ),
+        )
+        .run();
+}
+
+fn count(account: &Account) -> Option<u64> {
+    let bytes = account.data.get(COUNT_OFFSET..COUNT_OFFSET + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn counter_address(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"counter", authority.as_ref()], &COUNTER_PROGRAM_ID).0
+}
+
+fn setup_counter(mut commands: Commands) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "-",
+                TextStyle {
+                    font_size: 120.0,
+                    ..default()
+                },
+            ),
+            ..default()
+        },
+        CounterText,
+    ));
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.0),
+            top: Val::Px(10.0),
+            ..default()
+        }),
+        LeaderboardText,
+    ));
+}
+
+/// Tracks the counter of the connected wallet and starts a session key for it.
+fn player_counter_system(
+    mut commands: Commands,
+    mut ev_reader: EventReader<WalletEvent>,
+    wallet: Res<Wallet>,
+    counters: Query<Entity, With<PlayerCounter>>,
+    mut session_writer: EventWriter<StartSession>,
+) {
+    for event in ev_reader.read() {
+        if !matches!(
+            event,
+            WalletEvent::Connected | WalletEvent::AccountChanged(_) | WalletEvent::Disconnected
+        ) {
+            continue;
+        }
+
+        for entity in &counters {
+            commands.entity(entity).despawn();
+        }
+        if matches!(event, WalletEvent::Disconnected) {
+            continue;
+        }
+        let Some(authority) = wallet.info.as_ref().and_then(|info| info.pubkey().ok()) else {
+            continue;
+        };
+
+        let address = counter_address(&authority);
+        commands.spawn((PlayerCounter(authority), TrackAccount(address)));
+
+        // the session key pays its own fees, enough for a few hundred increments
+        if wallet.adapter.is_some() {
+            session_writer.send(StartSession {
+                programs: vec![COUNTER_PROGRAM_ID, ed25519_program::id()],
+                duration: Duration::from_secs(60 * 60),
+                fund_lamports: 2_000_000,
+            });
+        }
+    }
+}
+
+fn increment_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    counters: Query<&PlayerCounter>,
+    session: Option<Res<GameSession>>,
+) {
+    if !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+    let Ok(counter) = counters.get_single() else {
+        return;
+    };
+
+    let authority = counter.0;
+    let session = session.map(|session| session.0.clone());
+    commands.add(move |world: &mut World| {
+        let mut instructions = Vec::new();
+        let signer: SceneSigner = match session {
+            Some(session) => {
+                instructions.push(session.approval_instruction());
+                session
+            }
+            None => match wallet_signer(world) {
+                Some(signer) => signer,
+                None => return,
+            },
+        };
+        instructions.push(increment_instruction(&authority, &signer.pubkey()));
+
+        send_instructions(
+            world,
+            "increment counter",
+            signer,
+            instructions,
+            |world, _| {
+                info!("counter incremented");
+                // show the new rank right away instead of at the next scan
+                world.resource_mut::<Leaderboard>().refresh();
+            },
+        );
+    });
+}
+
+/// `increment`, signed by the authority itself or by a session key it approved.
+fn increment_instruction(authority: &Pubkey, signer: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        COUNTER_PROGRAM_ID,
+        &hash(b"global:increment").to_bytes()[..8],
+        vec![
+            AccountMeta::new(counter_address(authority), false),
+            AccountMeta::new_readonly(*authority, false),
+            AccountMeta::new(*signer, true),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+fn counter_render_system(
+    counters: Query<&SolanaAccountData, (With<PlayerCounter>, Changed<SolanaAccountData>)>,
+    mut text: Query<&mut Text, With<CounterText>>,
+) {
+    let Some(value) = counters.iter().find_map(|data| count(&data.0)) else {
+        return;
+    };
+    for mut text in &mut text {
+        text.sections[0].value = value.to_string();
+    }
+}
+
+fn leaderboard_render_system(
+    leaderboard: Res<Leaderboard>,
+    counters: Query<&PlayerCounter>,
+    mut text: Query<&mut Text, With<LeaderboardText>>,
+) {
+    if !leaderboard.is_changed() {
+        return;
+    }
+
+    let player = counters
+        .get_single()
+        .ok()
+        .map(|counter| counter_address(&counter.0));
+    let mut lines = String::from("Top counters\n");
+    for (i, entry) in leaderboard.entries().iter().enumerate() {
+        let marker = if Some(entry.pubkey) == player {
+            " (you)"
+        } else {
+            ""
+        };
+        lines.push_str(&format!("{}. {}{}\n", i + 1, entry.score, marker));
+    }
+
+    for mut text in &mut text {
+        text.sections[0].value.clone_from(&lines);
+    }
+}
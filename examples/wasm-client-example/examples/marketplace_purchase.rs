@@ -0,0 +1,157 @@
+//! A shop paying for items in SOL, press 1-3 to buy one.
+//!
+//! The payment goes to [`SHOP_TREASURY`] with a memo naming the item, so the game
+//! server can hand out purchases by watching the treasury's transactions.
+
+use bevy::prelude::*;
+use bevy_solana_client_common::rpc_client::ConfirmationStatus;
+use bevy_solana_client_wasm::signature_status::{SignatureStatusCache, TrackedTransaction};
+use solana_sdk::{
+    instruction::Instruction, native_token::LAMPORTS_PER_SOL, pubkey, pubkey::Pubkey,
+    system_instruction,
+};
+use wasm_client_example::{scene_app, send_instructions, wallet_signer, SceneStatus};
+
+/// Replace with the wallet receiving the shop's sales.
+const SHOP_TREASURY: Pubkey = pubkey!("8osUWUppX5ZfLyhamW7hFeYHdiQMwSXiZnuTZ2xmwv3z");
+
+const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TyNLWGbM5V3GnYXaDkmBdQ");
+
+struct Item {
+    name: &'static str,
+    lamports: u64,
+    key: KeyCode,
+}
+
+static ITEMS: [Item; 3] = [
+    Item {
+        name: "Health potion",
+        lamports: LAMPORTS_PER_SOL / 1000,
+        key: KeyCode::Digit1,
+    },
+    Item {
+        name: "Iron sword",
+        lamports: LAMPORTS_PER_SOL / 100,
+        key: KeyCode::Digit2,
+    },
+    Item {
+        name: "Dragon egg",
+        lamports: LAMPORTS_PER_SOL / 10,
+        key: KeyCode::Digit3,
+    },
+];
+
+/// A sent purchase, followed until it is finalized.
+#[derive(Component)]
+struct Purchase {
+    item: &'static str,
+    shown: Option<ConfirmationStatus>,
+}
+
+fn main() {
+    scene_app("Marketplace purchase")
+        .add_systems(Startup, setup_shop)
+        .add_systems(Update, (buy_system, purchase_status_system))
+        .run();
+}
+
+fn setup_shop(mut commands: Commands) {
+    let listing = ITEMS
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let sol = item.lamports as f64 / LAMPORTS_PER_SOL as f64;
+            format!("[{}] {} - {} SOL", i + 1, item.name, sol)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    commands.spawn(Text2dBundle {
+        text: Text::from_section(
+            listing,
+            TextStyle {
+                font_size: 28.0,
+                ..default()
+            },
+        ),
+        ..default()
+    });
+}
+
+fn buy_system(mut commands: Commands, keys: Res<ButtonInput<KeyCode>>) {
+    let Some(item) = ITEMS.iter().find(|item| keys.just_pressed(item.key)) else {
+        return;
+    };
+
+    commands.add(move |world: &mut World| {
+        let Some(signer) = wallet_signer(world) else {
+            world
+                .resource_mut::<SceneStatus>()
+                .set("Connect a wallet to shop");
+            return;
+        };
+        world
+            .resource_mut::<SceneStatus>()
+            .set(format!("Approve the {} purchase in your wallet", item.name));
+
+        let instructions = vec![
+            system_instruction::transfer(&signer.pubkey(), &SHOP_TREASURY, item.lamports),
+            Instruction::new_with_bytes(MEMO_PROGRAM_ID, item.name.as_bytes(), vec![]),
+        ];
+        send_instructions(
+            world,
+            "buy item",
+            signer,
+            instructions,
+            |world, signature| {
+                let tracked = world
+                    .query::<(Entity, &TrackedTransaction)>()
+                    .iter(world)
+                    .find(|(_, tx)| tx.signature == signature)
+                    .map(|(entity, _)| entity);
+                if let Some(entity) = tracked {
+                    world.entity_mut(entity).insert(Purchase {
+                        item: item.name,
+                        shown: None,
+                    });
+                }
+                world
+                    .resource_mut::<SceneStatus>()
+                    .set(format!("{} purchase sent", item.name));
+            },
+        );
+    });
+}
+
+fn purchase_status_system(
+    mut commands: Commands,
+    cache: Res<SignatureStatusCache>,
+    mut purchases: Query<(Entity, &TrackedTransaction, &mut Purchase)>,
+    mut status: ResMut<SceneStatus>,
+) {
+    for (entity, tx, mut purchase) in &mut purchases {
+        let Some(tx_status) = cache.get(&tx.signature) else {
+            continue;
+        };
+        if tx_status.err.is_some() {
+            status.set(format!("{} purchase failed", purchase.item));
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if tx_status.confirmation_status == purchase.shown {
+            continue;
+        }
+
+        purchase.shown = tx_status.confirmation_status;
+        match purchase.shown {
+            Some(ConfirmationStatus::Finalized) => {
+                status.set(format!("{} is yours!", purchase.item));
+                commands.entity(entity).despawn();
+            }
+            Some(ConfirmationStatus::Confirmed) => {
+                status.set(format!("{} purchase confirmed", purchase.item));
+            }
+            _ => status.set(format!("{} purchase processing", purchase.item)),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! An inventory screen listing the NFTs of the connected wallet, refreshed every 30 seconds.
+
+use bevy::prelude::*;
+use bevy_solana_client_wasm::{
+    watch_only::{WalletHoldings, WatchOnlyWalletBundle},
+    Wallet, WalletEvent,
+};
+use wasm_client_example::{scene_app, SceneStatus};
+
+const SLOT_SIZE: f32 = 110.0;
+const COLUMNS: usize = 6;
+
+/// The wallet whose holdings fill the inventory, kept up to date by the watch-only refresh.
+#[derive(Component)]
+struct InventoryOwner;
+
+#[derive(Component)]
+struct InventorySlot;
+
+fn main() {
+    scene_app("NFT inventory")
+        .add_systems(
+            Update,
+            (inventory_owner_system, inventory_render_system).chain(),
+        )
+        .run();
+}
+
+/// Follows the connected wallet, the inventory only reads balances so a watch-only
+/// entity is all it needs.
+fn inventory_owner_system(
+    mut commands: Commands,
+    mut ev_reader: EventReader<WalletEvent>,
+    wallet: Res<Wallet>,
+    owners: Query<Entity, With<InventoryOwner>>,
+    slots: Query<Entity, With<InventorySlot>>,
+    mut status: ResMut<SceneStatus>,
+) {
+    for event in ev_reader.read() {
+        if !matches!(
+            event,
+            WalletEvent::Connected | WalletEvent::AccountChanged(_) | WalletEvent::Disconnected
+        ) {
+            continue;
+        }
+
+        for entity in owners.iter().chain(slots.iter()) {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        if matches!(event, WalletEvent::Disconnected) {
+            continue;
+        }
+        let Some(address) = wallet.info.as_ref().and_then(|info| info.pubkey().ok()) else {
+            continue;
+        };
+        status.set("Loading inventory...");
+        commands.spawn((WatchOnlyWalletBundle::new(address), InventoryOwner));
+    }
+}
+
+fn inventory_render_system(
+    mut commands: Commands,
+    owners: Query<&WalletHoldings, (With<InventoryOwner>, Changed<WalletHoldings>)>,
+    slots: Query<Entity, With<InventorySlot>>,
+    mut status: ResMut<SceneStatus>,
+) {
+    for holdings in &owners {
+        // the initial empty holdings are only a placeholder until the first refresh
+        if holdings.lamports.is_none() {
+            continue;
+        }
+        for entity in &slots {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        let nfts: Vec<_> = holdings.nfts().collect();
+        status.set(format!("{} items in your inventory", nfts.len()));
+
+        for (i, nft) in nfts.iter().enumerate() {
+            let column = (i % COLUMNS) as f32 - (COLUMNS as f32 - 1.0) / 2.0;
+            let row = (i / COLUMNS) as f32;
+            let mint = nft.mint.to_string();
+
+            commands
+                .spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::srgb(0.2, 0.2, 0.3),
+                            custom_size: Some(Vec2::splat(SLOT_SIZE - 10.0)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(
+                            column * SLOT_SIZE,
+                            120.0 - row * SLOT_SIZE,
+                            0.0,
+                        ),
+                        ..default()
+                    },
+                    InventorySlot,
+                ))
+                .with_children(|slot| {
+                    slot.spawn(Text2dBundle {
+                        text: Text::from_section(
+                            format!("{}..{}", &mint[..4], &mint[mint.len() - 4..]),
+                            TextStyle {
+                                font_size: 16.0,
+                                ..default()
+                            },
+                        ),
+                        transform: Transform::from_xyz(0.0, 0.0, 1.0),
+                        ..default()
+                    });
+                });
+        }
+    }
+}
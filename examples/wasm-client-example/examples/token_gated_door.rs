@@ -0,0 +1,110 @@
+//! A door that only opens for wallets holding a token of [`KEY_MINT`].
+
+use bevy::prelude::*;
+use bevy_solana_client_common::{
+    rpc_client::RpcClient,
+    token::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID},
+};
+use bevy_solana_client_wasm::{
+    bridge::AsyncBridge, error::report_error, rpc::SolanaRpc, Wallet, WalletEvent,
+};
+use solana_sdk::{pubkey, pubkey::Pubkey};
+use wasm_client_example::{scene_app, SceneStatus};
+
+/// The devnet USDC mint, any amount of it opens the door.
+const KEY_MINT: Pubkey = pubkey!("4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU");
+
+const CLOSED_Y: f32 = 0.0;
+const OPEN_Y: f32 = 260.0;
+
+#[derive(Component)]
+struct Door {
+    open: bool,
+}
+
+fn main() {
+    scene_app("Token-gated door")
+        .add_systems(Startup, setup_door)
+        .add_systems(Update, (check_key_system, door_animation_system))
+        .run();
+}
+
+fn setup_door(mut commands: Commands) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(0.45, 0.3, 0.2),
+                custom_size: Some(Vec2::new(120.0, 240.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, CLOSED_Y, 0.0),
+            ..default()
+        },
+        Door { open: false },
+    ));
+}
+
+/// Looks for the key token whenever the wallet connects or switches accounts.
+fn check_key_system(
+    mut ev_reader: EventReader<WalletEvent>,
+    wallet: Res<Wallet>,
+    rpc: Res<SolanaRpc>,
+    bridge: Res<AsyncBridge>,
+    mut status: ResMut<SceneStatus>,
+    mut doors: Query<&mut Door>,
+) {
+    for event in ev_reader.read() {
+        match event {
+            WalletEvent::Connected | WalletEvent::AccountChanged(_) => {}
+            WalletEvent::Disconnected => {
+                doors.iter_mut().for_each(|mut door| door.open = false);
+                continue;
+            }
+            _ => continue,
+        }
+        let Some(owner) = wallet.info.as_ref().and_then(|info| info.pubkey().ok()) else {
+            continue;
+        };
+        status.set("Checking your keys...");
+
+        let rpc = rpc.clone();
+        let bridge = bridge.clone();
+        bridge.clone().spawn(move || async move {
+            let mut holds_key = false;
+            for program_id in [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID] {
+                match rpc.get_token_accounts_by_owner(&owner, &program_id).await {
+                    Ok(tokens) => {
+                        holds_key |= tokens
+                            .iter()
+                            .any(|token| token.mint == KEY_MINT && token.amount.amount > 0);
+                    }
+                    Err(err) => {
+                        bridge.send(move |world| report_error(world, "check key", &err));
+                        return;
+                    }
+                }
+            }
+
+            bridge.send(move |world| {
+                world.resource_mut::<SceneStatus>().set(if holds_key {
+                    "The door recognizes your key"
+                } else {
+                    "The door stays shut, you need the key token"
+                });
+                let mut doors = world.query::<&mut Door>();
+                for mut door in doors.iter_mut(world) {
+                    door.open = holds_key;
+                }
+            });
+        });
+    }
+}
+
+fn door_animation_system(time: Res<Time>, mut doors: Query<(&Door, &mut Transform)>) {
+    for (door, mut transform) in &mut doors {
+        let target = if door.open { OPEN_Y } else { CLOSED_Y };
+        let step = 200.0 * time.delta_seconds();
+        let y = transform.translation.y;
+        transform.translation.y = y + (target - y).clamp(-step, step);
+    }
+}
@@ -0,0 +1,166 @@
+//! Shared setup of the example scenes in `examples/`.
+//!
+//! The scenes only use the public plugin APIs, copy one as the starting point of a game.
+//! Run them natively with `cargo run --example counter_game`, signing with the Solana CLI
+//! keypair, or build them for the browser with `--target wasm32-unknown-unknown`.
+
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy_solana_client_common::{
+    rpc_client::RpcClient,
+    signer::{sign_and_send_transaction, GameSigner},
+};
+use bevy_solana_client_wasm::{
+    bridge::AsyncBridge,
+    error::{report_error, SolanaError},
+    rpc::SolanaRpc,
+    signature_status::TrackedTransaction,
+    BevySolanaClientWasm, Wallet, WalletEvent,
+};
+use solana_sdk::{
+    instruction::Instruction, message::Message, signature::Signature, transaction::Transaction,
+};
+
+/// A signer the scenes can move into rpc tasks.
+pub type SceneSigner = Arc<dyn GameSigner + Send + Sync>;
+
+/// A line of text at the bottom of the screen telling the player what is going on.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct SceneStatus(pub String);
+
+impl SceneStatus {
+    pub fn set(&mut self, status: impl Into<String>) {
+        self.0 = status.into();
+    }
+}
+
+#[derive(Component)]
+struct SceneStatusText;
+
+/// An app with the default plugins, the solana plugin, a camera and the status line.
+///
+/// Native builds connect the Solana CLI keypair as their wallet.
+pub fn scene_app(title: &str) -> App {
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: title.to_string(),
+            ..default()
+        }),
+        ..default()
+    }));
+    app.add_plugins(BevySolanaClientWasm);
+
+    #[cfg(target_arch = "wasm32")]
+    app.add_plugins(bevy_solana_client_wasm::wallet_session::WalletSessionPlugin);
+    #[cfg(not(target_arch = "wasm32"))]
+    app.add_plugins(bevy_solana_client_wasm::file_wallet::FileWalletPlugin::default());
+
+    app.init_resource::<SceneStatus>();
+    app.add_systems(Startup, setup_scene);
+    app.add_systems(
+        Update,
+        (
+            scene_wallet_status_system,
+            scene_error_status_system,
+            scene_status_render_system,
+        ),
+    );
+    app
+}
+
+fn setup_scene(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+    commands
+        .spawn(TextBundle::from_section(
+            "Connect a wallet to play",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::linear_rgb(0.9, 0.9, 0.9),
+                ..default()
+            },
+        ))
+        .insert(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            ..default()
+        })
+        .insert(SceneStatusText);
+}
+
+fn scene_wallet_status_system(
+    mut ev_reader: EventReader<WalletEvent>,
+    mut status: ResMut<SceneStatus>,
+) {
+    for event in ev_reader.read() {
+        match event {
+            WalletEvent::Connected => status.set("Wallet connected"),
+            WalletEvent::Disconnected => status.set("Connect a wallet to play"),
+            _ => {}
+        }
+    }
+}
+
+fn scene_error_status_system(
+    mut ev_reader: EventReader<SolanaError>,
+    mut status: ResMut<SceneStatus>,
+) {
+    for error in ev_reader.read() {
+        status.set(format!(
+            "{} failed: {} {}",
+            error.context, error.explanation.summary, error.explanation.hint
+        ));
+    }
+}
+
+fn scene_status_render_system(
+    status: Res<SceneStatus>,
+    mut text: Query<&mut Text, With<SceneStatusText>>,
+) {
+    if !status.is_changed() {
+        return;
+    }
+    for mut text in &mut text {
+        text.sections[0].value.clone_from(&status.0);
+    }
+}
+
+/// The connected wallet as a signer: the browser wallet on wasm, the keypair file natively.
+pub fn wallet_signer(world: &World) -> Option<SceneSigner> {
+    world.resource::<Wallet>().signer()
+}
+
+/// Signs `instructions` with `signer`, who also pays the fee, and sends them.
+///
+/// The sent transaction is spawned as a [`TrackedTransaction`] and handed to `on_sent`,
+/// failures are reported as [`SolanaError`]s under `context`.
+pub fn send_instructions(
+    world: &mut World,
+    context: &'static str,
+    signer: SceneSigner,
+    instructions: Vec<Instruction>,
+    on_sent: impl FnOnce(&mut World, Signature) + Send + 'static,
+) {
+    let rpc = world.resource::<SolanaRpc>().clone();
+    let bridge = world.resource::<AsyncBridge>().clone();
+
+    bridge.clone().spawn(move || async move {
+        let payer = signer.pubkey();
+        let result = async {
+            let mut tx = Transaction::new_unsigned(Message::new(&instructions, Some(&payer)));
+            tx.message.recent_blockhash = rpc.recent_blockhash().await?;
+            sign_and_send_transaction(signer.as_ref(), &mut tx, rpc.0.as_ref()).await
+        }
+        .await;
+
+        bridge.send(move |world| match result {
+            Ok(signature) => {
+                world.spawn(TrackedTransaction { signature });
+                on_sent(world, signature);
+            }
+            Err(err) => report_error(world, context, &err),
+        });
+    });
+}